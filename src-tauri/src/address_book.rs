@@ -0,0 +1,406 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::LazyLock,
+};
+
+use anyhow::{anyhow, Error};
+use log::info;
+use serde::{Deserialize, Serialize};
+use tari_common_types::tari_address::{TariAddress, TariAddressFeatures};
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+
+use crate::{
+    utils::{
+        address_utils::{verify_send, verify_tari_address},
+        store_utils::{current_network_key, load_json_store, save_json_store},
+        time_utils::unix_now,
+    },
+    wallet_adapter::TransactionInfo,
+};
+
+const LOG_TARGET: &str = "tari::universe::address_book";
+const ADDRESS_BOOK_FILE: &str = "address_book.json";
+const EXPORT_VERSION: u32 = 1;
+pub const MAX_LABEL_LENGTH: usize = 64;
+
+static INSTANCE: LazyLock<AddressBook> = LazyLock::new(AddressBook::new);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Contact {
+    pub label: String,
+    /// Base58 form of the address, used as the key regardless of how it was entered
+    pub address: String,
+    pub network: String,
+    pub supports_one_sided: bool,
+    pub supports_interactive: bool,
+    #[serde(default)]
+    pub note: Option<String>,
+    pub created_at: u64,
+}
+
+impl Contact {
+    /// Validates the address for the current network and that it can receive at least one kind of payment.
+    pub fn new(
+        label: &str,
+        address: &str,
+        note: Option<String>,
+        created_at: u64,
+    ) -> Result<Self, Error> {
+        let label = validate_label(label)?;
+        let tari_address = verify_tari_address(address).map_err(|e| anyhow!(e))?;
+        let supports =
+            |feature: TariAddressFeatures| verify_send(tari_address.to_base58(), feature).is_ok();
+        let supports_one_sided = supports(TariAddressFeatures::ONE_SIDED);
+        let supports_interactive = supports(TariAddressFeatures::INTERACTIVE);
+        if !supports_one_sided && !supports_interactive {
+            return Err(anyhow!("Address does not accept any kind of payment"));
+        }
+
+        Ok(Self {
+            label,
+            address: tari_address.to_base58(),
+            network: tari_address.network().to_string().to_lowercase(),
+            supports_one_sided,
+            supports_interactive,
+            note: note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty()),
+            created_at,
+        })
+    }
+}
+
+fn validate_label(label: &str) -> Result<String, Error> {
+    let label = label.trim();
+    if label.is_empty() {
+        return Err(anyhow!("Contact label cannot be empty"));
+    }
+    if label.chars().count() > MAX_LABEL_LENGTH {
+        return Err(anyhow!(
+            "Contact label cannot be longer than {} characters",
+            MAX_LABEL_LENGTH
+        ));
+    }
+    Ok(label.to_string())
+}
+
+/// Normalises emoji or base58 input to the base58 key used by the store
+fn address_key(address: &str) -> Result<String, Error> {
+    TariAddress::from_str(address.trim())
+        .map(|a| a.to_base58())
+        .map_err(|_| anyhow!("Invalid address format"))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AddressBookExport {
+    version: u32,
+    contacts: Vec<Contact>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedContact {
+    pub address: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AddressBookImportSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub skipped: Vec<SkippedContact>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AddressBookStore {
+    contacts: Vec<Contact>,
+}
+
+impl AddressBookStore {
+    /// Inserts the contact or replaces the one with the same address. Returns true when it was new.
+    fn upsert(&mut self, contact: Contact) -> bool {
+        match self
+            .contacts
+            .iter_mut()
+            .find(|c| c.address == contact.address)
+        {
+            Some(existing) => {
+                *existing = Contact {
+                    created_at: existing.created_at,
+                    ..contact
+                };
+                false
+            }
+            None => {
+                self.contacts.push(contact);
+                true
+            }
+        }
+    }
+
+    fn remove(&mut self, address: &str) -> bool {
+        let before = self.contacts.len();
+        self.contacts.retain(|c| c.address != address);
+        before != self.contacts.len()
+    }
+
+    fn list(&self, network: Option<&str>) -> Vec<Contact> {
+        let mut contacts: Vec<Contact> = self
+            .contacts
+            .iter()
+            .filter(|c| network.map_or(true, |n| c.network == n))
+            .cloned()
+            .collect();
+        contacts.sort_by_key(|c| c.label.to_lowercase());
+        contacts
+    }
+
+    fn labels(&self) -> HashMap<String, String> {
+        self.contacts
+            .iter()
+            .map(|c| (c.address.clone(), c.label.clone()))
+            .collect()
+    }
+
+    fn import(&mut self, contacts: Vec<Contact>, now: u64) -> AddressBookImportSummary {
+        let mut summary = AddressBookImportSummary::default();
+        for contact in contacts {
+            match Contact::new(&contact.label, &contact.address, contact.note, now) {
+                Ok(validated) => {
+                    if self.upsert(validated) {
+                        summary.added += 1;
+                    } else {
+                        summary.updated += 1;
+                    }
+                }
+                Err(e) => summary.skipped.push(SkippedContact {
+                    address: contact.address,
+                    reason: e.to_string(),
+                }),
+            }
+        }
+        summary
+    }
+}
+
+struct AddressBookState {
+    store: AddressBookStore,
+    path: Option<PathBuf>,
+}
+
+/// Named Tari addresses kept in the app config directory, used to label counterparties in history and exports.
+pub struct AddressBook {
+    state: Mutex<AddressBookState>,
+}
+
+impl AddressBook {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(AddressBookState {
+                store: AddressBookStore::default(),
+                path: None,
+            }),
+        }
+    }
+
+    pub fn current() -> &'static AddressBook {
+        &INSTANCE
+    }
+
+    pub async fn load(&self, app_handle: &AppHandle) -> Result<(), Error> {
+        let path = app_handle.path().app_config_dir()?.join(ADDRESS_BOOK_FILE);
+        let store = load_json_store(&path).await?;
+        let mut state = self.state.lock().await;
+        state.store = store;
+        state.path = Some(path);
+        Ok(())
+    }
+
+    async fn save(&self, state: &AddressBookState) -> Result<(), Error> {
+        let Some(path) = state.path.clone() else {
+            return Err(anyhow!("Address book has not been loaded"));
+        };
+        save_json_store(&path, &state.store).await
+    }
+
+    /// Contacts of the current network, or every stored contact when `all_networks` is set
+    pub async fn list(&self, all_networks: bool) -> Vec<Contact> {
+        let network = current_network_key();
+        let state = self.state.lock().await;
+        state
+            .store
+            .list((!all_networks).then_some(network.as_str()))
+    }
+
+    pub async fn save_contact(
+        &self,
+        label: String,
+        address: String,
+        note: Option<String>,
+    ) -> Result<Contact, Error> {
+        let contact = Contact::new(&label, &address, note, unix_now())?;
+        let mut state = self.state.lock().await;
+        state.store.upsert(contact.clone());
+        self.save(&state).await?;
+        info!(target: LOG_TARGET, "Saved contact {} ({})", contact.label, contact.address);
+        Ok(contact)
+    }
+
+    pub async fn remove_contact(&self, address: String) -> Result<bool, Error> {
+        let key = address_key(&address)?;
+        let mut state = self.state.lock().await;
+        let removed = state.store.remove(&key);
+        if removed {
+            self.save(&state).await?;
+        }
+        Ok(removed)
+    }
+
+    /// Address to label map used to resolve counterparties
+    pub async fn labels(&self) -> HashMap<String, String> {
+        self.state.lock().await.store.labels()
+    }
+
+    /// Labels for the counterparties appearing in `transactions`
+    pub async fn labels_for(&self, transactions: &[TransactionInfo]) -> HashMap<String, String> {
+        let labels = self.labels().await;
+        transactions
+            .iter()
+            .flat_map(|tx| [&tx.source_address, &tx.dest_address])
+            .filter_map(|address| {
+                labels
+                    .get(address)
+                    .map(|label| (address.clone(), label.clone()))
+            })
+            .collect()
+    }
+
+    pub async fn export(&self, path: &Path) -> Result<usize, Error> {
+        let contacts = self.state.lock().await.store.list(None);
+        let exported = contacts.len();
+        let content = serde_json::to_string_pretty(&AddressBookExport {
+            version: EXPORT_VERSION,
+            contacts,
+        })?;
+        tokio::fs::write(path, content).await?;
+        Ok(exported)
+    }
+
+    /// Imports contacts from an exported file, revalidating each one. Invalid or foreign-network entries are
+    /// reported as skipped rather than failing the whole import.
+    pub async fn import(&self, path: &Path) -> Result<AddressBookImportSummary, Error> {
+        let content = tokio::fs::read_to_string(path).await?;
+        let export: AddressBookExport = serde_json::from_str(&content)
+            .map_err(|e| anyhow!("Not an address book export: {}", e))?;
+        if export.version > EXPORT_VERSION {
+            return Err(anyhow!(
+                "Address book export version {} is not supported",
+                export.version
+            ));
+        }
+
+        self.import_contacts(export.contacts).await
+    }
+
+    /// Merges contacts from an export or a wallet backup, revalidating each one
+    pub async fn import_contacts(
+        &self,
+        contacts: Vec<Contact>,
+    ) -> Result<AddressBookImportSummary, Error> {
+        let mut state = self.state.lock().await;
+        let summary = state.store.import(contacts, unix_now());
+        if summary.added + summary.updated > 0 {
+            self.save(&state).await?;
+        }
+        info!(target: LOG_TARGET, "Imported address book: {} added, {} updated, {} skipped", summary.added, summary.updated, summary.skipped.len());
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ESME_ONE_SIDED_ADDRESS: &str = "f25eNHz2YnBVKHaqNuacGyDFB321RwwCnTr4vb2SjQCgDZVXyNNthc7zftQKRDu6evLjvSUD8W5akpPMdhS4HQ9kF3g";
+    const ESME_INTERACTIVE_ADDRESS: &str = "f45eNHz2YnBVKHaqNuacGyDFB321RwwCnTr4vb2SjQCgDZVXyNNthc7zftQKRDu6evLjvSUD8W5akpPMdhS4HQ9kF31";
+    const ESME_ONE_SIDED_EMOJI_ADDRESS: &str = "🍗📟😇🦀🚽💈🎠🍚🦂🌕🎩💨👂🏰📜👞🎵🐬🐚💄🚨🔋🐀🐯💻👗🐊👠🦀🐝🚦🍌🎋🎼🍗🎮🎉👗🐮🎨👾🔧🤖💋🐾💨🎃🍀🦂🐀🐬🔱🥝👕🎳⏰🎃🐉💍🙈🍉🔱🎣🐢👒🍊💦";
+    const NEXTNET_ONE_SIDED_ADDRESSS: &str = "32FZ9MmtkbcxNwF1Qia1RykS2i3ycaJC5er32xaFi1fpkNKjKvVo6VFPKjoigSME76EmsaDPZLXu2e3ivp5MWSU54j1";
+
+    #[test]
+    fn contact_is_tagged_with_features() {
+        let one_sided = Contact::new("Pool", ESME_ONE_SIDED_ADDRESS, None, 0).unwrap();
+        assert!(one_sided.supports_one_sided);
+        assert!(!one_sided.supports_interactive);
+
+        let interactive = Contact::new("Friend", ESME_INTERACTIVE_ADDRESS, None, 0).unwrap();
+        assert!(interactive.supports_interactive);
+    }
+
+    #[test]
+    fn contact_rejects_invalid_input() {
+        assert!(Contact::new("  ", ESME_ONE_SIDED_ADDRESS, None, 0).is_err());
+        assert!(Contact::new(
+            &"x".repeat(MAX_LABEL_LENGTH + 1),
+            ESME_ONE_SIDED_ADDRESS,
+            None,
+            0
+        )
+        .is_err());
+        assert!(Contact::new("Bad", "not an address", None, 0).is_err());
+        assert!(Contact::new("Other network", NEXTNET_ONE_SIDED_ADDRESSS, None, 0).is_err());
+    }
+
+    #[test]
+    fn emoji_and_base58_addresses_share_a_key() {
+        let mut store = AddressBookStore::default();
+        assert!(store.upsert(Contact::new("Pool", ESME_ONE_SIDED_ADDRESS, None, 1).unwrap()));
+        assert!(
+            !store.upsert(Contact::new("Renamed", ESME_ONE_SIDED_EMOJI_ADDRESS, None, 2).unwrap())
+        );
+
+        let contacts = store.list(None);
+        assert_eq!(contacts.len(), 1);
+        assert_eq!(contacts[0].label, "Renamed");
+        assert_eq!(contacts[0].created_at, 1);
+        assert_eq!(
+            store.labels().get(ESME_ONE_SIDED_ADDRESS),
+            Some(&"Renamed".to_string())
+        );
+    }
+
+    #[test]
+    fn import_skips_invalid_contacts() {
+        let mut store = AddressBookStore::default();
+        let valid = Contact::new("Pool", ESME_ONE_SIDED_ADDRESS, None, 0).unwrap();
+        let mut foreign = valid.clone();
+        foreign.address = NEXTNET_ONE_SIDED_ADDRESSS.to_string();
+
+        let summary = store.import(vec![valid, foreign], 10);
+        assert_eq!(summary.added, 1);
+        assert_eq!(summary.updated, 0);
+        assert_eq!(summary.skipped.len(), 1);
+        assert_eq!(summary.skipped[0].address, NEXTNET_ONE_SIDED_ADDRESSS);
+    }
+}
@@ -381,11 +381,42 @@ impl AppConfig {
             None
         };
 
-        self.move_out_of_original_location(old_config_path).await;
+        self.move_out_of_original_location(old_config_path.clone())
+            .await;
+        self.remove_secrets_from_moved_config(old_config_path).await;
 
         old_config_content
     }
 
+    /// The moved file is only kept for reference, the secrets it held are migrated into the sealed core config
+    async fn remove_secrets_from_moved_config(&self, config_path: PathBuf) {
+        let file = config_path.join("old").join("app_config.json");
+        let Ok(contents) = fs::read_to_string(&file).await else {
+            return;
+        };
+        let Ok(mut config) = serde_json::from_str::<serde_json::Value>(&contents) else {
+            return;
+        };
+        let Some(fields) = config.as_object_mut() else {
+            return;
+        };
+        let removed_tokens = fields.remove("airdrop_tokens").is_some();
+        let removed_address = fields.remove("remote_base_node_address").is_some();
+        if !removed_tokens && !removed_address {
+            return;
+        }
+        match serde_json::to_string_pretty(&config) {
+            Ok(contents) => {
+                let _unused = fs::write(&file, contents).await.inspect_err(|e| {
+                    warn!(target: LOG_TARGET, "Failed to remove secrets from old app_config.json: {}", e);
+                });
+            }
+            Err(e) => {
+                warn!(target: LOG_TARGET, "Failed to remove secrets from old app_config.json: {}", e);
+            }
+        }
+    }
+
     pub async fn load_or_create(&mut self, config_path: PathBuf) -> Result<(), anyhow::Error> {
         let file: PathBuf = config_path.join("app_config.json");
         self.config_file = Some(file.clone());
@@ -414,7 +445,7 @@ impl AppConfig {
     pub fn apply_loaded_config(&mut self, config: String) {
         match serde_json::from_str::<AppConfigFromFile>(&config) {
             Ok(config) => {
-                debug!("Loaded config from file {:?}", config);
+                debug!("Loaded config from file");
                 self.config_version = config.version;
                 self.mode = MiningMode::from_str(&config.mode).unwrap_or(MiningMode::Eco);
                 self.display_mode =
@@ -1074,8 +1105,8 @@ async fn create_monereo_address(path: PathBuf) -> Result<String, anyhow::Error>
 
     let monero_seed = MoneroSeed::generate()?;
     let cred = Credential {
-        tari_seed_passphrase: None,
         monero_seed: Some(*monero_seed.inner()),
+        ..Default::default()
     };
 
     info!(target: LOG_TARGET, "Setting monero seed in credential manager");
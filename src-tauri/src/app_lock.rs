@@ -0,0 +1,431 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+use std::{path::PathBuf, sync::LazyLock};
+
+use anyhow::{anyhow, Error};
+use argon2::{
+    password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use log::{info, warn};
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tokio::sync::{Mutex, MutexGuard};
+
+use crate::{
+    credential_manager::{Credential, CredentialError, CredentialManager},
+    utils::time_utils::unix_now,
+};
+
+const LOG_TARGET: &str = "tari::universe::app_lock";
+pub const APP_LOCKED_ERROR: &str = "The app is locked, unlock it to continue";
+pub const MIN_SECRET_LENGTH: usize = 4;
+pub const MAX_SECRET_LENGTH: usize = 128;
+pub const DEFAULT_AUTO_LOCK_SECS: u64 = 5 * 60;
+const MIN_AUTO_LOCK_SECS: u64 = 30;
+const MAX_AUTO_LOCK_SECS: u64 = 24 * 60 * 60;
+/// Wrong attempts allowed before each lockout
+const ATTEMPTS_BEFORE_LOCKOUT: u32 = 5;
+const BASE_LOCKOUT_SECS: u64 = 60;
+const MAX_LOCKOUT_SECS: u64 = 60 * 60;
+
+static INSTANCE: LazyLock<AppLock> = LazyLock::new(AppLock::new);
+
+/// Lockout after every batch of wrong attempts, doubling per batch up to an hour
+fn lockout_secs(failed_attempts: u32) -> u64 {
+    if failed_attempts < ATTEMPTS_BEFORE_LOCKOUT || failed_attempts % ATTEMPTS_BEFORE_LOCKOUT != 0 {
+        return 0;
+    }
+    let batch = failed_attempts / ATTEMPTS_BEFORE_LOCKOUT - 1;
+    BASE_LOCKOUT_SECS
+        .saturating_mul(1u64 << batch.min(16))
+        .min(MAX_LOCKOUT_SECS)
+}
+
+fn validate_secret(secret: &str) -> Result<(), Error> {
+    let length = secret.chars().count();
+    if length < MIN_SECRET_LENGTH {
+        return Err(anyhow!(
+            "PIN or passphrase must be at least {} characters",
+            MIN_SECRET_LENGTH
+        ));
+    }
+    if length > MAX_SECRET_LENGTH {
+        return Err(anyhow!(
+            "PIN or passphrase cannot be longer than {} characters",
+            MAX_SECRET_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+fn hash_secret(secret: &str) -> Result<String, Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(secret.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| anyhow!("Could not hash PIN: {}", e))
+}
+
+fn verify_secret(secret: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|hash| {
+            Argon2::default()
+                .verify_password(secret.as_bytes(), &hash)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+/// Persisted part of the lock, kept in the credential store so it cannot be removed or reset by editing a file.
+/// Failed attempts survive restarts so a lockout cannot be skipped by relaunching.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppLockStore {
+    secret_hash: Option<String>,
+    auto_lock_secs: u64,
+    failed_attempts: u32,
+    locked_until: Option<u64>,
+}
+
+impl Default for AppLockStore {
+    fn default() -> Self {
+        Self {
+            secret_hash: None,
+            auto_lock_secs: DEFAULT_AUTO_LOCK_SECS,
+            failed_attempts: 0,
+            locked_until: None,
+        }
+    }
+}
+
+impl AppLockStore {
+    fn is_enabled(&self) -> bool {
+        self.secret_hash.is_some()
+    }
+
+    fn ensure_not_locked_out(&self, now: u64) -> Result<(), Error> {
+        match self.locked_until {
+            Some(until) if until > now => Err(anyhow!(
+                "Too many wrong attempts, try again in {} seconds",
+                until - now
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the secret, counting wrong attempts towards a lockout
+    fn check_secret(&mut self, secret: &str, now: u64) -> Result<(), Error> {
+        let Some(hash) = &self.secret_hash else {
+            return Ok(());
+        };
+        self.ensure_not_locked_out(now)?;
+        if verify_secret(secret, hash) {
+            self.failed_attempts = 0;
+            self.locked_until = None;
+            return Ok(());
+        }
+
+        self.failed_attempts += 1;
+        let lockout = lockout_secs(self.failed_attempts);
+        if lockout > 0 {
+            self.locked_until = Some(now + lockout);
+            warn!(target: LOG_TARGET, "App lock locked out for {}s after {} wrong attempts", lockout, self.failed_attempts);
+        }
+        Err(anyhow!("Wrong PIN or passphrase"))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppLockStatus {
+    pub enabled: bool,
+    pub locked: bool,
+    pub auto_lock_secs: u64,
+    pub failed_attempts: u32,
+    pub locked_until: Option<u64>,
+}
+
+struct AppLockState {
+    store: AppLockStore,
+    /// Config dir the store was loaded for. `None` until the first use.
+    config_dir: Option<PathBuf>,
+    /// Unix time of the last sensitive action while unlocked. `None` means locked.
+    last_activity: Option<u64>,
+}
+
+impl AppLockState {
+    fn is_unlocked(&self, now: u64) -> bool {
+        !self.store.is_enabled()
+            || self
+                .last_activity
+                .is_some_and(|last| now.saturating_sub(last) < self.store.auto_lock_secs)
+    }
+
+    fn status(&self, now: u64) -> AppLockStatus {
+        AppLockStatus {
+            enabled: self.store.is_enabled(),
+            locked: !self.is_unlocked(now),
+            auto_lock_secs: self.store.auto_lock_secs,
+            failed_attempts: self.store.failed_attempts,
+            locked_until: self.store.locked_until.filter(|until| *until > now),
+        }
+    }
+}
+
+/// Optional PIN or passphrase guarding seed access, sending and payout address changes. The app starts locked and
+/// locks itself again after `auto_lock_secs` without a guarded action.
+pub struct AppLock {
+    state: Mutex<AppLockState>,
+}
+
+impl AppLock {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(AppLockState {
+                store: AppLockStore::default(),
+                config_dir: None,
+                last_activity: None,
+            }),
+        }
+    }
+
+    pub fn current() -> &'static AppLock {
+        &INSTANCE
+    }
+
+    /// Loads the store on first use, so guards never run against an unloaded, seemingly disabled lock
+    async fn loaded_state(
+        &self,
+        app_handle: &AppHandle,
+    ) -> Result<MutexGuard<'_, AppLockState>, Error> {
+        let mut state = self.state.lock().await;
+        if state.config_dir.is_none() {
+            let config_dir = app_handle.path().app_config_dir()?;
+            state.store = match CredentialManager::default_with_dir(config_dir.clone())
+                .get_credentials()
+                .await
+            {
+                Ok(credential) => credential.app_lock.unwrap_or_default(),
+                Err(CredentialError::NoEntry(_)) => AppLockStore::default(),
+                // Falling back to an unlocked app would defeat the lock, so refuse instead
+                Err(e) => return Err(anyhow!("App lock settings are unreadable: {}", e)),
+            };
+            state.config_dir = Some(config_dir);
+        }
+        Ok(state)
+    }
+
+    async fn save(&self, state: &AppLockState) -> Result<(), Error> {
+        let Some(config_dir) = state.config_dir.clone() else {
+            return Err(anyhow!("App lock has not been loaded"));
+        };
+        let cm = CredentialManager::default_with_dir(config_dir);
+        let mut credential = match cm.get_credentials().await {
+            Ok(credential) => credential,
+            Err(CredentialError::NoEntry(_)) => Credential::default(),
+            Err(e) => return Err(e.into()),
+        };
+        credential.app_lock = Some(state.store.clone());
+        cm.set_credentials(&credential).await?;
+        Ok(())
+    }
+
+    pub async fn status(&self, app_handle: &AppHandle) -> Result<AppLockStatus, Error> {
+        Ok(self.loaded_state(app_handle).await?.status(unix_now()))
+    }
+
+    /// Guard for sensitive commands. Each successful check extends the unlocked session.
+    pub async fn ensure_unlocked(&self, app_handle: &AppHandle) -> Result<(), Error> {
+        let mut state = self.loaded_state(app_handle).await?;
+        let now = unix_now();
+        if !state.is_unlocked(now) {
+            state.last_activity = None;
+            return Err(anyhow!(APP_LOCKED_ERROR));
+        }
+        if state.store.is_enabled() {
+            state.last_activity = Some(now);
+        }
+        Ok(())
+    }
+
+    pub async fn unlock(
+        &self,
+        app_handle: &AppHandle,
+        secret: &str,
+    ) -> Result<AppLockStatus, Error> {
+        let mut state = self.loaded_state(app_handle).await?;
+        let now = unix_now();
+        let result = state.store.check_secret(secret, now);
+        self.save(&state).await?;
+        result?;
+        state.last_activity = Some(now);
+        info!(target: LOG_TARGET, "App unlocked");
+        Ok(state.status(now))
+    }
+
+    pub async fn lock(&self, app_handle: &AppHandle) -> Result<AppLockStatus, Error> {
+        let mut state = self.loaded_state(app_handle).await?;
+        state.last_activity = None;
+        Ok(state.status(unix_now()))
+    }
+
+    /// Sets or changes the secret. Changing it requires the current one.
+    pub async fn set_secret(
+        &self,
+        app_handle: &AppHandle,
+        current_secret: Option<String>,
+        new_secret: &str,
+    ) -> Result<AppLockStatus, Error> {
+        validate_secret(new_secret)?;
+        let mut state = self.loaded_state(app_handle).await?;
+        let now = unix_now();
+        if state.store.is_enabled() {
+            let result = state
+                .store
+                .check_secret(current_secret.as_deref().unwrap_or_default(), now);
+            self.save(&state).await?;
+            result?;
+        }
+        state.store.secret_hash = Some(hash_secret(new_secret)?);
+        self.save(&state).await?;
+        state.last_activity = Some(now);
+        info!(target: LOG_TARGET, "App lock secret set");
+        Ok(state.status(now))
+    }
+
+    pub async fn remove_secret(
+        &self,
+        app_handle: &AppHandle,
+        current_secret: &str,
+    ) -> Result<AppLockStatus, Error> {
+        let mut state = self.loaded_state(app_handle).await?;
+        let now = unix_now();
+        let result = state.store.check_secret(current_secret, now);
+        if result.is_ok() {
+            state.store.secret_hash = None;
+        }
+        self.save(&state).await?;
+        result?;
+        info!(target: LOG_TARGET, "App lock removed");
+        Ok(state.status(now))
+    }
+
+    pub async fn set_auto_lock_secs(
+        &self,
+        app_handle: &AppHandle,
+        auto_lock_secs: u64,
+    ) -> Result<AppLockStatus, Error> {
+        if !(MIN_AUTO_LOCK_SECS..=MAX_AUTO_LOCK_SECS).contains(&auto_lock_secs) {
+            return Err(anyhow!(
+                "Auto-lock timeout must be between {} and {} seconds",
+                MIN_AUTO_LOCK_SECS,
+                MAX_AUTO_LOCK_SECS
+            ));
+        }
+        self.ensure_unlocked(app_handle).await?;
+        let mut state = self.loaded_state(app_handle).await?;
+        state.store.auto_lock_secs = auto_lock_secs;
+        self.save(&state).await?;
+        Ok(state.status(unix_now()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store_with_secret(secret: &str) -> AppLockStore {
+        AppLockStore {
+            secret_hash: Some(hash_secret(secret).unwrap()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn lockout_grows_per_batch_of_failures() {
+        assert_eq!(lockout_secs(ATTEMPTS_BEFORE_LOCKOUT - 1), 0);
+        assert_eq!(lockout_secs(ATTEMPTS_BEFORE_LOCKOUT), BASE_LOCKOUT_SECS);
+        assert_eq!(lockout_secs(ATTEMPTS_BEFORE_LOCKOUT + 1), 0);
+        assert_eq!(
+            lockout_secs(ATTEMPTS_BEFORE_LOCKOUT * 2),
+            BASE_LOCKOUT_SECS * 2
+        );
+        assert_eq!(lockout_secs(ATTEMPTS_BEFORE_LOCKOUT * 50), MAX_LOCKOUT_SECS);
+    }
+
+    #[test]
+    fn wrong_attempts_lock_out() {
+        let mut store = store_with_secret("1234");
+        for _ in 0..ATTEMPTS_BEFORE_LOCKOUT {
+            assert!(store.check_secret("0000", 100).is_err());
+        }
+        assert_eq!(store.locked_until, Some(100 + BASE_LOCKOUT_SECS));
+        // Even the right secret is refused until the lockout ends
+        assert!(store.check_secret("1234", 101).is_err());
+
+        store.check_secret("1234", 100 + BASE_LOCKOUT_SECS).unwrap();
+        assert_eq!(store.failed_attempts, 0);
+        assert_eq!(store.locked_until, None);
+    }
+
+    #[test]
+    fn session_expires_after_timeout() {
+        let state = AppLockState {
+            store: store_with_secret("1234"),
+            config_dir: None,
+            last_activity: Some(1000),
+        };
+        assert!(state.is_unlocked(1000 + DEFAULT_AUTO_LOCK_SECS - 1));
+        assert!(!state.is_unlocked(1000 + DEFAULT_AUTO_LOCK_SECS));
+
+        let disabled = AppLockState {
+            store: AppLockStore::default(),
+            config_dir: None,
+            last_activity: None,
+        };
+        assert!(disabled.is_unlocked(0));
+    }
+
+    #[test]
+    fn store_round_trips_through_credential() {
+        let mut store = store_with_secret("1234");
+        store.failed_attempts = 3;
+        let credential = Credential {
+            app_lock: Some(store),
+            ..Default::default()
+        };
+        let restored: Credential =
+            serde_cbor::from_slice(&serde_cbor::to_vec(&credential).unwrap()).unwrap();
+        let mut restored = restored.app_lock.unwrap();
+        assert!(restored.is_enabled());
+        assert_eq!(restored.failed_attempts, 3);
+        assert!(restored.check_secret("1234", 0).is_ok());
+    }
+
+    #[test]
+    fn secret_length_is_validated() {
+        assert!(validate_secret("123").is_err());
+        assert!(validate_secret(&"x".repeat(MAX_SECRET_LENGTH + 1)).is_err());
+        assert!(validate_secret("1234").is_ok());
+    }
+}
@@ -0,0 +1,559 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+use std::{collections::HashMap, path::PathBuf, sync::LazyLock, time::Duration};
+
+use anyhow::{anyhow, Error};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use tari_common_types::tari_address::TariAddressFeatures;
+use tari_core::transactions::tari_amount::MicroMinotari;
+use tauri::{AppHandle, Manager};
+use tokio::sync::{Mutex, MutexGuard};
+
+use crate::{
+    configs::{config_secrets, config_wallet::ConfigWallet, trait_config::ConfigImpl},
+    payment_request::{format_minotari_amount, validate_payment_id},
+    tasks_tracker::TasksTrackers,
+    utils::{
+        address_utils::verify_send,
+        store_utils::{load_json_store, save_json_store},
+        time_utils::unix_now,
+    },
+    wallet_profiles::active_wallet_id,
+    UniverseAppState,
+};
+
+const LOG_TARGET: &str = "tari::universe::auto_sweep";
+const AUTO_SWEEP_FILE: &str = "auto_sweep.json";
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// Lower bound between two sweeps whatever the trigger, so a misbehaving balance can never cause a burst of sends
+pub const MIN_SWEEP_INTERVAL_SECS: u64 = 60 * 60;
+/// Kept back on top of the reserve to pay the fee of a transaction spending many coinbase outputs
+pub const SWEEP_FEE_MARGIN: MicroMinotari = MicroMinotari(100_000);
+/// Sweeps smaller than this are not worth their fee
+pub const MIN_SWEEP_AMOUNT: MicroMinotari = MicroMinotari(1_000_000);
+const MAX_HISTORY: usize = 100;
+
+static INSTANCE: LazyLock<AutoSweep> = LazyLock::new(AutoSweep::new);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SweepTrigger {
+    /// Sweep once the available balance reaches `amount`
+    Threshold { amount: MicroMinotari },
+    /// Sweep whatever is above the reserve every `interval_secs`
+    Schedule { interval_secs: u64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AutoSweepSettings {
+    pub enabled: bool,
+    pub destination: String,
+    pub trigger: SweepTrigger,
+    pub reserve: MicroMinotari,
+    #[serde(default)]
+    pub payment_id: Option<String>,
+}
+
+impl AutoSweepSettings {
+    fn validate(mut self) -> Result<Self, Error> {
+        verify_send(self.destination.clone(), TariAddressFeatures::ONE_SIDED)
+            .map_err(|e| anyhow!(e))?;
+        if let SweepTrigger::Schedule { interval_secs } = self.trigger {
+            if interval_secs < MIN_SWEEP_INTERVAL_SECS {
+                return Err(anyhow!(
+                    "Sweep interval must be at least {} seconds",
+                    MIN_SWEEP_INTERVAL_SECS
+                ));
+            }
+        }
+        self.payment_id = self
+            .payment_id
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty());
+        if let Some(payment_id) = &self.payment_id {
+            validate_payment_id(payment_id).map_err(|e| anyhow!(e))?;
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SweepRecord {
+    pub swept_at: u64,
+    pub amount: MicroMinotari,
+    pub destination: String,
+    pub tx_id: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct AutoSweepStore {
+    /// Rules by wallet id, so a wallet only ever sweeps its own balance
+    rules: HashMap<String, AutoSweepRule>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct AutoSweepRule {
+    settings: Option<AutoSweepSettings>,
+    /// Destination the user explicitly confirmed. Sweeps only go out while it matches the settings.
+    confirmed_destination: Option<String>,
+    /// MAC over the wallet id, settings and confirmed destination under the config key. An edited file no longer
+    /// verifies, so it cannot arm a sweep.
+    mac: Option<String>,
+    last_sweep_at: Option<u64>,
+    history: Vec<SweepRecord>,
+}
+
+impl AutoSweepRule {
+    fn authenticated_fields<'a>(
+        &'a self,
+        wallet_id: &'a str,
+    ) -> (&'a str, &'a Option<AutoSweepSettings>, &'a Option<String>) {
+        (wallet_id, &self.settings, &self.confirmed_destination)
+    }
+
+    fn is_authentic(&self, wallet_id: &str) -> bool {
+        self.mac.as_deref().is_some_and(|mac| {
+            config_secrets::verify_authenticated(&self.authenticated_fields(wallet_id), mac)
+        })
+    }
+
+    /// Tags the rule after a change made by the user. Only a confirmed rule needs a tag.
+    fn authenticate(&mut self, wallet_id: &str) -> Result<(), Error> {
+        self.mac = match self.confirmed_destination {
+            Some(_) => Some(
+                config_secrets::authenticate(&self.authenticated_fields(wallet_id))?
+                    .ok_or_else(|| anyhow!("The config key is not available, try again later"))?,
+            ),
+            None => None,
+        };
+        Ok(())
+    }
+
+    fn destination_matches(&self) -> bool {
+        self.settings.as_ref().is_some_and(|settings| {
+            self.confirmed_destination.as_deref() == Some(settings.destination.as_str())
+        })
+    }
+
+    fn is_confirmed(&self, wallet_id: &str) -> bool {
+        self.destination_matches() && self.is_authentic(wallet_id)
+    }
+
+    fn is_armed(&self) -> bool {
+        self.destination_matches()
+            && self
+                .settings
+                .as_ref()
+                .is_some_and(|settings| settings.enabled)
+    }
+
+    /// Amount to sweep now, if any
+    fn due_amount(&self, available: MicroMinotari, now: u64) -> Option<MicroMinotari> {
+        if !self.is_armed() {
+            return None;
+        }
+        let settings = self.settings.as_ref()?;
+        if self
+            .last_sweep_at
+            .is_some_and(|last| now < last + MIN_SWEEP_INTERVAL_SECS)
+        {
+            return None;
+        }
+
+        let sweepable = available
+            .as_u64()
+            .checked_sub(settings.reserve.as_u64())?
+            .checked_sub(SWEEP_FEE_MARGIN.as_u64())?;
+        if sweepable < MIN_SWEEP_AMOUNT.as_u64() {
+            return None;
+        }
+        let due = match settings.trigger {
+            SweepTrigger::Threshold { amount } => available >= amount,
+            SweepTrigger::Schedule { interval_secs } => self
+                .last_sweep_at
+                .map_or(true, |last| now >= last + interval_secs),
+        };
+        due.then_some(MicroMinotari(sweepable))
+    }
+
+    fn record(&mut self, record: SweepRecord) {
+        self.last_sweep_at = Some(record.swept_at);
+        self.history.push(record);
+        if self.history.len() > MAX_HISTORY {
+            let excess = self.history.len() - MAX_HISTORY;
+            self.history.drain(..excess);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AutoSweepStatus {
+    pub settings: Option<AutoSweepSettings>,
+    pub destination_confirmed: bool,
+    pub last_sweep_at: Option<u64>,
+    pub history: Vec<SweepRecord>,
+}
+
+struct AutoSweepState {
+    store: AutoSweepStore,
+    path: Option<PathBuf>,
+}
+
+/// Sends mined rewards above a reserve to a confirmed address through the spend wallet
+pub struct AutoSweep {
+    state: Mutex<AutoSweepState>,
+    /// Held for the whole send, so two checks can never sweep the same funds
+    send_lock: Mutex<()>,
+}
+
+impl AutoSweep {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(AutoSweepState {
+                store: AutoSweepStore::default(),
+                path: None,
+            }),
+            send_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn current() -> &'static AutoSweep {
+        &INSTANCE
+    }
+
+    async fn loaded_state(
+        &self,
+        app_handle: &AppHandle,
+    ) -> Result<MutexGuard<'_, AutoSweepState>, Error> {
+        let mut state = self.state.lock().await;
+        if state.path.is_none() {
+            let path = app_handle.path().app_config_dir()?.join(AUTO_SWEEP_FILE);
+            state.store = load_json_store(&path).await?;
+            state.path = Some(path);
+        }
+        Ok(state)
+    }
+
+    async fn save(&self, state: &AutoSweepState) -> Result<(), Error> {
+        let Some(path) = state.path.clone() else {
+            return Err(anyhow!("Auto sweep settings have not been loaded"));
+        };
+        save_json_store(&path, &state.store).await
+    }
+
+    pub async fn status(&self, app_handle: &AppHandle) -> Result<AutoSweepStatus, Error> {
+        let wallet_id = active_wallet_id().await;
+        let state = self.loaded_state(app_handle).await?;
+        let rule = state
+            .store
+            .rules
+            .get(&wallet_id)
+            .cloned()
+            .unwrap_or_default();
+        Ok(Self::status_of(&rule, &wallet_id))
+    }
+
+    fn status_of(rule: &AutoSweepRule, wallet_id: &str) -> AutoSweepStatus {
+        AutoSweepStatus {
+            settings: rule.settings.clone(),
+            destination_confirmed: rule.is_confirmed(wallet_id),
+            last_sweep_at: rule.last_sweep_at,
+            history: rule.history.clone(),
+        }
+    }
+
+    /// Saves the settings. A new destination has to be confirmed again before anything is swept to it.
+    pub async fn update_settings(
+        &self,
+        app_handle: &AppHandle,
+        settings: AutoSweepSettings,
+    ) -> Result<AutoSweepStatus, Error> {
+        let settings = settings.validate()?;
+        let wallet_id = active_wallet_id().await;
+        let mut state = self.loaded_state(app_handle).await?;
+        let mut rule = state
+            .store
+            .rules
+            .get(&wallet_id)
+            .cloned()
+            .unwrap_or_default();
+        // A confirmation only carries over when it is genuine and for the same address
+        if !rule.is_authentic(&wallet_id)
+            || rule.confirmed_destination.as_deref() != Some(settings.destination.as_str())
+        {
+            rule.confirmed_destination = None;
+        }
+        info!(target: LOG_TARGET, "Auto sweep settings of wallet {} updated: enabled {}, destination {}, trigger {:?}, reserve {}", wallet_id, settings.enabled, settings.destination, settings.trigger, settings.reserve);
+        rule.settings = Some(settings);
+        rule.authenticate(&wallet_id)?;
+        let status = Self::status_of(&rule, &wallet_id);
+        state.store.rules.insert(wallet_id, rule);
+        self.save(&state).await?;
+        Ok(status)
+    }
+
+    /// Confirms the destination once. The caller repeats the address so a silently swapped one is never armed.
+    pub async fn confirm_destination(
+        &self,
+        app_handle: &AppHandle,
+        destination: &str,
+    ) -> Result<AutoSweepStatus, Error> {
+        let wallet_id = active_wallet_id().await;
+        let mut state = self.loaded_state(app_handle).await?;
+        let Some(mut rule) = state.store.rules.get(&wallet_id).cloned() else {
+            return Err(anyhow!("Auto sweep is not configured"));
+        };
+        let Some(settings) = &rule.settings else {
+            return Err(anyhow!("Auto sweep is not configured"));
+        };
+        if settings.destination != destination.trim() {
+            return Err(anyhow!(
+                "Destination does not match the auto sweep settings"
+            ));
+        }
+        rule.confirmed_destination = Some(settings.destination.clone());
+        rule.authenticate(&wallet_id)?;
+        let status = Self::status_of(&rule, &wallet_id);
+        state.store.rules.insert(wallet_id.clone(), rule);
+        self.save(&state).await?;
+        info!(target: LOG_TARGET, "Auto sweep destination of wallet {} confirmed: {}", wallet_id, destination.trim());
+        Ok(status)
+    }
+
+    /// Checks the balance periodically for the lifetime of the wallet phase
+    pub async fn spawn(app_handle: AppHandle) {
+        let mut shutdown_signal = TasksTrackers::current().wallet_phase.get_signal().await;
+        TasksTrackers::current()
+            .wallet_phase
+            .get_task_tracker()
+            .await
+            .spawn(async move {
+                let mut interval = tokio::time::interval(CHECK_INTERVAL);
+                loop {
+                    tokio::select! {
+                        _ = interval.tick() => {
+                            if let Err(e) = AutoSweep::current().sweep_if_due(&app_handle).await {
+                                warn!(target: LOG_TARGET, "Auto sweep check failed: {:?}", e);
+                            }
+                        }
+                        _ = shutdown_signal.wait() => {
+                            break;
+                        }
+                    }
+                }
+            });
+    }
+
+    async fn sweep_if_due(&self, app_handle: &AppHandle) -> Result<(), Error> {
+        if *ConfigWallet::content().await.is_watch_only() {
+            return Ok(());
+        }
+        let app_state = app_handle.state::<UniverseAppState>();
+        let Some(available) = app_state
+            .wallet_state_watch_rx
+            .borrow()
+            .as_ref()
+            .and_then(|state| state.balance.as_ref())
+            .map(|balance| balance.available_balance)
+        else {
+            return Ok(());
+        };
+        let spend_wallet_manager = app_state.spend_wallet_manager.read().await.clone();
+        // Change of an earlier send is not spendable yet, wait for it to settle
+        if spend_wallet_manager
+            .get_outgoing_transactions()
+            .await
+            .iter()
+            .any(|tx| !tx.state.is_final())
+        {
+            return Ok(());
+        }
+
+        let Ok(_send_guard) = self.send_lock.try_lock() else {
+            return Ok(());
+        };
+        let now = unix_now();
+        let wallet_id = active_wallet_id().await;
+        let (amount, settings) = {
+            let state = self.loaded_state(app_handle).await?;
+            let Some(rule) = state.store.rules.get(&wallet_id) else {
+                return Ok(());
+            };
+            match (rule.due_amount(available, now), &rule.settings) {
+                (Some(amount), Some(settings)) => {
+                    if !rule.is_authentic(&wallet_id) {
+                        warn!(target: LOG_TARGET, "Auto sweep rule of wallet {} does not verify, not sweeping", wallet_id);
+                        return Ok(());
+                    }
+                    (amount, settings.clone())
+                }
+                _ => return Ok(()),
+            }
+        };
+
+        info!(target: LOG_TARGET, "Sweeping {} to {}", amount, settings.destination);
+        let target_height = app_state.node_status_watch_rx.borrow().block_height;
+        let result = spend_wallet_manager
+            .send_one_sided_to_stealth_address(
+                app_handle,
+                format_minotari_amount(amount),
+                settings.destination.clone(),
+                settings.payment_id.clone(),
+                None,
+                target_height,
+            )
+            .await;
+        let record = match &result {
+            Ok(tx_id) => {
+                info!(target: LOG_TARGET, "Swept {} to {} in transaction {}", amount, settings.destination, tx_id);
+                SweepRecord {
+                    swept_at: now,
+                    amount,
+                    destination: settings.destination,
+                    tx_id: Some(*tx_id),
+                    error: None,
+                }
+            }
+            Err(e) => {
+                error!(target: LOG_TARGET, "Sweep of {} to {} failed: {:?}", amount, settings.destination, e);
+                SweepRecord {
+                    swept_at: now,
+                    amount,
+                    destination: settings.destination,
+                    tx_id: None,
+                    error: Some(e.to_string()),
+                }
+            }
+        };
+        // Failed attempts count towards the rate limit too, a failing send is not retried every minute
+        let mut state = self.loaded_state(app_handle).await?;
+        if let Some(rule) = state.store.rules.get_mut(&wallet_id) {
+            rule.record(record);
+        }
+        self.save(&state).await?;
+        result.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DESTINATION: &str = "f25eNHz2YnBVKHaqNuacGyDFB321RwwCnTr4vb2SjQCgDZVXyNNthc7zftQKRDu6evLjvSUD8W5akpPMdhS4HQ9kF3g";
+
+    fn armed_rule(trigger: SweepTrigger) -> AutoSweepRule {
+        AutoSweepRule {
+            settings: Some(AutoSweepSettings {
+                enabled: true,
+                destination: DESTINATION.to_string(),
+                trigger,
+                reserve: MicroMinotari(5_000_000),
+                payment_id: None,
+            }),
+            confirmed_destination: Some(DESTINATION.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn threshold_compares_the_available_balance() {
+        let store = armed_rule(SweepTrigger::Threshold {
+            amount: MicroMinotari(10_000_000),
+        });
+        assert_eq!(store.due_amount(MicroMinotari(9_999_999), 0), None);
+        assert_eq!(
+            store.due_amount(MicroMinotari(10_000_000), 0),
+            Some(MicroMinotari(5_000_000 - SWEEP_FEE_MARGIN.as_u64()))
+        );
+    }
+
+    #[test]
+    fn sweeps_are_rate_limited() {
+        let mut store = armed_rule(SweepTrigger::Schedule {
+            interval_secs: 2 * MIN_SWEEP_INTERVAL_SECS,
+        });
+        assert!(store.due_amount(MicroMinotari(20_000_000), 0).is_some());
+        store.record(SweepRecord {
+            swept_at: 1000,
+            amount: MicroMinotari(1),
+            destination: DESTINATION.to_string(),
+            tx_id: Some(1),
+            error: None,
+        });
+        assert!(store
+            .due_amount(MicroMinotari(20_000_000), 1000 + MIN_SWEEP_INTERVAL_SECS)
+            .is_none());
+        assert!(store
+            .due_amount(
+                MicroMinotari(20_000_000),
+                1000 + 2 * MIN_SWEEP_INTERVAL_SECS
+            )
+            .is_some());
+    }
+
+    #[test]
+    fn unconfirmed_destination_is_never_swept() {
+        let mut store = armed_rule(SweepTrigger::Threshold {
+            amount: MicroMinotari(1_000_000),
+        });
+        store.confirmed_destination = Some("another address".to_string());
+        assert!(store.due_amount(MicroMinotari(100_000_000), 0).is_none());
+
+        store.confirmed_destination = None;
+        assert!(store.due_amount(MicroMinotari(100_000_000), 0).is_none());
+    }
+
+    #[test]
+    fn rules_without_a_valid_mac_are_not_confirmed() {
+        let mut rule = armed_rule(SweepTrigger::Threshold {
+            amount: MicroMinotari(1_000_000),
+        });
+        assert!(!rule.is_confirmed("default"));
+        rule.mac = Some("00".repeat(32));
+        assert!(!rule.is_confirmed("default"));
+    }
+
+    #[test]
+    fn settings_validation() {
+        let settings = armed_rule(SweepTrigger::Schedule {
+            interval_secs: MIN_SWEEP_INTERVAL_SECS - 1,
+        })
+        .settings
+        .unwrap();
+        assert!(settings.clone().validate().is_err());
+
+        let settings = AutoSweepSettings {
+            trigger: SweepTrigger::Schedule {
+                interval_secs: MIN_SWEEP_INTERVAL_SECS,
+            },
+            payment_id: Some("  ".to_string()),
+            ..settings
+        }
+        .validate()
+        .unwrap();
+        assert_eq!(settings.payment_id, None);
+    }
+}
@@ -23,9 +23,9 @@
 use crate::binaries::binaries_resolver::{
     LatestVersionApiAdapter, VersionAsset, VersionDownloadInfo,
 };
+use crate::data_locations::get_binaries_root;
 use crate::github::request_client::RequestClient;
 use crate::github::ReleaseSource;
-use crate::APPLICATION_FOLDER_ID;
 use anyhow::{anyhow, Error};
 use async_trait::async_trait;
 use log::{error, info};
@@ -147,11 +147,7 @@ impl LatestVersionApiAdapter for TorReleaseAdapter {
     }
 
     fn get_binary_folder(&self) -> Result<PathBuf, Error> {
-        let cache_path =
-            dirs::cache_dir().ok_or_else(|| anyhow::anyhow!("Failed to get cache directory"))?;
-
-        let binary_folder_path = cache_path
-            .join(APPLICATION_FOLDER_ID)
+        let binary_folder_path = get_binaries_root()?
             .join("binaries")
             .join("tor-binaries")
             .join(
@@ -30,8 +30,8 @@ use tari_common::configuration::Network;
 use tokio::{fs::File, io::AsyncReadExt};
 
 use crate::{
+    data_locations::get_binaries_root,
     github::{self, request_client::RequestClient},
-    APPLICATION_FOLDER_ID,
 };
 
 use super::binaries_resolver::{LatestVersionApiAdapter, VersionAsset, VersionDownloadInfo};
@@ -92,18 +92,11 @@ impl LatestVersionApiAdapter for XmrigVersionApiAdapter {
     }
 
     fn get_binary_folder(&self) -> Result<PathBuf, Error> {
-        let cache_path =
-            dirs::cache_dir().ok_or_else(|| anyhow::anyhow!("Failed to get cache directory"))?;
-
-        let binary_folder_path = cache_path
-            .join(APPLICATION_FOLDER_ID)
-            .join("binaries")
-            .join("xmrig")
-            .join(
-                Network::get_current_or_user_setting_or_default()
-                    .to_string()
-                    .to_lowercase(),
-            );
+        let binary_folder_path = get_binaries_root()?.join("binaries").join("xmrig").join(
+            Network::get_current_or_user_setting_or_default()
+                .to_string()
+                .to_lowercase(),
+        );
 
         if !binary_folder_path.exists() {
             std::fs::create_dir_all(&binary_folder_path).unwrap_or_else(|e| {
@@ -0,0 +1,528 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+use anyhow::{anyhow, Error};
+use serde::{Deserialize, Serialize};
+use tari_core::transactions::{fee::Fee, tari_amount::MicroMinotari, weight::TransactionWeight};
+
+/// Serialized features and script size assumed per output. Deliberately on the high side: an estimate below the
+/// wallet's own fee would make a join fail for insufficient funds, one above it only leaves a small change output.
+const ESTIMATED_OUTPUT_METADATA_SIZE: usize = 96;
+/// Outputs are dust when spending them costs more than this fraction of their value (1 / ratio)
+const DUST_SPEND_COST_RATIO: u64 = 100;
+/// Upper bound of inputs in a single join, keeping the transaction well below the block weight limit
+pub const MAX_JOIN_INPUTS: usize = 500;
+pub const MAX_SPLIT_COUNT: u64 = 100;
+
+/// Fee of a single-kernel transaction with the given number of inputs and outputs
+pub fn estimate_fee(
+    fee_per_gram: MicroMinotari,
+    num_inputs: usize,
+    num_outputs: usize,
+) -> MicroMinotari {
+    let metadata_size = TransactionWeight::latest()
+        .round_up_features_and_scripts_size(ESTIMATED_OUTPUT_METADATA_SIZE * num_outputs);
+    Fee::new(TransactionWeight::latest()).calculate(
+        fee_per_gram,
+        1,
+        num_inputs,
+        num_outputs,
+        metadata_size,
+    )
+}
+
+/// What one more input adds to the fee of a transaction
+pub fn input_spend_cost(fee_per_gram: MicroMinotari) -> MicroMinotari {
+    MicroMinotari(
+        estimate_fee(fee_per_gram, 2, 1)
+            .as_u64()
+            .saturating_sub(estimate_fee(fee_per_gram, 1, 1).as_u64()),
+    )
+}
+
+pub fn default_dust_threshold(fee_per_gram: MicroMinotari) -> MicroMinotari {
+    MicroMinotari(input_spend_cost(fee_per_gram).as_u64() * DUST_SPEND_COST_RATIO)
+}
+
+/// Unspent output as reported by the wallet
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalletOutput {
+    /// Hex encoded commitment
+    pub commitment: String,
+    pub value: MicroMinotari,
+    /// First block height at which the output can be spent
+    pub maturity: u64,
+    /// Height of the block that mined the output, `None` while unconfirmed
+    pub mined_height: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputStatus {
+    /// Mined and mature, can be spent now
+    Spendable,
+    /// Mined but still locked until its maturity height, as coinbase outputs are
+    Immature,
+    /// Not mined yet
+    Unconfirmed,
+}
+
+impl OutputStatus {
+    fn of(output: &WalletOutput, tip_height: u64) -> Self {
+        match output.mined_height {
+            None => Self::Unconfirmed,
+            Some(_) if output.maturity > tip_height => Self::Immature,
+            Some(_) => Self::Spendable,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct UnspentOutput {
+    pub commitment: String,
+    pub value: MicroMinotari,
+    pub maturity: u64,
+    pub height: Option<u64>,
+    pub status: OutputStatus,
+    pub is_dust: bool,
+}
+
+impl UnspentOutput {
+    pub fn new(output: WalletOutput, tip_height: u64, dust_threshold: MicroMinotari) -> Self {
+        Self {
+            status: OutputStatus::of(&output, tip_height),
+            is_dust: output.value < dust_threshold,
+            commitment: output.commitment,
+            value: output.value,
+            maturity: output.maturity,
+            height: output.mined_height,
+        }
+    }
+
+    pub fn is_spendable(&self) -> bool {
+        self.status == OutputStatus::Spendable
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct UtxoStats {
+    pub count: usize,
+    pub total: MicroMinotari,
+    pub spendable_count: usize,
+    pub spendable_total: MicroMinotari,
+    pub smallest: Option<MicroMinotari>,
+    pub largest: Option<MicroMinotari>,
+    pub dust_threshold: MicroMinotari,
+    pub dust_count: usize,
+    pub dust_total: MicroMinotari,
+    /// Fee added by each input, which is what every dust output costs to spend
+    pub input_spend_cost: MicroMinotari,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct UtxoOverview {
+    /// Unspent outputs from smallest to largest
+    pub outputs: Vec<UnspentOutput>,
+    pub stats: UtxoStats,
+}
+
+impl UtxoOverview {
+    pub fn new(
+        wallet_outputs: Vec<WalletOutput>,
+        tip_height: u64,
+        fee_per_gram: MicroMinotari,
+        dust_threshold: Option<MicroMinotari>,
+    ) -> Self {
+        let dust_threshold = dust_threshold.unwrap_or_else(|| default_dust_threshold(fee_per_gram));
+        let mut outputs: Vec<UnspentOutput> = wallet_outputs
+            .into_iter()
+            .map(|output| UnspentOutput::new(output, tip_height, dust_threshold))
+            .collect();
+        outputs.sort_by_key(|output| output.value);
+        let sum = |outputs: &[&UnspentOutput]| {
+            MicroMinotari(outputs.iter().map(|output| output.value.as_u64()).sum())
+        };
+        let all: Vec<&UnspentOutput> = outputs.iter().collect();
+        let spendable: Vec<&UnspentOutput> = outputs.iter().filter(|o| o.is_spendable()).collect();
+        let dust: Vec<&UnspentOutput> = outputs.iter().filter(|o| o.is_dust).collect();
+        let stats = UtxoStats {
+            count: all.len(),
+            total: sum(&all),
+            spendable_count: spendable.len(),
+            spendable_total: sum(&spendable),
+            smallest: outputs.first().map(|output| output.value),
+            largest: outputs.last().map(|output| output.value),
+            dust_threshold,
+            dust_count: dust.len(),
+            dust_total: sum(&dust),
+            input_spend_cost: input_spend_cost(fee_per_gram),
+        };
+        Self { outputs, stats }
+    }
+
+    /// Values of the outputs that can be spent now
+    pub fn spendable_values(&self) -> Vec<MicroMinotari> {
+        self.outputs
+            .iter()
+            .filter(|output| output.is_spendable())
+            .map(|output| output.value)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CoinOperation {
+    /// Consolidate the smallest spendable outputs (up to [`MAX_JOIN_INPUTS`]) into a single one
+    Join,
+    /// Create `split_count` outputs of `amount_per_split` each
+    Split {
+        amount_per_split: MicroMinotari,
+        split_count: u64,
+    },
+}
+
+/// Estimate of what a join or split will do. The wallet picks the inputs itself, so the counts are what it is
+/// expected to select rather than a guarantee.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CoinOperationPreview {
+    pub operation: CoinOperation,
+    pub inputs: usize,
+    pub input_total: MicroMinotari,
+    pub amount_per_output: MicroMinotari,
+    pub output_count: u64,
+    pub fee: MicroMinotari,
+    pub fee_per_gram: MicroMinotari,
+    pub change: MicroMinotari,
+    /// Commitments of the outputs a join spends, exactly the ones submitted to the wallet. Empty for a split, where
+    /// the wallet selects the inputs.
+    pub commitments: Vec<String>,
+}
+
+impl CoinOperationPreview {
+    pub fn new(
+        overview: &UtxoOverview,
+        operation: CoinOperation,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<Self, Error> {
+        match operation {
+            CoinOperation::Join => Self::join(overview, fee_per_gram),
+            CoinOperation::Split {
+                amount_per_split,
+                split_count,
+            } => Self::split(
+                &overview.spendable_values(),
+                amount_per_split,
+                split_count,
+                fee_per_gram,
+            ),
+        }
+    }
+
+    fn join(overview: &UtxoOverview, fee_per_gram: MicroMinotari) -> Result<Self, Error> {
+        // The overview is sorted from smallest to largest, so this picks the smallest spendable outputs
+        let selected: Vec<&UnspentOutput> = overview
+            .outputs
+            .iter()
+            .filter(|output| output.is_spendable())
+            .take(MAX_JOIN_INPUTS)
+            .collect();
+        if selected.len() < 2 {
+            return Err(anyhow!("At least two spendable outputs are needed to join"));
+        }
+        let input_total: u64 = selected.iter().map(|output| output.value.as_u64()).sum();
+        let fee = estimate_fee(fee_per_gram, selected.len(), 1);
+        let amount = input_total
+            .checked_sub(fee.as_u64())
+            .filter(|amount| *amount > 0)
+            .ok_or_else(|| anyhow!("Joining these outputs would cost more than they are worth"))?;
+        Ok(Self {
+            operation: CoinOperation::Join,
+            inputs: selected.len(),
+            input_total: MicroMinotari(input_total),
+            amount_per_output: MicroMinotari(amount),
+            output_count: 1,
+            fee,
+            fee_per_gram,
+            change: MicroMinotari(0),
+            commitments: selected
+                .iter()
+                .map(|output| output.commitment.clone())
+                .collect(),
+        })
+    }
+
+    fn split(
+        amounts: &[MicroMinotari],
+        amount_per_split: MicroMinotari,
+        split_count: u64,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<Self, Error> {
+        if !(2..=MAX_SPLIT_COUNT).contains(&split_count) {
+            return Err(anyhow!(
+                "Split count must be between 2 and {}",
+                MAX_SPLIT_COUNT
+            ));
+        }
+        if amount_per_split.as_u64() == 0 {
+            return Err(anyhow!("Split amount must be greater than zero"));
+        }
+        let target = amount_per_split
+            .as_u64()
+            .checked_mul(split_count)
+            .ok_or_else(|| anyhow!("Split total is too large"))?;
+
+        let selection = select_inputs(
+            amounts,
+            MicroMinotari(target),
+            fee_per_gram,
+            split_count as usize + 1,
+        )
+        .ok_or_else(|| {
+            anyhow!(
+                "Insufficient spendable balance to split {} into {} outputs",
+                MicroMinotari(target),
+                split_count
+            )
+        })?;
+        Ok(Self {
+            operation: CoinOperation::Split {
+                amount_per_split,
+                split_count,
+            },
+            inputs: selection.inputs,
+            input_total: selection.input_total,
+            amount_per_output: amount_per_split,
+            output_count: split_count,
+            fee: selection.fee,
+            fee_per_gram,
+            change: selection.change,
+            commitments: Vec::new(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InputSelection {
+    pub inputs: usize,
+    pub input_total: MicroMinotari,
+    pub fee: MicroMinotari,
+    pub change: MicroMinotari,
+}
+
+/// Picks the largest outputs until they cover `target` plus the fee of spending them into `num_outputs` outputs
+/// (change included). Returns `None` when all outputs together are not enough.
+pub fn select_inputs(
+    amounts: &[MicroMinotari],
+    target: MicroMinotari,
+    fee_per_gram: MicroMinotari,
+    num_outputs: usize,
+) -> Option<InputSelection> {
+    let mut sorted = amounts.to_vec();
+    sorted.sort_by(|a, b| b.cmp(a));
+    let mut input_total = 0u64;
+    for (index, amount) in sorted.iter().enumerate() {
+        input_total += amount.as_u64();
+        let inputs = index + 1;
+        let fee = estimate_fee(fee_per_gram, inputs, num_outputs);
+        if input_total >= target.as_u64() + fee.as_u64() {
+            return Some(InputSelection {
+                inputs,
+                input_total: MicroMinotari(input_total),
+                fee,
+                change: MicroMinotari(input_total - target.as_u64() - fee.as_u64()),
+            });
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEE_PER_GRAM: MicroMinotari = MicroMinotari(5);
+
+    const TIP_HEIGHT: u64 = 1_000;
+
+    fn output(value: u64) -> WalletOutput {
+        WalletOutput {
+            commitment: format!("commitment_{}", value),
+            value: MicroMinotari(value),
+            maturity: 0,
+            mined_height: Some(10),
+        }
+    }
+
+    fn overview(values: &[u64]) -> UtxoOverview {
+        UtxoOverview::new(
+            values.iter().copied().map(output).collect(),
+            TIP_HEIGHT,
+            FEE_PER_GRAM,
+            None,
+        )
+    }
+
+    #[test]
+    fn overview_flags_dust_below_threshold() {
+        let overview = UtxoOverview::new(
+            [5_000_000, 100, 2_000, 300]
+                .into_iter()
+                .map(output)
+                .collect(),
+            TIP_HEIGHT,
+            FEE_PER_GRAM,
+            Some(MicroMinotari(1_000)),
+        );
+        assert_eq!(overview.outputs[0].value, MicroMinotari(100));
+        assert_eq!(overview.outputs[0].height, Some(10));
+        assert_eq!(overview.stats.count, 4);
+        assert_eq!(overview.stats.total, MicroMinotari(5_002_400));
+        assert_eq!(overview.stats.dust_count, 2);
+        assert_eq!(overview.stats.dust_total, MicroMinotari(400));
+        assert_eq!(overview.stats.smallest, Some(MicroMinotari(100)));
+        assert_eq!(overview.stats.largest, Some(MicroMinotari(5_000_000)));
+    }
+
+    #[test]
+    fn status_follows_height_and_maturity() {
+        let immature = WalletOutput {
+            maturity: TIP_HEIGHT + 1,
+            ..output(3_000)
+        };
+        let unconfirmed = WalletOutput {
+            mined_height: None,
+            ..output(2_000)
+        };
+        let overview = UtxoOverview::new(
+            vec![output(1_000), immature, unconfirmed],
+            TIP_HEIGHT,
+            FEE_PER_GRAM,
+            None,
+        );
+        let statuses: Vec<OutputStatus> = overview.outputs.iter().map(|o| o.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                OutputStatus::Spendable,
+                OutputStatus::Unconfirmed,
+                OutputStatus::Immature
+            ]
+        );
+        assert_eq!(overview.stats.spendable_count, 1);
+        assert_eq!(overview.stats.spendable_total, MicroMinotari(1_000));
+    }
+
+    #[test]
+    fn default_dust_threshold_scales_with_fee() {
+        assert!(input_spend_cost(FEE_PER_GRAM).as_u64() > 0);
+        assert!(default_dust_threshold(MicroMinotari(10)) > default_dust_threshold(FEE_PER_GRAM));
+    }
+
+    #[test]
+    fn join_consolidates_into_one_output_after_fee() {
+        let preview = CoinOperationPreview::new(
+            &overview(&[1_000_000, 2_000_000, 3_000_000]),
+            CoinOperation::Join,
+            FEE_PER_GRAM,
+        )
+        .unwrap();
+        assert_eq!(preview.inputs, 3);
+        assert_eq!(preview.commitments.len(), 3);
+        assert_eq!(preview.output_count, 1);
+        assert_eq!(
+            preview.amount_per_output.as_u64() + preview.fee.as_u64(),
+            6_000_000
+        );
+    }
+
+    #[test]
+    fn join_spends_only_the_smallest_spendable_outputs() {
+        let mut outputs: Vec<WalletOutput> = (1..=MAX_JOIN_INPUTS as u64 + 5)
+            .map(|i| output(1_000_000 + i))
+            .collect();
+        outputs.push(WalletOutput {
+            maturity: TIP_HEIGHT + 100,
+            ..output(10)
+        });
+        let overview = UtxoOverview::new(outputs, TIP_HEIGHT, FEE_PER_GRAM, None);
+        let preview =
+            CoinOperationPreview::new(&overview, CoinOperation::Join, FEE_PER_GRAM).unwrap();
+        assert_eq!(preview.inputs, MAX_JOIN_INPUTS);
+        assert_eq!(preview.commitments.first().unwrap(), "commitment_1000001");
+        assert!(!preview.commitments.contains(&"commitment_10".to_string()));
+        assert!(!preview.commitments.contains(&format!(
+            "commitment_{}",
+            1_000_000 + MAX_JOIN_INPUTS as u64 + 1
+        )));
+    }
+
+    #[test]
+    fn join_rejects_single_or_worthless_outputs() {
+        assert!(CoinOperationPreview::new(
+            &overview(&[1_000_000]),
+            CoinOperation::Join,
+            FEE_PER_GRAM
+        )
+        .is_err());
+        assert!(
+            CoinOperationPreview::new(&overview(&[1, 1]), CoinOperation::Join, FEE_PER_GRAM)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn split_selects_largest_outputs_first() {
+        let operation = CoinOperation::Split {
+            amount_per_split: MicroMinotari(1_000_000),
+            split_count: 4,
+        };
+        let preview = CoinOperationPreview::new(
+            &overview(&[500, 10_000_000, 2_000_000]),
+            operation,
+            FEE_PER_GRAM,
+        )
+        .unwrap();
+        assert_eq!(preview.inputs, 1);
+        assert_eq!(
+            preview.change.as_u64() + preview.fee.as_u64() + 4_000_000,
+            10_000_000
+        );
+    }
+
+    #[test]
+    fn split_rejects_bad_parameters_and_insufficient_balance() {
+        let split = |amount_per_split, split_count| CoinOperation::Split {
+            amount_per_split: MicroMinotari(amount_per_split),
+            split_count,
+        };
+        let available = overview(&[10_000_000]);
+        assert!(CoinOperationPreview::new(&available, split(1_000, 1), FEE_PER_GRAM).is_err());
+        assert!(CoinOperationPreview::new(
+            &available,
+            split(1_000, MAX_SPLIT_COUNT + 1),
+            FEE_PER_GRAM
+        )
+        .is_err());
+        assert!(CoinOperationPreview::new(&available, split(0, 2), FEE_PER_GRAM).is_err());
+        assert!(CoinOperationPreview::new(&available, split(5_000_000, 2), FEE_PER_GRAM).is_err());
+    }
+}
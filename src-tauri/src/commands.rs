@@ -20,36 +20,76 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use crate::address_book::{AddressBook, AddressBookImportSummary, Contact};
 use crate::app_config::{AirdropTokens, DisplayMode, GpuThreads, MiningMode};
 use crate::app_in_memory_config::{
     get_der_encode_pub_key, get_websocket_key, AirdropInMemoryConfig,
 };
+use crate::app_lock::{AppLock, AppLockStatus};
 use crate::auto_launcher::AutoLauncher;
+use crate::auto_sweep::{AutoSweep, AutoSweepSettings, AutoSweepStatus};
 use crate::binaries::{Binaries, BinaryResolver};
+use crate::coin_management::{CoinOperation, CoinOperationPreview, UtxoOverview};
 use crate::configs::config_core::{ConfigCore, ConfigCoreContent};
 use crate::configs::config_mining::{ConfigMining, ConfigMiningContent};
 use crate::configs::config_ui::{ConfigUI, ConfigUIContent};
 use crate::configs::config_wallet::{ConfigWallet, ConfigWalletContent};
 use crate::configs::trait_config::ConfigImpl;
-use crate::credential_manager::{CredentialError, CredentialManager};
+use crate::credential_backends::{self, SecretBackendConfig};
+use crate::credential_manager::{
+    secret_backend_status, switch_secret_backend, CredentialError, CredentialManager,
+    SecretBackendStatus,
+};
+use crate::data_locations::{
+    self, get_data_location_infos, DataComponent, DataLocationInfo, DataLocations,
+};
 use crate::events_manager::EventsManager;
 use crate::external_dependencies::{
     ExternalDependencies, ExternalDependency, RequiredExternalDependency,
 };
+use crate::fee_estimation::{FeeEstimates, FeeSelection, SendSummary};
 use crate::gpu_miner::EngineType;
 use crate::gpu_miner_adapter::{GpuMinerStatus, GpuNodeSource};
 use crate::gpu_status_file::GpuStatus;
-use crate::internal_wallet::{InternalWallet, PaperWalletConfig};
+use crate::internal_wallet::{InternalWallet, PaperWalletConfig, WATCH_ONLY_ERROR};
+use crate::mm_proxy_adapter::MergeMiningProxyConfig;
+use crate::monero_payout::{self, MoneroAddressStatus, MoneroRegeneration};
+use crate::node::block_explorer::{BlockDetails, BlockQuery, KernelLookupResult};
+use crate::node::chain_tip_verifier::{ChainTipVerificationConfig, ChainTipVerifier};
+use crate::node::network_stats::{
+    NetworkStats, NetworkStatsMetric, NetworkStatsSeriesPoint, StatsResolution,
+};
 use crate::node::node_manager::NodeType;
+use crate::node::node_storage::{NodeStorageEstimate, NodeStorageMode};
+use crate::node::orphan_chain_recovery::{
+    recover_from_orphan_chain, OrphanChainRecoveryReport, RecoveryTrigger,
+};
+use crate::node::peer_management::{
+    validate_peer_public_key, validate_seed_peer, BannedPeer, PeerDetails,
+};
+use crate::outgoing_transactions::OutgoingTransaction;
 use crate::p2pool::models::{Connections, P2poolStats};
+use crate::payment_request::{
+    format_minotari_amount, parse_minotari_amount, ParsedPaymentUri, PaymentRequest,
+    PaymentRequests, PaymentUri,
+};
 use crate::progress_tracker_old::ProgressTracker;
 use crate::setup::setup_manager::{SetupManager, SetupPhase};
 use crate::tasks_tracker::TasksTrackers;
 use crate::tor_adapter::TorConfig;
-use crate::utils::address_utils::verify_send;
+use crate::transaction_export::{self, TransactionExportRequest, TransactionExportSummary};
+use crate::transaction_index::{
+    TransactionIndex, TransactionIndexChanges, TransactionPage, TransactionQuery,
+};
+use crate::utils::address_utils::{
+    verify_monero_address, verify_monero_payout_address, verify_send, MoneroAddressInfo,
+};
 use crate::utils::app_flow_utils::FrontendReadyChannel;
+use crate::utils::disk_space_guard::{DiskSpaceGuard, DiskSpaceStatus, DiskSpaceThresholds};
 use crate::wallet_adapter::TransactionInfo;
+use crate::wallet_backup;
 use crate::wallet_manager::WalletManagerError;
+use crate::wallet_profiles::{self, WalletProfile};
 use crate::websocket_manager::WebsocketManagerStatusMessage;
 use crate::{airdrop, UniverseAppState, APPLICATION_FOLDER_ID};
 
@@ -62,13 +102,13 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fmt::Debug;
 use std::fs::{read_dir, remove_dir_all, remove_file, File};
-use std::str::FromStr;
+use std::path::PathBuf;
 use std::sync::atomic::Ordering;
 use std::thread::{available_parallelism, sleep};
 use std::time::{Duration, Instant, SystemTime};
 use tari_common::configuration::Network;
 use tari_common_types::tari_address::TariAddressFeatures;
-use tari_core::transactions::tari_amount::{MicroMinotari, Minotari};
+use tari_core::transactions::tari_amount::MicroMinotari;
 use tauri::ipc::InvokeError;
 use tauri::{Manager, PhysicalPosition, PhysicalSize};
 use tauri_plugin_sentry::sentry;
@@ -407,6 +447,7 @@ pub async fn get_network(
 #[tauri::command]
 pub async fn get_monero_seed_words(app: tauri::AppHandle) -> Result<Vec<String>, String> {
     let timer = Instant::now();
+    ensure_app_unlocked(&app).await?;
 
     if !*ConfigWallet::content().await.monero_address_is_generated() {
         return Err(
@@ -533,6 +574,8 @@ pub async fn get_paper_wallet_details(
     auth_uuid: Option<String>,
 ) -> Result<PaperWalletConfig, InvokeError> {
     let timer = Instant::now();
+    ensure_app_unlocked(&app).await.map_err(InvokeError::from)?;
+    ensure_can_spend().await.map_err(InvokeError::from)?;
     let config_path = app
         .path()
         .app_config_dir()
@@ -568,6 +611,8 @@ pub async fn get_seed_words(
     app: tauri::AppHandle,
 ) -> Result<Vec<String>, String> {
     let timer = Instant::now();
+    ensure_app_unlocked(&app).await?;
+    ensure_can_spend().await?;
     let config_path = app
         .path()
         .app_config_dir()
@@ -641,6 +686,7 @@ pub async fn get_airdrop_tokens(
 #[tauri::command]
 pub async fn get_transactions_history(
     state: tauri::State<'_, UniverseAppState>,
+    app_handle: tauri::AppHandle,
     continuation: bool,
     limit: Option<u32>,
 ) -> Result<Vec<TransactionInfo>, String> {
@@ -652,14 +698,22 @@ pub async fn get_transactions_history(
     state
         .is_getting_transactions_history
         .store(true, Ordering::SeqCst);
-    let transactions = state
-        .wallet_manager
-        .get_transactions_history(continuation, limit)
-        .await
-        .unwrap_or_else(|e| {
+    // A fresh listing refreshes the index first, continuations page through what is already indexed
+    if !continuation {
+        if let Err(e) = TransactionIndex::current()
+            .sync(&app_handle, &state.wallet_manager)
+            .await
+        {
             if !matches!(e, WalletManagerError::WalletNotStarted) {
-                warn!(target: LOG_TARGET, "Error getting transaction history: {}", e);
+                warn!(target: LOG_TARGET, "Error syncing transaction index: {}", e);
             }
+        }
+    }
+    let transactions = TransactionIndex::current()
+        .history_page(continuation, limit)
+        .await
+        .unwrap_or_else(|e| {
+            warn!(target: LOG_TARGET, "Error getting transaction history: {}", e);
             vec![]
         });
 
@@ -715,6 +769,7 @@ pub async fn import_seed_words(
     app: tauri::AppHandle,
 ) -> Result<(), String> {
     let timer = Instant::now();
+    ensure_app_unlocked(&app).await?;
     let config_path = app
         .path()
         .app_config_dir()
@@ -745,6 +800,42 @@ pub async fn import_seed_words(
     Ok(())
 }
 
+#[tauri::command]
+pub async fn import_view_key(
+    view_private_key: String,
+    spend_public_key: String,
+    birthday: Option<u16>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let timer = Instant::now();
+    ensure_app_unlocked(&app).await?;
+    let config_path = app
+        .path()
+        .app_config_dir()
+        .expect("Could not get config dir");
+    let data_dir = app
+        .path()
+        .app_local_data_dir()
+        .expect("Could not get data dir");
+
+    InternalWallet::create_watch_only(config_path, &view_private_key, &spend_public_key, birthday)
+        .await
+        .map_err(|e| {
+            error!(target: LOG_TARGET, "Error creating watch-only wallet: {:?}", e);
+            e.to_string()
+        })?;
+    TasksTrackers::current().stop_all_processes().await;
+    InternalWallet::clear_wallet_local_data(data_dir)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if timer.elapsed() > MAX_ACCEPTABLE_COMMAND_TIME {
+        warn!(target: LOG_TARGET, "import_view_key took too long: {:?}", timer.elapsed());
+    }
+    info!(target: LOG_TARGET, "[import_view_key] Restarting the app");
+    app.restart();
+}
+
 #[tauri::command]
 pub fn log_web_message(level: String, message: Vec<String>) {
     let joined_message = message.join(" ");
@@ -1119,6 +1210,11 @@ pub async fn set_monero_address(
     app_handle: tauri::AppHandle,
 ) -> Result<(), InvokeError> {
     let timer = Instant::now();
+    ensure_app_unlocked(&app_handle)
+        .await
+        .map_err(InvokeError::from)?;
+    verify_monero_payout_address(&monero_address).map_err(InvokeError::from)?;
+    let monero_address = monero_address.trim().to_string();
     ConfigWallet::update_field_requires_restart(
         ConfigWalletContent::set_user_monero_address,
         monero_address,
@@ -1342,7 +1438,7 @@ pub async fn set_airdrop_tokens<'r>(
 
     let user_id_changed = old_id != new_id;
 
-    ConfigCore::update_field(ConfigCoreContent::set_airdrop_tokens, airdrop_tokens)
+    ConfigCore::update_sensitive_field(ConfigCoreContent::set_airdrop_tokens, airdrop_tokens)
         .await
         .map_err(InvokeError::from_anyhow)?;
 
@@ -1799,22 +1895,34 @@ pub async fn reconnect(app_handle: tauri::AppHandle) -> Result<(), String> {
 
 #[tauri::command]
 pub async fn send_one_sided_to_stealth_address(
+    app_handle: tauri::AppHandle,
     state: tauri::State<'_, UniverseAppState>,
     amount: String,
     destination: String,
     payment_id: Option<String>,
-) -> Result<(), String> {
+    fee: Option<FeeSelection>,
+) -> Result<u64, String> {
     let timer = Instant::now();
-    info!(target: LOG_TARGET, "[send_one_sided_to_stealth_address] called with args: (amount: {:?}, destination: {:?}, payment_id: {:?})", amount, destination, payment_id);
-    let mut spend_wallet_manager = state.spend_wallet_manager.write().await;
-    spend_wallet_manager
-        .send_one_sided_to_stealth_address(amount, destination, payment_id)
+    ensure_app_unlocked(&app_handle).await?;
+    ensure_can_spend().await?;
+    info!(target: LOG_TARGET, "[send_one_sided_to_stealth_address] called with args: (amount: {:?}, destination: {:?}, payment_id: {:?}, fee: {:?})", amount, destination, payment_id, fee);
+    let target_height = state.node_status_watch_rx.borrow().block_height;
+    let spend_wallet_manager = state.spend_wallet_manager.read().await.clone();
+    let tx_id = spend_wallet_manager
+        .send_one_sided_to_stealth_address(
+            &app_handle,
+            amount,
+            destination,
+            payment_id,
+            fee,
+            target_height,
+        )
         .await
         .map_err(|e| e.to_string())?;
     if timer.elapsed() > MAX_ACCEPTABLE_COMMAND_TIME {
         warn!(target: LOG_TARGET, "send_one_sided_to_stealth_address took too long: {:?}", timer.elapsed());
     }
-    Ok(())
+    Ok(tx_id)
 }
 
 #[tauri::command]
@@ -1867,19 +1975,37 @@ pub fn validate_minotari_amount(
     amount: String,
     state: tauri::State<'_, UniverseAppState>,
 ) -> Result<(), InvokeError> {
-    let t_amount = Minotari::from_str(&amount).map_err(|e| e.to_string())?;
-    let m_amount = MicroMinotari::from(t_amount);
+    check_minotari_amount(&amount, &state).map_err(InvokeError::from)?;
+    Ok(())
+}
+
+async fn ensure_app_unlocked(app_handle: &tauri::AppHandle) -> Result<(), String> {
+    AppLock::current()
+        .ensure_unlocked(app_handle)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+async fn ensure_can_spend() -> Result<(), String> {
+    if *ConfigWallet::content().await.is_watch_only() {
+        return Err(WATCH_ONLY_ERROR.to_string());
+    }
+    Ok(())
+}
+
+fn check_minotari_amount(amount: &str, state: &UniverseAppState) -> Result<MicroMinotari, String> {
+    let m_amount = parse_minotari_amount(amount)?;
 
     let balance = state
         .wallet_state_watch_rx
         .borrow()
         .clone()
-        .and_then(|state| state.balance);
+        .and_then(|state| state.balance)
+        .ok_or_else(|| "Wallet balance is not available yet".to_string())?;
 
-    let available_balance = balance.expect("Could not get balance").available_balance;
-    match m_amount.cmp(&available_balance) {
-        std::cmp::Ordering::Less => Ok(()),
-        _ => Err(InvokeError::from("Insufficient balance".to_string())),
+    match m_amount.cmp(&balance.available_balance) {
+        std::cmp::Ordering::Less => Ok(m_amount),
+        _ => Err("Insufficient balance".to_string()),
     }
 }
 
@@ -1953,3 +2079,1082 @@ pub async fn set_warmup_seen(warmup_seen: bool) -> Result<(), String> {
 
     Ok(())
 }
+
+#[tauri::command]
+pub async fn get_connected_peers_details(
+    state: tauri::State<'_, UniverseAppState>,
+) -> Result<Vec<PeerDetails>, String> {
+    let timer = Instant::now();
+    let peers = state
+        .node_manager
+        .list_connected_peers_details()
+        .await
+        .map_err(|e| e.to_string())?;
+    if timer.elapsed() > MAX_ACCEPTABLE_COMMAND_TIME {
+        warn!(target: LOG_TARGET, "get_connected_peers_details took too long: {:?}", timer.elapsed());
+    }
+    Ok(peers)
+}
+
+#[tauri::command]
+pub async fn ban_peer(
+    public_key: String,
+    address: Option<String>,
+    duration_secs: Option<u64>,
+    reason: Option<String>,
+    state: tauri::State<'_, UniverseAppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), InvokeError> {
+    validate_peer_public_key(&public_key)?;
+    info!(target: LOG_TARGET, "[ban_peer] banning {} for {:?} seconds", public_key, duration_secs);
+
+    let mut banned_peer = BannedPeer::new(
+        public_key.clone(),
+        address,
+        reason.unwrap_or_else(|| "Banned by user".to_string()),
+        duration_secs.map(Duration::from_secs),
+    );
+    // The node can only exclude addresses, so resolve the ones it knows for the key
+    match state.node_manager.find_peer_addresses(&public_key).await {
+        Ok(addresses) => banned_peer.add_addresses(addresses),
+        Err(e) => {
+            warn!(target: LOG_TARGET, "[ban_peer] could not look up addresses of {}: {}", public_key, e);
+        }
+    }
+
+    let mut banned_peers = ConfigCore::content().await.node_banned_peers().clone();
+    banned_peers.retain(|peer| peer.public_key != public_key && peer.is_active());
+    banned_peers.push(banned_peer);
+
+    let mut custom_seed_peers = ConfigCore::content().await.node_custom_seed_peers().clone();
+    custom_seed_peers.retain(|seed| !seed.starts_with(&format!("{}::", public_key)));
+    ConfigCore::update_field(
+        ConfigCoreContent::set_node_custom_seed_peers,
+        custom_seed_peers,
+    )
+    .await
+    .map_err(InvokeError::from_anyhow)?;
+
+    ConfigCore::update_field_requires_restart(
+        ConfigCoreContent::set_node_banned_peers,
+        banned_peers,
+        vec![SetupPhase::Node, SetupPhase::Wallet, SetupPhase::Unknown],
+    )
+    .await
+    .map_err(InvokeError::from_anyhow)?;
+
+    SetupManager::get_instance()
+        .restart_phases_from_queue(app_handle)
+        .await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn unban_peer(
+    public_key: String,
+    app_handle: tauri::AppHandle,
+) -> Result<(), InvokeError> {
+    let mut banned_peers = ConfigCore::content().await.node_banned_peers().clone();
+    let previous_len = banned_peers.len();
+    banned_peers.retain(|peer| peer.public_key != public_key && peer.is_active());
+    if banned_peers.len() == previous_len {
+        return Err(InvokeError::from("Peer is not banned".to_string()));
+    }
+    info!(target: LOG_TARGET, "[unban_peer] unbanning {}", public_key);
+
+    ConfigCore::update_field_requires_restart(
+        ConfigCoreContent::set_node_banned_peers,
+        banned_peers,
+        vec![SetupPhase::Node, SetupPhase::Wallet, SetupPhase::Unknown],
+    )
+    .await
+    .map_err(InvokeError::from_anyhow)?;
+
+    SetupManager::get_instance()
+        .restart_phases_from_queue(app_handle)
+        .await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_banned_peers() -> Result<Vec<BannedPeer>, String> {
+    Ok(ConfigCore::content()
+        .await
+        .node_banned_peers()
+        .iter()
+        .filter(|peer| peer.is_active())
+        .cloned()
+        .collect())
+}
+
+#[tauri::command]
+pub async fn add_custom_seed_peer(
+    seed_peer: String,
+    app_handle: tauri::AppHandle,
+) -> Result<(), InvokeError> {
+    let seed_peer = seed_peer.trim().to_string();
+    validate_seed_peer(&seed_peer)?;
+
+    let mut custom_seed_peers = ConfigCore::content().await.node_custom_seed_peers().clone();
+    if custom_seed_peers.contains(&seed_peer) {
+        return Ok(());
+    }
+    info!(target: LOG_TARGET, "[add_custom_seed_peer] adding {}", seed_peer);
+    custom_seed_peers.push(seed_peer);
+
+    ConfigCore::update_field_requires_restart(
+        ConfigCoreContent::set_node_custom_seed_peers,
+        custom_seed_peers,
+        vec![SetupPhase::Node, SetupPhase::Wallet, SetupPhase::Unknown],
+    )
+    .await
+    .map_err(InvokeError::from_anyhow)?;
+
+    SetupManager::get_instance()
+        .restart_phases_from_queue(app_handle)
+        .await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn remove_custom_seed_peer(
+    seed_peer: String,
+    app_handle: tauri::AppHandle,
+) -> Result<(), InvokeError> {
+    let mut custom_seed_peers = ConfigCore::content().await.node_custom_seed_peers().clone();
+    let previous_len = custom_seed_peers.len();
+    custom_seed_peers.retain(|seed| seed != seed_peer.trim());
+    if custom_seed_peers.len() == previous_len {
+        return Ok(());
+    }
+    info!(target: LOG_TARGET, "[remove_custom_seed_peer] removing {}", seed_peer);
+
+    ConfigCore::update_field_requires_restart(
+        ConfigCoreContent::set_node_custom_seed_peers,
+        custom_seed_peers,
+        vec![SetupPhase::Node, SetupPhase::Wallet, SetupPhase::Unknown],
+    )
+    .await
+    .map_err(InvokeError::from_anyhow)?;
+
+    SetupManager::get_instance()
+        .restart_phases_from_queue(app_handle)
+        .await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn force_node_reconnect(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, UniverseAppState>,
+) -> Result<(), InvokeError> {
+    info!(target: LOG_TARGET, "[force_node_reconnect] resetting peers and restarting node");
+    state
+        .node_manager
+        .request_peer_db_reset()
+        .await
+        .map_err(InvokeError::from_anyhow)?;
+
+    let setup_manager = SetupManager::get_instance();
+    setup_manager
+        .add_phases_to_restart_queue(vec![
+            SetupPhase::Node,
+            SetupPhase::Wallet,
+            SetupPhase::Unknown,
+        ])
+        .await;
+    setup_manager.restart_phases_from_queue(app_handle).await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn recover_orphan_chain(
+    app_handle: tauri::AppHandle,
+) -> Result<Option<OrphanChainRecoveryReport>, InvokeError> {
+    info!(target: LOG_TARGET, "[recover_orphan_chain] manual recovery requested");
+    recover_from_orphan_chain(&app_handle, RecoveryTrigger::Manual)
+        .await
+        .map_err(InvokeError::from_anyhow)
+}
+
+#[tauri::command]
+pub async fn get_node_storage_estimate(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, UniverseAppState>,
+) -> Result<NodeStorageEstimate, InvokeError> {
+    let timer = Instant::now();
+    let data_dir = app
+        .path()
+        .app_local_data_dir()
+        .map_err(|e| InvokeError::from_anyhow(e.into()))?;
+    let estimate = state
+        .node_manager
+        .estimate_storage(&data_dir)
+        .await
+        .map_err(InvokeError::from_anyhow)?;
+    if timer.elapsed() > MAX_ACCEPTABLE_COMMAND_TIME {
+        warn!(target: LOG_TARGET, "get_node_storage_estimate took too long: {:?}", timer.elapsed());
+    }
+    Ok(estimate)
+}
+
+#[tauri::command]
+pub async fn set_node_storage_mode(
+    storage_mode: NodeStorageMode,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, UniverseAppState>,
+) -> Result<(), InvokeError> {
+    storage_mode.validate()?;
+    let current_mode = *ConfigCore::content().await.node_storage_mode();
+    if current_mode == storage_mode {
+        return Ok(());
+    }
+
+    let data_dir = app
+        .path()
+        .app_local_data_dir()
+        .map_err(|e| InvokeError::from_anyhow(e.into()))?;
+    let estimate = state
+        .node_manager
+        .estimate_storage(&data_dir)
+        .await
+        .map_err(InvokeError::from_anyhow)?;
+    estimate.ensure_fits(&storage_mode)?;
+    let requires_resync = !current_mode.can_migrate_in_place(&storage_mode);
+    if requires_resync
+        && !state
+            .node_manager
+            .is_local_current()
+            .await
+            .map_err(InvokeError::from_anyhow)?
+    {
+        return Err(InvokeError::from(
+            "Switching to this storage mode requires a resync, which is only available when running a local node"
+                .to_string(),
+        ));
+    }
+
+    ConfigCore::update_field_requires_restart(
+        ConfigCoreContent::set_node_storage_mode,
+        storage_mode,
+        vec![SetupPhase::Node, SetupPhase::Wallet, SetupPhase::Unknown],
+    )
+    .await
+    .map_err(InvokeError::from_anyhow)?;
+
+    if !requires_resync {
+        info!(target: LOG_TARGET, "[set_node_storage_mode] {:?} -> {:?} is pruned in place on restart", current_mode, storage_mode);
+    } else {
+        info!(target: LOG_TARGET, "[set_node_storage_mode] {:?} -> {:?} requires a resync, dropping the chain database", current_mode, storage_mode);
+        state
+            .node_manager
+            .request_chain_db_reset()
+            .await
+            .map_err(InvokeError::from_anyhow)?;
+    }
+
+    SetupManager::get_instance()
+        .restart_phases_from_queue(app)
+        .await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_data_locations(
+    app: tauri::AppHandle,
+) -> Result<Vec<DataLocationInfo>, InvokeError> {
+    get_data_location_infos(&app).map_err(InvokeError::from_anyhow)
+}
+
+#[tauri::command]
+pub async fn migrate_data_locations(
+    components: Vec<DataComponent>,
+    target_dir: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<DataLocations, InvokeError> {
+    let timer = Instant::now();
+    let components = if components.is_empty() {
+        DataComponent::all()
+    } else {
+        components
+    };
+    let target_dir = target_dir.map(PathBuf::from);
+    info!(target: LOG_TARGET, "[migrate_data_locations] Moving {:?} to {:?}", components, target_dir);
+
+    let locations = data_locations::migrate_data_locations(&app, components, target_dir)
+        .await
+        .map_err(InvokeError::from_anyhow)?;
+    info!(target: LOG_TARGET, "[migrate_data_locations] Finished in {:?}", timer.elapsed());
+    Ok(locations)
+}
+
+#[tauri::command]
+pub async fn get_disk_space_status() -> Result<Vec<DiskSpaceStatus>, InvokeError> {
+    Ok(DiskSpaceGuard::current().get_statuses().await)
+}
+
+#[tauri::command]
+pub async fn set_disk_space_thresholds(thresholds: DiskSpaceThresholds) -> Result<(), InvokeError> {
+    thresholds.validate().map_err(InvokeError::from_anyhow)?;
+    ConfigCore::update_field(ConfigCoreContent::set_disk_space_thresholds, thresholds)
+        .await
+        .map_err(InvokeError::from_anyhow)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_block_details(
+    query: BlockQuery,
+    state: tauri::State<'_, UniverseAppState>,
+) -> Result<Option<BlockDetails>, InvokeError> {
+    let timer = Instant::now();
+    let block = state
+        .node_manager
+        .get_block_details(query)
+        .await
+        .map_err(InvokeError::from_anyhow)?;
+    if timer.elapsed() > MAX_ACCEPTABLE_COMMAND_TIME {
+        warn!(target: LOG_TARGET, "get_block_details took too long: {:?}", timer.elapsed());
+    }
+    Ok(block)
+}
+
+#[tauri::command]
+pub async fn find_kernel(
+    public_nonce: String,
+    signature: String,
+    state: tauri::State<'_, UniverseAppState>,
+) -> Result<Option<KernelLookupResult>, InvokeError> {
+    let timer = Instant::now();
+    let kernel = state
+        .node_manager
+        .find_kernel(&public_nonce, &signature)
+        .await
+        .map_err(InvokeError::from_anyhow)?;
+    if timer.elapsed() > MAX_ACCEPTABLE_COMMAND_TIME {
+        warn!(target: LOG_TARGET, "find_kernel took too long: {:?}", timer.elapsed());
+    }
+    Ok(kernel)
+}
+
+#[tauri::command]
+pub async fn get_network_stats_series(
+    resolution: StatsResolution,
+    metric: NetworkStatsMetric,
+    from: Option<u64>,
+    to: Option<u64>,
+) -> Result<Vec<NetworkStatsSeriesPoint>, InvokeError> {
+    Ok(NetworkStats::current()
+        .series(resolution, metric, from, to)
+        .await)
+}
+
+#[tauri::command]
+pub async fn get_chain_tip_verification_config() -> Result<ChainTipVerificationConfig, InvokeError>
+{
+    Ok(ConfigCore::content().await.chain_tip_verification().clone())
+}
+
+#[tauri::command]
+pub async fn set_chain_tip_verification_config(
+    config: ChainTipVerificationConfig,
+) -> Result<(), InvokeError> {
+    config.validate().map_err(InvokeError::from_anyhow)?;
+    ConfigCore::update_field(ConfigCoreContent::set_chain_tip_verification, config)
+        .await
+        .map_err(InvokeError::from_anyhow)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_verified_chain_tip() -> Result<u64, InvokeError> {
+    ChainTipVerifier::from_current_config()
+        .await
+        .verified_tip()
+        .await
+        .map_err(InvokeError::from_anyhow)
+}
+
+#[tauri::command]
+pub async fn query_transactions(query: TransactionQuery) -> Result<TransactionPage, InvokeError> {
+    let timer = Instant::now();
+    let mut page = TransactionIndex::current()
+        .query(&query)
+        .await
+        .map_err(InvokeError::from_anyhow)?;
+    page.counterparty_labels = AddressBook::current().labels_for(&page.transactions).await;
+    if timer.elapsed() > MAX_ACCEPTABLE_COMMAND_TIME {
+        warn!(target: LOG_TARGET, "query_transactions took too long: {:?}", timer.elapsed());
+    }
+    Ok(page)
+}
+
+#[tauri::command]
+pub async fn sync_transaction_index(
+    state: tauri::State<'_, UniverseAppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<TransactionIndexChanges, InvokeError> {
+    TransactionIndex::current()
+        .sync(&app_handle, &state.wallet_manager)
+        .await
+        .map_err(|e| InvokeError::from_anyhow(e.into()))
+}
+
+#[tauri::command]
+pub async fn export_transactions(
+    request: TransactionExportRequest,
+) -> Result<TransactionExportSummary, InvokeError> {
+    transaction_export::export_transactions(request, AddressBook::current().labels().await)
+        .await
+        .map_err(InvokeError::from_anyhow)
+}
+
+#[tauri::command]
+pub async fn get_outgoing_transactions(
+    state: tauri::State<'_, UniverseAppState>,
+) -> Result<Vec<OutgoingTransaction>, InvokeError> {
+    let spend_wallet_manager = state.spend_wallet_manager.read().await.clone();
+    Ok(spend_wallet_manager.get_outgoing_transactions().await)
+}
+
+#[tauri::command]
+pub async fn cancel_outgoing_transaction(
+    tx_id: u64,
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, UniverseAppState>,
+) -> Result<(), InvokeError> {
+    let timer = Instant::now();
+    ensure_app_unlocked(&app_handle)
+        .await
+        .map_err(InvokeError::from)?;
+    ensure_can_spend().await.map_err(InvokeError::from)?;
+    info!(target: LOG_TARGET, "[cancel_outgoing_transaction] called for tx {}", tx_id);
+    let spend_wallet_manager = state.spend_wallet_manager.read().await.clone();
+    spend_wallet_manager
+        .cancel_transaction(&app_handle, tx_id)
+        .await
+        .map_err(InvokeError::from_anyhow)?;
+    if timer.elapsed() > MAX_ACCEPTABLE_COMMAND_TIME {
+        warn!(target: LOG_TARGET, "cancel_outgoing_transaction took too long: {:?}", timer.elapsed());
+    }
+    Ok(())
+}
+
+/// The wallet gRPC cannot rebroadcast a single transaction, so every transaction of the spend wallet is revalidated
+/// and the unmined ones are broadcast again.
+#[tauri::command]
+pub async fn revalidate_outgoing_transactions(
+    state: tauri::State<'_, UniverseAppState>,
+) -> Result<(), InvokeError> {
+    let timer = Instant::now();
+    ensure_can_spend().await.map_err(InvokeError::from)?;
+    info!(target: LOG_TARGET, "[revalidate_outgoing_transactions] called");
+    let spend_wallet_manager = state.spend_wallet_manager.read().await.clone();
+    spend_wallet_manager
+        .revalidate_transactions()
+        .await
+        .map_err(InvokeError::from_anyhow)?;
+    if timer.elapsed() > MAX_ACCEPTABLE_COMMAND_TIME {
+        warn!(target: LOG_TARGET, "revalidate_outgoing_transactions took too long: {:?}", timer.elapsed());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_address_book(all_networks: Option<bool>) -> Result<Vec<Contact>, InvokeError> {
+    Ok(AddressBook::current()
+        .list(all_networks.unwrap_or(false))
+        .await)
+}
+
+#[tauri::command]
+pub async fn save_contact(
+    label: String,
+    address: String,
+    note: Option<String>,
+) -> Result<Contact, InvokeError> {
+    AddressBook::current()
+        .save_contact(label, address, note)
+        .await
+        .map_err(InvokeError::from_anyhow)
+}
+
+#[tauri::command]
+pub async fn remove_contact(address: String) -> Result<bool, InvokeError> {
+    AddressBook::current()
+        .remove_contact(address)
+        .await
+        .map_err(InvokeError::from_anyhow)
+}
+
+#[tauri::command]
+pub async fn export_address_book(path: PathBuf) -> Result<usize, InvokeError> {
+    AddressBook::current()
+        .export(&path)
+        .await
+        .map_err(InvokeError::from_anyhow)
+}
+
+#[tauri::command]
+pub async fn import_address_book(path: PathBuf) -> Result<AddressBookImportSummary, InvokeError> {
+    AddressBook::current()
+        .import(&path)
+        .await
+        .map_err(InvokeError::from_anyhow)
+}
+
+#[tauri::command]
+pub async fn create_payment_request(
+    amount: Option<String>,
+    payment_id: Option<String>,
+    label: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<PaymentRequest, InvokeError> {
+    PaymentRequests::current()
+        .create(&app_handle, amount, payment_id, label)
+        .await
+        .map_err(InvokeError::from_anyhow)
+}
+
+#[tauri::command]
+pub async fn get_payment_requests() -> Result<Vec<PaymentRequest>, InvokeError> {
+    Ok(PaymentRequests::current().list().await)
+}
+
+#[tauri::command]
+pub async fn cancel_payment_request(payment_id: String) -> Result<PaymentRequest, InvokeError> {
+    PaymentRequests::current()
+        .cancel(&payment_id)
+        .await
+        .map_err(InvokeError::from_anyhow)
+}
+
+#[tauri::command]
+pub fn parse_payment_uri(
+    uri: String,
+    state: tauri::State<'_, UniverseAppState>,
+) -> Result<ParsedPaymentUri, InvokeError> {
+    let request = PaymentUri::parse(&uri).map_err(InvokeError::from)?;
+    // The request stays usable when the balance is short, the send flow just shows why it cannot go ahead yet
+    let amount_error = request
+        .amount
+        .and_then(|amount| check_minotari_amount(&format_minotari_amount(amount), &state).err());
+    Ok(ParsedPaymentUri {
+        request,
+        amount_error,
+    })
+}
+
+#[tauri::command]
+pub async fn get_wallet_profiles() -> Result<Vec<WalletProfile>, InvokeError> {
+    Ok(wallet_profiles::list_wallets().await)
+}
+
+#[tauri::command]
+pub async fn create_wallet_profile(
+    name: String,
+    seed_words: Option<Vec<String>>,
+    app_handle: tauri::AppHandle,
+) -> Result<WalletProfile, InvokeError> {
+    let timer = Instant::now();
+    ensure_app_unlocked(&app_handle)
+        .await
+        .map_err(InvokeError::from)?;
+    let profile = wallet_profiles::create_wallet(&app_handle, &name, seed_words)
+        .await
+        .map_err(InvokeError::from_anyhow)?;
+    if timer.elapsed() > MAX_ACCEPTABLE_COMMAND_TIME {
+        warn!(target: LOG_TARGET, "create_wallet_profile took too long: {:?}", timer.elapsed());
+    }
+    Ok(profile)
+}
+
+#[tauri::command]
+pub async fn remove_wallet_profile(
+    wallet_id: String,
+    confirmation: String,
+    app_handle: tauri::AppHandle,
+) -> Result<(), InvokeError> {
+    ensure_app_unlocked(&app_handle)
+        .await
+        .map_err(InvokeError::from)?;
+    wallet_profiles::remove_wallet(&app_handle, &wallet_id, &confirmation)
+        .await
+        .map_err(InvokeError::from_anyhow)
+}
+
+#[tauri::command]
+pub async fn switch_wallet(
+    wallet_id: String,
+    state: tauri::State<'_, UniverseAppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), InvokeError> {
+    let timer = Instant::now();
+    ensure_app_unlocked(&app_handle)
+        .await
+        .map_err(InvokeError::from)?;
+    let previous_wallet_id = wallet_profiles::active_wallet_id().await;
+    if previous_wallet_id == wallet_id {
+        return Ok(());
+    }
+    if !wallet_profiles::list_wallets()
+        .await
+        .iter()
+        .any(|profile| profile.id == wallet_id)
+    {
+        return Err(InvokeError::from(format!("Unknown wallet {}", wallet_id)));
+    }
+    info!(target: LOG_TARGET, "[switch_wallet] switching from {} to {}", previous_wallet_id, wallet_id);
+
+    ConfigWallet::update_field(ConfigWalletContent::set_active_wallet_id, wallet_id)
+        .await
+        .map_err(InvokeError::from_anyhow)?;
+
+    let tari_address = match ConfigWallet::load_active_wallet(&app_handle).await {
+        Ok(tari_address) => tari_address,
+        Err(e) => {
+            error!(target: LOG_TARGET, "Could not load wallet, staying on {}: {:?}", previous_wallet_id, e);
+            let _unused = ConfigWallet::update_field(
+                ConfigWalletContent::set_active_wallet_id,
+                previous_wallet_id,
+            )
+            .await;
+            return Err(InvokeError::from_anyhow(e));
+        }
+    };
+    // Only a wallet that loaded gets the wallet phase restarted, a failed switch leaves the queue alone
+    SetupManager::get_instance()
+        .add_phases_to_restart_queue(vec![SetupPhase::Wallet])
+        .await;
+    if let Err(e) = TransactionIndex::current().load(&app_handle).await {
+        error!(target: LOG_TARGET, "Transaction index will not be persisted: {:?}", e);
+    }
+
+    SetupManager::get_instance()
+        .restart_phases_from_queue(app_handle.clone())
+        .await;
+
+    // Miners only pick up the new address when they start, so running ones are restarted around mmproxy
+    let currently_mining = {
+        let cpu_mining_status = state.cpu_miner_status_watch_rx.borrow().clone();
+        let gpu_mining_status = state.gpu_latest_status.borrow().clone();
+        cpu_mining_status.is_mining || gpu_mining_status.is_mining
+    };
+    if currently_mining {
+        stop_mining(state.clone())
+            .await
+            .map_err(InvokeError::from)?;
+    }
+    if let Some(mm_proxy_config) = state.mm_proxy_manager.config().await {
+        state
+            .mm_proxy_manager
+            .change_config(MergeMiningProxyConfig {
+                tari_address,
+                ..mm_proxy_config
+            })
+            .await
+            .map_err(InvokeError::from_anyhow)?;
+    }
+    if currently_mining {
+        start_mining(state.clone(), app_handle.clone())
+            .await
+            .map_err(InvokeError::from)?;
+    }
+
+    EventsManager::handle_config_wallet_loaded(&app_handle, ConfigWallet::content().await).await;
+    if timer.elapsed() > MAX_ACCEPTABLE_COMMAND_TIME {
+        warn!(target: LOG_TARGET, "switch_wallet took too long: {:?}", timer.elapsed());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn export_wallet_backup(
+    path: PathBuf,
+    passphrase: String,
+    app_handle: tauri::AppHandle,
+) -> Result<(), InvokeError> {
+    let timer = Instant::now();
+    ensure_app_unlocked(&app_handle)
+        .await
+        .map_err(InvokeError::from)?;
+    ensure_can_spend().await.map_err(InvokeError::from)?;
+    wallet_backup::export_wallet_backup(&app_handle, &path, &passphrase)
+        .await
+        .map_err(InvokeError::from_anyhow)?;
+    if timer.elapsed() > MAX_ACCEPTABLE_COMMAND_TIME {
+        warn!(target: LOG_TARGET, "export_wallet_backup took too long: {:?}", timer.elapsed());
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn import_wallet_backup(
+    path: PathBuf,
+    passphrase: String,
+    app_handle: tauri::AppHandle,
+) -> Result<(), InvokeError> {
+    ensure_app_unlocked(&app_handle)
+        .await
+        .map_err(InvokeError::from)?;
+    let data_dir = app_handle
+        .path()
+        .app_local_data_dir()
+        .expect("Could not get data dir");
+
+    wallet_backup::restore_wallet_backup(&app_handle, &path, &passphrase)
+        .await
+        .map_err(|e| {
+            error!(target: LOG_TARGET, "Could not restore wallet backup: {:?}", e);
+            InvokeError::from_anyhow(e)
+        })?;
+    TasksTrackers::current().stop_all_processes().await;
+    InternalWallet::clear_wallet_local_data(data_dir)
+        .await
+        .map_err(InvokeError::from_anyhow)?;
+    info!(target: LOG_TARGET, "[import_wallet_backup] Restarting the app");
+    app_handle.restart();
+}
+
+#[tauri::command]
+pub async fn get_app_lock_status(
+    app_handle: tauri::AppHandle,
+) -> Result<AppLockStatus, InvokeError> {
+    AppLock::current()
+        .status(&app_handle)
+        .await
+        .map_err(InvokeError::from_anyhow)
+}
+
+#[tauri::command]
+pub async fn set_app_lock(
+    new_secret: String,
+    current_secret: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<AppLockStatus, InvokeError> {
+    AppLock::current()
+        .set_secret(&app_handle, current_secret, &new_secret)
+        .await
+        .map_err(InvokeError::from_anyhow)
+}
+
+#[tauri::command]
+pub async fn remove_app_lock(
+    current_secret: String,
+    app_handle: tauri::AppHandle,
+) -> Result<AppLockStatus, InvokeError> {
+    AppLock::current()
+        .remove_secret(&app_handle, &current_secret)
+        .await
+        .map_err(InvokeError::from_anyhow)
+}
+
+#[tauri::command]
+pub async fn unlock_app(
+    secret: String,
+    app_handle: tauri::AppHandle,
+) -> Result<AppLockStatus, InvokeError> {
+    AppLock::current()
+        .unlock(&app_handle, &secret)
+        .await
+        .map_err(InvokeError::from_anyhow)
+}
+
+#[tauri::command]
+pub async fn lock_app(app_handle: tauri::AppHandle) -> Result<AppLockStatus, InvokeError> {
+    AppLock::current()
+        .lock(&app_handle)
+        .await
+        .map_err(InvokeError::from_anyhow)
+}
+
+#[tauri::command]
+pub async fn set_app_auto_lock(
+    auto_lock_secs: u64,
+    app_handle: tauri::AppHandle,
+) -> Result<AppLockStatus, InvokeError> {
+    AppLock::current()
+        .set_auto_lock_secs(&app_handle, auto_lock_secs)
+        .await
+        .map_err(InvokeError::from_anyhow)
+}
+
+#[tauri::command]
+pub async fn get_auto_sweep(app_handle: tauri::AppHandle) -> Result<AutoSweepStatus, InvokeError> {
+    AutoSweep::current()
+        .status(&app_handle)
+        .await
+        .map_err(InvokeError::from_anyhow)
+}
+
+#[tauri::command]
+pub async fn set_auto_sweep(
+    settings: AutoSweepSettings,
+    app_handle: tauri::AppHandle,
+) -> Result<AutoSweepStatus, InvokeError> {
+    ensure_app_unlocked(&app_handle)
+        .await
+        .map_err(InvokeError::from)?;
+    ensure_can_spend().await.map_err(InvokeError::from)?;
+    AutoSweep::current()
+        .update_settings(&app_handle, settings)
+        .await
+        .map_err(InvokeError::from_anyhow)
+}
+
+#[tauri::command]
+pub async fn confirm_auto_sweep_destination(
+    destination: String,
+    app_handle: tauri::AppHandle,
+) -> Result<AutoSweepStatus, InvokeError> {
+    ensure_app_unlocked(&app_handle)
+        .await
+        .map_err(InvokeError::from)?;
+    AutoSweep::current()
+        .confirm_destination(&app_handle, &destination)
+        .await
+        .map_err(InvokeError::from_anyhow)
+}
+
+#[tauri::command]
+pub async fn get_utxo_overview(
+    dust_threshold: Option<MicroMinotari>,
+    state: tauri::State<'_, UniverseAppState>,
+) -> Result<UtxoOverview, InvokeError> {
+    let timer = Instant::now();
+    ensure_can_spend().await.map_err(InvokeError::from)?;
+    let tip_height = state.node_status_watch_rx.borrow().block_height;
+    let spend_wallet_manager = state.spend_wallet_manager.read().await.clone();
+    let overview = spend_wallet_manager
+        .get_utxo_overview(tip_height, dust_threshold)
+        .await
+        .map_err(InvokeError::from_anyhow)?;
+    if timer.elapsed() > MAX_ACCEPTABLE_COMMAND_TIME {
+        warn!(target: LOG_TARGET, "get_utxo_overview took too long: {:?}", timer.elapsed());
+    }
+    Ok(overview)
+}
+
+#[tauri::command]
+pub async fn preview_coin_operation(
+    operation: CoinOperation,
+    fee_per_gram: Option<u64>,
+    state: tauri::State<'_, UniverseAppState>,
+) -> Result<CoinOperationPreview, InvokeError> {
+    let timer = Instant::now();
+    ensure_can_spend().await.map_err(InvokeError::from)?;
+    let tip_height = state.node_status_watch_rx.borrow().block_height;
+    let spend_wallet_manager = state.spend_wallet_manager.read().await.clone();
+    let preview = spend_wallet_manager
+        .preview_coin_operation(operation, tip_height, fee_per_gram)
+        .await
+        .map_err(InvokeError::from_anyhow)?;
+    if timer.elapsed() > MAX_ACCEPTABLE_COMMAND_TIME {
+        warn!(target: LOG_TARGET, "preview_coin_operation took too long: {:?}", timer.elapsed());
+    }
+    Ok(preview)
+}
+
+#[tauri::command]
+pub async fn execute_coin_operation(
+    operation: CoinOperation,
+    fee_per_gram: Option<u64>,
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, UniverseAppState>,
+) -> Result<u64, InvokeError> {
+    let timer = Instant::now();
+    ensure_app_unlocked(&app_handle)
+        .await
+        .map_err(InvokeError::from)?;
+    ensure_can_spend().await.map_err(InvokeError::from)?;
+    info!(target: LOG_TARGET, "[execute_coin_operation] called with {:?}", operation);
+    let tip_height = state.node_status_watch_rx.borrow().block_height;
+    let spend_wallet_manager = state.spend_wallet_manager.read().await.clone();
+    let tx_id = spend_wallet_manager
+        .execute_coin_operation(&app_handle, operation, tip_height, fee_per_gram)
+        .await
+        .map_err(InvokeError::from_anyhow)?;
+    if timer.elapsed() > MAX_ACCEPTABLE_COMMAND_TIME {
+        warn!(target: LOG_TARGET, "execute_coin_operation took too long: {:?}", timer.elapsed());
+    }
+    Ok(tx_id)
+}
+
+#[tauri::command]
+pub async fn get_fee_estimates(
+    state: tauri::State<'_, UniverseAppState>,
+) -> Result<FeeEstimates, InvokeError> {
+    let timer = Instant::now();
+    let spend_wallet_manager = state.spend_wallet_manager.read().await.clone();
+    let estimates = spend_wallet_manager
+        .get_fee_estimates()
+        .await
+        .map_err(InvokeError::from_anyhow)?;
+    if timer.elapsed() > MAX_ACCEPTABLE_COMMAND_TIME {
+        warn!(target: LOG_TARGET, "get_fee_estimates took too long: {:?}", timer.elapsed());
+    }
+    Ok(estimates)
+}
+
+#[tauri::command]
+pub async fn preview_send(
+    amount: String,
+    destination: String,
+    fee: Option<FeeSelection>,
+    state: tauri::State<'_, UniverseAppState>,
+) -> Result<SendSummary, InvokeError> {
+    let timer = Instant::now();
+    ensure_can_spend().await.map_err(InvokeError::from)?;
+    let spend_wallet_manager = state.spend_wallet_manager.read().await.clone();
+    let summary = spend_wallet_manager
+        .preview_send(amount, destination, fee)
+        .await
+        .map_err(InvokeError::from_anyhow)?;
+    if timer.elapsed() > MAX_ACCEPTABLE_COMMAND_TIME {
+        warn!(target: LOG_TARGET, "preview_send took too long: {:?}", timer.elapsed());
+    }
+    Ok(summary)
+}
+
+#[tauri::command]
+pub fn validate_monero_address(address: String) -> Result<MoneroAddressInfo, String> {
+    verify_monero_address(&address)
+}
+
+#[tauri::command]
+pub async fn get_monero_address_status(
+    app_handle: tauri::AppHandle,
+) -> Result<MoneroAddressStatus, InvokeError> {
+    let config_dir = app_handle
+        .path()
+        .app_config_dir()
+        .expect("Could not get config dir");
+    monero_payout::status(config_dir)
+        .await
+        .map_err(InvokeError::from_anyhow)
+}
+
+#[tauri::command]
+pub async fn begin_monero_address_regeneration(
+    app_handle: tauri::AppHandle,
+) -> Result<MoneroRegeneration, InvokeError> {
+    ensure_app_unlocked(&app_handle)
+        .await
+        .map_err(InvokeError::from)?;
+    monero_payout::begin_regeneration()
+        .await
+        .map_err(InvokeError::from_anyhow)
+}
+
+#[tauri::command]
+pub async fn confirm_monero_address_regeneration(
+    seed_words: Vec<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, InvokeError> {
+    let timer = Instant::now();
+    ensure_app_unlocked(&app_handle)
+        .await
+        .map_err(InvokeError::from)?;
+    let config_dir = app_handle
+        .path()
+        .app_config_dir()
+        .expect("Could not get config dir");
+    let monero_address = monero_payout::confirm_regeneration(config_dir, seed_words)
+        .await
+        .map_err(InvokeError::from_anyhow)?;
+    ConfigWallet::update_field_requires_restart(
+        ConfigWalletContent::set_generated_monero_address,
+        monero_address.clone(),
+        vec![SetupPhase::Unknown],
+    )
+    .await
+    .map_err(InvokeError::from_anyhow)?;
+
+    SetupManager::get_instance()
+        .restart_phases_from_queue(app_handle)
+        .await;
+    if timer.elapsed() > MAX_ACCEPTABLE_COMMAND_TIME {
+        warn!(target: LOG_TARGET, "confirm_monero_address_regeneration took too long: {:?}", timer.elapsed());
+    }
+    Ok(monero_address)
+}
+
+#[tauri::command]
+pub async fn get_secret_backend_status(
+    app_handle: tauri::AppHandle,
+) -> Result<SecretBackendStatus, InvokeError> {
+    let config_dir = app_handle
+        .path()
+        .app_config_dir()
+        .map_err(|e| InvokeError::from_anyhow(e.into()))?;
+    Ok(secret_backend_status(config_dir).await)
+}
+
+#[tauri::command]
+pub async fn set_secret_backend(
+    backend: SecretBackendConfig,
+    passphrase: Option<String>,
+    app_handle: tauri::AppHandle,
+) -> Result<SecretBackendStatus, InvokeError> {
+    let timer = Instant::now();
+    ensure_app_unlocked(&app_handle)
+        .await
+        .map_err(InvokeError::from)?;
+    info!(target: LOG_TARGET, "[set_secret_backend] called with {:?}", backend);
+    if let Some(passphrase) = passphrase {
+        credential_backends::set_passphrase(&passphrase)
+            .map_err(|e| InvokeError::from(e.to_string()))?;
+    }
+    let config_dir = app_handle
+        .path()
+        .app_config_dir()
+        .map_err(|e| InvokeError::from_anyhow(e.into()))?;
+    switch_secret_backend(config_dir.clone(), backend)
+        .await
+        .map_err(InvokeError::from_anyhow)?;
+    if timer.elapsed() > MAX_ACCEPTABLE_COMMAND_TIME {
+        warn!(target: LOG_TARGET, "set_secret_backend took too long: {:?}", timer.elapsed());
+    }
+    Ok(secret_backend_status(config_dir).await)
+}
+
+/// Provides the encrypted file backend passphrase, then loads the wallet that could not be read without it
+#[tauri::command]
+pub async fn unlock_secret_backend(
+    passphrase: String,
+    app_handle: tauri::AppHandle,
+) -> Result<SecretBackendStatus, InvokeError> {
+    let timer = Instant::now();
+    credential_backends::set_passphrase(&passphrase)
+        .map_err(|e| InvokeError::from(e.to_string()))?;
+    let config_dir = app_handle
+        .path()
+        .app_config_dir()
+        .map_err(|e| InvokeError::from_anyhow(e.into()))?;
+    match CredentialManager::default_with_dir(config_dir.clone())
+        .get_credentials()
+        .await
+    {
+        Ok(_) | Err(CredentialError::NoEntry(_)) => {}
+        Err(e) => {
+            credential_backends::clear_passphrase();
+            return Err(InvokeError::from(e.to_string()));
+        }
+    }
+
+    ConfigCore::unseal_secrets(config_dir.clone()).await;
+    EventsManager::handle_config_core_loaded(&app_handle, ConfigCore::content().await).await;
+    ConfigWallet::load_active_wallet(&app_handle)
+        .await
+        .map_err(InvokeError::from_anyhow)?;
+    SetupManager::get_instance()
+        .add_phases_to_restart_queue(vec![SetupPhase::Wallet])
+        .await;
+    SetupManager::get_instance()
+        .restart_phases_from_queue(app_handle)
+        .await;
+    if timer.elapsed() > MAX_ACCEPTABLE_COMMAND_TIME {
+        warn!(target: LOG_TARGET, "unlock_secret_backend took too long: {:?}", timer.elapsed());
+    }
+    Ok(secret_backend_status(config_dir).await)
+}
@@ -20,22 +20,31 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use anyhow::Error;
 use getset::{Getters, Setters};
 use log::warn;
 use semver::Version;
 use serde::{Deserialize, Serialize};
-use std::{str::FromStr, sync::LazyLock, time::SystemTime};
+use std::{path::PathBuf, str::FromStr, sync::LazyLock, time::SystemTime};
 use tari_common::configuration::Network;
-use tauri::AppHandle;
+use tauri::{AppHandle, Manager};
 use tokio::sync::RwLock;
 
+use crate::data_locations::DataLocations;
+use crate::node::chain_tip_verifier::ChainTipVerificationConfig;
 use crate::node::node_manager::NodeType;
+use crate::node::node_storage::NodeStorageMode;
+use crate::node::peer_management::BannedPeer;
+use crate::utils::disk_space_guard::DiskSpaceThresholds;
 use crate::{
     ab_test_selector::ABTestSelector, app_config::AirdropTokens, events_manager::EventsManager,
     internal_wallet::generate_password, AppConfig,
 };
 
-use super::trait_config::{ConfigContentImpl, ConfigImpl};
+use super::{
+    config_secrets,
+    trait_config::{ConfigContentImpl, ConfigImpl},
+};
 
 static INSTANCE: LazyLock<RwLock<ConfigCore>> = LazyLock::new(|| RwLock::new(ConfigCore::new()));
 #[allow(clippy::struct_excessive_bools)]
@@ -63,6 +72,24 @@ pub struct ConfigCoreContent {
     airdrop_tokens: Option<AirdropTokens>,
     remote_base_node_address: String,
     node_type: NodeType,
+    node_custom_seed_peers: Vec<String>,
+    node_banned_peers: Vec<BannedPeer>,
+    orphan_chain_recovery_attempts: u32,
+    node_storage_mode: NodeStorageMode,
+    data_locations: DataLocations,
+    disk_space_thresholds: DiskSpaceThresholds,
+    chain_tip_verification: ChainTipVerificationConfig,
+    /// Airdrop tokens and remote node address as written to disk, sealed with the config key
+    #[getset(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sealed_secrets: Option<String>,
+}
+
+/// Fields of [`ConfigCoreContent`] that are only ever written to disk sealed
+#[derive(Serialize, Deserialize)]
+struct CoreSecrets {
+    airdrop_tokens: Option<AirdropTokens>,
+    remote_base_node_address: String,
 }
 
 fn default_monero_nodes() -> Vec<String> {
@@ -71,15 +98,21 @@ fn default_monero_nodes() -> Vec<String> {
     ]
 }
 
+pub(crate) fn default_remote_base_node_address() -> String {
+    default_remote_base_node_address_for(Network::get_current_or_user_setting_or_default())
+}
+
+pub(crate) fn default_remote_base_node_address_for(network: Network) -> String {
+    match network {
+        Network::MainNet => "https://grpc.tari.com:443".to_string(),
+        _ => {
+            format!("https://grpc.{}.tari.com:443", network.as_key_str())
+        }
+    }
+}
+
 impl Default for ConfigCoreContent {
     fn default() -> Self {
-        let network = Network::get_current_or_user_setting_or_default();
-        let remote_base_node_address = match network {
-            Network::MainNet => "https://grpc.tari.com:443".to_string(),
-            _ => {
-                format!("https://grpc.{}.tari.com:443", network.as_key_str())
-            }
-        };
         let anon_id = generate_password(20);
         let ab_test_selector = anon_id
             .chars()
@@ -110,13 +143,64 @@ impl Default for ConfigCoreContent {
             pre_release: false,
             last_changelog_version: Version::new(0, 0, 0),
             airdrop_tokens: None,
-            remote_base_node_address,
+            remote_base_node_address: default_remote_base_node_address(),
             node_type: NodeType::Local,
+            node_custom_seed_peers: vec![],
+            node_banned_peers: vec![],
+            orphan_chain_recovery_attempts: 0,
+            node_storage_mode: NodeStorageMode::default(),
+            data_locations: DataLocations::default(),
+            disk_space_thresholds: DiskSpaceThresholds::default(),
+            chain_tip_verification: ChainTipVerificationConfig::default(),
+            sealed_secrets: None,
         }
     }
 }
 impl ConfigContentImpl for ConfigCoreContent {}
 
+impl ConfigCoreContent {
+    /// Values that must not leave the machine, the public default node address is not one of them
+    pub fn secret_values(&self) -> Vec<String> {
+        let mut secrets = vec![];
+        if let Some(tokens) = &self.airdrop_tokens {
+            secrets.push(tokens.token.clone());
+            secrets.push(tokens.refresh_token.clone());
+        }
+        if self.remote_base_node_address != default_remote_base_node_address() {
+            secrets.push(self.remote_base_node_address.clone());
+        }
+        secrets
+    }
+
+    /// Moves the sealed secrets back into their fields, returns `false` while the config key is not loaded
+    fn unseal_secrets(&mut self) -> Result<bool, Error> {
+        let Some(sealed) = self.sealed_secrets.as_deref() else {
+            return Ok(true);
+        };
+        let Some(secrets) = config_secrets::unseal::<CoreSecrets>(sealed)? else {
+            return Ok(false);
+        };
+        self.merge_unsealed(secrets);
+        self.sealed_secrets = None;
+        Ok(true)
+    }
+
+    /// Values set while the secrets were sealed are newer than the sealed copy and win over it
+    fn merge_unsealed(&mut self, secrets: CoreSecrets) {
+        if self.airdrop_tokens.is_none() {
+            self.airdrop_tokens = secrets.airdrop_tokens;
+        }
+        if self.has_default_remote_base_node_address() {
+            self.remote_base_node_address = secrets.remote_base_node_address;
+        }
+    }
+
+    fn has_default_remote_base_node_address(&self) -> bool {
+        self.remote_base_node_address.is_empty()
+            || self.remote_base_node_address == default_remote_base_node_address()
+    }
+}
+
 pub struct ConfigCore {
     content: ConfigCoreContent,
     app_handle: RwLock<Option<AppHandle>>,
@@ -124,6 +208,12 @@ pub struct ConfigCore {
 
 impl ConfigCore {
     pub async fn initialize(app_handle: AppHandle, old_config: Option<AppConfig>) {
+        let config_dir = app_handle
+            .path()
+            .app_config_dir()
+            .expect("Could not get config dir");
+        Self::unseal_secrets(config_dir).await;
+
         let mut config = Self::current().write().await;
         config.load_app_handle(app_handle.clone()).await;
         config.handle_old_config_migration(old_config);
@@ -132,9 +222,31 @@ impl ConfigCore {
             config.content.mmproxy_monero_nodes = default_monero_nodes();
             let _unused = Self::_save_config(config.content.clone());
         }
+        DataLocations::set_current(config.content.data_locations.clone());
 
         EventsManager::handle_config_core_loaded(&app_handle, config.content.clone()).await;
     }
+
+    /// Loads the config key from the credential store and unseals the secrets. Saving afterwards seals any
+    /// plaintext secrets left by older versions.
+    pub async fn unseal_secrets(config_dir: PathBuf) {
+        if let Err(e) = config_secrets::load_config_key(config_dir).await {
+            warn!(
+                "Config secrets stay sealed, the credential store is unavailable: {}",
+                e
+            );
+            return;
+        }
+        let mut config = Self::current().write().await;
+        match config.content.unseal_secrets() {
+            Ok(_) => {
+                let _unused = Self::_save_config(config.content.clone()).inspect_err(|e| {
+                    warn!("Failed to seal config secrets: {}", e);
+                });
+            }
+            Err(e) => warn!("Failed to unseal config secrets: {}", e),
+        }
+    }
 }
 
 impl ConfigImpl for ConfigCore {
@@ -164,6 +276,50 @@ impl ConfigImpl for ConfigCore {
         "config_core".to_string()
     }
 
+    fn _prepare_for_save(mut config_content: Self::Config) -> Result<Self::Config, Error> {
+        // Still sealed because the config key is not loaded. Values set since then are written unsealed next to the
+        // sealed copy and replace its values once the key is loaded.
+        if config_content.sealed_secrets.is_some() {
+            if config_content.airdrop_tokens.is_some()
+                || !config_content.has_default_remote_base_node_address()
+            {
+                warn!("Config key is not loaded, saving config secrets changed meanwhile unsealed");
+            }
+            return Ok(config_content);
+        }
+        let secrets = CoreSecrets {
+            airdrop_tokens: config_content.airdrop_tokens.take(),
+            remote_base_node_address: std::mem::take(&mut config_content.remote_base_node_address),
+        };
+        match config_secrets::seal(&secrets)? {
+            Some(sealed) => config_content.sealed_secrets = Some(sealed),
+            None => {
+                // Without the key plaintext is all that can be kept, it gets sealed once the key is loaded
+                warn!("Config key is not loaded, saving config secrets unsealed");
+                config_content.airdrop_tokens = secrets.airdrop_tokens;
+                config_content.remote_base_node_address = secrets.remote_base_node_address;
+            }
+        }
+        Ok(config_content)
+    }
+
+    fn _restore_after_load(mut config_content: Self::Config) -> Self::Config {
+        config_content.chain_tip_verification = config_content
+            .chain_tip_verification
+            .replace_legacy_default();
+        match config_content.unseal_secrets() {
+            Ok(true) => return config_content,
+            Ok(false) => warn!("Config secrets stay sealed until the config key is loaded"),
+            Err(e) => warn!("Failed to unseal config secrets: {}", e),
+        }
+        // The default node keeps a remote node usable meanwhile, unless another one was set while sealed
+        if config_content.remote_base_node_address.is_empty() {
+            warn!("Using the default remote node until the config secrets are unsealed");
+            config_content.remote_base_node_address = default_remote_base_node_address();
+        }
+        config_content
+    }
+
     fn _get_content(&self) -> &Self::Config {
         &self.content
     }
@@ -204,3 +360,40 @@ impl ConfigImpl for ConfigCore {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(token: &str) -> AirdropTokens {
+        AirdropTokens {
+            token: token.to_string(),
+            refresh_token: format!("{}-refresh", token),
+        }
+    }
+
+    #[test]
+    fn values_set_while_sealed_win_over_the_sealed_copy() {
+        let sealed = || CoreSecrets {
+            airdrop_tokens: Some(tokens("sealed")),
+            remote_base_node_address: "https://sealed.example:443".to_string(),
+        };
+
+        let mut untouched = ConfigCoreContent::default();
+        untouched.merge_unsealed(sealed());
+        assert_eq!(untouched.airdrop_tokens, Some(tokens("sealed")));
+        assert_eq!(
+            untouched.remote_base_node_address,
+            "https://sealed.example:443"
+        );
+
+        let mut changed = ConfigCoreContent {
+            airdrop_tokens: Some(tokens("new")),
+            remote_base_node_address: "https://new.example:443".to_string(),
+            ..Default::default()
+        };
+        changed.merge_unsealed(sealed());
+        assert_eq!(changed.airdrop_tokens, Some(tokens("new")));
+        assert_eq!(changed.remote_base_node_address, "https://new.example:443");
+    }
+}
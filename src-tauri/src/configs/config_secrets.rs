@@ -0,0 +1,218 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Sealing of config fields that must not sit in plain JSON. The key lives in the credential store next to the
+//! wallet credentials and is cached here once loaded, so configs can be saved synchronously.
+
+use std::{
+    path::PathBuf,
+    sync::{PoisonError, RwLock},
+};
+
+use anyhow::{anyhow, Error};
+use blake2::{
+    digest::{consts::U32, Mac},
+    Blake2bMac,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    credential_manager::{Credential, CredentialError, CredentialManager},
+    crypto::{self, KEY_LENGTH, NONCE_LENGTH},
+};
+
+/// Replacement for secret values in logs, telemetry and feedback archives
+pub const REDACTED: &str = "<redacted>";
+const SEALED_PREFIX: &str = "sealed:v1:";
+const SEALED_AAD: &[u8] = b"tari-universe-config-secrets:1";
+const MAC_PERSONAL: &[u8] = b"tu-config-mac:1";
+
+static CONFIG_KEY: RwLock<Option<[u8; KEY_LENGTH]>> = RwLock::new(None);
+
+/// Reads the config key from the credential store, creating it on first use
+pub async fn load_config_key(config_dir: PathBuf) -> Result<(), CredentialError> {
+    let cm = CredentialManager::default_with_dir(config_dir);
+    let mut credential = match cm.get_credentials().await {
+        Ok(credential) => credential,
+        Err(CredentialError::NoEntry(_)) => Credential::default(),
+        Err(e) => return Err(e),
+    };
+    let key = match credential.config_key {
+        Some(key) => key,
+        None => {
+            let key = crypto::random_key();
+            credential.config_key = Some(key);
+            cm.set_credentials(&credential).await?;
+            key
+        }
+    };
+    *CONFIG_KEY.write().unwrap_or_else(PoisonError::into_inner) = Some(key);
+    Ok(())
+}
+
+pub fn has_config_key() -> bool {
+    config_key().is_some()
+}
+
+fn config_key() -> Option<[u8; KEY_LENGTH]> {
+    *CONFIG_KEY.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Encrypts the value with the cached config key, `None` while the key is not loaded
+pub fn seal<T: Serialize>(value: &T) -> Result<Option<String>, Error> {
+    config_key()
+        .map(|key| seal_with_key(&key, value))
+        .transpose()
+}
+
+/// Decrypts a value sealed by [`seal`], `None` while the key is not loaded
+pub fn unseal<T: DeserializeOwned>(sealed: &str) -> Result<Option<T>, Error> {
+    config_key()
+        .map(|key| unseal_with_key(&key, sealed))
+        .transpose()
+}
+
+/// Tags the value with a MAC under the cached config key, `None` while the key is not loaded
+pub fn authenticate<T: Serialize>(value: &T) -> Result<Option<String>, Error> {
+    config_key()
+        .map(|key| authenticate_with_key(&key, value))
+        .transpose()
+}
+
+/// Checks a tag made by [`authenticate`]. Without the config key nothing verifies.
+pub fn verify_authenticated<T: Serialize>(value: &T, tag: &str) -> bool {
+    config_key().is_some_and(|key| verify_with_key(&key, value, tag))
+}
+
+fn value_mac<T: Serialize>(key: &[u8; KEY_LENGTH], value: &T) -> Result<Blake2bMac<U32>, Error> {
+    let mut mac = Blake2bMac::<U32>::new_with_salt_and_personal(key, &[], MAC_PERSONAL)
+        .map_err(|e| anyhow!("Could not create config MAC: {}", e))?;
+    mac.update(&serde_json::to_vec(value)?);
+    Ok(mac)
+}
+
+fn authenticate_with_key<T: Serialize>(key: &[u8; KEY_LENGTH], value: &T) -> Result<String, Error> {
+    Ok(hex::encode(value_mac(key, value)?.finalize().into_bytes()))
+}
+
+fn verify_with_key<T: Serialize>(key: &[u8; KEY_LENGTH], value: &T, tag: &str) -> bool {
+    match (value_mac(key, value), hex::decode(tag)) {
+        (Ok(mac), Ok(tag)) => mac.verify_slice(&tag).is_ok(),
+        _ => false,
+    }
+}
+
+fn seal_with_key<T: Serialize>(key: &[u8; KEY_LENGTH], value: &T) -> Result<String, Error> {
+    let plaintext = serde_json::to_vec(value)?;
+    let (nonce, ciphertext) = crypto::seal(key, &plaintext, SEALED_AAD)
+        .map_err(|_| anyhow!("Could not seal config secrets"))?;
+    Ok(format!(
+        "{}{}{}",
+        SEALED_PREFIX,
+        hex::encode(nonce),
+        hex::encode(ciphertext)
+    ))
+}
+
+fn unseal_with_key<T: DeserializeOwned>(key: &[u8; KEY_LENGTH], sealed: &str) -> Result<T, Error> {
+    let sealed = sealed
+        .strip_prefix(SEALED_PREFIX)
+        .and_then(|sealed| hex::decode(sealed).ok())
+        .filter(|sealed| sealed.len() > NONCE_LENGTH)
+        .ok_or_else(|| anyhow!("Sealed config secrets are corrupted"))?;
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LENGTH);
+    let plaintext = crypto::open(key, nonce, ciphertext, SEALED_AAD)
+        .map_err(|_| anyhow!("Sealed config secrets do not match the config key"))?;
+    Ok(serde_json::from_slice(&plaintext)?)
+}
+
+/// Replaces every occurrence of the given secrets, used on files leaving the machine
+pub fn redact_secrets(content: &[u8], secrets: &[String]) -> Vec<u8> {
+    secrets
+        .iter()
+        .filter(|secret| !secret.is_empty())
+        .fold(content.to_vec(), |content, secret| {
+            replace_bytes(&content, secret.as_bytes(), REDACTED.as_bytes())
+        })
+}
+
+fn replace_bytes(haystack: &[u8], needle: &[u8], replacement: &[u8]) -> Vec<u8> {
+    let mut replaced = Vec::with_capacity(haystack.len());
+    let mut position = 0;
+    while position < haystack.len() {
+        if haystack[position..].starts_with(needle) {
+            replaced.extend_from_slice(replacement);
+            position += needle.len();
+        } else {
+            replaced.push(haystack[position]);
+            position += 1;
+        }
+    }
+    replaced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_values_round_trip_and_reject_other_keys() {
+        let key = [7u8; KEY_LENGTH];
+        let sealed = seal_with_key(&key, &"https://node.example:443").unwrap();
+        assert!(sealed.starts_with(SEALED_PREFIX));
+        assert!(!sealed.contains("node.example"));
+
+        let unsealed: String = unseal_with_key(&key, &sealed).unwrap();
+        assert_eq!(unsealed, "https://node.example:443");
+        assert!(unseal_with_key::<String>(&[8u8; KEY_LENGTH], &sealed).is_err());
+        assert!(unseal_with_key::<String>(&key, "https://node.example:443").is_err());
+    }
+
+    #[test]
+    fn authenticated_values_reject_changes_and_other_keys() {
+        let key = [7u8; KEY_LENGTH];
+        let tag = authenticate_with_key(&key, &("default", "destination")).unwrap();
+        assert!(verify_with_key(&key, &("default", "destination"), &tag));
+        assert!(!verify_with_key(&key, &("default", "other"), &tag));
+        assert!(!verify_with_key(&key, &("wallet_1", "destination"), &tag));
+        assert!(!verify_with_key(
+            &[8u8; KEY_LENGTH],
+            &("default", "destination"),
+            &tag
+        ));
+        assert!(!verify_with_key(
+            &key,
+            &("default", "destination"),
+            "not hex"
+        ));
+    }
+
+    #[test]
+    fn redacts_every_occurrence() {
+        let secrets = vec!["token-123".to_string(), String::new()];
+        let redacted = redact_secrets(b"auth token-123 then token-123.", &secrets);
+        assert_eq!(
+            String::from_utf8(redacted).unwrap(),
+            "auth <redacted> then <redacted>."
+        );
+    }
+}
@@ -25,12 +25,19 @@ use std::{sync::LazyLock, time::SystemTime};
 use getset::{Getters, Setters};
 use log::error;
 use serde::{Deserialize, Serialize};
+use tari_common_types::tari_address::TariAddress;
 use tauri::{AppHandle, Manager};
 use tokio::sync::RwLock;
 
 use crate::{
-    events_emitter::EventsEmitter, events_manager::EventsManager, internal_wallet::InternalWallet,
-    utils::wallet_utils::create_monereo_address, AppConfig, UniverseAppState,
+    credential_backends::SecretBackendConfig,
+    events_emitter::EventsEmitter,
+    events_manager::EventsManager,
+    internal_wallet::InternalWallet,
+    monero_payout::{push_history, MoneroAddressRecord},
+    utils::wallet_utils::create_monereo_address,
+    wallet_profiles::{WalletProfile, DEFAULT_WALLET_ID},
+    AppConfig, UniverseAppState,
 };
 
 use super::trait_config::{ConfigContentImpl, ConfigImpl};
@@ -52,8 +59,18 @@ pub struct ConfigWalletContent {
     monero_address: String,
     #[getset(get = "pub")]
     monero_address_is_generated: bool,
+    #[getset(get = "pub")]
+    monero_address_history: Vec<MoneroAddressRecord>,
     #[getset(get = "pub", set = "pub")]
     keyring_accessed: bool,
+    #[getset(get = "pub", set = "pub")]
+    is_watch_only: bool,
+    #[getset(get = "pub", set = "pub")]
+    active_wallet_id: String,
+    #[getset(get = "pub", set = "pub")]
+    wallet_profiles: Vec<WalletProfile>,
+    #[getset(get = "pub", set = "pub")]
+    secret_backend: SecretBackendConfig,
 }
 
 impl Default for ConfigWalletContent {
@@ -63,7 +80,12 @@ impl Default for ConfigWalletContent {
             created_at: SystemTime::now(),
             monero_address: "".to_string(),
             monero_address_is_generated: false,
+            monero_address_history: Vec::new(),
             keyring_accessed: false,
+            is_watch_only: false,
+            active_wallet_id: DEFAULT_WALLET_ID.to_string(),
+            wallet_profiles: vec![WalletProfile::default_profile()],
+            secret_backend: SecretBackendConfig::default(),
         }
     }
 }
@@ -72,15 +94,30 @@ impl ConfigContentImpl for ConfigWalletContent {}
 
 impl ConfigWalletContent {
     pub fn set_user_monero_address(&mut self, address: String) -> &mut Self {
-        self.monero_address = address;
-        self.monero_address_is_generated = false;
-
-        self
+        self.replace_monero_address(address, false)
     }
 
     pub fn set_generated_monero_address(&mut self, address: String) -> &mut Self {
+        self.replace_monero_address(address, true)
+    }
+
+    fn replace_monero_address(&mut self, address: String, is_generated: bool) -> &mut Self {
+        // Addresses set before the history existed are recorded the first time they get replaced
+        if self.monero_address_history.is_empty() {
+            push_history(
+                &mut self.monero_address_history,
+                MoneroAddressRecord::new(
+                    self.monero_address.clone(),
+                    self.monero_address_is_generated,
+                ),
+            );
+        }
+        push_history(
+            &mut self.monero_address_history,
+            MoneroAddressRecord::new(address.clone(), is_generated),
+        );
         self.monero_address = address;
-        self.monero_address_is_generated = true;
+        self.monero_address_is_generated = is_generated;
 
         self
     }
@@ -94,12 +131,6 @@ pub struct ConfigWallet {
 impl ConfigWallet {
     pub async fn initialize(app_handle: AppHandle, old_config: Option<AppConfig>) {
         let mut config = Self::current().write().await;
-        let state = app_handle.state::<UniverseAppState>();
-        let old_config_path = app_handle
-            .path()
-            .app_config_dir()
-            .expect("Could not get config dir");
-
         config.handle_old_config_migration(old_config);
         config.load_app_handle(app_handle.clone()).await;
         drop(config);
@@ -116,24 +147,9 @@ impl ConfigWallet {
             }
         }
 
-        match InternalWallet::load_or_create(old_config_path.clone()).await {
-            Ok(wallet) => {
-                state.cpu_miner_config.write().await.tari_address = wallet.get_tari_address();
-                state
-                    .wallet_manager
-                    .set_view_private_key_and_spend_key(
-                        wallet.get_view_key(),
-                        wallet.get_spend_key(),
-                    )
-                    .await;
-                let tari_address = wallet.get_tari_address();
-                *state.tari_address.write().await = tari_address.clone();
-                EventsEmitter::emit_wallet_address_update(&app_handle, tari_address).await;
-            }
-            Err(e) => {
-                error!(target: LOG_TARGET, "Error loading internal wallet: {:?}", e);
-            }
-        };
+        if let Err(e) = Self::load_active_wallet(&app_handle).await {
+            error!(target: LOG_TARGET, "Error loading internal wallet: {:?}", e);
+        }
 
         EventsManager::handle_config_wallet_loaded(
             &app_handle,
@@ -141,6 +157,32 @@ impl ConfigWallet {
         )
         .await;
     }
+
+    /// Loads the active wallet and points the mining wallet and miners at its address
+    pub async fn load_active_wallet(app_handle: &AppHandle) -> Result<TariAddress, anyhow::Error> {
+        let state = app_handle.state::<UniverseAppState>();
+        let config_path = app_handle
+            .path()
+            .app_config_dir()
+            .expect("Could not get config dir");
+
+        let wallet = InternalWallet::load_or_create(config_path).await?;
+        let _unused = ConfigWallet::update_field(
+            ConfigWalletContent::set_is_watch_only,
+            wallet.is_watch_only(),
+        )
+        .await;
+        let tari_address = wallet.get_tari_address();
+        state.cpu_miner_config.write().await.tari_address = tari_address.clone();
+        state
+            .wallet_manager
+            .set_view_private_key_and_spend_key(wallet.get_view_key(), wallet.get_spend_key())
+            .await;
+        *state.tari_address.write().await = tari_address.clone();
+        EventsEmitter::emit_wallet_address_update(app_handle, tari_address.clone()).await;
+
+        Ok(tari_address)
+    }
 }
 
 impl ConfigImpl for ConfigWallet {
@@ -191,6 +233,11 @@ impl ConfigImpl for ConfigWallet {
                 keyring_accessed: old_config.keyring_accessed(),
                 monero_address: old_config.monero_address().to_string(),
                 monero_address_is_generated: old_config.monero_address_is_generated(),
+                monero_address_history: Vec::new(),
+                is_watch_only: false,
+                active_wallet_id: DEFAULT_WALLET_ID.to_string(),
+                wallet_profiles: vec![WalletProfile::default_profile()],
+                secret_backend: SecretBackendConfig::default(),
             };
             let _unused = Self::_save_config(self.content.clone());
         } else {
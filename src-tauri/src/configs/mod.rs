@@ -24,6 +24,7 @@ mod trait_config_test;
 
 pub mod config_core;
 pub mod config_mining;
+pub mod config_secrets;
 pub mod config_ui;
 pub mod config_wallet;
 pub mod trait_config;
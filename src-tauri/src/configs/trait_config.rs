@@ -31,6 +31,7 @@ use tari_common::configuration::Network;
 use tauri::{AppHandle, Manager};
 use tokio::sync::RwLock;
 
+use super::config_secrets::REDACTED;
 use crate::{
     events_manager::EventsManager,
     setup::setup_manager::{SetupManager, SetupPhase},
@@ -101,7 +102,25 @@ pub trait ConfigImpl {
         }
     }
 
+    /// Turns the content into what is written to disk, the place to seal fields that must not be stored in plain JSON
+    fn _prepare_for_save(config_content: Self::Config) -> Result<Self::Config, Error> {
+        Ok(config_content)
+    }
+    /// Reverses [`Self::_prepare_for_save`] for content read from disk
+    fn _restore_after_load(config_content: Self::Config) -> Self::Config {
+        config_content
+    }
+    /// Value sent with the update telemetry event
+    fn _reported_value<I: Serialize>(value: &I, is_sensitive: bool) -> serde_json::Value {
+        if is_sensitive {
+            json!(REDACTED)
+        } else {
+            json!(value)
+        }
+    }
+
     fn _save_config(config_content: Self::Config) -> Result<(), Error> {
+        let config_content = Self::_prepare_for_save(config_content)?;
         let config_path = Self::_get_config_path();
         if let Some(parent) = config_path.parent() {
             fs::create_dir_all(parent)?;
@@ -114,7 +133,7 @@ pub trait ConfigImpl {
         let config_path = Self::_get_config_path();
         let config_content_serialized = fs::read_to_string(config_path)?;
         let config_content: Self::Config = serde_json::from_str(&config_content_serialized)?;
-        Ok(config_content)
+        Ok(Self::_restore_after_load(config_content))
     }
     async fn content() -> Self::Config
     where
@@ -130,7 +149,33 @@ pub trait ConfigImpl {
         F: FnOnce(&mut Self::Config, I) -> &mut Self::Config,
         Self: 'static,
     {
-        debug!(target: LOG_TARGET, "[{}] [update_field] with function: {:?} and value: {:?}", Self::_get_name(), std::any::type_name::<F>(), value);
+        Self::_update_field(setter_callback, value, false).await
+    }
+    /// [`Self::update_field`] for secrets, whose value is kept out of logs and telemetry
+    async fn update_sensitive_field<F, I>(setter_callback: F, value: I) -> Result<(), Error>
+    where
+        I: Serialize + Clone + Debug,
+        F: FnOnce(&mut Self::Config, I) -> &mut Self::Config,
+        Self: 'static,
+    {
+        Self::_update_field(setter_callback, value, true).await
+    }
+    async fn _update_field<F, I>(
+        setter_callback: F,
+        value: I,
+        is_sensitive: bool,
+    ) -> Result<(), Error>
+    where
+        I: Serialize + Clone + Debug,
+        F: FnOnce(&mut Self::Config, I) -> &mut Self::Config,
+        Self: 'static,
+    {
+        if is_sensitive {
+            debug!(target: LOG_TARGET, "[{}] [update_field] with function: {:?} and value: {}", Self::_get_name(), std::any::type_name::<F>(), REDACTED);
+        } else {
+            debug!(target: LOG_TARGET, "[{}] [update_field] with function: {:?} and value: {:?}", Self::_get_name(), std::any::type_name::<F>(), value);
+        }
+        let reported_value = Self::_reported_value(&value, is_sensitive);
         setter_callback(
             Self::current().write().await._get_content_mut(),
             value.clone(),
@@ -146,7 +191,7 @@ pub trait ConfigImpl {
                 json!({
                     "config": Self::_get_name(),
                     "field": std::any::type_name::<F>(),
-                    "value": value,
+                    "value": reported_value,
                 }),
             )
             .await;
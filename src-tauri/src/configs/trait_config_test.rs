@@ -147,6 +147,8 @@ mod tests {
     #![allow(clippy::unwrap_used)]
 
     use super::*;
+    use crate::configs::config_secrets::REDACTED;
+    use serde_json::json;
     use std::fs;
 
     fn clear_config_file() {
@@ -196,6 +198,13 @@ mod tests {
             *TestConfig::_load_config().unwrap().some_test_bool()
         );
     }
+    #[test]
+    fn test_sensitive_values_are_redacted() {
+        let value = "secret".to_string();
+        assert_eq!(TestConfig::_reported_value(&value, true), json!(REDACTED));
+        assert_eq!(TestConfig::_reported_value(&value, false), json!(value));
+    }
+
     #[tokio::test]
     async fn test_migrate_old_config() {
         let mut config = TestConfig::current().write().await;
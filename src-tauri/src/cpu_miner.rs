@@ -24,6 +24,7 @@ use crate::app_config::MiningMode;
 use crate::binaries::Binaries;
 use crate::commands::{CpuMinerConnection, CpuMinerConnectionStatus, CpuMinerStatus};
 use crate::configs::config_mining::ConfigMiningContent;
+use crate::node::network_stats::NetworkStats;
 use crate::process_stats_collector::ProcessStatsCollectorBuilder;
 use crate::process_watcher::ProcessWatcher;
 use crate::tasks_tracker::TasksTrackers;
@@ -309,8 +310,10 @@ impl CpuMiner {
                         let cpu_status = match xmrig_summary {
                             Some(xmrig_status) => {
                                 let hash_rate = xmrig_status.hashrate.total[0].unwrap_or_default();
+                                let (_, randomx_network_hashrate) =
+                                    NetworkStats::current().smoothed_hashrates(&node_status).await;
                                 let estimated_earnings =
-                                    estimate_earning(node_status.randomx_network_hashrate, hash_rate, node_status.block_reward);
+                                    estimate_earning(randomx_network_hashrate, hash_rate, node_status.block_reward);
 
                                 // // UNUSED, commented for now
                                 // let hasrate_sum = xmrig_status
@@ -0,0 +1,625 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+use std::{
+    ffi::OsString,
+    fs,
+    io::{ErrorKind, Read, Write},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::{LazyLock, Mutex, RwLock},
+    thread,
+    time::{Duration, Instant},
+};
+
+use keyring::{Entry, Error as KeyringError};
+use serde::{Deserialize, Serialize};
+use tari_utilities::SafePassword;
+
+use crate::{
+    credential_manager::CredentialError,
+    crypto::{self, validate_passphrase, KdfParams, KEY_LENGTH},
+};
+
+/// Passphrase of the encrypted file backend for unattended starts, used when none was entered in the app
+pub const CREDENTIALS_PASSPHRASE_ENV: &str = "TARI_UNIVERSE_CREDENTIALS_PASSPHRASE";
+/// Absolute path of an external secrets agent. It replaces the configured backend and can only be set by whoever
+/// launches the app, never from the webview or the config files.
+pub const SECRETS_COMMAND_ENV: &str = "TARI_UNIVERSE_SECRETS_COMMAND";
+const ENCRYPTED_FILE_VERSION: u32 = 1;
+const ENCRYPTED_FILE_AAD: &[u8] = b"tari-universe-credentials:1";
+/// Long enough for an agent that asks the user to unlock it
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+const COMMAND_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+static PASSPHRASE: LazyLock<RwLock<Option<SafePassword>>> = LazyLock::new(|| RwLock::new(None));
+/// Last key derived by the encrypted file backend, so that every credential read does not run Argon2 again
+static DERIVED_KEY: LazyLock<Mutex<Option<DerivedKey>>> = LazyLock::new(|| Mutex::new(None));
+
+struct DerivedKey {
+    passphrase: SafePassword,
+    kdf: KdfParams,
+    key: [u8; KEY_LENGTH],
+}
+
+/// Where credentials are kept, stored in the wallet config
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SecretBackendConfig {
+    /// OS keyring, falling back to a file in the config directory when no keyring is available
+    #[default]
+    Keyring,
+    /// File encrypted with a passphrase entered in the app or given through [`CREDENTIALS_PASSPHRASE_ENV`]
+    EncryptedFile,
+    /// External secrets agent from [`SECRETS_COMMAND_ENV`], run as `program get|set|delete <key>` exchanging hex on
+    /// stdin/stdout. Never read from the config or the webview.
+    #[serde(skip_deserializing)]
+    Command { program: PathBuf },
+    /// Read-only hex secrets at `directory/<key>`, e.g. systemd credentials or FIFOs fed by a secrets agent
+    Directory { directory: PathBuf },
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretBackendKind {
+    Keyring,
+    FallbackFile,
+    EncryptedFile,
+    Command,
+    Directory,
+}
+
+/// Implementations block on files, child processes or key derivation, so callers run them off the async workers
+pub trait SecretBackend: Send {
+    fn kind(&self) -> SecretBackendKind;
+    /// Stored secret, `None` when there is nothing under this backend's key
+    fn load(&self) -> Result<Option<Vec<u8>>, CredentialError>;
+    fn store(&self, secret: &[u8]) -> Result<(), CredentialError>;
+    fn delete(&self) -> Result<(), CredentialError>;
+}
+
+/// Keeps the encrypted file backend passphrase in memory for this session
+pub fn set_passphrase(passphrase: &str) -> Result<(), CredentialError> {
+    validate_passphrase(passphrase).map_err(|e| CredentialError::Backend(e.to_string()))?;
+    *PASSPHRASE.write().unwrap_or_else(|e| e.into_inner()) = Some(SafePassword::from(passphrase));
+    Ok(())
+}
+
+pub fn clear_passphrase() {
+    *PASSPHRASE.write().unwrap_or_else(|e| e.into_inner()) = None;
+    *DERIVED_KEY.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+pub fn has_passphrase() -> bool {
+    passphrase().is_some()
+}
+
+/// Secrets command set for this process, if any
+pub fn secrets_command() -> Result<Option<PathBuf>, CredentialError> {
+    parse_secrets_command(std::env::var_os(SECRETS_COMMAND_ENV))
+}
+
+fn parse_secrets_command(value: Option<OsString>) -> Result<Option<PathBuf>, CredentialError> {
+    let Some(program) = value
+        .filter(|program| !program.is_empty())
+        .map(PathBuf::from)
+    else {
+        return Ok(None);
+    };
+    if !program.is_absolute() {
+        return Err(CredentialError::Backend(format!(
+            "{} must be an absolute path",
+            SECRETS_COMMAND_ENV
+        )));
+    }
+    Ok(Some(program))
+}
+
+fn passphrase() -> Option<SafePassword> {
+    PASSPHRASE
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .or_else(|| {
+            std::env::var(CREDENTIALS_PASSPHRASE_ENV)
+                .ok()
+                .filter(|passphrase| !passphrase.is_empty())
+                .map(SafePassword::from)
+        })
+}
+
+pub struct KeyringBackend {
+    service_name: String,
+    username: String,
+}
+
+impl KeyringBackend {
+    pub fn new(service_name: &str, username: &str) -> Self {
+        Self {
+            service_name: service_name.to_string(),
+            username: username.to_string(),
+        }
+    }
+}
+
+impl SecretBackend for KeyringBackend {
+    fn kind(&self) -> SecretBackendKind {
+        SecretBackendKind::Keyring
+    }
+
+    fn load(&self) -> Result<Option<Vec<u8>>, CredentialError> {
+        let entry = Entry::new(&self.service_name, &self.username)?;
+        match entry.get_secret() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(KeyringError::NoEntry) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn store(&self, secret: &[u8]) -> Result<(), CredentialError> {
+        let entry = Entry::new(&self.service_name, &self.username)?;
+        let _unused = entry.delete_credential();
+        entry.set_secret(secret)?;
+        Ok(())
+    }
+
+    fn delete(&self) -> Result<(), CredentialError> {
+        let entry = Entry::new(&self.service_name, &self.username)?;
+        match entry.delete_credential() {
+            Ok(()) | Err(KeyringError::NoEntry) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Unencrypted file used when the keyring cannot be reached
+pub struct FallbackFileBackend {
+    path: PathBuf,
+}
+
+impl FallbackFileBackend {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl SecretBackend for FallbackFileBackend {
+    fn kind(&self) -> SecretBackendKind {
+        SecretBackendKind::FallbackFile
+    }
+
+    fn load(&self) -> Result<Option<Vec<u8>>, CredentialError> {
+        read_optional(&self.path)
+    }
+
+    fn store(&self, secret: &[u8]) -> Result<(), CredentialError> {
+        write_file(&self.path, secret)
+    }
+
+    fn delete(&self) -> Result<(), CredentialError> {
+        remove_optional(&self.path)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct EncryptedFile {
+    version: u32,
+    kdf: KdfParams,
+    nonce: String,
+    ciphertext: String,
+}
+
+pub struct EncryptedFileBackend {
+    path: PathBuf,
+    passphrase: SafePassword,
+}
+
+impl EncryptedFileBackend {
+    /// Fails with [`CredentialError::Locked`] until a passphrase is available
+    pub fn new(path: PathBuf) -> Result<Self, CredentialError> {
+        let passphrase = passphrase().ok_or(CredentialError::Locked)?;
+        Ok(Self { path, passphrase })
+    }
+
+    fn key(&self, kdf: &KdfParams) -> Result<[u8; KEY_LENGTH], CredentialError> {
+        let mut cached = DERIVED_KEY.lock().unwrap_or_else(|e| e.into_inner());
+        let key = match cached
+            .as_ref()
+            .filter(|derived| self.matches(derived) && derived.kdf == *kdf)
+            .map(|derived| derived.key)
+        {
+            Some(key) => key,
+            None => {
+                let key = kdf
+                    .derive_key(self.passphrase.reveal())
+                    .map_err(|e| CredentialError::Backend(e.to_string()))?;
+                *cached = Some(DerivedKey {
+                    passphrase: self.passphrase.clone(),
+                    kdf: kdf.clone(),
+                    key,
+                });
+                key
+            }
+        };
+        Ok(key)
+    }
+
+    /// Settings of the cached key when they are as strong as the defaults, so rewriting the file skips Argon2
+    fn store_kdf(&self) -> KdfParams {
+        DERIVED_KEY
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .filter(|derived| self.matches(derived) && derived.kdf.has_default_cost())
+            .map_or_else(KdfParams::default, |derived| derived.kdf.clone())
+    }
+
+    fn matches(&self, cached: &DerivedKey) -> bool {
+        cached.passphrase.reveal() == self.passphrase.reveal()
+    }
+}
+
+impl SecretBackend for EncryptedFileBackend {
+    fn kind(&self) -> SecretBackendKind {
+        SecretBackendKind::EncryptedFile
+    }
+
+    fn load(&self) -> Result<Option<Vec<u8>>, CredentialError> {
+        let Some(contents) = read_optional(&self.path)? else {
+            return Ok(None);
+        };
+        let corrupted =
+            || CredentialError::Backend("Encrypted credentials are corrupted".to_string());
+        let file: EncryptedFile = serde_json::from_slice(&contents).map_err(|_| corrupted())?;
+        if file.version > ENCRYPTED_FILE_VERSION {
+            return Err(CredentialError::Backend(format!(
+                "Encrypted credentials version {} is not supported",
+                file.version
+            )));
+        }
+        let nonce = hex::decode(&file.nonce).map_err(|_| corrupted())?;
+        let ciphertext = hex::decode(&file.ciphertext).map_err(|_| corrupted())?;
+        let secret = crypto::open(
+            &self.key(&file.kdf)?,
+            &nonce,
+            &ciphertext,
+            ENCRYPTED_FILE_AAD,
+        )
+        .map_err(|_| {
+            CredentialError::Backend(
+                "Wrong passphrase or corrupted encrypted credentials".to_string(),
+            )
+        })?;
+        Ok(Some(secret))
+    }
+
+    fn store(&self, secret: &[u8]) -> Result<(), CredentialError> {
+        let kdf = self.store_kdf();
+        let (nonce, ciphertext) = crypto::seal(&self.key(&kdf)?, secret, ENCRYPTED_FILE_AAD)
+            .map_err(|_| CredentialError::Backend("Could not encrypt credentials".to_string()))?;
+        let file = EncryptedFile {
+            version: ENCRYPTED_FILE_VERSION,
+            kdf,
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        };
+        let contents =
+            serde_json::to_vec(&file).map_err(|e| CredentialError::Backend(e.to_string()))?;
+        write_file(&self.path, &contents)
+    }
+
+    fn delete(&self) -> Result<(), CredentialError> {
+        remove_optional(&self.path)
+    }
+}
+
+/// Delegates to an external program. `get` prints the hex secret, or nothing when there is none, `set` reads it
+/// from stdin and `delete` removes it. A non-zero exit status or running past the timeout is an error.
+pub struct CommandBackend {
+    program: PathBuf,
+    key: String,
+    timeout: Duration,
+}
+
+impl CommandBackend {
+    pub fn new(program: &Path, key: &str) -> Self {
+        Self {
+            program: program.to_path_buf(),
+            key: key.to_string(),
+            timeout: COMMAND_TIMEOUT,
+        }
+    }
+
+    fn run(&self, action: &str, input: Option<&[u8]>) -> Result<Vec<u8>, CredentialError> {
+        let mut child = Command::new(&self.program)
+            .arg(action)
+            .arg(&self.key)
+            .stdin(if input.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        // Drained on their own threads so a chatty agent cannot stall on a full pipe
+        let stdout = child.stdout.take().map(read_to_end);
+        let stderr = child.stderr.take().map(read_to_end);
+        if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+            // Dropping stdin afterwards closes it, so the agent sees the end of the input
+            if let Err(e) = stdin.write_all(input) {
+                stop(&mut child);
+                return Err(e.into());
+            }
+        }
+        let status = match wait_with_timeout(&mut child, self.timeout) {
+            Ok(Some(status)) => status,
+            Err(e) => {
+                stop(&mut child);
+                return Err(e);
+            }
+            Ok(None) => {
+                stop(&mut child);
+                return Err(CredentialError::Backend(format!(
+                    "Secrets command `{} {}` did not finish within {}s",
+                    self.program.display(),
+                    action,
+                    self.timeout.as_secs()
+                )));
+            }
+        };
+        let join = |reader: Option<thread::JoinHandle<Vec<u8>>>| {
+            reader
+                .and_then(|reader| reader.join().ok())
+                .unwrap_or_default()
+        };
+        let (stdout, stderr) = (join(stdout), join(stderr));
+        if !status.success() {
+            return Err(CredentialError::Backend(format!(
+                "Secrets command `{} {}` failed: {}",
+                self.program.display(),
+                action,
+                String::from_utf8_lossy(&stderr).trim()
+            )));
+        }
+        Ok(stdout)
+    }
+}
+
+fn read_to_end(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut contents = Vec::new();
+        let _unused = pipe.read_to_end(&mut contents);
+        contents
+    })
+}
+
+/// `None` when the child is still running after `timeout`
+/// Kills the agent and reaps it so no zombie is left behind
+fn stop(child: &mut Child) {
+    let _unused = child.kill();
+    let _unused = child.wait();
+}
+
+fn wait_with_timeout(
+    child: &mut Child,
+    timeout: Duration,
+) -> Result<Option<std::process::ExitStatus>, CredentialError> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            return Ok(None);
+        }
+        thread::sleep(COMMAND_POLL_INTERVAL);
+    }
+}
+
+impl SecretBackend for CommandBackend {
+    fn kind(&self) -> SecretBackendKind {
+        SecretBackendKind::Command
+    }
+
+    fn load(&self) -> Result<Option<Vec<u8>>, CredentialError> {
+        decode_hex_secret(&self.run("get", None)?)
+    }
+
+    fn store(&self, secret: &[u8]) -> Result<(), CredentialError> {
+        self.run("set", Some(hex::encode(secret).as_bytes()))?;
+        Ok(())
+    }
+
+    fn delete(&self) -> Result<(), CredentialError> {
+        self.run("delete", None)?;
+        Ok(())
+    }
+}
+
+/// Secrets provisioned outside the app. Reading a FIFO blocks until its writer provides the secret.
+pub struct DirectoryBackend {
+    path: PathBuf,
+}
+
+impl DirectoryBackend {
+    pub fn new(directory: &Path, key: &str) -> Self {
+        Self {
+            path: directory.join(key),
+        }
+    }
+}
+
+impl SecretBackend for DirectoryBackend {
+    fn kind(&self) -> SecretBackendKind {
+        SecretBackendKind::Directory
+    }
+
+    fn load(&self) -> Result<Option<Vec<u8>>, CredentialError> {
+        match read_optional(&self.path)? {
+            Some(contents) => decode_hex_secret(&contents),
+            None => Ok(None),
+        }
+    }
+
+    fn store(&self, _secret: &[u8]) -> Result<(), CredentialError> {
+        Err(CredentialError::Backend(format!(
+            "Credentials directory is read-only, provision {} with the hex encoded secret",
+            self.path.display()
+        )))
+    }
+
+    /// Secrets provisioned outside the app are left for their owner to remove
+    fn delete(&self) -> Result<(), CredentialError> {
+        Ok(())
+    }
+}
+
+fn decode_hex_secret(contents: &[u8]) -> Result<Option<Vec<u8>>, CredentialError> {
+    let text = String::from_utf8_lossy(contents);
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    hex::decode(text)
+        .map(Some)
+        .map_err(|_| CredentialError::Backend("External secret is not valid hex".to_string()))
+}
+
+fn read_optional(path: &Path) -> Result<Option<Vec<u8>>, CredentialError> {
+    match fs::read(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn write_file(path: &Path, contents: &[u8]) -> Result<(), CredentialError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, contents)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+fn remove_optional(path: &Path) -> Result<(), CredentialError> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "tari-universe-credential-backends-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _unused = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn encrypted_file_round_trips_and_rejects_wrong_passphrase() {
+        let path = temp_dir("encrypted").join("credentials.enc");
+        let backend = EncryptedFileBackend {
+            path: path.clone(),
+            passphrase: SafePassword::from("correct horse battery"),
+        };
+        assert_eq!(backend.load().unwrap(), None);
+        backend.store(b"secret").unwrap();
+        assert_eq!(backend.load().unwrap(), Some(b"secret".to_vec()));
+        assert!(!fs::read(&path).unwrap().windows(6).any(|w| w == b"secret"));
+
+        let wrong = EncryptedFileBackend {
+            path: path.clone(),
+            passphrase: SafePassword::from("wrong passphrase"),
+        };
+        assert!(wrong.load().is_err());
+        backend.delete().unwrap();
+        assert_eq!(backend.load().unwrap(), None);
+    }
+
+    #[test]
+    fn directory_backend_reads_hex_and_is_read_only() {
+        let dir = temp_dir("directory");
+        let backend = DirectoryBackend::new(&dir, "key");
+        assert_eq!(backend.load().unwrap(), None);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("key"), "736563726574\n").unwrap();
+        assert_eq!(backend.load().unwrap(), Some(b"secret".to_vec()));
+        assert!(backend.store(b"other").is_err());
+    }
+
+    #[test]
+    fn hex_secrets_treat_blank_output_as_missing() {
+        assert_eq!(decode_hex_secret(b" \n").unwrap(), None);
+        assert!(decode_hex_secret(b"zz").is_err());
+    }
+
+    #[test]
+    fn backend_config_defaults_to_keyring() {
+        let config: SecretBackendConfig = serde_json::from_str(r#"{"type":"keyring"}"#).unwrap();
+        assert_eq!(config, SecretBackendConfig::default());
+    }
+
+    #[test]
+    fn secrets_command_only_comes_from_an_absolute_env_path() {
+        assert!(serde_json::from_str::<SecretBackendConfig>(
+            r#"{"type":"command","program":"/usr/bin/pass"}"#
+        )
+        .is_err());
+        assert_eq!(parse_secrets_command(None).unwrap(), None);
+        assert_eq!(parse_secrets_command(Some(OsString::new())).unwrap(), None);
+        assert!(parse_secrets_command(Some("pass".into())).is_err());
+        let program = std::env::temp_dir().join("secrets-agent");
+        assert_eq!(
+            parse_secrets_command(Some(program.clone().into())).unwrap(),
+            Some(program)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn command_backend_stops_a_hanging_agent() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = temp_dir("command");
+        fs::create_dir_all(&dir).unwrap();
+        let program = dir.join("agent.sh");
+        fs::write(&program, "#!/bin/sh\nsleep 10\n").unwrap();
+        fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+        let backend = CommandBackend {
+            timeout: Duration::from_millis(200),
+            ..CommandBackend::new(&program, "key")
+        };
+        let started = Instant::now();
+        assert!(backend.load().is_err());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
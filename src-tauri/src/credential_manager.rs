@@ -20,14 +20,20 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use crate::app_lock::AppLockStore;
 use crate::configs::config_wallet::{ConfigWallet, ConfigWalletContent};
 use crate::configs::trait_config::ConfigImpl;
+use crate::credential_backends::{
+    has_passphrase, secrets_command, CommandBackend, DirectoryBackend, EncryptedFileBackend,
+    FallbackFileBackend, KeyringBackend, SecretBackend, SecretBackendConfig, SecretBackendKind,
+    SECRETS_COMMAND_ENV,
+};
+use crate::wallet_profiles::{DEFAULT_WALLET_ID, WALLETS_DIR};
 use crate::APPLICATION_FOLDER_ID;
-use keyring::{Entry, Error as KeyringError};
-use log::info;
+use keyring::Error as KeyringError;
+use log::{info, warn};
 use serde::{Deserialize, Serialize};
-use std::fs::OpenOptions;
-use std::io::{self, Read, Write};
+use std::io;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use tari_common::configuration::Network;
@@ -36,10 +42,22 @@ use thiserror::Error;
 
 const LOG_TARGET: &str = "tari::universe::credential_manager";
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct Credential {
     pub tari_seed_passphrase: Option<SafePassword>,
     pub monero_seed: Option<[u8; 32]>,
+    /// Seeds of generated Monero addresses that have since been replaced, kept so their rewards stay recoverable
+    #[serde(default)]
+    pub retired_monero_seeds: Vec<[u8; 32]>,
+    /// Key sealing the secret fields of the JSON configs
+    #[serde(default)]
+    pub config_key: Option<[u8; 32]>,
+    /// Password of the spend wallet database, generated once per install
+    #[serde(default)]
+    pub spend_wallet_password: Option<SafePassword>,
+    /// PIN hash, auto-lock timeout and lockout counters of the app lock
+    #[serde(default)]
+    pub app_lock: Option<AppLockStore>,
 }
 
 #[derive(Error, Debug)]
@@ -54,9 +72,14 @@ pub enum CredentialError {
     NoEntry(String),
     #[error("Data previously stored in Keychain.\nKeychain access is now required to continue")]
     PreviouslyUsedKeyring,
+    #[error("Secret storage failed: {0}")]
+    Backend(String),
+    #[error("Encrypted credentials are locked, enter the passphrase to continue")]
+    Locked,
 }
 
 const FALLBACK_FILE_PATH: &str = "credentials_backup.bin";
+const ENCRYPTED_FILE_PATH: &str = "credentials.enc";
 
 const KEYCHAIN_USERNAME: &str = "inner_wallet_credentials";
 
@@ -95,15 +118,46 @@ impl CredentialManager {
         )
     }
 
+    /// Credentials of a named wallet get their own keyring entry, the default wallet keeps the original one
+    pub fn for_wallet(fallback_dir: PathBuf, wallet_id: &str) -> Self {
+        if wallet_id == DEFAULT_WALLET_ID {
+            return CredentialManager::default_with_dir(fallback_dir);
+        }
+
+        let network_key = Network::get_current().as_key_str();
+        CredentialManager::new(
+            APPLICATION_FOLDER_ID.into(),
+            format!("{}_{}_{}", KEYCHAIN_USERNAME, network_key, wallet_id),
+            fallback_dir
+                .join(network_key)
+                .join(WALLETS_DIR)
+                .join(wallet_id),
+        )
+    }
+
+    /// Removes the stored credentials from the configured backend and any legacy keyring or fallback copy, used
+    /// when a named wallet is deleted
+    pub async fn delete_credentials(&self) -> Result<(), CredentialError> {
+        let backend_config = active_backend_config().await?;
+        if let Some(backend) = self.configured_backend(&backend_config)? {
+            delete(backend).await?;
+        }
+        delete(Box::new(self.fallback_backend())).await?;
+        let _unused = delete(Box::new(self.keyring_backend())).await;
+        Ok(())
+    }
+
     pub async fn migrate(&self) -> Result<(), CredentialError> {
         // Shortcut and do nothing if we already have new credential format
         let creds = self.get_credentials().await;
+        let mut config_key = None;
         if let Ok(creds) = creds {
             info!(target: LOG_TARGET, "Found credentials");
             if creds.tari_seed_passphrase.is_some() {
                 info!(target: LOG_TARGET, "Credentials already migrated. Skipping.");
                 return Ok(());
             }
+            config_key = creds.config_key;
         };
 
         info!(target: LOG_TARGET, "No credentials found, migrating credentials");
@@ -119,13 +173,83 @@ impl CredentialManager {
             parent_dir.to_path_buf(),
         );
 
-        if let Ok(credential) = old_credential_manager.get_credentials().await {
+        // The old format predates configurable backends and only ever lived in the keyring or fallback file
+        if let Ok(mut credential) = old_credential_manager.get_from_keyring().await {
+            // The config key may have been created before the wallet credentials were migrated
+            credential.config_key = credential.config_key.or(config_key);
             self.set_credentials(&credential).await?
         }
 
         Ok(())
     }
 
+    /// Copies the credentials from one backend to another and reads them back. A target already holding the same
+    /// credentials, such as a provisioned read-only directory, is left untouched, and one holding other credentials
+    /// is refused rather than overwritten. Returns whether this call wrote to the target, a failed read-back removes
+    /// the copy again.
+    pub async fn copy_credentials(
+        &self,
+        from: &SecretBackendConfig,
+        to: &SecretBackendConfig,
+    ) -> Result<bool, CredentialError> {
+        let credential = match self.get_credentials_from(from).await {
+            Ok(credential) => credential,
+            Err(CredentialError::NoEntry(_)) => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        let serialized = serde_cbor::to_vec(&credential)?;
+        match self.get_credentials_from(to).await {
+            Ok(existing) if serde_cbor::to_vec(&existing)? == serialized => return Ok(false),
+            Ok(_) => {
+                return Err(CredentialError::Backend(
+                    "The new backend already holds different credentials".to_string(),
+                ))
+            }
+            Err(CredentialError::NoEntry(_)) => {}
+            Err(e) => return Err(e),
+        }
+        self.set_credentials_to(to, &credential).await?;
+        let matches = match self.get_credentials_from(to).await {
+            Ok(stored) => serde_cbor::to_vec(&stored)? == serialized,
+            Err(_) => false,
+        };
+        if !matches {
+            let _unused = self.delete_credentials_from(to).await;
+            return Err(CredentialError::Backend(
+                "Credentials read back from the new backend do not match".to_string(),
+            ));
+        }
+        Ok(true)
+    }
+
+    pub async fn delete_credentials_from(
+        &self,
+        backend_config: &SecretBackendConfig,
+    ) -> Result<(), CredentialError> {
+        match self.configured_backend(backend_config)? {
+            Some(backend) => delete(backend).await,
+            None => {
+                delete(Box::new(self.fallback_backend())).await?;
+                delete(Box::new(self.keyring_backend())).await
+            }
+        }
+    }
+
+    /// Backend that currently serves this manager's credentials
+    pub async fn active_backend_kind(&self) -> SecretBackendKind {
+        let Ok(backend_config) = active_backend_config().await else {
+            // The secrets command is set, but not to an absolute path
+            return SecretBackendKind::Command;
+        };
+        match backend_config {
+            SecretBackendConfig::Keyring if self.use_fallback() => SecretBackendKind::FallbackFile,
+            SecretBackendConfig::Keyring => SecretBackendKind::Keyring,
+            SecretBackendConfig::EncryptedFile => SecretBackendKind::EncryptedFile,
+            SecretBackendConfig::Command { .. } => SecretBackendKind::Command,
+            SecretBackendConfig::Directory { .. } => SecretBackendKind::Directory,
+        }
+    }
+
     fn use_fallback(&self) -> bool {
         self.fallback_mode.load(Ordering::SeqCst) || self.fallback_file().exists()
     }
@@ -142,12 +266,76 @@ impl CredentialManager {
     }
 
     pub async fn set_credentials(&self, credential: &Credential) -> Result<(), CredentialError> {
+        let backend_config = active_backend_config().await?;
+        self.set_credentials_to(&backend_config, credential).await
+    }
+
+    pub async fn get_credentials(&self) -> Result<Credential, CredentialError> {
+        let backend_config = active_backend_config().await?;
+        match self.get_credentials_from(&backend_config).await {
+            // A newly set secrets command starts out empty and takes over what the configured backend holds
+            Err(CredentialError::NoEntry(_))
+                if matches!(backend_config, SecretBackendConfig::Command { .. }) =>
+            {
+                let configured = ConfigWallet::content().await.secret_backend().clone();
+                if self.copy_credentials(&configured, &backend_config).await? {
+                    info!(target: LOG_TARGET, "Copied credentials {} to the secrets command", self.username);
+                }
+                self.get_credentials_from(&backend_config).await
+            }
+            result => result,
+        }
+    }
+
+    async fn set_credentials_to(
+        &self,
+        backend_config: &SecretBackendConfig,
+        credential: &Credential,
+    ) -> Result<(), CredentialError> {
+        let serialized = serde_cbor::to_vec(credential)?;
+        match self.configured_backend(backend_config)? {
+            Some(backend) => store(backend, serialized).await,
+            None => self.set_to_keyring(serialized).await,
+        }
+    }
+
+    async fn get_credentials_from(
+        &self,
+        backend_config: &SecretBackendConfig,
+    ) -> Result<Credential, CredentialError> {
+        match self.configured_backend(backend_config)? {
+            Some(backend) => self.decode(load(backend).await?),
+            None => self.get_from_keyring().await,
+        }
+    }
+
+    /// Backend for anything but the keyring, which is handled by [`Self::get_from_keyring`] and
+    /// [`Self::set_to_keyring`] because of its fallback file
+    fn configured_backend(
+        &self,
+        backend_config: &SecretBackendConfig,
+    ) -> Result<Option<Box<dyn SecretBackend>>, CredentialError> {
+        let backend: Box<dyn SecretBackend> = match backend_config {
+            SecretBackendConfig::Keyring => return Ok(None),
+            SecretBackendConfig::EncryptedFile => Box::new(EncryptedFileBackend::new(
+                self.fallback_dir.join(ENCRYPTED_FILE_PATH),
+            )?),
+            SecretBackendConfig::Command { program } => {
+                Box::new(CommandBackend::new(program, &self.username))
+            }
+            SecretBackendConfig::Directory { directory } => {
+                Box::new(DirectoryBackend::new(directory, &self.username))
+            }
+        };
+        Ok(Some(backend))
+    }
+
+    async fn set_to_keyring(&self, serialized: Vec<u8>) -> Result<(), CredentialError> {
         if self.use_fallback() {
-            self.save_to_file(credential)?;
-            return Ok(());
+            return store(Box::new(self.fallback_backend()), serialized).await;
         }
 
-        match self.save_to_keyring(credential) {
+        match store(Box::new(self.keyring_backend()), serialized.clone()).await {
             Ok(_) => {
                 let _unused =
                     ConfigWallet::update_field(ConfigWalletContent::set_keyring_accessed, true)
@@ -156,8 +344,7 @@ impl CredentialManager {
             }
             Err(CredentialError::Keyring(e)) => {
                 if self.set_fallback_mode().await {
-                    self.save_to_file(credential)?;
-                    Ok(())
+                    store(Box::new(self.fallback_backend()), serialized).await
                 } else {
                     Err(e.into())
                 }
@@ -166,13 +353,14 @@ impl CredentialManager {
         }
     }
 
-    pub async fn get_credentials(&self) -> Result<Credential, CredentialError> {
+    async fn get_from_keyring(&self) -> Result<Credential, CredentialError> {
         if self.use_fallback() {
-            return self.load_from_file();
+            return self.decode(load(Box::new(self.fallback_backend())).await?);
         }
 
-        match self.load_from_keyring() {
-            Ok(credential) => {
+        match load(Box::new(self.keyring_backend())).await {
+            Ok(secret) => {
+                let credential = self.decode(secret)?;
                 let _unused =
                     ConfigWallet::update_field(ConfigWalletContent::set_keyring_accessed, true)
                         .await;
@@ -180,7 +368,7 @@ impl CredentialManager {
             }
             Err(CredentialError::Keyring(_)) => {
                 if self.set_fallback_mode().await {
-                    self.load_from_file()
+                    self.decode(load(Box::new(self.fallback_backend())).await?)
                 } else {
                     Err(CredentialError::PreviouslyUsedKeyring)
                 }
@@ -189,54 +377,130 @@ impl CredentialManager {
         }
     }
 
-    fn save_to_keyring(&self, credential: &Credential) -> Result<(), CredentialError> {
-        let entry = Entry::new(&self.service_name, &self.username)?;
+    fn decode(&self, secret: Option<Vec<u8>>) -> Result<Credential, CredentialError> {
+        let secret = secret.ok_or_else(|| CredentialError::NoEntry(self.username.clone()))?;
+        Ok(serde_cbor::from_slice(&secret)?)
+    }
 
-        let _unused = entry.delete_credential();
+    fn keyring_backend(&self) -> KeyringBackend {
+        KeyringBackend::new(&self.service_name, &self.username)
+    }
 
-        let serialized = serde_cbor::to_vec(credential)?;
-        entry.set_secret(&serialized)?;
-        Ok(())
+    fn fallback_backend(&self) -> FallbackFileBackend {
+        FallbackFileBackend::new(self.fallback_file())
     }
 
-    fn load_from_keyring(&self) -> Result<Credential, CredentialError> {
-        let entry = Entry::new(&self.service_name, &self.username)?;
-        let encoded = match entry.get_secret() {
-            Ok(secret) => secret,
-            Err(_e @ KeyringError::NoEntry) => {
-                return Err(CredentialError::NoEntry(self.username.clone()))
-            }
-            Err(e) => return Err(e.into()),
-        };
-        let credential: Credential = serde_cbor::from_slice(&encoded)?;
-        Ok(credential)
+    fn fallback_file(&self) -> PathBuf {
+        self.fallback_dir.join(FALLBACK_FILE_PATH)
     }
+}
 
-    fn save_to_file(&self, credential: &Credential) -> Result<(), CredentialError> {
-        let serialized = serde_cbor::to_vec(credential)?;
-        if let Some(parent) = self.fallback_file().parent() {
-            if !parent.exists() {
-                std::fs::create_dir_all(parent)?;
-            }
+/// Backends block on Argon2, child processes and FIFOs, so they run on the blocking pool
+async fn blocking<T: Send + 'static>(
+    operation: impl FnOnce() -> Result<T, CredentialError> + Send + 'static,
+) -> Result<T, CredentialError> {
+    tokio::task::spawn_blocking(operation)
+        .await
+        .map_err(|e| CredentialError::Backend(e.to_string()))?
+}
+
+async fn load(backend: Box<dyn SecretBackend>) -> Result<Option<Vec<u8>>, CredentialError> {
+    blocking(move || backend.load()).await
+}
+
+async fn store(backend: Box<dyn SecretBackend>, secret: Vec<u8>) -> Result<(), CredentialError> {
+    blocking(move || backend.store(&secret)).await
+}
+
+async fn delete(backend: Box<dyn SecretBackend>) -> Result<(), CredentialError> {
+    blocking(move || backend.delete()).await
+}
+
+/// Backend in effect: a secrets command set through [`SECRETS_COMMAND_ENV`] replaces the configured one
+async fn active_backend_config() -> Result<SecretBackendConfig, CredentialError> {
+    match secrets_command()? {
+        Some(program) => Ok(SecretBackendConfig::Command { program }),
+        None => Ok(ConfigWallet::content().await.secret_backend().clone()),
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SecretBackendStatus {
+    pub config: SecretBackendConfig,
+    pub active: SecretBackendKind,
+    /// The encrypted file backend is waiting for its passphrase
+    pub locked: bool,
+    pub writable: bool,
+}
+
+pub async fn secret_backend_status(config_dir: PathBuf) -> SecretBackendStatus {
+    let config = match active_backend_config().await {
+        Ok(config) => config,
+        Err(e) => {
+            warn!(target: LOG_TARGET, "Ignoring the secrets command: {}", e);
+            ConfigWallet::content().await.secret_backend().clone()
         }
-        let mut file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(self.fallback_file())?;
-        file.write_all(&serialized)?;
-        Ok(())
+    };
+    SecretBackendStatus {
+        active: CredentialManager::default_with_dir(config_dir)
+            .active_backend_kind()
+            .await,
+        locked: config == SecretBackendConfig::EncryptedFile && !has_passphrase(),
+        writable: !matches!(config, SecretBackendConfig::Directory { .. }),
+        config,
     }
+}
 
-    fn load_from_file(&self) -> Result<Credential, CredentialError> {
-        let mut file = OpenOptions::new().read(true).open(self.fallback_file())?;
-        let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer)?;
-        let credential: Credential = serde_cbor::from_slice(&buffer)?;
-        Ok(credential)
+/// Moves the credentials of every wallet to `target`. The config only switches once all of them were copied and
+/// read back, the copies left in the previous backend are removed afterwards.
+pub async fn switch_secret_backend(
+    config_dir: PathBuf,
+    target: SecretBackendConfig,
+) -> Result<(), anyhow::Error> {
+    if secrets_command()?.is_some() {
+        return Err(anyhow::anyhow!(
+            "Credentials are kept by the secrets command from {}, unset it to choose another backend",
+            SECRETS_COMMAND_ENV
+        ));
     }
+    let content = ConfigWallet::content().await;
+    let current = content.secret_backend().clone();
+    if current == target {
+        return Ok(());
+    }
+    let mut wallet_ids: Vec<String> = content
+        .wallet_profiles()
+        .iter()
+        .map(|profile| profile.id.clone())
+        .collect();
+    if !wallet_ids.iter().any(|id| id == DEFAULT_WALLET_ID) {
+        wallet_ids.insert(0, DEFAULT_WALLET_ID.to_string());
+    }
+    let managers: Vec<CredentialManager> = wallet_ids
+        .iter()
+        .map(|id| CredentialManager::for_wallet(config_dir.clone(), id))
+        .collect();
 
-    fn fallback_file(&self) -> PathBuf {
-        self.fallback_dir.join(FALLBACK_FILE_PATH)
+    let mut copied = Vec::new();
+    for manager in &managers {
+        match manager.copy_credentials(&current, &target).await {
+            Ok(true) => copied.push(manager),
+            Ok(false) => {}
+            Err(e) => {
+                for manager in copied {
+                    let _unused = manager.delete_credentials_from(&target).await;
+                }
+                return Err(anyhow::anyhow!("Could not migrate credentials: {}", e));
+            }
+        }
+    }
+    ConfigWallet::update_field(ConfigWalletContent::set_secret_backend, target.clone()).await?;
+    info!(target: LOG_TARGET, "Switched secret storage from {:?} to {:?}", current, target);
+
+    for manager in &managers {
+        if let Err(e) = manager.delete_credentials_from(&current).await {
+            warn!(target: LOG_TARGET, "Could not remove credentials {} from the previous backend: {}", manager.username, e);
+        }
     }
+    Ok(())
 }
@@ -0,0 +1,205 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Passphrase based sealing shared by wallet backups, the encrypted credential file and the sealed config fields:
+//! Argon2id derives the key and XChaCha20-Poly1305 encrypts with a random nonce.
+
+use anyhow::{anyhow, Error};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+const KDF_ALGORITHM: &str = "argon2id";
+pub const KEY_LENGTH: usize = 32;
+const SALT_LENGTH: usize = 16;
+pub const NONCE_LENGTH: usize = 24;
+pub const MIN_PASSPHRASE_LENGTH: usize = 8;
+// Upper bounds for KDF settings read from a file, far above the defaults but low enough that a crafted file
+// cannot exhaust memory or stall the app
+const MAX_KDF_MEMORY_KIB: u32 = 256 * 1024;
+const MAX_KDF_ITERATIONS: u32 = 16;
+const MAX_KDF_PARALLELISM: u32 = 16;
+
+/// Argon2 settings, stored next to the ciphertext so they can be raised later without breaking older files
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KdfParams {
+    pub algorithm: String,
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    pub salt: String,
+}
+
+impl KdfParams {
+    pub fn with_random_salt(memory_kib: u32, iterations: u32, parallelism: u32) -> Self {
+        let mut salt = [0u8; SALT_LENGTH];
+        rand::thread_rng().fill_bytes(&mut salt);
+        Self {
+            algorithm: KDF_ALGORITHM.to_string(),
+            memory_kib,
+            iterations,
+            parallelism,
+            salt: hex::encode(salt),
+        }
+    }
+
+    pub fn derive_key(&self, passphrase: &[u8]) -> Result<[u8; KEY_LENGTH], Error> {
+        if self.algorithm != KDF_ALGORITHM {
+            return Err(anyhow!("Unsupported key derivation {}", self.algorithm));
+        }
+        if self.memory_kib > MAX_KDF_MEMORY_KIB
+            || self.iterations > MAX_KDF_ITERATIONS
+            || self.parallelism > MAX_KDF_PARALLELISM
+        {
+            return Err(anyhow!(
+                "Key derivation parameters exceed the supported limits"
+            ));
+        }
+        let salt =
+            hex::decode(&self.salt).map_err(|_| anyhow!("Key derivation salt is corrupted"))?;
+        let params = Params::new(
+            self.memory_kib,
+            self.iterations,
+            self.parallelism,
+            Some(KEY_LENGTH),
+        )
+        .map_err(|e| anyhow!("Invalid key derivation parameters: {}", e))?;
+        let mut key = [0u8; KEY_LENGTH];
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase, &salt, &mut key)
+            .map_err(|e| anyhow!("Could not derive encryption key: {}", e))?;
+        Ok(key)
+    }
+
+    /// Whether these settings are the same strength as the defaults, ignoring the salt
+    pub fn has_default_cost(&self) -> bool {
+        let defaults = Self::default();
+        self.algorithm == defaults.algorithm
+            && self.memory_kib == defaults.memory_kib
+            && self.iterations == defaults.iterations
+            && self.parallelism == defaults.parallelism
+    }
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self::with_random_salt(64 * 1024, 3, 1)
+    }
+}
+
+pub fn validate_passphrase(passphrase: &str) -> Result<(), Error> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LENGTH {
+        return Err(anyhow!(
+            "Passphrase must be at least {} characters",
+            MIN_PASSPHRASE_LENGTH
+        ));
+    }
+    Ok(())
+}
+
+pub fn random_key() -> [u8; KEY_LENGTH] {
+    let mut key = [0u8; KEY_LENGTH];
+    rand::thread_rng().fill_bytes(&mut key);
+    key
+}
+
+/// Encrypts `plaintext` under a fresh random nonce, returning the nonce and the ciphertext
+pub fn seal(
+    key: &[u8; KEY_LENGTH],
+    plaintext: &[u8],
+    aad: &[u8],
+) -> Result<([u8; NONCE_LENGTH], Vec<u8>), Error> {
+    let mut nonce = [0u8; NONCE_LENGTH];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let ciphertext = XChaCha20Poly1305::new(Key::from_slice(key))
+        .encrypt(
+            XNonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .map_err(|_| anyhow!("Could not encrypt"))?;
+    Ok((nonce, ciphertext))
+}
+
+/// Decrypts what [`seal`] produced. Fails on a wrong key, a tampered ciphertext or a different `aad`.
+pub fn open(
+    key: &[u8; KEY_LENGTH],
+    nonce: &[u8],
+    ciphertext: &[u8],
+    aad: &[u8],
+) -> Result<Vec<u8>, Error> {
+    if nonce.len() != NONCE_LENGTH {
+        return Err(anyhow!("Nonce has the wrong length"));
+    }
+    XChaCha20Poly1305::new(Key::from_slice(key))
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| anyhow!("Could not decrypt"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seal_round_trips_only_with_the_same_key_and_aad() {
+        let key = random_key();
+        let (nonce, ciphertext) = seal(&key, b"secret", b"aad").unwrap();
+        assert_eq!(open(&key, &nonce, &ciphertext, b"aad").unwrap(), b"secret");
+        assert!(open(&random_key(), &nonce, &ciphertext, b"aad").is_err());
+        assert!(open(&key, &nonce, &ciphertext, b"other").is_err());
+        assert!(open(&key, &nonce[1..], &ciphertext, b"aad").is_err());
+    }
+
+    #[test]
+    fn derive_key_rejects_oversized_params() {
+        let kdf = KdfParams::with_random_salt(64, 1, 1);
+        assert!(kdf.derive_key(b"passphrase").is_ok());
+        for kdf in [
+            KdfParams {
+                memory_kib: MAX_KDF_MEMORY_KIB + 1,
+                ..kdf.clone()
+            },
+            KdfParams {
+                iterations: MAX_KDF_ITERATIONS + 1,
+                ..kdf.clone()
+            },
+            KdfParams {
+                parallelism: MAX_KDF_PARALLELISM + 1,
+                ..kdf.clone()
+            },
+        ] {
+            assert!(kdf.derive_key(b"passphrase").is_err());
+        }
+    }
+}
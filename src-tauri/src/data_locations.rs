@@ -0,0 +1,595 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        LazyLock, RwLock,
+    },
+};
+
+use anyhow::{anyhow, Error};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+use tokio::sync::watch;
+
+use crate::{
+    configs::{
+        config_core::{ConfigCore, ConfigCoreContent},
+        trait_config::ConfigImpl,
+    },
+    events::DataLocationMigrationStatus,
+    events_manager::EventsManager,
+    setup::setup_manager::{SetupManager, SetupPhase},
+    transaction_index::TransactionIndex,
+    utils::file_utils::{get_available_disk_space, get_dir_size, get_disk_mount_point},
+    APPLICATION_FOLDER_ID,
+};
+
+static LOG_TARGET: &str = "tari::universe::data_locations";
+
+const COPY_BUFFER_SIZE: usize = 1024 * 1024;
+
+static CURRENT: LazyLock<RwLock<DataLocations>> =
+    LazyLock::new(|| RwLock::new(DataLocations::default()));
+static MIGRATION_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum DataComponent {
+    Node,
+    Wallet,
+    P2pool,
+    Binaries,
+}
+
+impl DataComponent {
+    pub fn all() -> Vec<DataComponent> {
+        vec![
+            DataComponent::Node,
+            DataComponent::Wallet,
+            DataComponent::P2pool,
+            DataComponent::Binaries,
+        ]
+    }
+
+    /// Folders, relative to the component root, that belong to this component.
+    /// p2pool keeps its block cache next to its working folder because it runs from the component root.
+    pub fn folders(&self) -> &'static [&'static str] {
+        match self {
+            DataComponent::Node => &["node"],
+            DataComponent::Wallet => &["wallet", "spend_wallet", "transaction_index", "wallets"],
+            DataComponent::P2pool => {
+                &["sha-p2pool", "mmproxy", "block_cache", "block_cache_backup"]
+            }
+            DataComponent::Binaries => &["binaries"],
+        }
+    }
+
+    /// Phases running processes that keep files of this component open.
+    fn affected_phases(&self) -> Vec<SetupPhase> {
+        match self {
+            DataComponent::Node => vec![SetupPhase::Node, SetupPhase::Wallet, SetupPhase::Unknown],
+            DataComponent::Wallet => vec![SetupPhase::Wallet],
+            DataComponent::P2pool => vec![SetupPhase::Unknown],
+            DataComponent::Binaries => SetupPhase::all(),
+        }
+    }
+}
+
+/// Custom roots for the data of each component. `None` keeps the default location.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DataLocations {
+    pub node: Option<PathBuf>,
+    pub wallet: Option<PathBuf>,
+    pub p2pool: Option<PathBuf>,
+    pub binaries: Option<PathBuf>,
+}
+
+impl DataLocations {
+    pub fn current() -> DataLocations {
+        CURRENT
+            .read()
+            .map(|locations| locations.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn set_current(locations: DataLocations) {
+        match CURRENT.write() {
+            Ok(mut current) => *current = locations,
+            Err(e) => error!(target: LOG_TARGET, "Failed to update data locations: {:?}", e),
+        }
+    }
+
+    pub fn get(&self, component: DataComponent) -> Option<&PathBuf> {
+        match component {
+            DataComponent::Node => self.node.as_ref(),
+            DataComponent::Wallet => self.wallet.as_ref(),
+            DataComponent::P2pool => self.p2pool.as_ref(),
+            DataComponent::Binaries => self.binaries.as_ref(),
+        }
+    }
+
+    fn set(&mut self, component: DataComponent, root: Option<PathBuf>) {
+        match component {
+            DataComponent::Node => self.node = root,
+            DataComponent::Wallet => self.wallet = root,
+            DataComponent::P2pool => self.p2pool = root,
+            DataComponent::Binaries => self.binaries = root,
+        }
+    }
+
+    /// Returns the root the component should use, falling back to `default_root`.
+    pub fn resolve(&self, component: DataComponent, default_root: &Path) -> PathBuf {
+        self.get(component)
+            .cloned()
+            .unwrap_or_else(|| default_root.to_path_buf())
+    }
+}
+
+pub fn get_default_binaries_root() -> Result<PathBuf, Error> {
+    let cache_path = dirs::cache_dir().ok_or_else(|| anyhow!("Failed to get cache directory"))?;
+    Ok(cache_path.join(APPLICATION_FOLDER_ID))
+}
+
+/// Root under which the `binaries` folder lives.
+pub fn get_binaries_root() -> Result<PathBuf, Error> {
+    match DataLocations::current().binaries {
+        Some(root) => Ok(root),
+        None => get_default_binaries_root(),
+    }
+}
+
+pub fn get_default_root(
+    app_handle: &AppHandle,
+    component: DataComponent,
+) -> Result<PathBuf, Error> {
+    match component {
+        DataComponent::Binaries => get_default_binaries_root(),
+        _ => Ok(app_handle.path().app_local_data_dir()?),
+    }
+}
+
+pub fn get_component_root(
+    app_handle: &AppHandle,
+    component: DataComponent,
+) -> Result<PathBuf, Error> {
+    let default_root = get_default_root(app_handle, component)?;
+    Ok(DataLocations::current().resolve(component, &default_root))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DataLocationInfo {
+    pub component: DataComponent,
+    pub root: PathBuf,
+    pub is_custom: bool,
+}
+
+pub fn get_data_location_infos(app_handle: &AppHandle) -> Result<Vec<DataLocationInfo>, Error> {
+    let locations = DataLocations::current();
+    DataComponent::all()
+        .into_iter()
+        .map(|component| {
+            Ok(DataLocationInfo {
+                component,
+                root: get_component_root(app_handle, component)?,
+                is_custom: locations.get(component).is_some(),
+            })
+        })
+        .collect()
+}
+
+struct PlannedMove {
+    component: DataComponent,
+    source_root: PathBuf,
+    target_root: PathBuf,
+}
+
+/// Moves the data of `components` to `target_root` (or back to the default location when `None`).
+///
+/// Affected phases are stopped for the duration of the copy. The config is only switched once every
+/// file has been copied and verified, otherwise the partial copy is removed and the old paths are kept.
+pub async fn migrate_data_locations(
+    app_handle: &AppHandle,
+    components: Vec<DataComponent>,
+    target_root: Option<PathBuf>,
+) -> Result<DataLocations, Error> {
+    if MIGRATION_IN_PROGRESS.swap(true, Ordering::SeqCst) {
+        return Err(anyhow!("Data migration is already in progress"));
+    }
+    let result = migrate_data_locations_inner(app_handle, components, target_root).await;
+    MIGRATION_IN_PROGRESS.store(false, Ordering::SeqCst);
+    result
+}
+
+async fn migrate_data_locations_inner(
+    app_handle: &AppHandle,
+    components: Vec<DataComponent>,
+    target_root: Option<PathBuf>,
+) -> Result<DataLocations, Error> {
+    if let Some(target_root) = &target_root {
+        if !target_root.is_absolute() {
+            return Err(anyhow!("Target directory must be an absolute path"));
+        }
+        fs::create_dir_all(target_root)?;
+    }
+
+    let mut moves = vec![];
+    for component in components {
+        let source_root = get_component_root(app_handle, component)?;
+        let target = match &target_root {
+            Some(target_root) => target_root.clone(),
+            None => get_default_root(app_handle, component)?,
+        };
+        if paths_match(&source_root, &target) {
+            info!(target: LOG_TARGET, "{:?} data already located in {:?}", component, target);
+            continue;
+        }
+        validate_move(component, &source_root, &target)?;
+        moves.push(PlannedMove {
+            component,
+            source_root,
+            target_root: target,
+        });
+    }
+
+    let mut locations = ConfigCore::content().await.data_locations().clone();
+    if moves.is_empty() {
+        return Ok(locations);
+    }
+
+    let move_sizes: Vec<u64> = moves
+        .iter()
+        .map(|m| {
+            m.component
+                .folders()
+                .iter()
+                .map(|folder| get_dir_size(&m.source_root.join(folder)))
+                .sum()
+        })
+        .collect();
+    let total_bytes: u64 = move_sizes.iter().sum();
+    // Several components can land on the same disk, so the space has to be checked per volume
+    let mut required_per_volume: HashMap<PathBuf, (PathBuf, u64)> = HashMap::new();
+    for (m, size) in moves.iter().zip(&move_sizes) {
+        let volume = get_disk_mount_point(&m.target_root).unwrap_or_else(|| m.target_root.clone());
+        required_per_volume
+            .entry(volume)
+            .or_insert_with(|| (m.target_root.clone(), 0))
+            .1 += size;
+    }
+    for (volume, (target_root, required)) in &required_per_volume {
+        if let Some(available) = get_available_disk_space(target_root) {
+            if available < *required {
+                return Err(anyhow!(
+                    "Not enough free space on {:?}: {} bytes required, {} bytes available",
+                    volume,
+                    required,
+                    available
+                ));
+            }
+        }
+    }
+
+    let mut phases: Vec<SetupPhase> = vec![];
+    for phase in moves.iter().flat_map(|m| m.component.affected_phases()) {
+        if !phases.contains(&phase) {
+            phases.push(phase);
+        }
+    }
+    info!(target: LOG_TARGET, "Stopping phases {:?} to migrate data", phases);
+    SetupManager::get_instance()
+        .shutdown_phases(app_handle.clone(), phases.clone())
+        .await;
+
+    let copy_result = copy_components(app_handle, &moves, total_bytes).await;
+    let result = match copy_result {
+        Ok(()) => {
+            for m in &moves {
+                let is_default = get_default_root(app_handle, m.component)
+                    .map(|default_root| paths_match(&default_root, &m.target_root))
+                    .unwrap_or(false);
+                locations.set(m.component, (!is_default).then(|| m.target_root.clone()));
+            }
+            match ConfigCore::update_field(ConfigCoreContent::set_data_locations, locations.clone())
+                .await
+            {
+                Ok(()) => {
+                    DataLocations::set_current(locations.clone());
+                    for m in &moves {
+                        remove_component_folders(m.component, &m.source_root);
+                    }
+                    if moves.iter().any(|m| m.component == DataComponent::Wallet) {
+                        if let Err(e) = TransactionIndex::current().load(app_handle).await {
+                            warn!(target: LOG_TARGET, "Failed to reload transaction index: {:?}", e);
+                        }
+                    }
+                    Ok(locations)
+                }
+                Err(e) => {
+                    rollback(&moves);
+                    Err(e)
+                }
+            }
+        }
+        Err(e) => {
+            error!(target: LOG_TARGET, "Data migration failed, rolling back: {:?}", e);
+            rollback(&moves);
+            Err(e)
+        }
+    };
+
+    EventsManager::handle_data_location_migration_progress(
+        app_handle,
+        None,
+        total_bytes,
+        total_bytes,
+        if result.is_ok() {
+            DataLocationMigrationStatus::Completed
+        } else {
+            DataLocationMigrationStatus::RolledBack
+        },
+    )
+    .await;
+
+    SetupManager::get_instance()
+        .resume_phases(app_handle.clone(), phases)
+        .await;
+    result
+}
+
+async fn copy_components(
+    app_handle: &AppHandle,
+    moves: &[PlannedMove],
+    total_bytes: u64,
+) -> Result<(), Error> {
+    let mut copied_before = 0;
+    for m in moves {
+        let component = m.component;
+        let source_root = m.source_root.clone();
+        let target_root = m.target_root.clone();
+        let (progress_tx, mut progress_rx) = watch::channel(0u64);
+        let mut copy_task = tokio::task::spawn_blocking(move || {
+            let mut copied = 0u64;
+            for folder in component.folders() {
+                let source = source_root.join(folder);
+                if source.exists() {
+                    copy_dir_verified(&source, &target_root.join(folder), &mut |bytes| {
+                        copied += bytes;
+                        let _unused = progress_tx.send(copied);
+                    })?;
+                }
+            }
+            Ok::<u64, Error>(copied)
+        });
+
+        let copied = loop {
+            tokio::select! {
+                result = &mut copy_task => break result??,
+                changed = progress_rx.changed() => {
+                    if changed.is_err() {
+                        break (&mut copy_task).await??;
+                    }
+                    let copied = *progress_rx.borrow();
+                    EventsManager::handle_data_location_migration_progress(
+                        app_handle,
+                        Some(component),
+                        copied_before + copied,
+                        total_bytes,
+                        DataLocationMigrationStatus::Copying,
+                    )
+                    .await;
+                }
+            }
+        };
+        info!(target: LOG_TARGET, "Copied {} bytes of {:?} data to {:?}", copied, component, m.target_root);
+        copied_before += copied;
+    }
+    Ok(())
+}
+
+fn rollback(moves: &[PlannedMove]) {
+    for m in moves {
+        remove_component_folders(m.component, &m.target_root);
+    }
+}
+
+fn remove_component_folders(component: DataComponent, root: &Path) {
+    for folder in component.folders() {
+        let path = root.join(folder);
+        if path.exists() {
+            if let Err(e) = fs::remove_dir_all(&path) {
+                warn!(target: LOG_TARGET, "Failed to remove {:?}: {:?}", path, e);
+            }
+        }
+    }
+}
+
+fn paths_match(a: &Path, b: &Path) -> bool {
+    let a = dunce::canonicalize(a).unwrap_or_else(|_| a.to_path_buf());
+    let b = dunce::canonicalize(b).unwrap_or_else(|_| b.to_path_buf());
+    a == b
+}
+
+fn validate_move(
+    component: DataComponent,
+    source_root: &Path,
+    target_root: &Path,
+) -> Result<(), Error> {
+    let source_root =
+        dunce::canonicalize(source_root).unwrap_or_else(|_| source_root.to_path_buf());
+    let target_root =
+        dunce::canonicalize(target_root).unwrap_or_else(|_| target_root.to_path_buf());
+    for folder in component.folders() {
+        let source = source_root.join(folder);
+        let target = target_root.join(folder);
+        if target.starts_with(&source) {
+            return Err(anyhow!("Cannot move {:?} into itself", source));
+        }
+        let is_occupied = fs::read_dir(&target)
+            .map(|mut entries| entries.next().is_some())
+            .unwrap_or(false);
+        if is_occupied {
+            return Err(anyhow!("Target directory {:?} is not empty", target));
+        }
+    }
+    Ok(())
+}
+
+/// Recursively copies `source` into `target`, checking the hash of every written file.
+fn copy_dir_verified(
+    source: &Path,
+    target: &Path,
+    on_progress: &mut dyn FnMut(u64),
+) -> Result<(), Error> {
+    fs::create_dir_all(target)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let target_path = target.join(entry.file_name());
+        if file_type.is_dir() {
+            copy_dir_verified(&entry.path(), &target_path, on_progress)?;
+        } else if file_type.is_file() {
+            copy_file_verified(&entry.path(), &target_path, on_progress)?;
+        }
+    }
+    Ok(())
+}
+
+fn copy_file_verified(
+    source: &Path,
+    target: &Path,
+    on_progress: &mut dyn FnMut(u64),
+) -> Result<(), Error> {
+    let mut reader = File::open(source)?;
+    let mut writer = File::create(target)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        writer.write_all(&buffer[..read])?;
+        on_progress(read as u64);
+    }
+    writer.sync_all()?;
+    let source_hash = hasher.finalize();
+
+    let mut reader = File::open(target)?;
+    let mut hasher = Sha256::new();
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    if hasher.finalize() != source_hash {
+        return Err(anyhow!("Verification failed for {:?}", target));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env::temp_dir;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = temp_dir().join(format!("tari_data_locations_{}", name));
+        let _unused = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn copies_nested_folders_and_reports_progress() {
+        let root = test_dir("copy");
+        let source = root.join("source");
+        fs::create_dir_all(source.join("nested")).unwrap();
+        fs::write(source.join("a.txt"), b"hello").unwrap();
+        fs::write(source.join("nested").join("b.bin"), vec![7u8; 4096]).unwrap();
+
+        let mut copied = 0;
+        copy_dir_verified(&source, &root.join("target"), &mut |bytes| copied += bytes).unwrap();
+
+        assert_eq!(copied, 5 + 4096);
+        assert_eq!(
+            fs::read(root.join("target").join("a.txt")).unwrap(),
+            b"hello"
+        );
+        assert_eq!(
+            fs::read(root.join("target").join("nested").join("b.bin")).unwrap(),
+            vec![7u8; 4096]
+        );
+        let _unused = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn rejects_non_empty_or_nested_targets() {
+        let root = test_dir("validate");
+        fs::create_dir_all(root.join("old").join("node").join("esmeralda")).unwrap();
+        fs::create_dir_all(root.join("occupied").join("node")).unwrap();
+        fs::write(root.join("occupied").join("node").join("file"), b"x").unwrap();
+
+        assert!(validate_move(DataComponent::Node, &root.join("old"), &root.join("new")).is_ok());
+        assert!(validate_move(
+            DataComponent::Node,
+            &root.join("old"),
+            &root.join("occupied")
+        )
+        .is_err());
+        assert!(validate_move(
+            DataComponent::Node,
+            &root.join("old"),
+            &root.join("old").join("node")
+        )
+        .is_err());
+        let _unused = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn resolves_custom_root_or_default() {
+        let locations = DataLocations {
+            wallet: Some(PathBuf::from("/mnt/disk")),
+            ..Default::default()
+        };
+        let default_root = PathBuf::from("/home/user/.local/share/tari");
+        assert_eq!(
+            locations.resolve(DataComponent::Wallet, &default_root),
+            PathBuf::from("/mnt/disk")
+        );
+        assert_eq!(
+            locations.resolve(DataComponent::Node, &default_root),
+            default_root
+        );
+    }
+}
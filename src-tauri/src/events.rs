@@ -24,9 +24,14 @@ use serde::Serialize;
 use std::collections::HashMap;
 
 use crate::{
+    data_locations::DataComponent,
     gpu_status_file::GpuDevice,
     node::node_adapter::NodeIdentity,
     node::node_manager::NodeType,
+    outgoing_transactions::{OutgoingTransaction, OutgoingTransactionState},
+    payment_request::PaymentUri,
+    transaction_index::TransactionStatusChange,
+    utils::disk_space_guard::DiskSpaceStatus,
     wallet_adapter::{TransactionInfo, WalletBalance},
 };
 
@@ -70,6 +75,12 @@ pub enum EventType {
     InitWalletScanningProgress,
     ConnectionStatus,
     ShowStageSecurityModal,
+    DataLocationMigrationProgress,
+    DiskSpaceStatusUpdate,
+    TransactionsUpdated,
+    OutgoingTransactionUpdated,
+    PaymentRequestUpdated,
+    PaymentUriOpened,
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -79,6 +90,7 @@ pub enum ProgressEvents {
     Hardware,
     Node,
     Unknown,
+    OrphanChainRecovery,
 }
 #[derive(Clone, Debug, Serialize)]
 pub struct ProgressTrackerUpdatePayload {
@@ -161,3 +173,43 @@ pub enum ConnectionStatusPayload {
     #[allow(dead_code)]
     Failed,
 }
+
+#[derive(Serialize, Clone, Debug)]
+pub enum DataLocationMigrationStatus {
+    Copying,
+    Completed,
+    RolledBack,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DataLocationMigrationProgressPayload {
+    pub component: Option<DataComponent>,
+    pub copied_bytes: u64,
+    pub total_bytes: u64,
+    pub status: DataLocationMigrationStatus,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskSpaceStatusUpdatePayload {
+    pub statuses: Vec<DiskSpaceStatus>,
+    pub phases_stopped: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TransactionsUpdatedPayload {
+    pub added: Vec<TransactionInfo>,
+    pub status_changes: Vec<TransactionStatusChange>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OutgoingTransactionUpdatedPayload {
+    pub transaction: OutgoingTransaction,
+    pub previous_state: Option<OutgoingTransactionState>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PaymentUriOpenedPayload {
+    pub uri: String,
+    pub request: Option<PaymentUri>,
+    pub error: Option<String>,
+}
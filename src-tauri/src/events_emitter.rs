@@ -22,7 +22,9 @@ use std::collections::HashMap;
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 use crate::events::{
-    ConnectionStatusPayload, CriticalProblemPayload, InitWalletScanningProgressPayload,
+    ConnectionStatusPayload, CriticalProblemPayload, DataLocationMigrationProgressPayload,
+    DiskSpaceStatusUpdatePayload, InitWalletScanningProgressPayload,
+    OutgoingTransactionUpdatedPayload, PaymentUriOpenedPayload, TransactionsUpdatedPayload,
 };
 #[cfg(target_os = "windows")]
 use crate::external_dependencies::RequiredExternalDependency;
@@ -39,6 +41,7 @@ use crate::{
     },
     gpu_status_file::GpuDevice,
     hardware::hardware_status_monitor::PublicDeviceProperties,
+    payment_request::PaymentRequest,
     setup::setup_manager::SetupPhase,
     utils::app_flow_utils::FrontendReadyChannel,
     wallet_adapter::{TransactionInfo, WalletBalance},
@@ -549,4 +552,82 @@ impl EventsEmitter {
             error!(target: LOG_TARGET, "Failed to emit ConnectionStatus event: {:?}", e);
         }
     }
+
+    pub async fn emit_data_location_migration_progress(
+        app_handle: &AppHandle,
+        payload: DataLocationMigrationProgressPayload,
+    ) {
+        let _unused = FrontendReadyChannel::current().wait_for_ready().await;
+        let event = Event {
+            event_type: EventType::DataLocationMigrationProgress,
+            payload,
+        };
+        if let Err(e) = app_handle.emit(BACKEND_STATE_UPDATE, event) {
+            error!(target: LOG_TARGET, "Failed to emit DataLocationMigrationProgress event: {:?}", e);
+        }
+    }
+
+    pub async fn emit_disk_space_status_update(
+        app_handle: &AppHandle,
+        payload: DiskSpaceStatusUpdatePayload,
+    ) {
+        let _unused = FrontendReadyChannel::current().wait_for_ready().await;
+        let event = Event {
+            event_type: EventType::DiskSpaceStatusUpdate,
+            payload,
+        };
+        if let Err(e) = app_handle.emit(BACKEND_STATE_UPDATE, event) {
+            error!(target: LOG_TARGET, "Failed to emit DiskSpaceStatusUpdate event: {:?}", e);
+        }
+    }
+
+    pub async fn emit_transactions_updated(
+        app_handle: &AppHandle,
+        payload: TransactionsUpdatedPayload,
+    ) {
+        let _unused = FrontendReadyChannel::current().wait_for_ready().await;
+        let event = Event {
+            event_type: EventType::TransactionsUpdated,
+            payload,
+        };
+        if let Err(e) = app_handle.emit(BACKEND_STATE_UPDATE, event) {
+            error!(target: LOG_TARGET, "Failed to emit TransactionsUpdated event: {:?}", e);
+        }
+    }
+
+    pub async fn emit_outgoing_transaction_updated(
+        app_handle: &AppHandle,
+        payload: OutgoingTransactionUpdatedPayload,
+    ) {
+        let _unused = FrontendReadyChannel::current().wait_for_ready().await;
+        let event = Event {
+            event_type: EventType::OutgoingTransactionUpdated,
+            payload,
+        };
+        if let Err(e) = app_handle.emit(BACKEND_STATE_UPDATE, event) {
+            error!(target: LOG_TARGET, "Failed to emit OutgoingTransactionUpdated event: {:?}", e);
+        }
+    }
+
+    pub async fn emit_payment_request_updated(app_handle: &AppHandle, payload: PaymentRequest) {
+        let _unused = FrontendReadyChannel::current().wait_for_ready().await;
+        let event = Event {
+            event_type: EventType::PaymentRequestUpdated,
+            payload,
+        };
+        if let Err(e) = app_handle.emit(BACKEND_STATE_UPDATE, event) {
+            error!(target: LOG_TARGET, "Failed to emit PaymentRequestUpdated event: {:?}", e);
+        }
+    }
+
+    pub async fn emit_payment_uri_opened(app_handle: &AppHandle, payload: PaymentUriOpenedPayload) {
+        let _unused = FrontendReadyChannel::current().wait_for_ready().await;
+        let event = Event {
+            event_type: EventType::PaymentUriOpened,
+            payload,
+        };
+        if let Err(e) = app_handle.emit(BACKEND_STATE_UPDATE, event) {
+            error!(target: LOG_TARGET, "Failed to emit PaymentUriOpened event: {:?}", e);
+        }
+    }
 }
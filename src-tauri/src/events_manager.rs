@@ -22,15 +22,24 @@
 
 use std::{collections::HashMap, time::Duration};
 
-use log::error;
+use log::{error, warn};
 use tari_core::transactions::tari_amount::MicroMinotari;
 use tauri::{AppHandle, Manager};
 
 use crate::configs::config_mining::ConfigMiningContent;
 use crate::configs::config_wallet::ConfigWalletContent;
-use crate::events::ConnectionStatusPayload;
+use crate::data_locations::DataComponent;
+use crate::events::{
+    ConnectionStatusPayload, DataLocationMigrationProgressPayload, DataLocationMigrationStatus,
+    DiskSpaceStatusUpdatePayload, OutgoingTransactionUpdatedPayload, PaymentUriOpenedPayload,
+    TransactionsUpdatedPayload,
+};
 #[cfg(target_os = "windows")]
 use crate::external_dependencies::RequiredExternalDependency;
+use crate::outgoing_transactions::{OutgoingTransaction, OutgoingTransactionState};
+use crate::payment_request::{PaymentRequest, PaymentUri};
+use crate::transaction_index::TransactionStatusChange;
+use crate::utils::disk_space_guard::DiskSpaceStatus;
 use crate::{configs::config_core::ConfigCoreContent, events::CriticalProblemPayload};
 
 use crate::{
@@ -42,6 +51,7 @@ use crate::{
     hardware::hardware_status_monitor::GpuDeviceProperties,
     setup::setup_manager::SetupPhase,
     tasks_tracker::TasksTrackers,
+    wallet_adapter::TransactionInfo,
     BaseNodeStatus, GpuMinerStatus, UniverseAppState,
 };
 
@@ -294,4 +304,91 @@ impl EventsManager {
     ) {
         EventsEmitter::emit_connection_status_changed(app, status).await;
     }
+
+    pub async fn handle_data_location_migration_progress(
+        app: &AppHandle,
+        component: Option<DataComponent>,
+        copied_bytes: u64,
+        total_bytes: u64,
+        status: DataLocationMigrationStatus,
+    ) {
+        EventsEmitter::emit_data_location_migration_progress(
+            app,
+            DataLocationMigrationProgressPayload {
+                component,
+                copied_bytes,
+                total_bytes,
+                status,
+            },
+        )
+        .await;
+    }
+
+    pub async fn handle_disk_space_status_update(
+        app: &AppHandle,
+        statuses: Vec<DiskSpaceStatus>,
+        phases_stopped: bool,
+    ) {
+        EventsEmitter::emit_disk_space_status_update(
+            app,
+            DiskSpaceStatusUpdatePayload {
+                statuses,
+                phases_stopped,
+            },
+        )
+        .await;
+    }
+
+    pub async fn handle_transactions_updated(
+        app: &AppHandle,
+        added: Vec<TransactionInfo>,
+        status_changes: Vec<TransactionStatusChange>,
+    ) {
+        EventsEmitter::emit_transactions_updated(
+            app,
+            TransactionsUpdatedPayload {
+                added,
+                status_changes,
+            },
+        )
+        .await;
+    }
+
+    pub async fn handle_outgoing_transaction_updated(
+        app: &AppHandle,
+        transaction: OutgoingTransaction,
+        previous_state: Option<OutgoingTransactionState>,
+    ) {
+        EventsEmitter::emit_outgoing_transaction_updated(
+            app,
+            OutgoingTransactionUpdatedPayload {
+                transaction,
+                previous_state,
+            },
+        )
+        .await;
+    }
+
+    pub async fn handle_payment_request_updated(app: &AppHandle, request: PaymentRequest) {
+        EventsEmitter::emit_payment_request_updated(app, request).await;
+    }
+
+    pub async fn handle_payment_uri_opened(app: &AppHandle, uri: String) {
+        let (request, error) = match PaymentUri::parse(&uri) {
+            Ok(request) => (Some(request), None),
+            Err(e) => {
+                warn!(target: LOG_TARGET, "Opened invalid payment URI {}: {}", uri, e);
+                (None, Some(e))
+            }
+        };
+        EventsEmitter::emit_payment_uri_opened(
+            app,
+            PaymentUriOpenedPayload {
+                uri,
+                request,
+                error,
+            },
+        )
+        .await;
+    }
 }
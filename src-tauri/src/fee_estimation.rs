@@ -0,0 +1,273 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+use anyhow::{anyhow, Error};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use tari_core::transactions::tari_amount::MicroMinotari;
+
+use crate::{
+    coin_management::select_inputs,
+    node::node_adapter::{BlockFeeStats, MempoolStats},
+    spend_wallet_adapter::DEFAULT_FEE_PER_GRAM,
+};
+
+const LOG_TARGET: &str = "tari::universe::fee_estimation";
+
+/// Transaction weight that fits in one block (mainnet consensus `max_block_transaction_weight`)
+const BLOCK_WEIGHT_CAPACITY: u64 = 127_795;
+pub const MAX_FEE_PER_GRAM: u64 = 1_000;
+/// Blocks within which a send at the normal preset should be mined
+const NORMAL_TARGET_BLOCKS: usize = 3;
+/// A one-sided send pays the recipient and returns change
+const SEND_OUTPUTS: usize = 2;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FeePriority {
+    Slow,
+    Normal,
+    Fast,
+}
+
+/// How the fee of a send is chosen: a preset derived from the mempool, or an explicit fee-per-gram
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FeeSelection {
+    Priority { priority: FeePriority },
+    Custom { fee_per_gram: u64 },
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FeeEstimates {
+    pub slow: u64,
+    pub normal: u64,
+    pub fast: u64,
+    pub mempool: MempoolStats,
+    /// Full blocks needed to clear the current mempool
+    pub blocks_to_clear: u64,
+}
+
+impl FeeEstimates {
+    /// Normal aims for one of the next few blocks and fast for the next one, each by outbidding the cheapest
+    /// transaction of the block template it has to get into. The slow preset always stays at the minimum.
+    pub fn from_mempool(mempool: MempoolStats) -> Self {
+        let blocks_to_clear = mempool.unconfirmed_weight.div_ceil(BLOCK_WEIGHT_CAPACITY);
+        let normal = fee_to_confirm_within(&mempool.block_fees, NORMAL_TARGET_BLOCKS);
+        Self {
+            slow: DEFAULT_FEE_PER_GRAM,
+            normal,
+            fast: fee_to_confirm_within(&mempool.block_fees, 1).max(normal),
+            mempool,
+            blocks_to_clear,
+        }
+    }
+
+    /// Presets fall back to the default fee-per-gram when the node cannot report its mempool
+    pub fn or_fallback(estimates: Result<Self, Error>) -> Option<Self> {
+        estimates
+            .inspect_err(|e| {
+                warn!(target: LOG_TARGET, "Could not estimate fees from the mempool: {}", e);
+            })
+            .ok()
+    }
+
+    pub fn fee_per_gram(&self, priority: FeePriority) -> u64 {
+        match priority {
+            FeePriority::Slow => self.slow,
+            FeePriority::Normal => self.normal,
+            FeePriority::Fast => self.fast,
+        }
+    }
+}
+
+/// Block templates are filled highest fee-per-gram first and only the last one can have room left. While the
+/// mempool fits in `blocks` templates any fee gets in; otherwise the fee has to beat the cheapest transaction of
+/// the last template in range.
+fn fee_to_confirm_within(block_fees: &[BlockFeeStats], blocks: usize) -> u64 {
+    let fee = match block_fees.get(blocks.saturating_sub(1)) {
+        Some(block) if block_fees.len() > blocks => block.min_fee_per_gram.saturating_add(1),
+        _ => DEFAULT_FEE_PER_GRAM,
+    };
+    fee.clamp(DEFAULT_FEE_PER_GRAM, MAX_FEE_PER_GRAM)
+}
+
+impl FeeSelection {
+    pub fn validate(self) -> Result<Self, Error> {
+        if let Self::Custom { fee_per_gram } = self {
+            if !(DEFAULT_FEE_PER_GRAM..=MAX_FEE_PER_GRAM).contains(&fee_per_gram) {
+                return Err(anyhow!(
+                    "Fee per gram must be between {} and {}",
+                    DEFAULT_FEE_PER_GRAM,
+                    MAX_FEE_PER_GRAM
+                ));
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn fee_per_gram(&self, estimates: Option<&FeeEstimates>) -> u64 {
+        match self {
+            Self::Custom { fee_per_gram } => *fee_per_gram,
+            Self::Priority { priority } => estimates.map_or(DEFAULT_FEE_PER_GRAM, |estimates| {
+                estimates.fee_per_gram(*priority)
+            }),
+        }
+    }
+}
+
+/// Dry-run result of a send: what leaves the wallet and what comes back as change
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SendSummary {
+    pub amount: MicroMinotari,
+    pub fee: MicroMinotari,
+    pub fee_per_gram: u64,
+    /// Amount plus fee
+    pub total: MicroMinotari,
+    pub change: MicroMinotari,
+    pub inputs: usize,
+    pub available: MicroMinotari,
+}
+
+impl SendSummary {
+    pub fn new(
+        unspent: &[MicroMinotari],
+        amount: MicroMinotari,
+        fee_per_gram: u64,
+    ) -> Result<Self, Error> {
+        let available = MicroMinotari(unspent.iter().map(MicroMinotari::as_u64).sum());
+        let selection =
+            select_inputs(unspent, amount, MicroMinotari(fee_per_gram), SEND_OUTPUTS)
+                .ok_or_else(|| anyhow!("Insufficient balance to cover the amount and fee"))?;
+        Ok(Self {
+            amount,
+            fee: selection.fee,
+            fee_per_gram,
+            total: MicroMinotari(amount.as_u64() + selection.fee.as_u64()),
+            change: selection.change,
+            inputs: selection.inputs,
+            available,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One block template per entry of `min_fees`, each full except the last
+    fn mempool(min_fees: &[u64]) -> MempoolStats {
+        MempoolStats {
+            unconfirmed_txs: 10,
+            unconfirmed_weight: BLOCK_WEIGHT_CAPACITY * min_fees.len() as u64,
+            block_fees: min_fees
+                .iter()
+                .map(|&min_fee_per_gram| BlockFeeStats {
+                    min_fee_per_gram,
+                    avg_fee_per_gram: min_fee_per_gram * 2,
+                    max_fee_per_gram: min_fee_per_gram * 4,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn quiet_mempool_uses_minimum_fees() {
+        let estimates = FeeEstimates::from_mempool(mempool(&[DEFAULT_FEE_PER_GRAM]));
+        assert_eq!(estimates.blocks_to_clear, 1);
+        assert_eq!(estimates.slow, DEFAULT_FEE_PER_GRAM);
+        assert_eq!(estimates.normal, DEFAULT_FEE_PER_GRAM);
+        assert_eq!(estimates.fast, DEFAULT_FEE_PER_GRAM);
+    }
+
+    #[test]
+    fn congested_mempool_outbids_the_target_block() {
+        let estimates = FeeEstimates::from_mempool(mempool(&[40, 25, 12, 6, 5]));
+        assert_eq!(estimates.slow, DEFAULT_FEE_PER_GRAM);
+        assert_eq!(estimates.normal, 13);
+        assert_eq!(estimates.fast, 41);
+
+        // The whole mempool fits in the next three blocks
+        let estimates = FeeEstimates::from_mempool(mempool(&[40, 25, 12]));
+        assert_eq!(estimates.normal, DEFAULT_FEE_PER_GRAM);
+        assert_eq!(estimates.fast, 41);
+
+        let flooded = FeeEstimates::from_mempool(mempool(&[u64::MAX; 5]));
+        assert_eq!(flooded.normal, MAX_FEE_PER_GRAM);
+        assert_eq!(flooded.fast, MAX_FEE_PER_GRAM);
+    }
+
+    #[test]
+    fn unreachable_mempool_falls_back_to_default() {
+        let estimates =
+            FeeEstimates::or_fallback(Err(anyhow!("method get_mempool_stats not allowed")));
+        let normal = FeeSelection::Priority {
+            priority: FeePriority::Normal,
+        };
+        assert_eq!(
+            normal.fee_per_gram(estimates.as_ref()),
+            DEFAULT_FEE_PER_GRAM
+        );
+    }
+
+    #[test]
+    fn custom_fee_must_be_in_range() {
+        assert!(FeeSelection::Custom {
+            fee_per_gram: DEFAULT_FEE_PER_GRAM - 1
+        }
+        .validate()
+        .is_err());
+        assert!(FeeSelection::Custom {
+            fee_per_gram: DEFAULT_FEE_PER_GRAM
+        }
+        .validate()
+        .is_ok());
+        assert!(FeeSelection::Custom {
+            fee_per_gram: MAX_FEE_PER_GRAM + 1
+        }
+        .validate()
+        .is_err());
+        let custom = FeeSelection::Custom { fee_per_gram: 25 }
+            .validate()
+            .unwrap();
+        assert_eq!(custom.fee_per_gram(None), 25);
+    }
+
+    #[test]
+    fn priority_falls_back_to_default_without_estimates() {
+        let fast = FeeSelection::Priority {
+            priority: FeePriority::Fast,
+        };
+        assert_eq!(fast.fee_per_gram(None), DEFAULT_FEE_PER_GRAM);
+        let estimates = FeeEstimates::from_mempool(mempool(&[30, 20]));
+        assert_eq!(fast.fee_per_gram(Some(&estimates)), estimates.fast);
+    }
+
+    #[test]
+    fn send_summary_accounts_for_fee_and_change() {
+        let unspent = vec![MicroMinotari(3_000_000), MicroMinotari(2_000_000)];
+        let summary = SendSummary::new(&unspent, MicroMinotari(4_000_000), 5).unwrap();
+        assert_eq!(summary.inputs, 2);
+        assert_eq!(summary.available, MicroMinotari(5_000_000));
+        assert_eq!(summary.total.as_u64() + summary.change.as_u64(), 5_000_000);
+        assert!(SendSummary::new(&unspent, MicroMinotari(5_000_000), 5).is_err());
+    }
+}
@@ -35,6 +35,7 @@ use zip::ZipWriter;
 
 use crate::app_in_memory_config::AppInMemoryConfig;
 use crate::configs::config_core::ConfigCore;
+use crate::configs::config_secrets::redact_secrets;
 use crate::configs::trait_config::ConfigImpl;
 use crate::utils::file_utils::{make_relative_path, path_as_string};
 
@@ -63,8 +64,11 @@ impl Feedback {
         paths_queue.push(directory.to_path_buf().clone());
 
         let mut buffer = Vec::new();
+        // Uploaded files may predate the redaction of secrets at the source, so they are scrubbed here
+        let secrets = ConfigCore::content().await.secret_values();
 
-        let log_regex_filter = Regex::new(r"^(.*[0-9]+\.log|.*\.zip)$")
+        // Rolled logs and compressed archives are left out, secrets in compressed bytes cannot be redacted
+        let skipped_files_filter = Regex::new(r"^(.*[0-9]+\.log|.*\.(zip|gz|tgz|tar|7z))$")
             .map_err(|e| anyhow!("Failed to create log file filter: {}", e))?;
 
         while let Some(next) = paths_queue.pop() {
@@ -78,19 +82,21 @@ impl Feedback {
                     .and_then(|name| name.to_str())
                     .ok_or_else(|| anyhow::anyhow!("Failed to get file name"))?;
 
-                if entry_metadata.is_file() && !log_regex_filter.is_match(entry_file_name_as_str) {
+                if entry_metadata.is_file()
+                    && !skipped_files_filter.is_match(entry_file_name_as_str)
+                {
                     let mut f = File::open(&entry_path)?;
                     f.read_to_end(&mut buffer)?;
                     let relative_path = make_relative_path(directory, &entry_path);
                     zip.start_file(path_as_string(&relative_path), file_options)?;
-                    zip.write_all(buffer.as_ref())?;
+                    zip.write_all(&redact_secrets(&buffer, &secrets))?;
                     buffer.clear();
                 } else if entry_metadata.is_dir() {
                     let relative_path = make_relative_path(directory, &entry_path);
                     zip.add_directory(path_as_string(&relative_path), file_options)?;
                     paths_queue.push(entry_path.clone());
                 } else {
-                    // Skip log files
+                    // Skip rolled logs and archives
                 }
             }
         }
@@ -39,6 +39,7 @@ use crate::binaries::{Binaries, BinaryResolver};
 use crate::events_manager::EventsManager;
 use crate::gpu_miner_adapter::GpuNodeSource;
 use crate::gpu_status_file::{GpuDevice, GpuStatusFile};
+use crate::node::network_stats::NetworkStats;
 use crate::process_stats_collector::ProcessStatsCollectorBuilder;
 use crate::tasks_tracker::TasksTrackers;
 use crate::utils::math_utils::estimate_earning;
@@ -304,8 +305,10 @@ impl GpuMiner {
 
                         let gpu_status = match gpu_raw_status {
                             Some(gpu_raw_status) => {
+                                let (sha_network_hashrate, _) =
+                                    NetworkStats::current().smoothed_hashrates(&node_status).await;
                                 let estimated_earnings = estimate_earning(
-                                    node_status.sha_network_hashrate,
+                                    sha_network_hashrate,
                                     gpu_raw_status.hash_rate,
                                     node_status.block_reward,
                                 );
@@ -29,7 +29,7 @@ use std::path::PathBuf;
 use std::str::FromStr;
 use tari_common::configuration::Network;
 use tari_common_types::tari_address::{TariAddress, TariAddressError, TariAddressFeatures};
-use tari_common_types::types::CompressedPublicKey;
+use tari_common_types::types::{CompressedPublicKey, PrivateKey};
 use tari_crypto::ristretto::RistrettoPublicKey;
 use tari_key_manager::cipher_seed::CipherSeed;
 use tari_key_manager::key_manager::KeyManager;
@@ -49,23 +49,37 @@ use tari_key_manager::SeedWords;
 use tari_utilities::hex::Hex;
 
 use crate::credential_manager::{Credential, CredentialError, CredentialManager};
+use crate::data_locations::{DataComponent, DataLocations};
+use crate::spend_wallet_adapter::SpendWalletAdapter;
+use crate::transaction_index::TransactionIndex;
 use crate::wallet_adapter::WalletBalance;
+use crate::wallet_profiles::{
+    active_wallet_id, wallet_config_dir, wallet_data_dir, DEFAULT_WALLET_ID,
+};
 
 const KEY_MANAGER_COMMS_SECRET_KEY_BRANCH_KEY: &str = "comms";
 const LOG_TARGET: &str = "tari::universe::internal_wallet";
+pub const WATCH_ONLY_ERROR: &str = "This action is not available for a watch-only wallet";
 
 pub struct InternalWallet {
     tari_address: TariAddress,
     config: WalletConfig,
+    wallet_id: String,
+    app_config_dir: PathBuf,
 }
 
 impl InternalWallet {
+    /// Loads the active wallet, creating it when it does not exist yet
     pub async fn load_or_create(config_path: PathBuf) -> Result<Self, anyhow::Error> {
-        let network = Network::get_current_or_user_setting_or_default()
-            .to_string()
-            .to_lowercase();
+        let wallet_id = active_wallet_id().await;
+        Self::load_or_create_for(config_path, &wallet_id).await
+    }
 
-        let file = config_path.join(network).join("wallet_config.json");
+    pub async fn load_or_create_for(
+        config_path: PathBuf,
+        wallet_id: &str,
+    ) -> Result<Self, anyhow::Error> {
+        let file = wallet_config_dir(&config_path, wallet_id).join("wallet_config.json");
 
         let file_parent = file
             .parent()
@@ -81,14 +95,18 @@ impl InternalWallet {
                 Ok(mut config) => {
                     config.config_path = Some(file_parent.to_path_buf());
 
-                    let cm = CredentialManager::default_with_dir(config_path.clone());
-                    if let Err(e) = cm.migrate().await {
-                        warn!(target: LOG_TARGET, "Failed to migrate wallet credentials: {}", e.to_string());
+                    if wallet_id == DEFAULT_WALLET_ID {
+                        let cm = CredentialManager::default_with_dir(config_path.clone());
+                        if let Err(e) = cm.migrate().await {
+                            warn!(target: LOG_TARGET, "Failed to migrate wallet credentials: {}", e.to_string());
+                        }
                     }
 
                     return Ok(Self {
                         tari_address: TariAddress::from_base58(&config.tari_address_base58)?,
                         config,
+                        wallet_id: wallet_id.to_string(),
+                        app_config_dir: config_path,
                     });
                 }
                 Err(e) => {
@@ -97,20 +115,29 @@ impl InternalWallet {
             }
         }
         info!(target: LOG_TARGET, "Wallet config does not exist or is corrupt. Creating new wallet");
-        let (wallet, config) = InternalWallet::create_new_wallet(None, config_path).await?;
+        let (wallet, config) =
+            InternalWallet::create_new_wallet(None, config_path, wallet_id).await?;
         let config = serde_json::to_string(&config)?;
         fs::write(file, config).await?;
         Ok(wallet)
     }
 
+    /// Replaces the active wallet with one restored from seed words
     pub async fn create_from_seed(
         config_path: PathBuf,
         seed_words: Vec<String>,
     ) -> Result<Self, anyhow::Error> {
-        let network = Network::get_current_or_user_setting_or_default()
-            .to_string()
-            .to_lowercase();
-        let file = config_path.join(network).join("wallet_config.json");
+        let wallet_id = active_wallet_id().await;
+        Self::create_for(config_path, &wallet_id, Some(seed_words)).await
+    }
+
+    /// Writes a wallet under the given id, from seed words or from a fresh seed
+    pub async fn create_for(
+        config_path: PathBuf,
+        wallet_id: &str,
+        seed_words: Option<Vec<String>>,
+    ) -> Result<Self, anyhow::Error> {
+        let file = wallet_config_dir(&config_path, wallet_id).join("wallet_config.json");
         let file_parent = file
             .parent()
             .ok_or_else(|| anyhow!("Failed to get parent directory of wallet config file"))?;
@@ -119,16 +146,82 @@ impl InternalWallet {
         });
 
         let (wallet, config) =
-            InternalWallet::create_new_wallet(Some(seed_words), config_path).await?;
+            InternalWallet::create_new_wallet(seed_words, config_path, wallet_id).await?;
         let config = serde_json::to_string(&config)?;
         fs::write(file, config).await?;
         Ok(wallet)
     }
 
+    /// Replaces the wallet with a watch-only one built from a private view key and a public spend key.
+    /// No seed is stored, so the wallet can scan for incoming funds but never spend them.
+    pub async fn create_watch_only(
+        config_path: PathBuf,
+        view_key_private_hex: &str,
+        spend_public_key_hex: &str,
+        birthday: Option<u16>,
+    ) -> Result<Self, anyhow::Error> {
+        let wallet_id = active_wallet_id().await;
+        let file = wallet_config_dir(&config_path, &wallet_id).join("wallet_config.json");
+        let file_parent = file
+            .parent()
+            .ok_or_else(|| anyhow!("Failed to get parent directory of wallet config file"))?;
+        create_dir_all(file_parent).unwrap_or_else(|error| {
+            warn!(target: LOG_TARGET, "Could not create wallet config file parent directory - {}", error);
+        });
+
+        let view_key_private = PrivateKey::from_hex(view_key_private_hex.trim())
+            .map_err(|_| anyhow!("Invalid private view key"))?;
+        let spend_key_pub = CompressedPublicKey::from_hex(spend_public_key_hex.trim())
+            .map_err(|_| anyhow!("Invalid public spend key"))?;
+        let view_key_pub = CompressedPublicKey::from_secret_key(&view_key_private);
+        let tari_address = TariAddress::new_dual_address(
+            view_key_pub,
+            spend_key_pub.clone(),
+            Network::default(),
+            TariAddressFeatures::create_one_sided_only(),
+            None,
+        )
+        .map_err(|e| anyhow!(e.to_string()))?;
+
+        // A watch-only install keeps no seed, so drop the passphrase that protected the previous one.
+        let cm = CredentialManager::for_wallet(config_path.clone(), &wallet_id);
+        match cm.get_credentials().await {
+            Ok(mut creds) => {
+                creds.tari_seed_passphrase = None;
+                cm.set_credentials(&creds).await?;
+            }
+            Err(CredentialError::NoEntry(_)) => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        let config = WalletConfig {
+            tari_address_base58: tari_address.to_base58(),
+            view_key_private_hex: view_key_private.to_hex(),
+            spend_public_key_hex: spend_key_pub.to_hex(),
+            seed_words_encrypted_base58: "".to_string(),
+            passphrase: None,
+            config_path: Some(file_parent.to_path_buf()),
+            watch_only: true,
+            birthday,
+        };
+        fs::write(file, serde_json::to_string(&config)?).await?;
+
+        Ok(Self {
+            tari_address,
+            config,
+            wallet_id,
+            app_config_dir: config_path,
+        })
+    }
+
     pub fn get_tari_address(&self) -> TariAddress {
         self.tari_address.clone()
     }
 
+    pub fn is_watch_only(&self) -> bool {
+        self.config.watch_only
+    }
+
     pub async fn get_paper_wallet_details(
         &self,
         anon_id: String,
@@ -179,6 +272,7 @@ impl InternalWallet {
     async fn create_new_wallet(
         seed_words: Option<Vec<String>>,
         path: PathBuf,
+        wallet_id: &str,
     ) -> Result<(Self, WalletConfig), anyhow::Error> {
         let mut config = WalletConfig {
             tari_address_base58: "".to_string(),
@@ -187,9 +281,11 @@ impl InternalWallet {
             spend_public_key_hex: "".to_string(),
             config_path: Some(path.to_path_buf()),
             passphrase: None,
+            watch_only: false,
+            birthday: None,
         };
 
-        let cm = CredentialManager::default_with_dir(path);
+        let cm = CredentialManager::for_wallet(path.clone(), wallet_id);
         let passphrase = match cm.get_credentials().await {
             Ok(mut creds) => match creds.tari_seed_passphrase {
                 Some(p) => Some(p),
@@ -202,7 +298,7 @@ impl InternalWallet {
             Err(CredentialError::NoEntry(_)) => {
                 let credentials = Credential {
                     tari_seed_passphrase: Some(SafePassword::from(generate_password(32))),
-                    monero_seed: None,
+                    ..Default::default()
                 };
                 cm.set_credentials(&credentials).await?;
                 credentials.tari_seed_passphrase
@@ -215,20 +311,50 @@ impl InternalWallet {
         };
 
         let seed = match seed_words {
-            Some(sw) => {
-                let seed_words = SeedWords::from_str(&sw.join(" "))?;
-                CipherSeed::from_mnemonic_with_language(
-                    &seed_words,
-                    MnemonicLanguage::English,
-                    None,
-                )?
-            }
+            Some(sw) => Self::seed_from_words(&sw)?,
             None => CipherSeed::new(),
         };
 
         let seed_file = seed.encipher(passphrase)?;
         config.seed_words_encrypted_base58 = seed_file.to_monero_base58();
 
+        let (tari_address, view_key_private, comms_pub_key) = Self::derive_keys(&seed).await?;
+
+        config.tari_address_base58 = tari_address.to_base58();
+        config.view_key_private_hex = view_key_private.to_hex();
+        config.spend_public_key_hex = comms_pub_key.to_hex();
+        Ok((
+            Self {
+                tari_address,
+                config: config.clone(),
+                wallet_id: wallet_id.to_string(),
+                app_config_dir: path,
+            },
+            config,
+        ))
+    }
+
+    /// Address the seed words would restore to, without writing anything
+    pub async fn address_from_seed_words(
+        seed_words: &[String],
+    ) -> Result<TariAddress, anyhow::Error> {
+        let seed = Self::seed_from_words(seed_words)?;
+        let (tari_address, _, _) = Self::derive_keys(&seed).await?;
+        Ok(tari_address)
+    }
+
+    fn seed_from_words(seed_words: &[String]) -> Result<CipherSeed, anyhow::Error> {
+        let seed_words = SeedWords::from_str(&seed_words.join(" "))?;
+        Ok(CipherSeed::from_mnemonic_with_language(
+            &seed_words,
+            MnemonicLanguage::English,
+            None,
+        )?)
+    }
+
+    async fn derive_keys(
+        seed: &CipherSeed,
+    ) -> Result<(TariAddress, PrivateKey, CompressedPublicKey), anyhow::Error> {
         let comms_key_manager = KeyManager::<RistrettoPublicKey, KeyDigest>::from(
             seed.clone(),
             KEY_MANAGER_COMMS_SECRET_KEY_BRANCH_KEY.to_string(),
@@ -246,24 +372,14 @@ impl InternalWallet {
         let view_key_private = tx_key_manager.get_private_key(&view_key.key_id).await?;
         let view_key_pub = view_key.pub_key;
         let tari_address = TariAddress::new_dual_address(
-            view_key_pub.clone(),
+            view_key_pub,
             comms_pub_key.clone(),
             network,
             TariAddressFeatures::create_one_sided_only(),
             None,
         )
         .map_err(|e| anyhow!(e.to_string()))?;
-
-        config.tari_address_base58 = tari_address.to_base58();
-        config.view_key_private_hex = view_key_private.to_hex();
-        config.spend_public_key_hex = comms_pub_key.to_hex();
-        Ok((
-            Self {
-                tari_address,
-                config: config.clone(),
-            },
-            config,
-        ))
+        Ok((tari_address, view_key_private, comms_pub_key))
     }
 
     pub async fn decrypt_seed_words(&self) -> Result<SeedWords, anyhow::Error> {
@@ -280,17 +396,14 @@ impl InternalWallet {
     }
 
     async fn get_seed(&self) -> Result<CipherSeed, anyhow::Error> {
-        let path = match &self.config.config_path {
-            Some(p) => p.clone(),
-            None => return Err(anyhow!("No config path found")),
-        };
-        let path_parent = path
-            .parent()
-            .ok_or_else(|| anyhow!("Failed to get parent directory of wallet config file"))?;
-        let passphrase = CredentialManager::default_with_dir(path_parent.to_path_buf())
-            .get_credentials()
-            .await?
-            .tari_seed_passphrase;
+        if self.config.watch_only {
+            return Err(anyhow!(WATCH_ONLY_ERROR));
+        }
+        let passphrase =
+            CredentialManager::for_wallet(self.app_config_dir.clone(), &self.wallet_id)
+                .get_credentials()
+                .await?
+                .tari_seed_passphrase;
         let seed_binary = Vec::<u8>::from_monero_base58(&self.config.seed_words_encrypted_base58)
             .map_err(|e| anyhow!(e.to_string()))?;
         let seed = CipherSeed::from_enciphered_bytes(&seed_binary, passphrase)?;
@@ -299,6 +412,12 @@ impl InternalWallet {
     }
 
     pub async fn get_birthday(&self) -> Result<u16, anyhow::Error> {
+        if self.config.watch_only {
+            return self
+                .config
+                .birthday
+                .ok_or_else(|| anyhow!("No birthday set for watch-only wallet"));
+        }
         let seed = self.get_seed().await?;
         Ok(seed.birthday())
     }
@@ -313,8 +432,13 @@ impl InternalWallet {
         let network = Network::get_current_or_user_setting_or_default()
             .to_string()
             .to_lowercase();
-        let wallet_dir = cache_path.join("wallet").join(network);
-        fs::remove_dir_all(wallet_dir).await?;
+        let wallet_base_dir = wallet_data_dir(
+            &DataLocations::current().resolve(DataComponent::Wallet, &cache_path),
+            &active_wallet_id().await,
+        );
+        fs::remove_dir_all(wallet_base_dir.join("wallet").join(network)).await?;
+        SpendWalletAdapter::erase_network_data(&wallet_base_dir).await?;
+        TransactionIndex::current().clear().await?;
         Ok(())
     }
 }
@@ -345,6 +469,10 @@ pub struct WalletConfig {
     // TODO: "This is for Universe users < v0.5.x who wouldn't be migrated yet. Once we're confident that all users have been migrated, we can remove this."
     pub(crate) passphrase: Option<SafePassword>,
     config_path: Option<PathBuf>,
+    #[serde(default)]
+    watch_only: bool,
+    #[serde(default)]
+    birthday: Option<u16>,
 }
 
 #[derive(Debug, Serialize, Clone)]
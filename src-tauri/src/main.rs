@@ -23,6 +23,7 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use address_book::AddressBook;
 use commands::CpuMinerStatus;
 use cpu_miner::CpuMinerConfig;
 use events_manager::EventsManager;
@@ -30,9 +31,11 @@ use gpu_miner_adapter::GpuMinerStatus;
 use log::{error, info, warn};
 use mining_status_manager::MiningStatusManager;
 use node::local_node_adapter::LocalNodeAdapter;
+use node::network_stats::NetworkStats;
 use node::node_adapter::BaseNodeStatus;
 use node::node_manager::NodeType;
 use p2pool::models::Connections;
+use payment_request::PaymentRequests;
 use process_stats_collector::ProcessStatsCollectorBuilder;
 
 use node::remote_node_adapter::RemoteNodeAdapter;
@@ -43,10 +46,13 @@ use std::path::Path;
 use systemtray_manager::{SystemTrayData, SystemTrayManager};
 use tasks_tracker::TasksTrackers;
 use tauri_plugin_cli::CliExt;
+use tauri_plugin_deep_link::DeepLinkExt;
 use telemetry_service::TelemetryService;
 use tokio::sync::watch::{self};
 use tor_control_client::TorStatus;
+use transaction_index::TransactionIndex;
 use updates_manager::UpdatesManager;
+use utils::disk_space_guard::DiskSpaceGuard;
 use utils::locks_utils::try_write_with_retry;
 use utils::system_status::SystemStatus;
 use wallet_adapter::WalletState;
@@ -90,21 +96,29 @@ use crate::tor_manager::TorManager;
 use crate::wallet_manager::WalletManager;
 
 mod ab_test_selector;
+mod address_book;
 mod airdrop;
 mod app_config;
 mod app_in_memory_config;
+mod app_lock;
 mod auto_launcher;
+mod auto_sweep;
 mod binaries;
+mod coin_management;
 mod commands;
 mod configs;
 mod consts;
 mod cpu_miner;
+mod credential_backends;
 mod credential_manager;
+mod crypto;
+mod data_locations;
 mod download_utils;
 mod events;
 mod events_emitter;
 mod events_manager;
 mod external_dependencies;
+mod fee_estimation;
 mod feedback;
 mod github;
 mod gpu_miner;
@@ -115,11 +129,14 @@ mod internal_wallet;
 mod mining_status_manager;
 mod mm_proxy_adapter;
 mod mm_proxy_manager;
+mod monero_payout;
 mod network_utils;
 mod node;
+mod outgoing_transactions;
 mod p2pool;
 mod p2pool_adapter;
 mod p2pool_manager;
+mod payment_request;
 mod port_allocator;
 mod process_adapter;
 mod process_killer;
@@ -140,10 +157,14 @@ mod tests;
 mod tor_adapter;
 mod tor_control_client;
 mod tor_manager;
+mod transaction_export;
+mod transaction_index;
 mod updates_manager;
 mod utils;
 mod wallet_adapter;
+mod wallet_backup;
 mod wallet_manager;
+mod wallet_profiles;
 mod websocket_events_manager;
 mod websocket_manager;
 mod xmrig;
@@ -927,7 +948,6 @@ async fn initialize_frontend_updates(app: &tauri::AppHandle) -> Result<(), anyho
 struct UniverseAppState {
     stop_start_mutex: Arc<Mutex<()>>,
     node_status_watch_rx: Arc<watch::Receiver<BaseNodeStatus>>,
-    #[allow(dead_code)]
     wallet_state_watch_rx: Arc<watch::Receiver<Option<WalletState>>>,
     cpu_miner_status_watch_rx: Arc<watch::Receiver<CpuMinerStatus>>,
     gpu_latest_status: Arc<watch::Receiver<GpuMinerStatus>>,
@@ -1028,7 +1048,7 @@ fn main() {
         wallet_state_watch_tx,
         &mut stats_collector,
     );
-    let spend_wallet_manager = SpendWalletManager::new(node_manager.clone());
+    let spend_wallet_manager = SpendWalletManager::new(node_manager.clone(), &mut stats_collector);
     let (p2pool_stats_tx, p2pool_stats_rx) = watch::channel(None);
     let p2pool_manager = P2poolManager::new(p2pool_stats_tx, &mut stats_collector);
 
@@ -1161,6 +1181,7 @@ fn main() {
                 }
             };
         }))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_cli::init())
         .setup(|app| {
@@ -1191,6 +1212,28 @@ fn main() {
 
             // Do this after logging has started otherwise we can't actually see any errors
             app.manage(app_state_clone);
+
+            #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+            if let Err(e) = app.deep_link().register_all() {
+                warn!(target: LOG_TARGET, "Could not register {}: links: {:?}", payment_request::URI_SCHEME, e);
+            }
+            let deep_link_handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                for url in event.urls() {
+                    let app_handle = deep_link_handle.clone();
+                    tauri::async_runtime::spawn(async move {
+                        EventsManager::handle_payment_uri_opened(&app_handle, url.to_string()).await;
+                    });
+                }
+            });
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                for url in urls {
+                    let app_handle = app.handle().clone();
+                    tauri::async_runtime::spawn(async move {
+                        EventsManager::handle_payment_uri_opened(&app_handle, url.to_string()).await;
+                    });
+                }
+            }
             match app.cli().matches() {
                 Ok(matches) => {
                     if let Some(backup_path) = matches.args.get("import-backup") {
@@ -1318,7 +1361,70 @@ fn main() {
             commands::validate_minotari_amount,
             commands::trigger_phases_restart,
             commands::set_node_type,
-            commands::set_warmup_seen
+            commands::set_warmup_seen,
+            commands::get_connected_peers_details,
+            commands::ban_peer,
+            commands::unban_peer,
+            commands::get_banned_peers,
+            commands::add_custom_seed_peer,
+            commands::remove_custom_seed_peer,
+            commands::force_node_reconnect,
+            commands::recover_orphan_chain,
+            commands::get_node_storage_estimate,
+            commands::set_node_storage_mode,
+            commands::get_data_locations,
+            commands::migrate_data_locations,
+            commands::get_disk_space_status,
+            commands::set_disk_space_thresholds,
+            commands::get_block_details,
+            commands::find_kernel,
+            commands::get_network_stats_series,
+            commands::get_chain_tip_verification_config,
+            commands::set_chain_tip_verification_config,
+            commands::get_verified_chain_tip,
+            commands::query_transactions,
+            commands::sync_transaction_index,
+            commands::export_transactions,
+            commands::get_outgoing_transactions,
+            commands::cancel_outgoing_transaction,
+            commands::revalidate_outgoing_transactions,
+            commands::get_address_book,
+            commands::save_contact,
+            commands::remove_contact,
+            commands::export_address_book,
+            commands::import_address_book,
+            commands::create_payment_request,
+            commands::get_payment_requests,
+            commands::cancel_payment_request,
+            commands::parse_payment_uri,
+            commands::import_view_key,
+            commands::get_wallet_profiles,
+            commands::create_wallet_profile,
+            commands::remove_wallet_profile,
+            commands::switch_wallet,
+            commands::export_wallet_backup,
+            commands::import_wallet_backup,
+            commands::get_app_lock_status,
+            commands::set_app_lock,
+            commands::remove_app_lock,
+            commands::unlock_app,
+            commands::lock_app,
+            commands::set_app_auto_lock,
+            commands::get_auto_sweep,
+            commands::set_auto_sweep,
+            commands::confirm_auto_sweep_destination,
+            commands::get_utxo_overview,
+            commands::preview_coin_operation,
+            commands::execute_coin_operation,
+            commands::get_fee_estimates,
+            commands::preview_send,
+            commands::validate_monero_address,
+            commands::get_monero_address_status,
+            commands::begin_monero_address_regeneration,
+            commands::confirm_monero_address_regeneration,
+            commands::get_secret_backend_status,
+            commands::set_secret_backend,
+            commands::unlock_secret_backend
         ])
         .build(tauri::generate_context!())
         .inspect_err(
@@ -1350,8 +1456,17 @@ fn main() {
             info!(target: LOG_TARGET, "RunEvent Ready");
             let handle_clone = app_handle.clone();
             tauri::async_runtime::spawn(async move {
+                if let Err(e) = AddressBook::current().load(&handle_clone).await {
+                    error!(target: LOG_TARGET, "Could not load address book: {:?}", e);
+                }
+                if let Err(e) = PaymentRequests::current().load(&handle_clone).await {
+                    error!(target: LOG_TARGET, "Could not load payment requests: {:?}", e);
+                }
                 SetupManager::get_instance().start_setup(handle_clone.clone()).await;
                 SetupManager::spawn_sleep_mode_handler(handle_clone.clone()).await;
+                DiskSpaceGuard::spawn_monitor(handle_clone.clone()).await;
+                NetworkStats::spawn_sampler(handle_clone.clone()).await;
+                TransactionIndex::spawn_sync_task(handle_clone.clone()).await;
             });
         }
         tauri::RunEvent::ExitRequested { api: _, code, .. } => {
@@ -0,0 +1,291 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+use std::{
+    collections::HashSet,
+    path::PathBuf,
+    sync::LazyLock,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, Error};
+use log::info;
+use monero_address_creator::{network::Mainnet, Seed as MoneroSeed};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::{
+    configs::{config_wallet::ConfigWallet, trait_config::ConfigImpl},
+    credential_manager::{Credential, CredentialError, CredentialManager},
+    utils::address_utils::{verify_monero_address, MoneroAddressInfo},
+};
+
+const LOG_TARGET: &str = "tari::universe::monero_payout";
+pub const MAX_MONERO_ADDRESS_HISTORY: usize = 50;
+
+/// Seed generated by [`begin_regeneration`] that only replaces the stored one once the user has written it down
+static PENDING_SEED: LazyLock<Mutex<Option<[u8; 32]>>> = LazyLock::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MoneroAddressRecord {
+    pub address: String,
+    pub is_generated: bool,
+    /// Seconds since the epoch when the address was first recorded as the payout address
+    pub set_at: u64,
+}
+
+impl MoneroAddressRecord {
+    pub fn new(address: String, is_generated: bool) -> Self {
+        Self {
+            address,
+            is_generated,
+            set_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Appends `record` unless it repeats the latest entry, dropping the oldest ones beyond the cap
+pub fn push_history(history: &mut Vec<MoneroAddressRecord>, record: MoneroAddressRecord) {
+    if record.address.is_empty()
+        || history
+            .last()
+            .is_some_and(|last| last.address == record.address)
+    {
+        return;
+    }
+    history.push(record);
+    if history.len() > MAX_MONERO_ADDRESS_HISTORY {
+        let excess = history.len() - MAX_MONERO_ADDRESS_HISTORY;
+        history.drain(..excess);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MoneroAddressHistoryEntry {
+    #[serde(flatten)]
+    pub record: MoneroAddressRecord,
+    pub recoverable: bool,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MoneroAddressStatus {
+    pub address: String,
+    pub is_generated: bool,
+    /// `None` when the stored address does not parse, e.g. one saved before validation existed
+    pub info: Option<MoneroAddressInfo>,
+    /// Whether the address can be restored from the seeds kept in the credential store
+    pub recoverable: bool,
+    pub warning: Option<String>,
+    /// Previous payout addresses, most recent first
+    pub history: Vec<MoneroAddressHistoryEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MoneroRegeneration {
+    pub address: String,
+    pub seed_words: Vec<String>,
+}
+
+pub(crate) fn seed_address(seed: [u8; 32]) -> Result<String, Error> {
+    MoneroSeed::new(seed)
+        .to_address::<Mainnet>()
+        .map_err(|e| anyhow!(e.to_string()))
+}
+
+fn seed_words(seed: [u8; 32]) -> Result<Vec<String>, Error> {
+    MoneroSeed::new(seed)
+        .seed_words()
+        .map_err(|e| anyhow!(e.to_string()))
+}
+
+async fn load_credentials(config_dir: PathBuf) -> Result<Option<Credential>, Error> {
+    match CredentialManager::default_with_dir(config_dir)
+        .get_credentials()
+        .await
+    {
+        Ok(credential) => Ok(Some(credential)),
+        Err(CredentialError::NoEntry(_)) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Seed of the current generated Monero address, if the credential store holds one
+pub async fn current_seed(config_dir: PathBuf) -> Result<Option<[u8; 32]>, Error> {
+    Ok(load_credentials(config_dir)
+        .await?
+        .and_then(|credential| credential.monero_seed))
+}
+
+/// Makes `seed` the current Monero seed. The replaced seed is retired rather than deleted so the previous
+/// generated address stays recoverable.
+pub async fn store_seed(config_dir: PathBuf, seed: [u8; 32]) -> Result<(), Error> {
+    let cm = CredentialManager::default_with_dir(config_dir.clone());
+    let mut credential = load_credentials(config_dir).await?.unwrap_or_default();
+    if let Some(previous) = credential.monero_seed.replace(seed) {
+        if previous != seed && !credential.retired_monero_seeds.contains(&previous) {
+            credential.retired_monero_seeds.push(previous);
+        }
+    }
+    credential
+        .retired_monero_seeds
+        .retain(|retired| *retired != seed);
+    cm.set_credentials(&credential).await?;
+    Ok(())
+}
+
+/// Addresses derived from the current and retired Monero seeds in the credential store
+async fn recoverable_addresses(config_dir: PathBuf) -> Result<HashSet<String>, Error> {
+    let Some(credential) = load_credentials(config_dir).await? else {
+        return Ok(HashSet::new());
+    };
+    credential
+        .monero_seed
+        .into_iter()
+        .chain(credential.retired_monero_seeds)
+        .map(seed_address)
+        .collect()
+}
+
+fn unrecoverable_warning(is_generated: bool) -> String {
+    if is_generated {
+        "The seed of this generated address is no longer stored, rewards paid to it cannot be recovered from this \
+         device"
+            .to_string()
+    } else {
+        "This address was entered manually and cannot be recovered from stored seed words, make sure you hold its \
+         keys"
+            .to_string()
+    }
+}
+
+pub async fn status(config_dir: PathBuf) -> Result<MoneroAddressStatus, Error> {
+    let config = ConfigWallet::content().await;
+    let recoverable = recoverable_addresses(config_dir).await?;
+    let address = config.monero_address().clone();
+    let is_generated = *config.monero_address_is_generated();
+    let is_recoverable = recoverable.contains(&address);
+
+    Ok(MoneroAddressStatus {
+        info: verify_monero_address(&address).ok(),
+        recoverable: is_recoverable,
+        warning: (!is_recoverable).then(|| unrecoverable_warning(is_generated)),
+        history: config
+            .monero_address_history()
+            .iter()
+            .rev()
+            .map(|record| MoneroAddressHistoryEntry {
+                recoverable: recoverable.contains(&record.address),
+                record: record.clone(),
+            })
+            .collect(),
+        address,
+        is_generated,
+    })
+}
+
+/// Generates a fresh Monero seed and returns its words for the user to back up. Nothing changes until
+/// [`confirm_regeneration`] is called with the same words.
+pub async fn begin_regeneration() -> Result<MoneroRegeneration, Error> {
+    let seed = *MoneroSeed::generate()
+        .map_err(|e| anyhow!(e.to_string()))?
+        .inner();
+    let regeneration = MoneroRegeneration {
+        address: seed_address(seed)?,
+        seed_words: seed_words(seed)?,
+    };
+    *PENDING_SEED.lock().await = Some(seed);
+    Ok(regeneration)
+}
+
+/// Stores the pending seed once `confirmation_words` prove it was backed up. Returns the new address.
+pub async fn confirm_regeneration(
+    config_dir: PathBuf,
+    confirmation_words: Vec<String>,
+) -> Result<String, Error> {
+    let mut pending = PENDING_SEED.lock().await;
+    let seed = (*pending).ok_or_else(|| anyhow!("No Monero address regeneration in progress"))?;
+    if !words_match(&seed_words(seed)?, &confirmation_words) {
+        return Err(anyhow!("Seed words do not match the new Monero seed"));
+    }
+
+    store_seed(config_dir, seed).await?;
+    *pending = None;
+
+    let address = seed_address(seed)?;
+    info!(target: LOG_TARGET, "Regenerated Monero payout address");
+    Ok(address)
+}
+
+fn words_match(expected: &[String], given: &[String]) -> bool {
+    expected.len() == given.len()
+        && expected
+            .iter()
+            .zip(given)
+            .all(|(expected, given)| expected.eq_ignore_ascii_case(given.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(address: &str) -> MoneroAddressRecord {
+        MoneroAddressRecord::new(address.to_string(), false)
+    }
+
+    #[test]
+    fn history_skips_repeats_and_empty_addresses() {
+        let mut history = Vec::new();
+        push_history(&mut history, record("a"));
+        push_history(&mut history, record("a"));
+        push_history(&mut history, record(""));
+        push_history(&mut history, record("b"));
+        push_history(&mut history, record("a"));
+        let addresses: Vec<&str> = history.iter().map(|r| r.address.as_str()).collect();
+        assert_eq!(addresses, vec!["a", "b", "a"]);
+    }
+
+    #[test]
+    fn history_is_capped() {
+        let mut history = Vec::new();
+        for i in 0..MAX_MONERO_ADDRESS_HISTORY + 5 {
+            push_history(&mut history, record(&i.to_string()));
+        }
+        assert_eq!(history.len(), MAX_MONERO_ADDRESS_HISTORY);
+        assert_eq!(history[0].address, "5");
+    }
+
+    #[test]
+    fn confirmation_words_must_match_in_order() {
+        let expected = vec!["alpha".to_string(), "bravo".to_string()];
+        assert!(words_match(
+            &expected,
+            &["Alpha".to_string(), " bravo ".to_string()]
+        ));
+        assert!(!words_match(
+            &expected,
+            &["bravo".to_string(), "alpha".to_string()]
+        ));
+        assert!(!words_match(&expected, &["alpha".to_string()]));
+    }
+}
@@ -20,104 +20,20 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use serde::Deserialize;
-use std::fmt::Write as _;
 use tari_common::configuration::Network;
 
-fn get_text_explore_blocks_url(network: Network, block_height: u64) -> String {
+/// Base URL of the text explorer run for the given network. The JSON API is served under `?json`.
+pub(crate) fn get_text_explore_url(network: Network) -> String {
     match network {
-        Network::MainNet => {
-            format!("https://textexplore.tari.com/blocks/{}?json", block_height)
-        }
-        _ => format!(
-            "https://textexplore-{}.tari.com/blocks/{}?json",
-            network.as_key_str(),
-            block_height
-        ),
+        Network::MainNet => "https://textexplore.tari.com".to_string(),
+        _ => format!("https://textexplore-{}.tari.com", network.as_key_str()),
     }
 }
 
-fn get_text_explore_url(network: Network) -> String {
+/// Base URL of the web block explorer run for the given network. Its pages are also served as JSON under `?json`.
+pub(crate) fn get_explore_url(network: Network) -> String {
     match network {
-        Network::MainNet => "https://textexplore.tari.com/?json".to_string(),
-        _ => format!(
-            "https://textexplore-{}.tari.com/?json",
-            network.as_key_str()
-        ),
+        Network::MainNet => "https://explore.tari.com".to_string(),
+        _ => format!("https://explore-{}.tari.com", network.as_key_str()),
     }
 }
-
-pub(crate) async fn get_best_block_from_block_scan(network: Network) -> Result<u64, anyhow::Error> {
-    #[derive(Deserialize)]
-    struct BlockScanResponse {
-        #[serde(rename = "tipInfo")]
-        tip_info: TipInfo,
-    }
-
-    #[derive(Deserialize)]
-    struct TipInfo {
-        metadata: Metadata,
-    }
-
-    #[derive(Deserialize)]
-    struct Metadata {
-        best_block_height: String,
-    }
-
-    let response = reqwest::get(&get_text_explore_url(network)).await?;
-    if response.status() == reqwest::StatusCode::NOT_FOUND {
-        return Err(anyhow::anyhow!("Block scan API not found"));
-    }
-    let response = response.json::<BlockScanResponse>().await?;
-
-    let best_block_height = response
-        .tip_info
-        .metadata
-        .best_block_height
-        .parse::<u64>()?;
-
-    Ok(best_block_height)
-}
-
-pub(crate) async fn get_block_info_from_block_scan(
-    network: Network,
-    block_height: &u64,
-) -> Result<(u64, String), anyhow::Error> {
-    #[derive(Deserialize)]
-    struct BlockHeader {
-        hash: HashData,
-        height: String,
-    }
-
-    #[derive(Deserialize)]
-    struct HashData {
-        data: Vec<u8>,
-    }
-
-    #[derive(Deserialize)]
-    struct BlockResponse {
-        header: BlockHeader,
-    }
-
-    let response = reqwest::get(&get_text_explore_blocks_url(network, *block_height)).await?;
-    if response.status() == reqwest::StatusCode::NOT_FOUND {
-        return Err(anyhow::anyhow!(
-            "Block {} not found on block scan",
-            block_height
-        ));
-    }
-    let response = response.json::<BlockResponse>().await?;
-
-    let hash = response
-        .header
-        .hash
-        .data
-        .iter()
-        .fold(String::new(), |mut acc, x| {
-            write!(acc, "{:02x}", x).expect("Unable to write");
-            acc
-        });
-    let height = response.header.height.parse::<u64>()?;
-
-    Ok((height, hash))
-}
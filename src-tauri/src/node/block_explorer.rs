@@ -0,0 +1,302 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use anyhow::{anyhow, Error};
+use minotari_node_grpc_client::grpc::{
+    BlockHeader, HistoricalBlock, TransactionKernel, TransactionOutput,
+};
+use serde::{Deserialize, Serialize};
+use tari_utilities::hex::{from_hex, Hex};
+
+// Values of `OutputFeatures::output_type` and `ProofOfWork::pow_algo` as sent over gRPC
+const COINBASE_OUTPUT_TYPE: u32 = 1;
+pub(crate) const POW_ALGO_RANDOMX: u64 = 0;
+pub(crate) const POW_ALGO_SHA3X: u64 = 1;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockQuery {
+    Height(u64),
+    Hash(String),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub enum PowAlgorithm {
+    RandomX,
+    Sha3x,
+    Unknown(u64),
+}
+
+impl From<u64> for PowAlgorithm {
+    fn from(value: u64) -> Self {
+        match value {
+            POW_ALGO_RANDOMX => PowAlgorithm::RandomX,
+            POW_ALGO_SHA3X => PowAlgorithm::Sha3x,
+            other => PowAlgorithm::Unknown(other),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockHeaderDetails {
+    pub hash: String,
+    pub height: u64,
+    pub version: u32,
+    pub prev_hash: String,
+    pub timestamp: u64,
+    pub nonce: u64,
+    pub pow_algo: PowAlgorithm,
+    pub output_mr: String,
+    pub kernel_mr: String,
+    pub input_mr: String,
+    pub total_kernel_offset: String,
+    pub total_script_offset: String,
+    pub kernel_mmr_size: u64,
+    pub output_mmr_size: u64,
+}
+
+impl From<&BlockHeader> for BlockHeaderDetails {
+    fn from(header: &BlockHeader) -> Self {
+        Self {
+            hash: header.hash.to_hex(),
+            height: header.height,
+            version: header.version,
+            prev_hash: header.prev_hash.to_hex(),
+            timestamp: header.timestamp,
+            nonce: header.nonce,
+            pow_algo: header
+                .pow
+                .as_ref()
+                .map(|pow| PowAlgorithm::from(pow.pow_algo))
+                .unwrap_or(PowAlgorithm::Unknown(u64::MAX)),
+            output_mr: header.output_mr.to_hex(),
+            kernel_mr: header.kernel_mr.to_hex(),
+            input_mr: header.input_mr.to_hex(),
+            total_kernel_offset: header.total_kernel_offset.to_hex(),
+            total_script_offset: header.total_script_offset.to_hex(),
+            kernel_mmr_size: header.kernel_mmr_size,
+            output_mmr_size: header.output_mmr_size,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KernelDetails {
+    pub excess: String,
+    pub excess_sig_nonce: String,
+    pub excess_sig: String,
+    pub fee: u64,
+    pub lock_height: u64,
+    pub features: u32,
+    pub hash: String,
+}
+
+impl From<&TransactionKernel> for KernelDetails {
+    fn from(kernel: &TransactionKernel) -> Self {
+        let (excess_sig_nonce, excess_sig) = kernel
+            .excess_sig
+            .as_ref()
+            .map(|sig| (sig.public_nonce.to_hex(), sig.signature.to_hex()))
+            .unwrap_or_default();
+        Self {
+            excess: kernel.excess.to_hex(),
+            excess_sig_nonce,
+            excess_sig,
+            fee: kernel.fee,
+            lock_height: kernel.lock_height,
+            features: kernel.features,
+            hash: kernel.hash.to_hex(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CoinbaseDetails {
+    pub commitment: String,
+    pub coinbase_extra: String,
+    pub coinbase_extra_hex: String,
+}
+
+impl CoinbaseDetails {
+    fn from_output(output: &TransactionOutput) -> Option<Self> {
+        let features = output.features.as_ref()?;
+        if features.output_type != COINBASE_OUTPUT_TYPE {
+            return None;
+        }
+        Some(Self {
+            commitment: output.commitment.to_hex(),
+            coinbase_extra: String::from_utf8_lossy(&features.coinbase_extra).to_string(),
+            coinbase_extra_hex: features.coinbase_extra.to_hex(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockDetails {
+    pub header: BlockHeaderDetails,
+    pub inputs_count: usize,
+    pub outputs_count: usize,
+    pub kernels: Vec<KernelDetails>,
+    pub coinbases: Vec<CoinbaseDetails>,
+}
+
+impl TryFrom<&HistoricalBlock> for BlockDetails {
+    type Error = Error;
+
+    fn try_from(historical_block: &HistoricalBlock) -> Result<Self, Self::Error> {
+        let block = historical_block
+            .block
+            .as_ref()
+            .ok_or_else(|| anyhow!("Block data missing from node response"))?;
+        let header = block
+            .header
+            .as_ref()
+            .ok_or_else(|| anyhow!("Block header missing from node response"))?;
+        let (inputs_count, outputs_count, kernels, coinbases) = match &block.body {
+            Some(body) => (
+                body.inputs.len(),
+                body.outputs.len(),
+                body.kernels.iter().map(KernelDetails::from).collect(),
+                body.outputs
+                    .iter()
+                    .filter_map(CoinbaseDetails::from_output)
+                    .collect(),
+            ),
+            None => (0, 0, vec![], vec![]),
+        };
+        Ok(Self {
+            header: BlockHeaderDetails::from(header),
+            inputs_count,
+            outputs_count,
+            kernels,
+            coinbases,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KernelLookupResult {
+    pub kernel: KernelDetails,
+    pub block_height: u64,
+    pub block_hash: String,
+    pub block_timestamp: u64,
+}
+
+impl KernelLookupResult {
+    /// Picks the kernel with the given excess signature out of the block that contains it.
+    pub fn from_block(block: &BlockDetails, public_nonce: &str, signature: &str) -> Option<Self> {
+        let kernel = block.kernels.iter().find(|kernel| {
+            kernel.excess_sig_nonce.eq_ignore_ascii_case(public_nonce)
+                && kernel.excess_sig.eq_ignore_ascii_case(signature)
+        })?;
+        Some(Self {
+            kernel: kernel.clone(),
+            block_height: block.header.height,
+            block_hash: block.header.hash.clone(),
+            block_timestamp: block.header.timestamp,
+        })
+    }
+}
+
+pub fn decode_hex_field(name: &str, value: &str) -> Result<Vec<u8>, Error> {
+    let bytes = from_hex(value.trim()).map_err(|e| anyhow!("Invalid {} hex: {}", name, e))?;
+    if bytes.len() != 32 {
+        return Err(anyhow!("{} must be 32 bytes, got {}", name, bytes.len()));
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use minotari_node_grpc_client::grpc::{
+        AggregateBody, Block, OutputFeatures, ProofOfWork, Signature,
+    };
+
+    fn historical_block() -> HistoricalBlock {
+        HistoricalBlock {
+            block: Some(Block {
+                header: Some(BlockHeader {
+                    hash: vec![0xaa; 32],
+                    height: 42,
+                    timestamp: 1_700_000_000,
+                    pow: Some(ProofOfWork {
+                        pow_algo: POW_ALGO_SHA3X,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                body: Some(AggregateBody {
+                    outputs: vec![
+                        TransactionOutput {
+                            features: Some(OutputFeatures {
+                                output_type: COINBASE_OUTPUT_TYPE,
+                                coinbase_extra: b"universe".to_vec(),
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        },
+                        TransactionOutput::default(),
+                    ],
+                    kernels: vec![TransactionKernel {
+                        fee: 25,
+                        excess_sig: Some(Signature {
+                            public_nonce: vec![0x01; 32],
+                            signature: vec![0x02; 32],
+                        }),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }),
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn converts_block_with_coinbase_extra() {
+        let details = BlockDetails::try_from(&historical_block()).unwrap();
+        assert_eq!(details.header.height, 42);
+        assert_eq!(details.header.pow_algo, PowAlgorithm::Sha3x);
+        assert_eq!(details.outputs_count, 2);
+        assert_eq!(details.coinbases.len(), 1);
+        assert_eq!(details.coinbases[0].coinbase_extra, "universe");
+        assert_eq!(details.kernels[0].fee, 25);
+    }
+
+    #[test]
+    fn finds_kernel_by_excess_signature() {
+        let details = BlockDetails::try_from(&historical_block()).unwrap();
+        let nonce = "01".repeat(32);
+        let sig = "02".repeat(32).to_uppercase();
+        let result = KernelLookupResult::from_block(&details, &nonce, &sig).unwrap();
+        assert_eq!(result.block_height, 42);
+        assert!(KernelLookupResult::from_block(&details, &nonce, &nonce).is_none());
+    }
+
+    #[test]
+    fn rejects_malformed_signature_hex() {
+        assert!(decode_hex_field("signature", "zz").is_err());
+        assert!(decode_hex_field("signature", "0102").is_err());
+        assert!(decode_hex_field("signature", &"ab".repeat(32)).is_ok());
+    }
+}
@@ -0,0 +1,661 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Error};
+use async_trait::async_trait;
+use futures::future::join_all;
+use log::{info, warn};
+use minotari_node_grpc_client::grpc::{Empty, ListHeadersRequest, Sorting};
+use minotari_node_grpc_client::BaseNodeGrpcClient;
+use serde::{Deserialize, Serialize};
+use tari_common::configuration::Network;
+use tari_utilities::hex::Hex;
+
+use crate::configs::config_core::{
+    default_remote_base_node_address, default_remote_base_node_address_for, ConfigCore,
+};
+use crate::configs::trait_config::ConfigImpl;
+use crate::network_utils::{get_explore_url, get_text_explore_url};
+
+const LOG_TARGET: &str = "tari::universe::chain_tip_verifier";
+const DEFAULT_SOURCE_TIMEOUT_SECS: u64 = 10;
+
+/// A node that reports itself as synced this many blocks below the verified tip is not trusted yet
+const MAX_SYNCED_TIP_LAG: u64 = 10;
+const SYNCED_TIP_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+/// After this long the node's own sync state wins, so unreachable or lagging sources cannot block startup
+const SYNCED_TIP_GRACE_PERIOD: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChainTipSourceConfig {
+    /// Remote base node queried over gRPC, e.g. `https://grpc.tari.com:443`
+    BaseNode { address: String },
+    /// Text explorer serving its JSON API under `?json`, e.g. `https://textexplore.tari.com`
+    Explorer { url: String },
+    /// Public base node of the network the app runs on
+    PublicBaseNode,
+    /// Public block explorer of the network the app runs on
+    PublicExplorer,
+    /// Public text explorer of the network the app runs on
+    PublicTextExplorer,
+}
+
+impl ChainTipSourceConfig {
+    fn validate(&self) -> Result<(), Error> {
+        match self {
+            ChainTipSourceConfig::BaseNode { address } => {
+                if address.trim().is_empty() {
+                    return Err(anyhow!("Base node address must not be empty"));
+                }
+            }
+            ChainTipSourceConfig::Explorer { url } => {
+                if !(url.starts_with("http://") || url.starts_with("https://")) {
+                    return Err(anyhow!("Explorer url must start with http:// or https://"));
+                }
+            }
+            ChainTipSourceConfig::PublicBaseNode
+            | ChainTipSourceConfig::PublicExplorer
+            | ChainTipSourceConfig::PublicTextExplorer => {}
+        }
+        Ok(())
+    }
+
+    /// The public sources are resolved here, so they follow the network the app currently runs on
+    fn into_source(self) -> Box<dyn ChainTipSource> {
+        let network = Network::get_current_or_user_setting_or_default();
+        let explorer = |url: String| -> Box<dyn ChainTipSource> {
+            Box::new(ExplorerTipSource {
+                url: url.trim_end_matches('/').to_string(),
+                client: reqwest::Client::new(),
+            })
+        };
+        match self {
+            ChainTipSourceConfig::BaseNode { address } => Box::new(BaseNodeTipSource { address }),
+            ChainTipSourceConfig::Explorer { url } => explorer(url),
+            ChainTipSourceConfig::PublicBaseNode => Box::new(BaseNodeTipSource {
+                address: default_remote_base_node_address(),
+            }),
+            ChainTipSourceConfig::PublicExplorer => explorer(get_explore_url(network)),
+            ChainTipSourceConfig::PublicTextExplorer => explorer(get_text_explore_url(network)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ChainTipVerificationConfig {
+    pub sources: Vec<ChainTipSourceConfig>,
+    /// Number of sources that must agree before a tip or block hash is trusted
+    pub quorum: usize,
+    pub source_timeout_secs: u64,
+}
+
+/// Smallest number of sources that outvotes the rest
+fn majority(sources: usize) -> usize {
+    sources / 2 + 1
+}
+
+impl ChainTipVerificationConfig {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.sources.is_empty() {
+            return Err(anyhow!("At least one chain tip source is required"));
+        }
+        if self.quorum == 0 || self.quorum > self.sources.len() {
+            return Err(anyhow!(
+                "Quorum must be between 1 and the number of sources ({})",
+                self.sources.len()
+            ));
+        }
+        if self.source_timeout_secs == 0 {
+            return Err(anyhow!("Source timeout must be greater than zero"));
+        }
+        self.sources
+            .iter()
+            .try_for_each(ChainTipSourceConfig::validate)
+    }
+
+    /// Earlier versions persisted two network specific sources with a quorum of two, so a single slow or lying
+    /// source failed every verification. Such configs are replaced by the current default.
+    pub fn replace_legacy_default(self) -> Self {
+        let is_legacy_default = self.quorum == 2
+            && [
+                Network::MainNet,
+                Network::StageNet,
+                Network::NextNet,
+                Network::LocalNet,
+                Network::Igor,
+                Network::Esmeralda,
+            ]
+            .into_iter()
+            .any(|network| {
+                self.sources
+                    == [
+                        ChainTipSourceConfig::Explorer {
+                            url: get_text_explore_url(network),
+                        },
+                        ChainTipSourceConfig::BaseNode {
+                            address: default_remote_base_node_address_for(network),
+                        },
+                    ]
+            });
+        if is_legacy_default {
+            Self::default()
+        } else {
+            self
+        }
+    }
+}
+
+impl Default for ChainTipVerificationConfig {
+    fn default() -> Self {
+        let sources = vec![
+            ChainTipSourceConfig::PublicTextExplorer,
+            ChainTipSourceConfig::PublicExplorer,
+            ChainTipSourceConfig::PublicBaseNode,
+        ];
+        Self {
+            quorum: majority(sources.len()),
+            sources,
+            source_timeout_secs: DEFAULT_SOURCE_TIMEOUT_SECS,
+        }
+    }
+}
+
+#[async_trait]
+pub trait ChainTipSource: Send + Sync {
+    fn name(&self) -> String;
+    async fn tip_height(&self) -> Result<u64, Error>;
+    /// Hex encoded hash of the block at `height`, `None` if the source does not have it
+    async fn block_hash(&self, height: u64) -> Result<Option<String>, Error>;
+}
+
+struct BaseNodeTipSource {
+    address: String,
+}
+
+#[async_trait]
+impl ChainTipSource for BaseNodeTipSource {
+    fn name(&self) -> String {
+        format!("base node {}", self.address)
+    }
+
+    async fn tip_height(&self) -> Result<u64, Error> {
+        let mut client = BaseNodeGrpcClient::connect(self.address.clone()).await?;
+        let tip = client.get_tip_info(Empty {}).await?.into_inner();
+        if !tip.initial_sync_achieved {
+            return Err(anyhow!("Base node is not synced"));
+        }
+        tip.metadata
+            .map(|metadata| metadata.best_block_height)
+            .ok_or_else(|| anyhow!("No metadata found"))
+    }
+
+    async fn block_hash(&self, height: u64) -> Result<Option<String>, Error> {
+        let mut client = BaseNodeGrpcClient::connect(self.address.clone()).await?;
+        let mut res = client
+            .list_headers(ListHeadersRequest {
+                from_height: height,
+                num_headers: 1,
+                sorting: Sorting::Desc as i32,
+            })
+            .await?
+            .into_inner();
+        Ok(res
+            .message()
+            .await?
+            .and_then(|response| response.header)
+            .filter(|header| header.height == height)
+            .map(|header| header.hash.to_hex()))
+    }
+}
+
+struct ExplorerTipSource {
+    url: String,
+    client: reqwest::Client,
+}
+
+#[async_trait]
+impl ChainTipSource for ExplorerTipSource {
+    fn name(&self) -> String {
+        format!("explorer {}", self.url)
+    }
+
+    async fn tip_height(&self) -> Result<u64, Error> {
+        #[derive(Deserialize)]
+        struct ExplorerResponse {
+            #[serde(rename = "tipInfo")]
+            tip_info: TipInfo,
+        }
+
+        #[derive(Deserialize)]
+        struct TipInfo {
+            metadata: Metadata,
+        }
+
+        #[derive(Deserialize)]
+        struct Metadata {
+            best_block_height: String,
+        }
+
+        let response = self
+            .client
+            .get(format!("{}/?json", self.url))
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<ExplorerResponse>()
+            .await?;
+        Ok(response
+            .tip_info
+            .metadata
+            .best_block_height
+            .parse::<u64>()?)
+    }
+
+    async fn block_hash(&self, height: u64) -> Result<Option<String>, Error> {
+        #[derive(Deserialize)]
+        struct BlockResponse {
+            header: BlockHeader,
+        }
+
+        #[derive(Deserialize)]
+        struct BlockHeader {
+            hash: HashData,
+            height: String,
+        }
+
+        #[derive(Deserialize)]
+        struct HashData {
+            data: Vec<u8>,
+        }
+
+        let response = self
+            .client
+            .get(format!("{}/blocks/{}?json", self.url, height))
+            .send()
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let response = response.error_for_status()?.json::<BlockResponse>().await?;
+        if response.header.height.parse::<u64>()? != height {
+            return Err(anyhow!("Explorer returned a block at a different height"));
+        }
+        Ok(Some(response.header.hash.data.to_hex()))
+    }
+}
+
+/// Cross-checks the chain tip against several independent sources and only trusts answers a quorum agrees on.
+/// Sources that time out or fail are left out, and a minority reporting a different chain is outvoted.
+pub struct ChainTipVerifier {
+    sources: Vec<Box<dyn ChainTipSource>>,
+    quorum: usize,
+    source_timeout: Duration,
+}
+
+impl ChainTipVerifier {
+    pub fn new(
+        sources: Vec<Box<dyn ChainTipSource>>,
+        quorum: usize,
+        source_timeout: Duration,
+    ) -> Self {
+        Self {
+            sources,
+            quorum: quorum.max(1),
+            source_timeout,
+        }
+    }
+
+    pub fn from_config(config: ChainTipVerificationConfig) -> Self {
+        Self::new(
+            config
+                .sources
+                .into_iter()
+                .map(ChainTipSourceConfig::into_source)
+                .collect(),
+            config.quorum,
+            Duration::from_secs(config.source_timeout_secs),
+        )
+    }
+
+    pub async fn from_current_config() -> Self {
+        Self::from_config(ConfigCore::content().await.chain_tip_verification().clone())
+    }
+
+    /// Highest height that at least `quorum` sources have reached. A source claiming a tip far ahead of
+    /// everyone else cannot move it.
+    pub async fn verified_tip(&self) -> Result<u64, Error> {
+        let mut heights = self.query_all(|source| source.tip_height()).await;
+        if heights.len() < self.quorum {
+            return Err(anyhow!(
+                "Only {} of {} chain tip sources responded, {} required",
+                heights.len(),
+                self.sources.len(),
+                self.quorum
+            ));
+        }
+        heights.sort_unstable_by(|a, b| b.cmp(a));
+        Ok(heights[self.quorum - 1])
+    }
+
+    /// Block hash at `height` that at least `quorum` sources agree on.
+    pub async fn verified_block_hash(&self, height: u64) -> Result<String, Error> {
+        let hashes = self
+            .query_all(|source| source.block_hash(height))
+            .await
+            .into_iter()
+            .flatten()
+            .map(|hash| hash.to_lowercase());
+        let mut votes: HashMap<String, usize> = HashMap::new();
+        for hash in hashes {
+            *votes.entry(hash).or_default() += 1;
+        }
+        let (hash, count) = votes
+            .iter()
+            .max_by_key(|(_, count)| **count)
+            .map(|(hash, count)| (hash.clone(), *count))
+            .unwrap_or_default();
+        if count < self.quorum {
+            return Err(anyhow!(
+                "No quorum for block hash at height {}: best agreement {} of {} required, {} distinct hashes reported",
+                height,
+                count,
+                self.quorum,
+                votes.len()
+            ));
+        }
+        Ok(hash)
+    }
+
+    async fn query_all<'a, T, F, Fut>(&'a self, query: F) -> Vec<T>
+    where
+        F: Fn(&'a dyn ChainTipSource) -> Fut,
+        Fut: std::future::Future<Output = Result<T, Error>> + 'a,
+    {
+        let results = join_all(self.sources.iter().map(|source| {
+            let source = source.as_ref();
+            let request = tokio::time::timeout(self.source_timeout, query(source));
+            async move { (source.name(), request.await) }
+        }))
+        .await;
+
+        results
+            .into_iter()
+            .filter_map(|(name, result)| match result {
+                Ok(Ok(value)) => Some(value),
+                Ok(Err(e)) => {
+                    warn!(target: LOG_TARGET, "Chain tip source {} failed: {}", name, e);
+                    None
+                }
+                Err(_) => {
+                    warn!(target: LOG_TARGET, "Chain tip source {} timed out", name);
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+/// Decides whether a node that reports initial sync achieved is actually near the verified chain tip.
+pub struct SyncedTipGate {
+    verifier: ChainTipVerifier,
+    first_claimed_at: Option<Instant>,
+    last_checked_at: Option<Instant>,
+    lagging: bool,
+}
+
+impl SyncedTipGate {
+    pub fn new(verifier: ChainTipVerifier) -> Self {
+        Self {
+            verifier,
+            first_claimed_at: None,
+            last_checked_at: None,
+            lagging: false,
+        }
+    }
+
+    /// Returns true once `local_tip` is within reach of the verified tip, or when the tip cannot be verified.
+    pub async fn accept(&mut self, local_tip: u64) -> bool {
+        let first_claimed_at = *self.first_claimed_at.get_or_insert_with(Instant::now);
+        if first_claimed_at.elapsed() >= SYNCED_TIP_GRACE_PERIOD {
+            if self.lagging {
+                warn!(target: LOG_TARGET, "Node still behind the verified chain tip after {:?}, accepting its sync state", SYNCED_TIP_GRACE_PERIOD);
+            }
+            return true;
+        }
+        if self.last_checked_at.map_or(true, |checked_at| {
+            checked_at.elapsed() >= SYNCED_TIP_CHECK_INTERVAL
+        }) {
+            self.last_checked_at = Some(Instant::now());
+            self.lagging = match self.verifier.verified_tip().await {
+                Ok(verified_tip) if verified_tip > local_tip.saturating_add(MAX_SYNCED_TIP_LAG) => {
+                    info!(target: LOG_TARGET, "Node reports synced at height {} but verified chain tip is {}, waiting", local_tip, verified_tip);
+                    true
+                }
+                Ok(_) => false,
+                Err(e) => {
+                    warn!(target: LOG_TARGET, "Could not verify chain tip, trusting node sync state: {}", e);
+                    false
+                }
+            };
+        }
+        !self.lagging
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    struct FakeSource {
+        tip: Result<u64, ()>,
+        hash: Option<&'static str>,
+        delay: Duration,
+    }
+
+    impl FakeSource {
+        fn boxed(tip: u64, hash: &'static str) -> Box<dyn ChainTipSource> {
+            Box::new(Self {
+                tip: Ok(tip),
+                hash: Some(hash),
+                delay: Duration::ZERO,
+            })
+        }
+    }
+
+    #[async_trait]
+    impl ChainTipSource for FakeSource {
+        fn name(&self) -> String {
+            "fake".to_string()
+        }
+
+        async fn tip_height(&self) -> Result<u64, Error> {
+            tokio::time::sleep(self.delay).await;
+            self.tip.map_err(|_| anyhow!("unreachable"))
+        }
+
+        async fn block_hash(&self, _height: u64) -> Result<Option<String>, Error> {
+            tokio::time::sleep(self.delay).await;
+            Ok(self.hash.map(str::to_string))
+        }
+    }
+
+    /// Minimal stand-in for the text explorer: answers every request with a tip at `tip` and a block whose
+    /// hash bytes are all `hash_byte`.
+    async fn spawn_explorer(tip: u64, hash_byte: u8) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buffer = [0u8; 1024];
+                let read = stream.read(&mut buffer).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+                let path = request.split_whitespace().nth(1).unwrap_or("/").to_string();
+                let body = match path.strip_prefix("/blocks/") {
+                    Some(rest) => {
+                        let height = rest.trim_end_matches("?json");
+                        format!(
+                            r#"{{"header":{{"hash":{{"data":{:?}}},"height":"{}"}}}}"#,
+                            vec![hash_byte; 32],
+                            height
+                        )
+                    }
+                    None => format!(
+                        r#"{{"tipInfo":{{"metadata":{{"best_block_height":"{}"}}}}}}"#,
+                        tip
+                    ),
+                };
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                let _unused = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn tip_ignores_source_claiming_far_ahead() {
+        let verifier = ChainTipVerifier::new(
+            vec![
+                FakeSource::boxed(1000, "aa"),
+                FakeSource::boxed(1001, "aa"),
+                FakeSource::boxed(9_999_999, "bb"),
+            ],
+            2,
+            Duration::from_secs(1),
+        );
+        assert_eq!(verifier.verified_tip().await.unwrap(), 1001);
+        assert_eq!(verifier.verified_block_hash(900).await.unwrap(), "aa");
+    }
+
+    #[tokio::test]
+    async fn slow_and_failing_sources_do_not_count() {
+        let verifier = ChainTipVerifier::new(
+            vec![
+                FakeSource::boxed(1000, "aa"),
+                Box::new(FakeSource {
+                    tip: Ok(1000),
+                    hash: Some("aa"),
+                    delay: Duration::from_secs(5),
+                }),
+                Box::new(FakeSource {
+                    tip: Err(()),
+                    hash: None,
+                    delay: Duration::ZERO,
+                }),
+            ],
+            2,
+            Duration::from_millis(100),
+        );
+        assert!(verifier.verified_tip().await.is_err());
+        assert!(verifier.verified_block_hash(900).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn block_hash_requires_agreement() {
+        let verifier = ChainTipVerifier::new(
+            vec![FakeSource::boxed(1000, "aa"), FakeSource::boxed(1000, "bb")],
+            2,
+            Duration::from_secs(1),
+        );
+        assert!(verifier.verified_block_hash(900).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn verifies_against_local_explorers() {
+        let honest_a = spawn_explorer(500, 0xab).await;
+        let honest_b = spawn_explorer(499, 0xab).await;
+        let liar = spawn_explorer(10_000, 0xcd).await;
+        let config = ChainTipVerificationConfig {
+            sources: vec![
+                ChainTipSourceConfig::Explorer { url: honest_a },
+                ChainTipSourceConfig::Explorer { url: honest_b },
+                ChainTipSourceConfig::Explorer { url: liar },
+            ],
+            quorum: 2,
+            source_timeout_secs: 5,
+        };
+        config.validate().unwrap();
+        let verifier = ChainTipVerifier::from_config(config);
+        assert_eq!(verifier.verified_tip().await.unwrap(), 500);
+        assert_eq!(
+            verifier.verified_block_hash(450).await.unwrap(),
+            "ab".repeat(32)
+        );
+    }
+
+    #[test]
+    fn rejects_quorum_larger_than_sources() {
+        let mut config = ChainTipVerificationConfig::default();
+        config.quorum = config.sources.len() + 1;
+        assert!(config.validate().is_err());
+        config.quorum = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn default_uses_public_sources_with_majority_quorum() {
+        let config = ChainTipVerificationConfig::default();
+        assert_eq!(config.sources.len(), 3);
+        assert_eq!(config.quorum, 2);
+        config.validate().unwrap();
+        assert!(!serde_json::to_string(&config).unwrap().contains("tari.com"));
+    }
+
+    #[test]
+    fn replaces_legacy_default() {
+        let legacy = ChainTipVerificationConfig {
+            sources: vec![
+                ChainTipSourceConfig::Explorer {
+                    url: get_text_explore_url(Network::NextNet),
+                },
+                ChainTipSourceConfig::BaseNode {
+                    address: default_remote_base_node_address_for(Network::NextNet),
+                },
+            ],
+            quorum: 2,
+            source_timeout_secs: DEFAULT_SOURCE_TIMEOUT_SECS,
+        };
+        assert_eq!(
+            legacy.replace_legacy_default(),
+            ChainTipVerificationConfig::default()
+        );
+
+        let custom = ChainTipVerificationConfig {
+            sources: vec![ChainTipSourceConfig::BaseNode {
+                address: "https://my-node:18142".to_string(),
+            }],
+            quorum: 1,
+            source_timeout_secs: 5,
+        };
+        assert_eq!(custom.clone().replace_legacy_default(), custom);
+    }
+}
@@ -25,6 +25,8 @@ use crate::node::node_adapter::{
     BaseNodeStatus, NodeAdapter, NodeAdapterService, NodeStatusMonitor,
 };
 use crate::node::node_manager::NodeType;
+use crate::node::node_storage::NodeStorageMode;
+use crate::node::peer_management::{preset_peer_seeds, BannedPeer, SyncPeer};
 use crate::port_allocator::PortAllocator;
 use crate::process_adapter::{ProcessAdapter, ProcessInstance, ProcessStartupSpec};
 use crate::utils::file_utils::convert_to_string;
@@ -34,9 +36,9 @@ use log::{info, warn};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::AtomicU64;
-use std::sync::Arc;
-use tari_common::configuration::Network;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
+use tari_common::configuration::{utils::get_default_config, Network};
 use tari_crypto::ristretto::RistrettoPublicKey;
 use tari_shutdown::Shutdown;
 use tokio::sync::watch;
@@ -45,6 +47,19 @@ use tokio::sync::watch;
 use crate::utils::windows_setup_utils::add_firewall_rule;
 
 const LOG_TARGET: &str = "tari::universe::local_node_adapter";
+/// Restricted gRPC methods the app calls on its own node. The node rejects calls to methods missing here, so anything
+/// new on `NodeAdapterService` outside the node's default list has to be added.
+const GRPC_SERVER_ALLOW_METHODS: &[&str] = &[
+    "list_connected_peers",
+    "get_peers",
+    "get_blocks",
+    "get_header_by_hash",
+    "get_network_difficulty",
+    "search_kernels",
+    "get_mempool_stats",
+    "get_mempool_fee_per_gram_stats",
+    "get_sync_info",
+];
 
 #[derive(Serialize, Deserialize, Default)]
 struct MinotariNodeMigrationInfo {
@@ -74,10 +89,20 @@ pub(crate) struct LocalNodeAdapter {
     status_broadcast: watch::Sender<BaseNodeStatus>,
     pub(crate) use_tor: bool,
     pub(crate) tcp_listener_port: u16,
-    pub(crate) use_pruned_mode: bool,
+    pub(crate) storage_mode: NodeStorageMode,
     pub(crate) tor_control_port: Option<u16>,
     required_initial_peers: u32,
     pub(crate) ab_test_group: ABTestSelector,
+    pub(crate) custom_seed_peers: Vec<String>,
+    // Shared with the status monitor, which adds the addresses of banned peers that still connect
+    pub(crate) banned_peers: Arc<RwLock<Vec<BannedPeer>>>,
+    // Filled by the status monitor with the peers the node synced from
+    sync_peers: Arc<RwLock<Vec<SyncPeer>>>,
+    // Shared between clones so a reset requested through the node manager is picked up by the watcher
+    reset_peer_db_on_start: Arc<AtomicBool>,
+    reset_chain_db_on_start: Arc<AtomicBool>,
+    // Height to rewind the blockchain to on the next start, 0 when no rewind is pending
+    rewind_chain_to_height: Arc<AtomicU64>,
 }
 
 impl LocalNodeAdapter {
@@ -88,14 +113,49 @@ impl LocalNodeAdapter {
             grpc_address: Some(("127.0.0.1".to_string(), grpc_port)),
             status_broadcast,
             tcp_listener_port,
-            use_pruned_mode: false,
+            storage_mode: NodeStorageMode::default(),
             required_initial_peers: 3,
             use_tor: false,
             tor_control_port: None,
             ab_test_group: ABTestSelector::GroupA,
+            custom_seed_peers: vec![],
+            banned_peers: Arc::new(RwLock::new(vec![])),
+            sync_peers: Arc::new(RwLock::new(vec![])),
+            reset_peer_db_on_start: Arc::new(AtomicBool::new(false)),
+            reset_chain_db_on_start: Arc::new(AtomicBool::new(false)),
+            rewind_chain_to_height: Arc::new(AtomicU64::new(0)),
         }
     }
 
+    /// Drops the peer database on the next start so the node has to rediscover its peers
+    pub fn request_peer_db_reset(&self) {
+        self.reset_peer_db_on_start.store(true, Ordering::SeqCst);
+    }
+
+    /// Drops the blockchain database on the next start so the node resyncs from its peers
+    pub fn request_chain_db_reset(&self) {
+        self.reset_chain_db_on_start.store(true, Ordering::SeqCst);
+    }
+
+    /// Rewinds the blockchain to `height` once the node has started. Stays in effect for every start until cleared
+    pub fn request_chain_rewind(&self, height: u64) {
+        self.rewind_chain_to_height.store(height, Ordering::SeqCst);
+    }
+
+    pub fn clear_chain_rewind(&self) {
+        self.rewind_chain_to_height.store(0, Ordering::SeqCst);
+    }
+
+    /// Takes the recorded sync peers, leaving the record empty for the chain synced from here on
+    pub fn take_sync_peers(&self) -> Vec<SyncPeer> {
+        std::mem::take(
+            &mut *self
+                .sync_peers
+                .write()
+                .unwrap_or_else(PoisonError::into_inner),
+        )
+    }
+
     pub fn get_grpc_address(&self) -> Option<(String, u16)> {
         self.grpc_address.clone()
     }
@@ -153,6 +213,21 @@ impl NodeAdapter for LocalNodeAdapter {
     fn set_ab_group(&mut self, ab_test_group: ABTestSelector) {
         self.ab_test_group = ab_test_group;
     }
+
+    fn set_custom_seed_peers(&mut self, custom_seed_peers: Vec<String>) {
+        self.custom_seed_peers = custom_seed_peers;
+    }
+
+    fn set_banned_peers(&mut self, banned_peers: Vec<BannedPeer>) {
+        *self
+            .banned_peers
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = banned_peers;
+    }
+
+    fn set_storage_mode(&mut self, storage_mode: NodeStorageMode) {
+        self.storage_mode = storage_mode;
+    }
 }
 
 impl ProcessAdapter for LocalNodeAdapter {
@@ -205,6 +280,26 @@ impl ProcessAdapter for LocalNodeAdapter {
         }
         migration_info.save(&migration_file)?;
 
+        if self.reset_peer_db_on_start.swap(false, Ordering::SeqCst) {
+            let peer_db_dir = network_dir.join("peer_db");
+            if peer_db_dir.exists() {
+                info!(target: LOG_TARGET, "Forced reconnect: removing peer db at {:?}", peer_db_dir);
+                let _unused = fs::remove_dir_all(peer_db_dir).inspect_err(|e| {
+                    warn!(target: LOG_TARGET, "Failed to remove peer db: {:?}", e);
+                });
+            }
+        }
+
+        if self.reset_chain_db_on_start.swap(false, Ordering::SeqCst) {
+            let node_db_dir = network_dir.join("data");
+            if node_db_dir.exists() {
+                info!(target: LOG_TARGET, "Chain reset: removing node db at {:?}", node_db_dir);
+                let _unused = fs::remove_dir_all(node_db_dir).inspect_err(|e| {
+                    warn!(target: LOG_TARGET, "Failed to remove node db: {:?}", e);
+                });
+            }
+        }
+
         // if is_first_start {
         //     let peer_db_dir = network_dir.join("peer_db");
         //     if peer_db_dir.exists() {
@@ -252,16 +347,21 @@ impl ProcessAdapter for LocalNodeAdapter {
                 self.required_initial_peers
             ),
             "-p".to_string(),
-            "base_node.grpc_server_allow_methods=\"list_connected_peers, get_blocks\"".to_string(),
+            format!(
+                "base_node.grpc_server_allow_methods=\"{}\"",
+                GRPC_SERVER_ALLOW_METHODS.join(", ")
+            ),
             "-p".to_string(),
             "base_node.p2p.allow_test_addresses=true".to_string(),
             "-p".to_string(),
             "base_node.p2p.dht.network_discovery.min_desired_peers=12".to_string(),
         ];
-        if self.use_pruned_mode {
-            args.push("-p".to_string());
-            args.push("base_node.storage.pruning_horizon=100".to_string());
-        }
+        info!(target: LOG_TARGET, "Using node storage mode: {:?}", self.storage_mode);
+        args.push("-p".to_string());
+        args.push(format!(
+            "base_node.storage.pruning_horizon={}",
+            self.storage_mode.pruning_horizon()
+        ));
         // Uncomment to test winning blocks
         // if cfg!(debug_assertions) {
         // args.push("--network".to_string());
@@ -342,6 +442,42 @@ impl ProcessAdapter for LocalNodeAdapter {
             }
         }
 
+        // Peer settings chosen by the user are network specific, like the seeds they refer to
+        let network_key = Network::get_current_or_user_setting_or_default().as_key_str();
+        if !self.custom_seed_peers.is_empty() {
+            // Setting peer_seeds replaces the network's list, so the node's own seeds are passed along
+            let mut seeds = default_peer_seeds(network_key);
+            for seed in &self.custom_seed_peers {
+                if !seeds.contains(seed) {
+                    seeds.push(seed.clone());
+                }
+            }
+            args.push("-p".to_string());
+            args.push(format!(
+                "{key}.p2p.seeds.peer_seeds={seeds}",
+                key = network_key,
+                seeds = seeds.join(","),
+            ));
+        }
+
+        let excluded_dial_addresses = self
+            .banned_peers
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter(|peer| peer.is_active())
+            .flat_map(BannedPeer::excluded_dial_addresses)
+            .collect::<Vec<String>>();
+        if !excluded_dial_addresses.is_empty() {
+            info!(target: LOG_TARGET, "Excluding {} banned peer addresses", excluded_dial_addresses.len());
+            args.push("-p".to_string());
+            args.push(format!(
+                "{key}.base_node.p2p.dht.excluded_dial_addresses={addresses}",
+                key = network_key,
+                addresses = excluded_dial_addresses.join(","),
+            ));
+        }
+
         // AB testing
         if self.ab_test_group == ABTestSelector::GroupB {
             info!(target: LOG_TARGET, "Using AB test group B");
@@ -358,6 +494,15 @@ impl ProcessAdapter for LocalNodeAdapter {
             args.push("base_node.p2p.dht.minimize_connections=false".to_string());
         }
 
+        // minotari_node has no rewind over gRPC, so the rewind command is run through the watch loop.
+        // The watch loop repeats the command, so the rewind has to be cleared once the tip is at the fork point.
+        let rewind_height = self.rewind_chain_to_height.load(Ordering::SeqCst);
+        if rewind_height > 0 {
+            info!(target: LOG_TARGET, "Rewinding blockchain to height {}", rewind_height);
+            args.push("--watch".to_string());
+            args.push(format!("rewind-blockchain {}", rewind_height));
+        }
+
         #[cfg(target_os = "windows")]
         add_firewall_rule("minotari_node.exe".to_string(), binary_version_path.clone())?;
 
@@ -383,6 +528,8 @@ impl ProcessAdapter for LocalNodeAdapter {
                 self.status_broadcast.clone(),
                 Arc::new(AtomicU64::new(0)),
                 Some(data_dir),
+                self.banned_peers.clone(),
+                self.sync_peers.clone(),
             ),
         ))
     }
@@ -395,3 +542,34 @@ impl ProcessAdapter for LocalNodeAdapter {
         "node_pid"
     }
 }
+
+/// Seed peers the node ships with for `network_key`, taken from the config presets compiled into tari_common
+fn default_peer_seeds(network_key: &str) -> Vec<String> {
+    let seeds = get_default_config(true)
+        .iter()
+        .flat_map(|preset| preset_peer_seeds(preset, network_key))
+        .collect::<Vec<String>>();
+    if seeds.is_empty() {
+        warn!(target: LOG_TARGET, "No default peer seeds found for {}, only custom seeds are used", network_key);
+    }
+    seeds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allow_list_covers_fee_estimation() {
+        for method in ["get_mempool_stats", "get_mempool_fee_per_gram_stats"] {
+            assert!(GRPC_SERVER_ALLOW_METHODS.contains(&method), "{method}");
+        }
+    }
+
+    #[test]
+    fn allow_list_covers_sync_peer_tracking() {
+        for method in ["get_sync_info", "list_connected_peers"] {
+            assert!(GRPC_SERVER_ALLOW_METHODS.contains(&method), "{method}");
+        }
+    }
+}
@@ -20,7 +20,14 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+pub mod block_explorer;
+pub mod chain_tip_verifier;
 pub mod local_node_adapter;
+pub mod network_stats;
 pub mod node_adapter;
 pub mod node_manager;
+pub mod node_storage;
+pub mod orphan_chain_recovery;
+pub mod peer_management;
 pub mod remote_node_adapter;
+pub mod sync_progress;
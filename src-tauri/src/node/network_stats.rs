@@ -0,0 +1,521 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    sync::LazyLock,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Error;
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use tari_common::configuration::Network;
+use tauri::{AppHandle, Manager};
+use tokio::{sync::Mutex, time::interval};
+
+use crate::{
+    node::node_adapter::NetworkDifficulties, tasks_tracker::TasksTrackers, BaseNodeStatus,
+    UniverseAppState,
+};
+
+const LOG_TARGET: &str = "tari::universe::network_stats";
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(60);
+// Persist every 10 samples so a crash loses at most ten minutes of history
+const SAMPLES_PER_SAVE: u32 = 10;
+// Window used for the smoothed hashrates fed into earnings estimates
+const SMOOTHING_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+static INSTANCE: LazyLock<NetworkStats> = LazyLock::new(NetworkStats::new);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsResolution {
+    Minute,
+    Hour,
+    Day,
+}
+
+impl StatsResolution {
+    fn bucket_secs(&self) -> u64 {
+        match self {
+            StatsResolution::Minute => 60,
+            StatsResolution::Hour => 60 * 60,
+            StatsResolution::Day => 24 * 60 * 60,
+        }
+    }
+
+    fn retention_secs(&self) -> u64 {
+        match self {
+            StatsResolution::Minute => 24 * 60 * 60,
+            StatsResolution::Hour => 30 * 24 * 60 * 60,
+            StatsResolution::Day => 365 * 24 * 60 * 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkStatsMetric {
+    Sha3xHashrate,
+    RandomxHashrate,
+    Sha3xDifficulty,
+    RandomxDifficulty,
+    BlockTime,
+    BlockReward,
+    Connections,
+}
+
+/// Aggregate of the samples taken within one bucket.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct NetworkStatsPoint {
+    pub timestamp: u64,
+    pub sha3x_hashrate: f64,
+    pub randomx_hashrate: f64,
+    // Target difficulties read from the block headers, missing in points recorded before they were sampled
+    #[serde(default)]
+    pub sha3x_difficulty: Option<f64>,
+    #[serde(default)]
+    pub randomx_difficulty: Option<f64>,
+    pub block_reward: f64,
+    pub num_connections: f64,
+    pub start_height: u64,
+    pub end_height: u64,
+    pub start_block_time: u64,
+    pub end_block_time: u64,
+    pub samples: u32,
+}
+
+impl NetworkStatsPoint {
+    pub fn from_status(
+        timestamp: u64,
+        status: &BaseNodeStatus,
+        difficulties: &NetworkDifficulties,
+    ) -> Self {
+        Self {
+            timestamp,
+            sha3x_hashrate: status.sha_network_hashrate as f64,
+            randomx_hashrate: status.randomx_network_hashrate as f64,
+            sha3x_difficulty: difficulties.sha3x.map(|difficulty| difficulty as f64),
+            randomx_difficulty: difficulties.randomx.map(|difficulty| difficulty as f64),
+            block_reward: status.block_reward.as_u64() as f64,
+            num_connections: status.num_connections as f64,
+            start_height: status.block_height,
+            end_height: status.block_height,
+            start_block_time: status.block_time,
+            end_block_time: status.block_time,
+            samples: 1,
+        }
+    }
+
+    fn merge(&mut self, other: &NetworkStatsPoint) {
+        let total = f64::from(self.samples + other.samples);
+        let (own_weight, other_weight) = (
+            f64::from(self.samples) / total,
+            f64::from(other.samples) / total,
+        );
+        let weighted = |a: f64, b: f64| a * own_weight + b * other_weight;
+        let weighted_option = |a: Option<f64>, b: Option<f64>| match (a, b) {
+            (Some(a), Some(b)) => Some(weighted(a, b)),
+            (a, b) => a.or(b),
+        };
+        self.sha3x_hashrate = weighted(self.sha3x_hashrate, other.sha3x_hashrate);
+        self.randomx_hashrate = weighted(self.randomx_hashrate, other.randomx_hashrate);
+        self.sha3x_difficulty = weighted_option(self.sha3x_difficulty, other.sha3x_difficulty);
+        self.randomx_difficulty =
+            weighted_option(self.randomx_difficulty, other.randomx_difficulty);
+        self.block_reward = weighted(self.block_reward, other.block_reward);
+        self.num_connections = weighted(self.num_connections, other.num_connections);
+        if other.start_height < self.start_height {
+            self.start_height = other.start_height;
+            self.start_block_time = other.start_block_time;
+        }
+        if other.end_height > self.end_height {
+            self.end_height = other.end_height;
+            self.end_block_time = other.end_block_time;
+        }
+        self.samples += other.samples;
+    }
+
+    /// Average seconds between blocks (both algorithms) over the bucket.
+    pub fn average_block_time(&self) -> Option<f64> {
+        let blocks = self.end_height.checked_sub(self.start_height)?;
+        let seconds = self.end_block_time.checked_sub(self.start_block_time)?;
+        (blocks > 0).then(|| seconds as f64 / blocks as f64)
+    }
+
+    fn value(&self, metric: NetworkStatsMetric) -> Option<f64> {
+        match metric {
+            NetworkStatsMetric::Sha3xHashrate => Some(self.sha3x_hashrate),
+            NetworkStatsMetric::RandomxHashrate => Some(self.randomx_hashrate),
+            NetworkStatsMetric::Sha3xDifficulty => self.sha3x_difficulty,
+            NetworkStatsMetric::RandomxDifficulty => self.randomx_difficulty,
+            NetworkStatsMetric::BlockTime => self.average_block_time(),
+            NetworkStatsMetric::BlockReward => Some(self.block_reward),
+            NetworkStatsMetric::Connections => Some(self.num_connections),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NetworkStatsSeriesPoint {
+    pub timestamp: u64,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkStatsStore {
+    minute: VecDeque<NetworkStatsPoint>,
+    hour: VecDeque<NetworkStatsPoint>,
+    day: VecDeque<NetworkStatsPoint>,
+}
+
+impl NetworkStatsStore {
+    fn buckets_mut(&mut self, resolution: StatsResolution) -> &mut VecDeque<NetworkStatsPoint> {
+        match resolution {
+            StatsResolution::Minute => &mut self.minute,
+            StatsResolution::Hour => &mut self.hour,
+            StatsResolution::Day => &mut self.day,
+        }
+    }
+
+    fn buckets(&self, resolution: StatsResolution) -> &VecDeque<NetworkStatsPoint> {
+        match resolution {
+            StatsResolution::Minute => &self.minute,
+            StatsResolution::Hour => &self.hour,
+            StatsResolution::Day => &self.day,
+        }
+    }
+
+    pub fn record(&mut self, point: NetworkStatsPoint) {
+        for resolution in [
+            StatsResolution::Minute,
+            StatsResolution::Hour,
+            StatsResolution::Day,
+        ] {
+            let bucket_start = point.timestamp - point.timestamp % resolution.bucket_secs();
+            let buckets = self.buckets_mut(resolution);
+            match buckets.back_mut() {
+                Some(last) if last.timestamp == bucket_start => last.merge(&point),
+                _ => buckets.push_back(NetworkStatsPoint {
+                    timestamp: bucket_start,
+                    ..point
+                }),
+            }
+            let oldest_allowed = point.timestamp.saturating_sub(resolution.retention_secs());
+            while buckets
+                .front()
+                .is_some_and(|bucket| bucket.timestamp < oldest_allowed)
+            {
+                buckets.pop_front();
+            }
+        }
+    }
+
+    pub fn series(
+        &self,
+        resolution: StatsResolution,
+        metric: NetworkStatsMetric,
+        from: Option<u64>,
+        to: Option<u64>,
+    ) -> Vec<NetworkStatsSeriesPoint> {
+        self.buckets(resolution)
+            .iter()
+            .filter(|point| from.map_or(true, |from| point.timestamp >= from))
+            .filter(|point| to.map_or(true, |to| point.timestamp <= to))
+            .filter_map(|point| {
+                point.value(metric).map(|value| NetworkStatsSeriesPoint {
+                    timestamp: point.timestamp,
+                    value,
+                })
+            })
+            .collect()
+    }
+
+    /// Sample-weighted average of the (sha3x, randomx) hashrates since `since`.
+    pub fn smoothed_hashrates(&self, since: u64) -> Option<(u64, u64)> {
+        let mut window = self.minute.iter().filter(|point| point.timestamp >= since);
+        let mut total = *window.next()?;
+        for point in window {
+            total.merge(point);
+        }
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        Some((
+            total.sha3x_hashrate.round() as u64,
+            total.randomx_hashrate.round() as u64,
+        ))
+    }
+}
+
+struct NetworkStatsState {
+    store: NetworkStatsStore,
+    path: Option<PathBuf>,
+    unsaved_samples: u32,
+}
+
+pub struct NetworkStats {
+    state: Mutex<NetworkStatsState>,
+}
+
+impl NetworkStats {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(NetworkStatsState {
+                store: NetworkStatsStore::default(),
+                path: None,
+                unsaved_samples: 0,
+            }),
+        }
+    }
+
+    pub fn current() -> &'static NetworkStats {
+        &INSTANCE
+    }
+
+    fn get_store_path(data_dir: &Path) -> PathBuf {
+        data_dir.join("network_stats").join(format!(
+            "{}.json",
+            Network::get_current_or_user_setting_or_default()
+                .to_string()
+                .to_lowercase()
+        ))
+    }
+
+    async fn load(&self, data_dir: &Path) {
+        let path = Self::get_store_path(data_dir);
+        let store = match tokio::fs::read_to_string(&path).await {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!(target: LOG_TARGET, "Discarding unreadable network stats {:?}: {:?}", path, e);
+                NetworkStatsStore::default()
+            }),
+            Err(_) => NetworkStatsStore::default(),
+        };
+        let mut state = self.state.lock().await;
+        state.store = store;
+        state.path = Some(path);
+    }
+
+    async fn save(&self) -> Result<(), Error> {
+        let mut state = self.state.lock().await;
+        let Some(path) = state.path.clone() else {
+            return Ok(());
+        };
+        let content = serde_json::to_string(&state.store)?;
+        state.unsaved_samples = 0;
+        drop(state);
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let tmp_path = path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_path, content).await?;
+        tokio::fs::rename(&tmp_path, &path).await?;
+        Ok(())
+    }
+
+    async fn record(&self, status: &BaseNodeStatus, difficulties: &NetworkDifficulties) -> bool {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut state = self.state.lock().await;
+        state.store.record(NetworkStatsPoint::from_status(
+            timestamp,
+            status,
+            difficulties,
+        ));
+        state.unsaved_samples += 1;
+        state.unsaved_samples >= SAMPLES_PER_SAVE
+    }
+
+    pub async fn series(
+        &self,
+        resolution: StatsResolution,
+        metric: NetworkStatsMetric,
+        from: Option<u64>,
+        to: Option<u64>,
+    ) -> Vec<NetworkStatsSeriesPoint> {
+        self.state
+            .lock()
+            .await
+            .store
+            .series(resolution, metric, from, to)
+    }
+
+    /// Hashrates averaged over the last hour, falling back to the latest node values.
+    pub async fn smoothed_hashrates(&self, latest: &BaseNodeStatus) -> (u64, u64) {
+        let since = SystemTime::now()
+            .checked_sub(SMOOTHING_WINDOW)
+            .and_then(|since| since.duration_since(UNIX_EPOCH).ok())
+            .map(|since| since.as_secs())
+            .unwrap_or_default();
+        self.state
+            .lock()
+            .await
+            .store
+            .smoothed_hashrates(since)
+            .unwrap_or((latest.sha_network_hashrate, latest.randomx_network_hashrate))
+    }
+
+    pub async fn spawn_sampler(app_handle: AppHandle) {
+        info!(target: LOG_TARGET, "Spawning network stats sampler");
+        match app_handle.path().app_local_data_dir() {
+            Ok(data_dir) => NetworkStats::current().load(&data_dir).await,
+            Err(e) => error!(target: LOG_TARGET, "Network stats will not be persisted: {:?}", e),
+        }
+
+        TasksTrackers::current()
+            .common
+            .get_task_tracker()
+            .await
+            .spawn(async move {
+                let state = app_handle.state::<UniverseAppState>();
+                let node_status_watch_rx = (*state.node_status_watch_rx).clone();
+                let node_manager = state.node_manager.clone();
+                let mut shutdown_signal = TasksTrackers::current().common.get_signal().await;
+                let mut interval = interval(SAMPLE_INTERVAL);
+                loop {
+                    tokio::select! {
+                        _ = interval.tick() => {
+                            let status = *node_status_watch_rx.borrow();
+                            // Hashrate estimates from a node that is still syncing are meaningless
+                            if !status.is_synced || status.block_height == 0 {
+                                continue;
+                            }
+                            let difficulties = node_manager
+                                .get_network_difficulties()
+                                .await
+                                .unwrap_or_else(|e| {
+                                    debug!(target: LOG_TARGET, "Could not read network difficulty: {:?}", e);
+                                    NetworkDifficulties::default()
+                                });
+                            if NetworkStats::current().record(&status, &difficulties).await {
+                                if let Err(e) = NetworkStats::current().save().await {
+                                    error!(target: LOG_TARGET, "Failed to save network stats: {:?}", e);
+                                }
+                            }
+                        }
+                        _ = shutdown_signal.wait() => {
+                            let _unused = NetworkStats::current().save().await;
+                            break;
+                        }
+                    }
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tari_core::transactions::tari_amount::MicroMinotari;
+
+    fn difficulties(sha3x: u64) -> NetworkDifficulties {
+        NetworkDifficulties {
+            sha3x: Some(sha3x),
+            randomx: None,
+        }
+    }
+
+    fn status(height: u64, block_time: u64, sha3x_hashrate: u64) -> BaseNodeStatus {
+        BaseNodeStatus {
+            sha_network_hashrate: sha3x_hashrate,
+            randomx_network_hashrate: 1_000,
+            block_reward: MicroMinotari(10),
+            block_height: height,
+            block_time,
+            is_synced: true,
+            num_connections: 8,
+        }
+    }
+
+    #[test]
+    fn downsamples_into_hour_buckets() {
+        let mut store = NetworkStatsStore::default();
+        let start = 1_700_000_000 - 1_700_000_000 % 3600;
+        for minute in 0..90 {
+            let height = 100 + minute / 2;
+            store.record(NetworkStatsPoint::from_status(
+                start + minute * 60,
+                &status(height, start + height * 120, 100 + minute),
+                &difficulties(1_000 + minute),
+            ));
+        }
+
+        assert_eq!(store.minute.len(), 90);
+        assert_eq!(store.hour.len(), 2);
+        let first_hour = store.hour[0];
+        assert_eq!(first_hour.samples, 60);
+        assert!((first_hour.sha3x_hashrate - 129.5).abs() < 1e-6);
+        assert_eq!(first_hour.average_block_time(), Some(120.0));
+
+        let series = store.series(
+            StatsResolution::Hour,
+            NetworkStatsMetric::Sha3xDifficulty,
+            Some(start + 3600),
+            None,
+        );
+        assert_eq!(series.len(), 1);
+        assert!((series[0].value - 1_074.5).abs() < 1e-6);
+        assert!(store
+            .series(
+                StatsResolution::Hour,
+                NetworkStatsMetric::RandomxDifficulty,
+                None,
+                None
+            )
+            .is_empty());
+    }
+
+    #[test]
+    fn prunes_points_past_retention() {
+        let mut store = NetworkStatsStore::default();
+        store.record(NetworkStatsPoint::from_status(
+            0,
+            &status(1, 0, 10),
+            &NetworkDifficulties::default(),
+        ));
+        store.record(NetworkStatsPoint::from_status(
+            2 * 24 * 60 * 60,
+            &status(2, 120, 10),
+            &NetworkDifficulties::default(),
+        ));
+        assert_eq!(store.minute.len(), 1);
+        assert_eq!(store.day.len(), 2);
+    }
+
+    #[test]
+    fn smooths_hashrate_spikes() {
+        let mut store = NetworkStatsStore::default();
+        for (minute, hashrate) in [100, 100, 100, 500].iter().enumerate() {
+            store.record(NetworkStatsPoint::from_status(
+                minute as u64 * 60,
+                &status(1, 0, *hashrate),
+                &NetworkDifficulties::default(),
+            ));
+        }
+        assert_eq!(store.smoothed_hashrates(0), Some((200, 1_000)));
+        assert_eq!(store.smoothed_hashrates(10_000), None);
+    }
+}
@@ -21,15 +21,29 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use crate::ab_test_selector::ABTestSelector;
+use crate::configs::config_core::{ConfigCore, ConfigCoreContent};
+use crate::configs::trait_config::ConfigImpl;
+use crate::node::block_explorer::{
+    decode_hex_field, BlockDetails, BlockQuery, KernelLookupResult, POW_ALGO_RANDOMX,
+    POW_ALGO_SHA3X,
+};
+use crate::node::chain_tip_verifier::{ChainTipVerifier, SyncedTipGate};
 use crate::node::node_manager::NodeType;
+use crate::node::node_storage::NodeStorageMode;
+use crate::node::peer_management::{
+    peer_addresses, record_sync_peers, BannedPeer, PeerDetails, SyncPeer,
+};
+use crate::node::sync_progress::{SyncPhase, SyncRateTracker};
 use crate::process_adapter::{HealthStatus, StatusMonitor};
 use anyhow::{anyhow, Error};
 use async_trait::async_trait;
 use minotari_node_grpc_client::grpc::{
-    BlockHeader, Empty, GetBlocksRequest, GetNetworkStateRequest, SyncState,
+    BlockHeader, Empty, GetBlocksRequest, GetHeaderByHashRequest, GetMempoolFeePerGramStatsRequest,
+    GetNetworkStateRequest, GetPeersRequest, HeightRequest, SearchKernelsRequest, Signature,
+    SyncState,
 };
 use std::path::PathBuf;
-use std::time::{Duration, SystemTime};
+use std::time::{Duration, Instant, SystemTime};
 use tari_utilities::epoch_time::EpochTime;
 use tokio::fs;
 
@@ -40,8 +54,8 @@ use serde::Serialize;
 use serde_json::json;
 use std::collections::HashMap;
 use std::fmt::Write as _;
-use std::sync::atomic::AtomicU64;
-use std::sync::{Arc, Once};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Once, PoisonError, RwLock};
 use tari_common::configuration::Network;
 use tari_core::transactions::tari_amount::MicroMinotari;
 use tari_crypto::ristretto::RistrettoPublicKey;
@@ -53,9 +67,13 @@ use tauri_plugin_sentry::sentry::protocol::Event;
 use tokio::sync::watch;
 use tokio::time::timeout;
 
-use crate::network_utils::{get_best_block_from_block_scan, get_block_info_from_block_scan};
-
 const LOG_TARGET: &str = "tari::universe::minotari_node_adapter";
+/// Minimum time between checks of the connected peers for banned ones
+const BANNED_PEER_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// Blocks alternate between the mining algorithms, so the last few headers contain both
+const DIFFICULTY_LOOKBACK_BLOCKS: u64 = 20;
+/// Block templates of mempool fee stats requested; enough to cover every fee preset
+const MEMPOOL_FEE_STATS_BLOCKS: u64 = 5;
 
 #[async_trait]
 pub trait NodeAdapter {
@@ -66,6 +84,9 @@ pub trait NodeAdapter {
     fn use_tor(&mut self, use_tor: bool);
     fn set_tor_control_port(&mut self, tor_control_port: Option<u16>);
     fn set_ab_group(&mut self, ab_group: ABTestSelector);
+    fn set_custom_seed_peers(&mut self, custom_seed_peers: Vec<String>);
+    fn set_banned_peers(&mut self, banned_peers: Vec<BannedPeer>);
+    fn set_storage_mode(&mut self, storage_mode: NodeStorageMode);
 }
 
 #[derive(Debug, Clone)]
@@ -141,6 +162,76 @@ impl NodeAdapterService {
         Ok(blocks)
     }
 
+    pub async fn get_block_details(
+        &self,
+        query: BlockQuery,
+    ) -> Result<Option<BlockDetails>, Error> {
+        let mut client = BaseNodeGrpcClient::connect(self.connection_address.clone()).await?;
+
+        let (height, expected_hash) = match query {
+            BlockQuery::Height(height) => (height, None),
+            BlockQuery::Hash(hash) => {
+                let hash = decode_hex_field("block hash", &hash)?;
+                let header = match client
+                    .get_header_by_hash(GetHeaderByHashRequest { hash: hash.clone() })
+                    .await
+                {
+                    Ok(response) => response.into_inner().header,
+                    Err(status) if status.code() == tonic::Code::NotFound => None,
+                    Err(e) => return Err(e.into()),
+                };
+                match header {
+                    Some(header) => (header.height, Some(hash)),
+                    None => return Ok(None),
+                }
+            }
+        };
+
+        let mut res = client
+            .get_blocks(GetBlocksRequest {
+                heights: vec![height],
+            })
+            .await?
+            .into_inner();
+        let Some(block) = res.message().await? else {
+            return Ok(None);
+        };
+        let details = BlockDetails::try_from(&block)?;
+        // The chain may have reorged between the header and the block request
+        if expected_hash.is_some_and(|hash| hash.to_hex() != details.header.hash) {
+            return Ok(None);
+        }
+        Ok(Some(details))
+    }
+
+    pub async fn find_kernel(
+        &self,
+        public_nonce: &str,
+        signature: &str,
+    ) -> Result<Option<KernelLookupResult>, Error> {
+        let excess_sig = Signature {
+            public_nonce: decode_hex_field("public nonce", public_nonce)?,
+            signature: decode_hex_field("signature", signature)?,
+        };
+        let mut client = BaseNodeGrpcClient::connect(self.connection_address.clone()).await?;
+        let mut res = client
+            .search_kernels(SearchKernelsRequest {
+                signatures: vec![excess_sig],
+            })
+            .await?
+            .into_inner();
+
+        while let Some(block) = res.message().await? {
+            let details = BlockDetails::try_from(&block)?;
+            if let Some(result) =
+                KernelLookupResult::from_block(&details, public_nonce.trim(), signature.trim())
+            {
+                return Ok(Some(result));
+            }
+        }
+        Ok(None)
+    }
+
     pub async fn get_identity(&self) -> Result<NodeIdentity, Error> {
         let mut client = BaseNodeGrpcClient::connect(self.connection_address.clone()).await?;
         let id = client.identify(Empty {}).await?;
@@ -153,16 +244,76 @@ impl NodeAdapterService {
         })
     }
 
+    pub async fn get_mempool_stats(&self) -> Result<MempoolStats, Error> {
+        let mut client = BaseNodeGrpcClient::connect(self.connection_address.clone()).await?;
+        let res = client.get_mempool_stats(Empty {}).await?.into_inner();
+        let mut fee_stats = client
+            .get_mempool_fee_per_gram_stats(GetMempoolFeePerGramStatsRequest {
+                count: MEMPOOL_FEE_STATS_BLOCKS,
+            })
+            .await?
+            .into_inner()
+            .mempool_fee_per_gram_stats;
+        fee_stats.sort_by_key(|stat| stat.order);
+        Ok(MempoolStats {
+            unconfirmed_txs: res.unconfirmed_txs,
+            unconfirmed_weight: res.unconfirmed_weight,
+            block_fees: fee_stats
+                .into_iter()
+                .map(|stat| BlockFeeStats {
+                    min_fee_per_gram: stat.min_fee_per_gram,
+                    avg_fee_per_gram: stat.avg_fee_per_gram,
+                    max_fee_per_gram: stat.max_fee_per_gram,
+                })
+                .collect(),
+        })
+    }
+
+    /// Target difficulty of the latest block of each algorithm, read from the recent block headers
+    pub async fn get_network_difficulties(&self) -> Result<NetworkDifficulties, Error> {
+        let mut client = BaseNodeGrpcClient::connect(self.connection_address.clone()).await?;
+        let mut res = client
+            .get_network_difficulty(HeightRequest {
+                from_tip: DIFFICULTY_LOOKBACK_BLOCKS,
+                start_height: 0,
+                end_height: 0,
+            })
+            .await?
+            .into_inner();
+
+        let mut sha3x: Option<(u64, u64)> = None;
+        let mut randomx: Option<(u64, u64)> = None;
+        while let Some(header) = res.message().await? {
+            let latest = match header.pow_algo {
+                POW_ALGO_SHA3X => &mut sha3x,
+                POW_ALGO_RANDOMX => &mut randomx,
+                _ => continue,
+            };
+            if latest.map_or(true, |(height, _)| header.height > height) {
+                *latest = Some((header.height, header.difficulty));
+            }
+        }
+        Ok(NetworkDifficulties {
+            sha3x: sha3x.map(|(_, difficulty)| difficulty),
+            randomx: randomx.map(|(_, difficulty)| difficulty),
+        })
+    }
+
+    /// Waits until the node reports it is synced. With `detect_stalls` a sync that stops making progress returns
+    /// `SyncStalled`, which only makes sense for a local node whose peers can be rotated.
     #[allow(clippy::too_many_lines)]
     pub async fn wait_synced(
         &self,
         progress_params_tx: &watch::Sender<HashMap<String, String>>,
         progress_percentage_tx: &watch::Sender<f64>,
         shutdown_signal: ShutdownSignal,
+        detect_stalls: bool,
     ) -> Result<u64, NodeStatusMonitorError> {
         let mut client = BaseNodeGrpcClient::connect(self.connection_address.clone())
             .await
             .map_err(|_e| NodeStatusMonitorError::NodeNotStarted)?;
+        let mut synced_tip_gate = SyncedTipGate::new(ChainTipVerifier::from_current_config().await);
+        let mut sync_rate_tracker = SyncRateTracker::new();
 
         loop {
             if shutdown_signal.is_triggered() {
@@ -253,6 +404,35 @@ impl NodeAdapterService {
                 );
             }
 
+            let sync_phase = if sync_progress.state == SyncState::Header as i32 {
+                Some(SyncPhase::Header)
+            } else if sync_progress.state == SyncState::Block as i32 {
+                Some(SyncPhase::Block)
+            } else {
+                None
+            };
+            if let Some(sync_phase) = sync_phase {
+                let local_block_height = tip_res
+                    .metadata
+                    .as_ref()
+                    .map_or(0, |metadata| metadata.best_block_height);
+                let estimate = sync_rate_tracker.record(
+                    sync_phase,
+                    sync_progress.local_height,
+                    local_block_height,
+                    sync_progress.tip_height,
+                    Instant::now(),
+                );
+                estimate.write_progress_params(&mut progress_params);
+                if detect_stalls && estimate.is_stalled {
+                    progress_params_tx.send(progress_params).ok();
+                    warn!(target: LOG_TARGET, "{:?} sync stalled at height {} of {}", sync_phase, sync_progress.local_height, sync_progress.tip_height);
+                    return Err(NodeStatusMonitorError::SyncStalled(
+                        sync_progress.local_height,
+                    ));
+                }
+            }
+
             progress_percentage_tx.send(percentage).ok();
             progress_params_tx.send(progress_params).ok();
 
@@ -262,12 +442,14 @@ impl NodeAdapterService {
                     .clone()
                     .is_some_and(|metadata| metadata.best_block_height > 0)
             {
-                info!(target: LOG_TARGET, "Initial sync achieved");
                 let tip_height = match tip_res.metadata {
                     Some(metadata) => metadata.best_block_height,
                     None => 0,
                 };
-                return Ok(tip_height);
+                if synced_tip_gate.accept(tip_height).await {
+                    info!(target: LOG_TARGET, "Initial sync achieved");
+                    return Ok(tip_height);
+                }
             }
 
             tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
@@ -308,7 +490,105 @@ impl NodeAdapterService {
         Ok(connected_peers)
     }
 
+    pub async fn list_connected_peers_details(
+        &self,
+        banned_peers: &[BannedPeer],
+        custom_seeds: &[String],
+    ) -> Result<Vec<PeerDetails>, anyhow::Error> {
+        let mut client = BaseNodeGrpcClient::connect(self.connection_address.clone()).await?;
+        let peers_list = client
+            .list_connected_peers(Empty {})
+            .await
+            .map_err(|e| anyhow::anyhow!("Error list_connected_peers: {}", e))?
+            .into_inner()
+            .connected_peers;
+
+        Ok(peers_list
+            .iter()
+            .map(|peer| PeerDetails::from_grpc(peer, banned_peers, custom_seeds))
+            .collect())
+    }
+
+    /// Peers the node is currently syncing blocks from, empty when it is not behind
+    pub async fn get_sync_peers(&self) -> Result<Vec<SyncPeer>, anyhow::Error> {
+        let mut client = BaseNodeGrpcClient::connect(self.connection_address.clone()).await?;
+        let sync_info = client
+            .get_sync_info(Empty {})
+            .await
+            .map_err(|e| anyhow::anyhow!("Error get_sync_info: {}", e))?
+            .into_inner();
+        if sync_info.peer_node_id.is_empty() || sync_info.tip_height <= sync_info.local_height {
+            return Ok(vec![]);
+        }
+        let node_ids = sync_info
+            .peer_node_id
+            .iter()
+            .map(|node_id| node_id.to_hex())
+            .collect::<Vec<String>>();
+        Ok(client
+            .list_connected_peers(Empty {})
+            .await
+            .map_err(|e| anyhow::anyhow!("Error list_connected_peers: {}", e))?
+            .into_inner()
+            .connected_peers
+            .iter()
+            .filter(|peer| node_ids.contains(&peer.node_id.to_hex()))
+            .map(|peer| SyncPeer {
+                public_key: peer.public_key.to_hex(),
+                addresses: peer_addresses(peer),
+                claimed_tip_height: sync_info.tip_height,
+            })
+            .collect())
+    }
+
+    /// Addresses the node knows for a peer, from its connections and its peer database
+    pub async fn find_peer_addresses(
+        &self,
+        public_key: &str,
+    ) -> Result<Vec<String>, anyhow::Error> {
+        let mut client = BaseNodeGrpcClient::connect(self.connection_address.clone()).await?;
+        let mut addresses = client
+            .list_connected_peers(Empty {})
+            .await
+            .map_err(|e| anyhow::anyhow!("Error list_connected_peers: {}", e))?
+            .into_inner()
+            .connected_peers
+            .iter()
+            .filter(|peer| peer.public_key.to_hex() == public_key)
+            .flat_map(peer_addresses)
+            .collect::<Vec<String>>();
+
+        let mut known_peers = client
+            .get_peers(GetPeersRequest {})
+            .await
+            .map_err(|e| anyhow::anyhow!("Error get_peers: {}", e))?
+            .into_inner();
+        while let Some(response) = known_peers
+            .message()
+            .await
+            .map_err(|e| anyhow::anyhow!("Error get_peers: {}", e))?
+        {
+            if let Some(peer) = response
+                .peer
+                .filter(|peer| peer.public_key.to_hex() == public_key)
+            {
+                addresses.extend(peer_addresses(&peer));
+            }
+        }
+        addresses.sort();
+        addresses.dedup();
+        Ok(addresses)
+    }
+
     pub async fn check_if_is_orphan_chain(&self) -> Result<bool, anyhow::Error> {
+        Ok(self.detect_orphan_chain().await?.is_some())
+    }
+
+    /// Compares a few recent local blocks against the quorum verified chain and returns the first height that
+    /// does not match, if any.
+    pub async fn detect_orphan_chain(
+        &self,
+    ) -> Result<Option<OrphanChainDivergence>, anyhow::Error> {
         static REPORT_TO_SENTRY: Once = Once::new();
         let BaseNodeStatus {
             is_synced,
@@ -317,40 +597,50 @@ impl NodeAdapterService {
         } = self.get_network_state().await?;
         if !is_synced {
             info!(target: LOG_TARGET, "Node is not synced, skipping orphan chain check");
-            return Ok(false);
+            return Ok(None);
         }
 
-        let network = Network::get_current_or_user_setting_or_default();
-        let block_scan_tip = get_best_block_from_block_scan(network).await?;
+        let verifier = ChainTipVerifier::from_current_config().await;
+        let reference_tip = verifier.verified_tip().await?;
         let heights: Vec<u64> = vec![
-            block_scan_tip.saturating_sub(50),
-            block_scan_tip.saturating_sub(100),
-            block_scan_tip.saturating_sub(200),
+            reference_tip.saturating_sub(50),
+            reference_tip.saturating_sub(100),
+            reference_tip.saturating_sub(200),
         ];
-        let mut block_scan_blocks: Vec<(u64, String)> = vec![];
+        let mut reference_blocks: Vec<(u64, String)> = vec![];
 
         for height in &heights {
-            let block_scan_block = get_block_info_from_block_scan(network, height).await?;
-            block_scan_blocks.push(block_scan_block);
+            match verifier.verified_block_hash(*height).await {
+                Ok(hash) => reference_blocks.push((*height, hash)),
+                Err(e) => {
+                    warn!(target: LOG_TARGET, "Skipping orphan chain check at height {}: {}", height, e)
+                }
+            }
+        }
+        if reference_blocks.is_empty() {
+            return Err(anyhow!(
+                "Could not verify any reference block for the orphan chain check"
+            ));
         }
 
         let local_blocks = self.get_historical_blocks(heights).await?;
-        for block_scan_block in &block_scan_blocks {
+        // Report the deepest mismatch so the fork point search starts below the divergence
+        for reference_block in reference_blocks.iter().rev() {
             if !local_blocks
                 .iter()
-                .any(|local_block| block_scan_block.1 == local_block.1)
+                .any(|local_block| reference_block.1 == local_block.1)
             {
-                let local_block = local_blocks.iter().find(|b| b.0 == block_scan_block.0);
-                error!(target: LOG_TARGET, "Miner is stuck on orphan chain. Block at height: {} and hash: {} does not exist locally", block_scan_block.0, block_scan_block.1);
+                let local_block = local_blocks.iter().find(|b| b.0 == reference_block.0);
+                error!(target: LOG_TARGET, "Miner is stuck on orphan chain. Block at height: {} and hash: {} does not exist locally", reference_block.0, reference_block.1);
                 if let Some(local_block) = local_block {
                     error!(target: LOG_TARGET, "Local block at height: {} and hash: {}", local_block.0, local_block.1);
                 }
                 REPORT_TO_SENTRY.call_once(|| {
                     let error_msg = "Orphan chain detected".to_string();
                     let mut extra = vec![
-                        ("block_scan_block_height", block_scan_block.0.to_string()),
-                        ("block_scan_block_hash", block_scan_block.1.clone()),
-                        ("block_scan_tip_height", block_scan_tip.to_string()),
+                        ("reference_block_height", reference_block.0.to_string()),
+                        ("reference_block_hash", reference_block.1.clone()),
+                        ("reference_tip_height", reference_tip.to_string()),
                         ("local_tip_height", local_tip.to_string()),
                     ];
 
@@ -370,11 +660,17 @@ impl NodeAdapterService {
                         ..Default::default()
                     });
                 });
-                return Ok(true);
+                return Ok(Some(OrphanChainDivergence {
+                    height: reference_block.0,
+                    reference_hash: reference_block.1.clone(),
+                    local_hash: local_block.map(|b| b.1.clone()),
+                    reference_tip,
+                    local_tip,
+                }));
             }
         }
 
-        Ok(false)
+        Ok(None)
     }
 }
 
@@ -385,6 +681,9 @@ pub(crate) struct NodeStatusMonitor {
     status_broadcast: watch::Sender<BaseNodeStatus>,
     last_block_time: Arc<AtomicU64>,
     base_path: Option<PathBuf>,
+    banned_peers: Arc<RwLock<Vec<BannedPeer>>>,
+    last_banned_peer_check: Arc<AtomicU64>,
+    sync_peers: Arc<RwLock<Vec<SyncPeer>>>,
 }
 
 impl NodeStatusMonitor {
@@ -394,6 +693,8 @@ impl NodeStatusMonitor {
         status_broadcast: watch::Sender<BaseNodeStatus>,
         last_block_time: Arc<AtomicU64>,
         base_path: Option<PathBuf>,
+        banned_peers: Arc<RwLock<Vec<BannedPeer>>>,
+        sync_peers: Arc<RwLock<Vec<SyncPeer>>>,
     ) -> Self {
         Self {
             node_type,
@@ -401,6 +702,92 @@ impl NodeStatusMonitor {
             status_broadcast,
             last_block_time,
             base_path,
+            banned_peers,
+            last_banned_peer_check: Arc::new(AtomicU64::new(0)),
+            sync_peers,
+        }
+    }
+
+    /// Remembers which peers the node synced from, so the ones that served an orphan chain can be banned
+    async fn record_sync_peers(&self) {
+        match self.node_service.get_sync_peers().await {
+            Ok(sync_peers) if !sync_peers.is_empty() => record_sync_peers(
+                &mut self
+                    .sync_peers
+                    .write()
+                    .unwrap_or_else(PoisonError::into_inner),
+                sync_peers,
+            ),
+            Ok(_) => {}
+            Err(e) => warn!(target: LOG_TARGET, "Could not read sync peers: {}", e),
+        }
+    }
+
+    /// Bans only take effect as excluded dial addresses when the node starts, so a banned peer that connects to us
+    /// or was banned before its address was known stays connected until the next restart. Its addresses are added
+    /// to the ban so that restart excludes them.
+    async fn record_banned_peer_addresses(&self) {
+        let banned_peers = self
+            .banned_peers
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter(|peer| peer.is_active())
+            .cloned()
+            .collect::<Vec<BannedPeer>>();
+        if banned_peers.is_empty() {
+            return;
+        }
+        let now = EpochTime::now().as_u64();
+        if now.saturating_sub(self.last_banned_peer_check.load(Ordering::SeqCst))
+            < BANNED_PEER_CHECK_INTERVAL.as_secs()
+        {
+            return;
+        }
+        self.last_banned_peer_check.store(now, Ordering::SeqCst);
+
+        let connected_banned_peers = match self
+            .node_service
+            .list_connected_peers_details(&banned_peers, &[])
+            .await
+        {
+            Ok(peers) => peers
+                .into_iter()
+                .filter(|peer| peer.is_banned)
+                .collect::<Vec<PeerDetails>>(),
+            Err(e) => {
+                warn!(target: LOG_TARGET, "Could not check connected peers against bans: {}", e);
+                return;
+            }
+        };
+        if connected_banned_peers.is_empty() {
+            return;
+        }
+        warn!(target: LOG_TARGET, "{:?} Node is connected to {} banned peers, they are dropped on the next restart", self.node_type, connected_banned_peers.len());
+
+        let updated_bans = {
+            let mut bans = self
+                .banned_peers
+                .write()
+                .unwrap_or_else(PoisonError::into_inner);
+            let previous = bans.clone();
+            for peer in connected_banned_peers {
+                if let Some(ban) = bans
+                    .iter_mut()
+                    .find(|ban| ban.public_key == peer.public_key && ban.is_active())
+                {
+                    ban.add_addresses(peer.addresses);
+                }
+            }
+            (*bans != previous).then(|| bans.clone())
+        };
+        if let Some(updated_bans) = updated_bans {
+            let _unused =
+                ConfigCore::update_field(ConfigCoreContent::set_node_banned_peers, updated_bans)
+                    .await
+                    .inspect_err(|e| {
+                        warn!(target: LOG_TARGET, "Failed to save banned peer addresses: {}", e);
+                    });
         }
     }
 }
@@ -412,6 +799,10 @@ impl StatusMonitor for NodeStatusMonitor {
             Ok(res) => match res {
                 Ok(status) => {
                     let _res = self.status_broadcast.send(status);
+                    self.record_banned_peer_addresses().await;
+                    if self.node_type == NodeType::Local {
+                        self.record_sync_peers().await;
+                    }
                     if status.num_connections == 0 {
                         warn!(
                             "{:?} Node Health Check Warning: No connections | status: {:?}",
@@ -500,12 +891,44 @@ impl StatusMonitor for NodeStatusMonitor {
     }
 }
 
+#[derive(Clone, Debug, Serialize)]
+pub struct OrphanChainDivergence {
+    pub height: u64,
+    pub reference_hash: String,
+    pub local_hash: Option<String>,
+    pub reference_tip: u64,
+    pub local_tip: u64,
+}
+
 #[derive(Clone, Debug, Serialize)]
 pub struct NodeIdentity {
     pub public_key: RistrettoPublicKey,
     pub public_addresses: Vec<String>,
 }
 
+#[derive(Clone, Debug, Default, Serialize, PartialEq)]
+pub struct MempoolStats {
+    pub unconfirmed_txs: u64,
+    /// Total weight of the unconfirmed transactions, in grams
+    pub unconfirmed_weight: u64,
+    /// Fee-per-gram spread of the mempool split into block templates, starting with the next block. Only the last
+    /// entry can be a partly filled block.
+    pub block_fees: Vec<BlockFeeStats>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, PartialEq)]
+pub struct BlockFeeStats {
+    pub min_fee_per_gram: u64,
+    pub avg_fee_per_gram: u64,
+    pub max_fee_per_gram: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NetworkDifficulties {
+    pub sha3x: Option<u64>,
+    pub randomx: Option<u64>,
+}
+
 #[derive(Clone, Copy, Debug, Serialize)]
 pub(crate) struct BaseNodeStatus {
     pub sha_network_hashrate: u64,
@@ -537,4 +960,6 @@ pub enum NodeStatusMonitorError {
     UnknownError(#[from] anyhow::Error),
     #[error("Node not started")]
     NodeNotStarted,
+    #[error("Sync stalled at height {0}")]
+    SyncStalled(u64),
 }
@@ -40,10 +40,15 @@ use tokio_util::task::TaskTracker;
 
 use crate::configs::config_core::ConfigCore;
 use crate::configs::trait_config::ConfigImpl;
+use crate::data_locations::{DataComponent, DataLocations};
 use crate::events_manager::EventsManager;
+use crate::node::block_explorer::{BlockDetails, BlockQuery, KernelLookupResult};
 use crate::node::node_adapter::{
-    NodeAdapter, NodeAdapterService, NodeIdentity, NodeStatusMonitorError,
+    MempoolStats, NetworkDifficulties, NodeAdapter, NodeAdapterService, NodeIdentity,
+    NodeStatusMonitorError, OrphanChainDivergence,
 };
+use crate::node::node_storage::{estimate_node_storage, NodeStorageEstimate};
+use crate::node::peer_management::{PeerDetails, SyncPeer};
 use crate::process_adapter::ProcessAdapter;
 use crate::process_stats_collector::ProcessStatsCollectorBuilder;
 use crate::process_watcher::ProcessWatcher;
@@ -53,6 +58,7 @@ use crate::tasks_tracker::TasksTrackers;
 use crate::{BaseNodeStatus, LocalNodeAdapter, RemoteNodeAdapter};
 
 const LOG_TARGET: &str = "tari::universe::minotari_node_manager";
+const PEER_ROTATION_RESTART_DELAY: Duration = Duration::from_secs(10);
 
 #[derive(Debug, thiserror::Error)]
 pub enum NodeManagerError {
@@ -96,6 +102,15 @@ pub struct NodeManager {
     local_node_watch_rx: watch::Receiver<BaseNodeStatus>,
     remote_node_watch_rx: watch::Receiver<BaseNodeStatus>,
     local_node_db_cleared: Arc<AtomicBool>,
+    // Kept so the local node can be restarted outside of the setup phases
+    local_node_start_paths: Arc<RwLock<Option<LocalNodeStartPaths>>>,
+}
+
+#[derive(Clone)]
+struct LocalNodeStartPaths {
+    base_path: PathBuf,
+    config_path: PathBuf,
+    log_path: PathBuf,
 }
 
 impl NodeManager {
@@ -136,6 +151,7 @@ impl NodeManager {
             local_node_watch_rx,
             remote_node_watch_rx,
             local_node_db_cleared: Arc::new(AtomicBool::new(false)),
+            local_node_start_paths: Arc::new(RwLock::new(None)),
         }
     }
 
@@ -154,6 +170,11 @@ impl NodeManager {
         let task_tracker = TasksTrackers::current().node_phase.get_task_tracker().await;
 
         if self.is_local().await? {
+            *self.local_node_start_paths.write().await = Some(LocalNodeStartPaths {
+                base_path: base_path.clone(),
+                config_path: config_path.clone(),
+                log_path: log_path.clone(),
+            });
             self.configure_adapter(
                 self.local_node_watcher.clone(),
                 self.is_local_current().await?,
@@ -225,8 +246,22 @@ impl NodeManager {
         if let Some(node_watcher) = node_watcher.as_mut() {
             node_watcher.adapter.use_tor(use_tor);
             node_watcher.adapter.set_tor_control_port(tor_control_port);
-            let ab_group = *ConfigCore::content().await.ab_group();
-            node_watcher.adapter.set_ab_group(ab_group);
+            let config_core = ConfigCore::content().await;
+            node_watcher.adapter.set_ab_group(*config_core.ab_group());
+            node_watcher
+                .adapter
+                .set_custom_seed_peers(config_core.node_custom_seed_peers().clone());
+            node_watcher
+                .adapter
+                .set_storage_mode(*config_core.node_storage_mode());
+            node_watcher.adapter.set_banned_peers(
+                config_core
+                    .node_banned_peers()
+                    .iter()
+                    .filter(|peer| peer.is_active())
+                    .cloned()
+                    .collect(),
+            );
 
             if let Some(remote_grpc_address) = remote_grpc_address {
                 node_watcher.adapter.set_grpc_address(remote_grpc_address)?;
@@ -296,11 +331,14 @@ impl NodeManager {
         static MAX_FAILED_REQUESTS: u32 = 10;
         loop {
             let current_service = self.get_current_service().await?;
+            // A remote node's peers are not ours to rotate
+            let detect_stalls = self.is_local_current().await?;
             match current_service
                 .wait_synced(
                     progress_params_tx,
                     progress_percentage_tx,
                     shutdown_signal.clone(),
+                    detect_stalls,
                 )
                 .await
             {
@@ -311,6 +349,14 @@ impl NodeManager {
                     NodeStatusMonitorError::NodeNotStarted => {
                         continue;
                     }
+                    NodeStatusMonitorError::SyncStalled(_) => {
+                        if let Err(e) = self.rotate_sync_peers().await {
+                            warn!(target: LOG_TARGET, "Failed to rotate sync peers: {}", e);
+                        }
+                        // Give the restarted node time to open its gRPC port
+                        sleep(PEER_ROTATION_RESTART_DELAY).await;
+                        continue;
+                    }
                     _ => {
                         failed_request_counter += 1;
                         if failed_request_counter >= MAX_FAILED_REQUESTS {
@@ -336,17 +382,34 @@ impl NodeManager {
     }
 
     pub async fn clean_data_folder(&self, base_path: &Path) -> Result<(), anyhow::Error> {
-        fs::remove_dir_all(
-            base_path
-                .join("node")
-                .join(Network::get_current().to_string().to_lowercase()),
-        )
-        .await?;
+        let base_path = DataLocations::current().resolve(DataComponent::Node, base_path);
+        fs::remove_dir_all(Self::get_local_node_data_dir(&base_path)).await?;
         self.local_node_db_cleared
             .store(true, std::sync::atomic::Ordering::SeqCst);
         Ok(())
     }
 
+    pub fn get_local_node_data_dir(base_path: &Path) -> PathBuf {
+        base_path
+            .join("node")
+            .join(Network::get_current().to_string().to_lowercase())
+    }
+
+    pub async fn estimate_storage(
+        &self,
+        base_path: &Path,
+    ) -> Result<NodeStorageEstimate, anyhow::Error> {
+        let storage_mode = *ConfigCore::content().await.node_storage_mode();
+        let chain_height = self.base_node_watch_tx.borrow().block_height;
+        let base_path = DataLocations::current().resolve(DataComponent::Node, base_path);
+        let node_data_dir = Self::get_local_node_data_dir(&base_path);
+        tokio::task::spawn_blocking(move || {
+            estimate_node_storage(&node_data_dir, storage_mode, chain_height)
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to estimate node storage: {}", e))
+    }
+
     pub async fn get_node_type(&self) -> Result<NodeType, anyhow::Error> {
         let node_type = self.node_type.read().await;
         Ok(node_type.clone())
@@ -366,6 +429,16 @@ impl NodeManager {
         })
     }
 
+    pub async fn get_mempool_stats(&self) -> Result<MempoolStats, anyhow::Error> {
+        let current_service = self.get_current_service().await?;
+        current_service.get_mempool_stats().await
+    }
+
+    pub async fn get_network_difficulties(&self) -> Result<NetworkDifficulties, anyhow::Error> {
+        let current_service = self.get_current_service().await?;
+        current_service.get_network_difficulties().await
+    }
+
     pub async fn get_connection_details(
         &self,
     ) -> Result<(RistrettoPublicKey, String), anyhow::Error> {
@@ -392,11 +465,151 @@ impl NodeManager {
         current_service.check_if_is_orphan_chain().await
     }
 
+    pub async fn detect_orphan_chain(
+        &self,
+    ) -> Result<Option<OrphanChainDivergence>, anyhow::Error> {
+        let current_service = self.get_current_service().await?;
+        current_service.detect_orphan_chain().await
+    }
+
+    pub async fn get_historical_blocks(
+        &self,
+        heights: Vec<u64>,
+    ) -> Result<Vec<(u64, String)>, anyhow::Error> {
+        let current_service = self.get_current_service().await?;
+        current_service.get_historical_blocks(heights).await
+    }
+
+    pub async fn get_block_details(
+        &self,
+        query: BlockQuery,
+    ) -> Result<Option<BlockDetails>, anyhow::Error> {
+        let current_service = self.get_current_service().await?;
+        current_service.get_block_details(query).await
+    }
+
+    pub async fn find_kernel(
+        &self,
+        public_nonce: &str,
+        signature: &str,
+    ) -> Result<Option<KernelLookupResult>, anyhow::Error> {
+        let current_service = self.get_current_service().await?;
+        current_service.find_kernel(public_nonce, signature).await
+    }
+
     pub async fn list_connected_peers(&self) -> Result<Vec<String>, anyhow::Error> {
         let current_service = self.get_current_service().await?;
         current_service.list_connected_peers().await
     }
 
+    pub async fn list_connected_peers_details(&self) -> Result<Vec<PeerDetails>, anyhow::Error> {
+        let current_service = self.get_current_service().await?;
+        let config_core = ConfigCore::content().await;
+        current_service
+            .list_connected_peers_details(
+                config_core.node_banned_peers(),
+                config_core.node_custom_seed_peers(),
+            )
+            .await
+    }
+
+    pub async fn find_peer_addresses(
+        &self,
+        public_key: &str,
+    ) -> Result<Vec<String>, anyhow::Error> {
+        let current_service = self.get_current_service().await?;
+        current_service.find_peer_addresses(public_key).await
+    }
+
+    /// Clears the local node peer database on its next start. The caller is responsible for restarting the node phase.
+    pub async fn request_peer_db_reset(&self) -> Result<(), anyhow::Error> {
+        if !self.is_local().await? {
+            return Err(anyhow::anyhow!(
+                "Peer reset is only available when running a local node"
+            ));
+        }
+        let local_node_watcher = self.local_node_watcher.read().await;
+        if let Some(local_node_watcher) = local_node_watcher.as_ref() {
+            local_node_watcher.adapter.request_peer_db_reset();
+        }
+        Ok(())
+    }
+
+    /// Restarts the local node with a fresh peer database after its sync stalled. Remote nodes are left alone.
+    /// The process is restarted here rather than through a failing health check, which the process watcher ignores
+    /// while the node is still starting up.
+    pub async fn rotate_sync_peers(&self) -> Result<(), anyhow::Error> {
+        if !self.is_local().await? {
+            return Ok(());
+        }
+        let Some(start_paths) = self.local_node_start_paths.read().await.clone() else {
+            return Ok(());
+        };
+        info!(target: LOG_TARGET, "Sync stalled, restarting local node with a fresh peer db");
+        {
+            let mut local_node_watcher = self.local_node_watcher.write().await;
+            if let Some(local_node_watcher) = local_node_watcher.as_mut() {
+                local_node_watcher.adapter.request_peer_db_reset();
+                local_node_watcher.stop().await?;
+            }
+        }
+        start_watcher(
+            &self.local_node_watcher,
+            start_paths.base_path,
+            start_paths.config_path,
+            start_paths.log_path,
+            TasksTrackers::current().node_phase.get_signal().await,
+            TasksTrackers::current().node_phase.get_task_tracker().await,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Clears the local node blockchain database on its next start. The caller is responsible for restarting the node phase.
+    pub async fn request_chain_db_reset(&self) -> Result<(), anyhow::Error> {
+        if !self.is_local_current().await? {
+            return Err(anyhow::anyhow!(
+                "Chain reset is only available when running a local node"
+            ));
+        }
+        let local_node_watcher = self.local_node_watcher.read().await;
+        if let Some(local_node_watcher) = local_node_watcher.as_ref() {
+            local_node_watcher.adapter.request_chain_db_reset();
+        }
+        Ok(())
+    }
+
+    /// Rewinds the local node blockchain to `height` on its next start. The caller is responsible for restarting the node
+    /// phase and for clearing the rewind once the tip reached `height`.
+    pub async fn request_chain_rewind(&self, height: u64) -> Result<(), anyhow::Error> {
+        if !self.is_local_current().await? {
+            return Err(anyhow::anyhow!(
+                "Chain rewind is only available when running a local node"
+            ));
+        }
+        let local_node_watcher = self.local_node_watcher.read().await;
+        if let Some(local_node_watcher) = local_node_watcher.as_ref() {
+            local_node_watcher.adapter.request_chain_rewind(height);
+        }
+        Ok(())
+    }
+
+    pub async fn clear_chain_rewind(&self) {
+        let local_node_watcher = self.local_node_watcher.read().await;
+        if let Some(local_node_watcher) = local_node_watcher.as_ref() {
+            local_node_watcher.adapter.clear_chain_rewind();
+        }
+    }
+
+    /// Takes the peers the local node synced from since the last call
+    pub async fn take_sync_peers(&self) -> Vec<SyncPeer> {
+        let local_node_watcher = self.local_node_watcher.read().await;
+        local_node_watcher
+            .as_ref()
+            .map(|watcher| watcher.adapter.take_sync_peers())
+            .unwrap_or_default()
+    }
+
     // Self Checks
     pub async fn is_local(&self) -> Result<bool, anyhow::Error> {
         let node_type = self.get_node_type().await?;
@@ -687,7 +900,7 @@ async fn monitor_local_node_sync_and_switch(
                     local_node_watcher.as_ref().and_then(|watcher| watcher.adapter.get_service())
                 } {
                     match local_node_service
-                        .wait_synced(&progress_params_tx, &progress_percentage_tx, shutdown_signal.clone())
+                        .wait_synced(&progress_params_tx, &progress_percentage_tx, shutdown_signal.clone(), true)
                         .await
                     {
                         Ok(synced_height) => {
@@ -705,6 +918,12 @@ async fn monitor_local_node_sync_and_switch(
                         Err(NodeStatusMonitorError::NodeNotStarted) => {
                             info!(target: LOG_TARGET, "Local node not started, waiting...");
                         }
+                        Err(NodeStatusMonitorError::SyncStalled(_)) => {
+                            if let Err(e) = node_manager.rotate_sync_peers().await {
+                                warn!(target: LOG_TARGET, "Failed to rotate sync peers: {}", e);
+                            }
+                            sleep(PEER_ROTATION_RESTART_DELAY).await;
+                        }
                         Err(e) => {
                             error!(target: LOG_TARGET, "NodeManagerError: {}", NodeManagerError::UnknownError(e.into()));
                         }
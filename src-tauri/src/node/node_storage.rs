@@ -0,0 +1,254 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::file_utils::{get_available_disk_space, get_dir_size};
+
+pub const MIN_PRUNING_HORIZON: u64 = 100;
+pub const DEFAULT_PRUNING_HORIZON: u64 = 1000;
+
+// Rough averages observed on mainnet, used when there is no local database to calibrate against
+const ARCHIVAL_BYTES_PER_BLOCK: u64 = 48 * 1024;
+// Headers and kernels are kept for every block even when pruned
+const PRUNED_BYTES_PER_BLOCK: u64 = 3 * 1024;
+const PRUNED_UTXO_SET_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+// LMDB grows in steps and keeps free pages around, so leave headroom on top of the estimate
+const DISK_HEADROOM_PERCENT: u64 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum NodeStorageMode {
+    #[default]
+    Archival,
+    Pruned {
+        horizon: u64,
+    },
+}
+
+impl NodeStorageMode {
+    /// Value for `base_node.storage.pruning_horizon`, where 0 means archival
+    pub fn pruning_horizon(&self) -> u64 {
+        match self {
+            NodeStorageMode::Archival => 0,
+            NodeStorageMode::Pruned { horizon } => *horizon,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            NodeStorageMode::Pruned { horizon } if *horizon < MIN_PRUNING_HORIZON => Err(format!(
+                "Pruning horizon must be at least {} blocks",
+                MIN_PRUNING_HORIZON
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether the existing chain database can be kept when switching from `self` to `target`.
+    /// The node prunes an archival database or shrinks a pruned horizon in place on its next start, but going back
+    /// to archival or to a longer horizon needs the pruned blocks again and therefore a resync.
+    pub fn can_migrate_in_place(&self, target: &NodeStorageMode) -> bool {
+        match (self, target) {
+            (NodeStorageMode::Archival, _) => true,
+            (
+                NodeStorageMode::Pruned { horizon: current },
+                NodeStorageMode::Pruned { horizon: target },
+            ) => target <= current,
+            (NodeStorageMode::Pruned { .. }, NodeStorageMode::Archival) => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeStorageEstimate {
+    pub current_mode: NodeStorageMode,
+    pub chain_height: u64,
+    pub current_usage_bytes: u64,
+    pub available_space_bytes: Option<u64>,
+    pub archival_estimate_bytes: u64,
+    pub pruned_estimate_bytes: u64,
+    pub pruned_horizon: u64,
+}
+
+impl NodeStorageEstimate {
+    pub fn estimate_for(&self, mode: &NodeStorageMode) -> u64 {
+        match mode {
+            NodeStorageMode::Archival => self.archival_estimate_bytes,
+            NodeStorageMode::Pruned { horizon } => {
+                estimate_pruned_size(self.chain_height, *horizon, self.archival_bytes_per_block())
+            }
+        }
+    }
+
+    fn archival_bytes_per_block(&self) -> u64 {
+        self.archival_estimate_bytes
+            .checked_div(self.chain_height)
+            .unwrap_or(ARCHIVAL_BYTES_PER_BLOCK)
+    }
+
+    /// Checks that the target mode fits on the disk, counting the space freed by dropping the current database
+    /// when the migration requires a resync. Migrations done in place only ever shrink the database.
+    pub fn ensure_fits(&self, target: &NodeStorageMode) -> Result<(), String> {
+        let Some(available_space) = self.available_space_bytes else {
+            return Ok(());
+        };
+        if self.current_mode.can_migrate_in_place(target) {
+            return Ok(());
+        }
+        let required = self.estimate_for(target) * (100 + DISK_HEADROOM_PERCENT) / 100;
+        let usable = available_space.saturating_add(self.current_usage_bytes);
+        if required > usable {
+            return Err(format!(
+                "Not enough disk space: {} MB required, {} MB available",
+                required / 1_000_000,
+                usable / 1_000_000
+            ));
+        }
+        Ok(())
+    }
+}
+
+pub fn estimate_node_storage(
+    node_data_dir: &Path,
+    current_mode: NodeStorageMode,
+    chain_height: u64,
+) -> NodeStorageEstimate {
+    let current_usage_bytes = get_dir_size(node_data_dir);
+    let available_space_bytes = get_available_disk_space(node_data_dir);
+
+    // Calibrate against the local database once it holds a meaningful part of the chain
+    let archival_bytes_per_block = match current_mode {
+        NodeStorageMode::Archival if chain_height > 1000 && current_usage_bytes > 0 => {
+            current_usage_bytes / chain_height
+        }
+        _ => ARCHIVAL_BYTES_PER_BLOCK,
+    };
+    let pruned_horizon = match current_mode {
+        NodeStorageMode::Pruned { horizon } => horizon,
+        NodeStorageMode::Archival => DEFAULT_PRUNING_HORIZON,
+    };
+
+    NodeStorageEstimate {
+        current_mode,
+        chain_height,
+        current_usage_bytes,
+        available_space_bytes,
+        archival_estimate_bytes: chain_height * archival_bytes_per_block,
+        pruned_estimate_bytes: estimate_pruned_size(
+            chain_height,
+            pruned_horizon,
+            archival_bytes_per_block,
+        ),
+        pruned_horizon,
+    }
+}
+
+fn estimate_pruned_size(chain_height: u64, horizon: u64, archival_bytes_per_block: u64) -> u64 {
+    let full_blocks = horizon.min(chain_height);
+    PRUNED_UTXO_SET_BYTES
+        + chain_height * PRUNED_BYTES_PER_BLOCK
+        + full_blocks * archival_bytes_per_block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIB: u64 = 1024 * 1024 * 1024;
+
+    fn estimate(
+        current_mode: NodeStorageMode,
+        available_space_bytes: Option<u64>,
+    ) -> NodeStorageEstimate {
+        NodeStorageEstimate {
+            current_mode,
+            chain_height: 100_000,
+            current_usage_bytes: 5 * GIB,
+            available_space_bytes,
+            archival_estimate_bytes: 100_000 * ARCHIVAL_BYTES_PER_BLOCK,
+            pruned_estimate_bytes: estimate_pruned_size(
+                100_000,
+                DEFAULT_PRUNING_HORIZON,
+                ARCHIVAL_BYTES_PER_BLOCK,
+            ),
+            pruned_horizon: DEFAULT_PRUNING_HORIZON,
+        }
+    }
+
+    #[test]
+    fn test_can_migrate_in_place() {
+        let archival = NodeStorageMode::Archival;
+        let short = NodeStorageMode::Pruned { horizon: 500 };
+        let long = NodeStorageMode::Pruned { horizon: 5000 };
+
+        assert!(archival.can_migrate_in_place(&archival));
+        assert!(archival.can_migrate_in_place(&short));
+        assert!(long.can_migrate_in_place(&short));
+        assert!(short.can_migrate_in_place(&short));
+        assert!(!short.can_migrate_in_place(&long));
+        assert!(!short.can_migrate_in_place(&archival));
+    }
+
+    #[test]
+    fn test_pruned_estimate_includes_utxo_set() {
+        assert_eq!(PRUNED_UTXO_SET_BYTES, 2 * GIB);
+        assert_eq!(
+            estimate_pruned_size(0, DEFAULT_PRUNING_HORIZON, 1024),
+            2 * GIB
+        );
+        assert_eq!(
+            estimate_pruned_size(10, DEFAULT_PRUNING_HORIZON, 1024),
+            2 * GIB + 10 * PRUNED_BYTES_PER_BLOCK + 10 * 1024
+        );
+    }
+
+    #[test]
+    fn test_ensure_fits_in_place_needs_no_space() {
+        let estimate = estimate(NodeStorageMode::Archival, Some(0));
+        assert!(estimate
+            .ensure_fits(&NodeStorageMode::Pruned { horizon: 1000 })
+            .is_ok());
+    }
+
+    #[test]
+    fn test_ensure_fits_counts_freed_database() {
+        let target = NodeStorageMode::Archival;
+        let mode = NodeStorageMode::Pruned { horizon: 1000 };
+        let required =
+            estimate(mode, None).estimate_for(&target) * (100 + DISK_HEADROOM_PERCENT) / 100;
+
+        let fits = estimate(mode, Some(required - 5 * GIB));
+        assert!(fits.ensure_fits(&target).is_ok());
+
+        let too_small = estimate(mode, Some(required - 5 * GIB - 1));
+        assert!(too_small.ensure_fits(&target).is_err());
+    }
+
+    #[test]
+    fn test_ensure_fits_without_disk_info() {
+        let estimate = estimate(NodeStorageMode::Pruned { horizon: 1000 }, None);
+        assert!(estimate.ensure_fits(&NodeStorageMode::Archival).is_ok());
+    }
+}
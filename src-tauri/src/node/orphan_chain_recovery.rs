@@ -0,0 +1,388 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use log::{debug, info, warn};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tokio::time::sleep;
+
+use crate::configs::config_core::{ConfigCore, ConfigCoreContent};
+use crate::configs::trait_config::ConfigImpl;
+use crate::node::chain_tip_verifier::ChainTipVerifier;
+use crate::node::node_manager::NodeManager;
+use crate::node::peer_management::{peers_past_fork_point, BannedPeer};
+use crate::progress_trackers::progress_plans::{ProgressOrphanChainRecoveryPlan, ProgressPlans};
+use crate::progress_trackers::progress_stepper::ProgressStepperBuilder;
+use crate::setup::setup_manager::{SetupManager, SetupPhase};
+use crate::UniverseAppState;
+
+const LOG_TARGET: &str = "tari::universe::orphan_chain_recovery";
+pub const MAX_AUTOMATIC_RECOVERY_ATTEMPTS: u32 = 3;
+const REWIND_TIMEOUT: Duration = Duration::from_secs(60 * 5);
+const REWIND_POLL_INTERVAL: Duration = Duration::from_secs(2);
+// Long enough for the node to resync on the main chain, short enough that an honest peer is not shut out for good
+const ORPHAN_CHAIN_BAN_DURATION: Duration = Duration::from_secs(60 * 60 * 24);
+
+static RECOVERY_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RecoveryTrigger {
+    Automatic,
+    Manual,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OrphanChainRecoveryReport {
+    pub diverged_at_height: u64,
+    pub fork_point: u64,
+    pub rewound: bool,
+    pub banned_peers: Vec<String>,
+    pub attempt: u32,
+}
+
+/// Detects whether the local node is on an orphan chain and, if so, finds the fork point and rewinds the node to it.
+/// Falls back to a fresh chain database when the rewind does not complete.
+/// Returns `Ok(None)` when the node turned out to be on the right chain.
+pub async fn recover_from_orphan_chain(
+    app_handle: &AppHandle,
+    trigger: RecoveryTrigger,
+) -> Result<Option<OrphanChainRecoveryReport>, anyhow::Error> {
+    if RECOVERY_IN_PROGRESS.swap(true, Ordering::SeqCst) {
+        return Err(anyhow!("Orphan chain recovery is already in progress"));
+    }
+    let result = recover_from_orphan_chain_inner(app_handle, trigger).await;
+    RECOVERY_IN_PROGRESS.store(false, Ordering::SeqCst);
+    result
+}
+
+async fn recover_from_orphan_chain_inner(
+    app_handle: &AppHandle,
+    trigger: RecoveryTrigger,
+) -> Result<Option<OrphanChainRecoveryReport>, anyhow::Error> {
+    let state = app_handle.state::<UniverseAppState>();
+    let node_manager = state.node_manager.clone();
+    if !node_manager.is_local_current().await? {
+        return Err(anyhow!(
+            "Orphan chain recovery is only available when running a local node"
+        ));
+    }
+
+    let attempt = *ConfigCore::content().await.orphan_chain_recovery_attempts() + 1;
+    if trigger == RecoveryTrigger::Automatic && attempt > MAX_AUTOMATIC_RECOVERY_ATTEMPTS {
+        return Err(anyhow!(
+            "Maximum number of automatic orphan chain recovery attempts ({}) reached",
+            MAX_AUTOMATIC_RECOVERY_ATTEMPTS
+        ));
+    }
+
+    let mut progress_stepper = ProgressStepperBuilder::new()
+        .add_step(ProgressPlans::OrphanChainRecovery(
+            ProgressOrphanChainRecoveryPlan::CompareWithReference,
+        ))
+        .add_step(ProgressPlans::OrphanChainRecovery(
+            ProgressOrphanChainRecoveryPlan::FindForkPoint,
+        ))
+        .add_step(ProgressPlans::OrphanChainRecovery(
+            ProgressOrphanChainRecoveryPlan::BanForkPeers,
+        ))
+        .add_step(ProgressPlans::OrphanChainRecovery(
+            ProgressOrphanChainRecoveryPlan::RewindChain,
+        ))
+        .add_step(ProgressPlans::OrphanChainRecovery(
+            ProgressOrphanChainRecoveryPlan::Done,
+        ))
+        .build(app_handle.clone());
+
+    progress_stepper
+        .resolve_step(ProgressPlans::OrphanChainRecovery(
+            ProgressOrphanChainRecoveryPlan::CompareWithReference,
+        ))
+        .await;
+    let divergence = match node_manager.detect_orphan_chain().await? {
+        Some(divergence) => divergence,
+        None => {
+            info!(target: LOG_TARGET, "Local node is on the reference chain, nothing to recover");
+            ConfigCore::update_field(ConfigCoreContent::set_orphan_chain_recovery_attempts, 0)
+                .await?;
+            progress_stepper
+                .resolve_step(ProgressPlans::OrphanChainRecovery(
+                    ProgressOrphanChainRecoveryPlan::Done,
+                ))
+                .await;
+            return Ok(None);
+        }
+    };
+    ConfigCore::update_field(
+        ConfigCoreContent::set_orphan_chain_recovery_attempts,
+        attempt,
+    )
+    .await?;
+    info!(target: LOG_TARGET, "Starting orphan chain recovery attempt {} ({:?}), diverged at height {}", attempt, trigger, divergence.height);
+
+    progress_stepper
+        .resolve_step(ProgressPlans::OrphanChainRecovery(
+            ProgressOrphanChainRecoveryPlan::FindForkPoint,
+        ))
+        .await;
+    let verifier = Arc::new(ChainTipVerifier::from_current_config().await);
+    let fork_point = find_fork_point(
+        divergence.height,
+        |height| {
+            let node_manager = node_manager.clone();
+            async move {
+                let blocks = node_manager.get_historical_blocks(vec![height]).await?;
+                Ok(blocks.into_iter().next().map(|(_, hash)| hash))
+            }
+        },
+        |height| {
+            let verifier = verifier.clone();
+            async move { verifier.verified_block_hash(height).await }
+        },
+    )
+    .await?;
+    info!(target: LOG_TARGET, "Fork point found at height {}", fork_point);
+
+    progress_stepper
+        .resolve_step(ProgressPlans::OrphanChainRecovery(
+            ProgressOrphanChainRecoveryPlan::BanForkPeers,
+        ))
+        .await;
+    let banned_peers = ban_fork_peers(&node_manager, fork_point).await?;
+    info!(target: LOG_TARGET, "Banned {} peers that served the orphan chain", banned_peers.len());
+
+    progress_stepper
+        .resolve_step(ProgressPlans::OrphanChainRecovery(
+            ProgressOrphanChainRecoveryPlan::RewindChain,
+        ))
+        .await;
+    let rewound = if fork_point > 0 {
+        node_manager.request_chain_rewind(fork_point).await?;
+        restart_node_phases(app_handle).await;
+        let rewound = wait_for_rewind(&node_manager, fork_point).await;
+        node_manager.clear_chain_rewind().await;
+        rewound
+    } else {
+        false
+    };
+    if rewound {
+        info!(target: LOG_TARGET, "Blockchain rewound to height {}", fork_point);
+    } else {
+        warn!(target: LOG_TARGET, "Could not rewind blockchain to height {}, resyncing from scratch", fork_point);
+        node_manager.request_chain_db_reset().await?;
+    }
+    // Restart once more so the node runs without the rewind command, or with the fresh chain database
+    restart_node_phases(app_handle).await;
+
+    progress_stepper
+        .resolve_step(ProgressPlans::OrphanChainRecovery(
+            ProgressOrphanChainRecoveryPlan::Done,
+        ))
+        .await;
+
+    Ok(Some(OrphanChainRecoveryReport {
+        diverged_at_height: divergence.height,
+        fork_point,
+        rewound,
+        banned_peers,
+        attempt,
+    }))
+}
+
+/// Bans, for `ORPHAN_CHAIN_BAN_DURATION`, the peers the node synced from that claimed a chain above the fork point.
+/// The bans take effect with the restart that performs the rewind.
+async fn ban_fork_peers(
+    node_manager: &NodeManager,
+    fork_point: u64,
+) -> Result<Vec<String>, anyhow::Error> {
+    let sync_peers = node_manager.take_sync_peers().await;
+    let config_core = ConfigCore::content().await;
+    let custom_seed_peers = config_core.node_custom_seed_peers().clone();
+    let mut banned_peers = config_core.node_banned_peers().clone();
+    banned_peers.retain(BannedPeer::is_active);
+
+    let mut newly_banned = vec![];
+    for peer in peers_past_fork_point(&sync_peers, fork_point) {
+        let is_custom_seed = custom_seed_peers
+            .iter()
+            .any(|seed| seed.starts_with(&format!("{}::", peer.public_key)));
+        let is_banned = banned_peers
+            .iter()
+            .any(|banned| banned.public_key == peer.public_key);
+        if is_custom_seed || is_banned {
+            continue;
+        }
+        let mut banned_peer = BannedPeer::new(
+            peer.public_key.clone(),
+            None,
+            format!(
+                "Served an orphan chain with tip {} above fork point {}",
+                peer.claimed_tip_height, fork_point
+            ),
+            Some(ORPHAN_CHAIN_BAN_DURATION),
+        );
+        banned_peer.add_addresses(peer.addresses);
+        banned_peers.push(banned_peer);
+        newly_banned.push(peer.public_key);
+    }
+
+    if !newly_banned.is_empty() {
+        ConfigCore::update_field(ConfigCoreContent::set_node_banned_peers, banned_peers).await?;
+    }
+    Ok(newly_banned)
+}
+
+async fn restart_node_phases(app_handle: &AppHandle) {
+    let setup_manager = SetupManager::get_instance();
+    setup_manager
+        .add_phases_to_restart_queue(vec![
+            SetupPhase::Node,
+            SetupPhase::Wallet,
+            SetupPhase::Unknown,
+        ])
+        .await;
+    setup_manager
+        .restart_phases_from_queue(app_handle.clone())
+        .await;
+}
+
+/// Waits until the restarted node reports a tip at or below `fork_point`
+async fn wait_for_rewind(node_manager: &NodeManager, fork_point: u64) -> bool {
+    let deadline = Instant::now() + REWIND_TIMEOUT;
+    while Instant::now() < deadline {
+        sleep(REWIND_POLL_INTERVAL).await;
+        let service = match node_manager.get_current_service().await {
+            Ok(service) => service,
+            Err(_) => continue,
+        };
+        match service.get_network_state().await {
+            Ok(status) if status.block_height > 0 && status.block_height <= fork_point => {
+                return true;
+            }
+            Ok(status) => {
+                debug!(target: LOG_TARGET, "Waiting for rewind, tip at height {}", status.block_height);
+            }
+            Err(e) => {
+                debug!(target: LOG_TARGET, "Waiting for rewind, node not ready: {}", e);
+            }
+        }
+    }
+    false
+}
+
+/// Returns the highest height at which the local chain and the reference chain still agree.
+/// Walks back exponentially from `mismatch_height` until a matching block is found and then bisects.
+pub(crate) async fn find_fork_point<L, LF, R, RF>(
+    mismatch_height: u64,
+    local_hash: L,
+    reference_hash: R,
+) -> Result<u64, anyhow::Error>
+where
+    L: Fn(u64) -> LF,
+    LF: Future<Output = Result<Option<String>, anyhow::Error>>,
+    R: Fn(u64) -> RF,
+    RF: Future<Output = Result<String, anyhow::Error>>,
+{
+    let matches = |height: u64| {
+        let local = local_hash(height);
+        let reference = reference_hash(height);
+        async move {
+            let local = local.await?;
+            let reference = reference.await?;
+            Ok::<bool, anyhow::Error>(local.is_some_and(|local| local == reference))
+        }
+    };
+
+    let mut bad = mismatch_height;
+    let mut step = 1u64;
+    let mut good = loop {
+        let candidate = bad.saturating_sub(step);
+        if candidate == 0 || matches(candidate).await? {
+            break candidate;
+        }
+        bad = candidate;
+        step = step.saturating_mul(2);
+    };
+
+    while bad - good > 1 {
+        let middle = good + (bad - good) / 2;
+        if matches(middle).await? {
+            good = middle;
+        } else {
+            bad = middle;
+        }
+    }
+
+    Ok(good)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn run(mismatch_height: u64, fork_point: u64) -> u64 {
+        find_fork_point(
+            mismatch_height,
+            |height| async move {
+                if height <= fork_point {
+                    Ok(Some(format!("main-{}", height)))
+                } else {
+                    Ok(Some(format!("orphan-{}", height)))
+                }
+            },
+            |height| async move { Ok(format!("main-{}", height)) },
+        )
+        .await
+        .expect("fork point search failed")
+    }
+
+    #[tokio::test]
+    async fn test_find_fork_point() {
+        assert_eq!(run(1000, 999).await, 999);
+        assert_eq!(run(1000, 873).await, 873);
+        assert_eq!(run(1000, 1).await, 1);
+        assert_eq!(run(1000, 0).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_find_fork_point_missing_local_block() {
+        let fork_point = find_fork_point(
+            100,
+            |height| async move {
+                if height <= 40 {
+                    Ok(Some(height.to_string()))
+                } else {
+                    Ok(None)
+                }
+            },
+            |height| async move { Ok(height.to_string()) },
+        )
+        .await
+        .expect("fork point search failed");
+        assert_eq!(fork_point, 40);
+    }
+}
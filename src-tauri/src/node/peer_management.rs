@@ -0,0 +1,428 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fmt::Write as _;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::time::{Duration, SystemTime};
+
+use minotari_node_grpc_client::grpc::Peer;
+use serde::{Deserialize, Serialize};
+use tari_crypto::ristretto::RistrettoPublicKey;
+use tari_utilities::hex::Hex;
+
+// Multiaddr protocol codes we are able to render. Anything else (onion, dns, ...) is shown as hex.
+// Multicodec protocol codes, encoded as unsigned varints in the binary multiaddr
+const MULTIADDR_IP4: u64 = 0x04;
+const MULTIADDR_TCP: u64 = 0x06;
+const MULTIADDR_UDP: u64 = 0x0111;
+const MULTIADDR_IP6: u64 = 0x29;
+
+/// Connection details of a single peer as reported by the base node `list_connected_peers` call.
+/// The node does not expose per-peer chain height or connection direction over gRPC.
+#[derive(Debug, Clone, Serialize)]
+pub struct PeerDetails {
+    pub public_key: String,
+    pub node_id: String,
+    pub address: String,
+    /// Every address the node knows for this peer, `address` is the first of them
+    pub addresses: Vec<String>,
+    pub user_agent: String,
+    pub avg_latency_ms: Option<u64>,
+    pub last_seen: String,
+    pub connection_attempts: u32,
+    pub is_banned: bool,
+    pub is_custom_seed: bool,
+}
+
+impl PeerDetails {
+    pub fn from_grpc(peer: &Peer, banned_peers: &[BannedPeer], custom_seeds: &[String]) -> Self {
+        let public_key = peer.public_key.to_hex();
+        let address = peer.addresses.first();
+        let is_banned = banned_peers
+            .iter()
+            .any(|banned| banned.public_key == public_key && banned.is_active());
+        let is_custom_seed = custom_seeds
+            .iter()
+            .any(|seed| seed.starts_with(&format!("{}::", public_key)));
+
+        Self {
+            node_id: peer.node_id.to_hex(),
+            address: address
+                .map(|address| decode_multiaddr(&address.address))
+                .unwrap_or_default(),
+            addresses: peer_addresses(peer),
+            user_agent: peer.user_agent.clone(),
+            avg_latency_ms: address
+                .and_then(|address| address.avg_latency.as_ref())
+                .map(|latency| latency.latency),
+            last_seen: address
+                .map(|address| address.last_seen.clone())
+                .unwrap_or_default(),
+            connection_attempts: address
+                .map(|address| address.connection_attempts)
+                .unwrap_or_default(),
+            public_key,
+            is_banned,
+            is_custom_seed,
+        }
+    }
+}
+
+/// All addresses of a peer in their textual form
+pub fn peer_addresses(peer: &Peer) -> Vec<String> {
+    peer.addresses
+        .iter()
+        .map(|address| decode_multiaddr(&address.address))
+        .collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BannedPeer {
+    pub public_key: String,
+    pub address: Option<String>,
+    /// Addresses the node reported for the peer, learned when banning by public key or when the peer connected
+    /// despite the ban
+    #[serde(default)]
+    pub addresses: Vec<String>,
+    pub reason: String,
+    pub banned_at: SystemTime,
+    /// `None` means the ban never expires
+    pub banned_until: Option<SystemTime>,
+}
+
+impl BannedPeer {
+    pub fn new(
+        public_key: String,
+        address: Option<String>,
+        reason: String,
+        duration: Option<Duration>,
+    ) -> Self {
+        let banned_at = SystemTime::now();
+        Self {
+            public_key,
+            address,
+            addresses: vec![],
+            reason,
+            banned_at,
+            banned_until: duration.map(|duration| banned_at + duration),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.banned_until
+            .map_or(true, |banned_until| banned_until > SystemTime::now())
+    }
+
+    /// Records addresses seen for the peer, ignoring ones already known
+    pub fn add_addresses(&mut self, addresses: Vec<String>) {
+        for address in addresses {
+            if self.address.as_ref() != Some(&address) && !self.addresses.contains(&address) {
+                self.addresses.push(address);
+            }
+        }
+    }
+
+    /// Addresses in the format accepted by `p2p.dht.excluded_dial_addresses`.
+    /// Only ip4/ip6 addresses can be excluded, onion addresses are ignored.
+    pub fn excluded_dial_addresses(&self) -> Vec<String> {
+        self.address
+            .iter()
+            .chain(self.addresses.iter())
+            .filter(|address| address.starts_with("/ip4/") || address.starts_with("/ip6/"))
+            .cloned()
+            .collect()
+    }
+}
+
+/// A peer the local node synced blocks from, with the highest tip it claimed while doing so
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncPeer {
+    pub public_key: String,
+    pub addresses: Vec<String>,
+    pub claimed_tip_height: u64,
+}
+
+/// Adds the current sync peers to `records`, keeping one entry per peer
+pub fn record_sync_peers(records: &mut Vec<SyncPeer>, sync_peers: Vec<SyncPeer>) {
+    for peer in sync_peers {
+        match records
+            .iter_mut()
+            .find(|record| record.public_key == peer.public_key)
+        {
+            Some(record) => {
+                record.claimed_tip_height = record.claimed_tip_height.max(peer.claimed_tip_height);
+                for address in peer.addresses {
+                    if !record.addresses.contains(&address) {
+                        record.addresses.push(address);
+                    }
+                }
+            }
+            None => records.push(peer),
+        }
+    }
+}
+
+/// Sync peers whose claimed chain went past `fork_point`. When the node ended up on an orphan chain above that
+/// height, these are the peers it took the orphan blocks from.
+pub fn peers_past_fork_point(records: &[SyncPeer], fork_point: u64) -> Vec<SyncPeer> {
+    records
+        .iter()
+        .filter(|peer| peer.claimed_tip_height > fork_point)
+        .cloned()
+        .collect()
+}
+
+/// Validates a seed peer in the `<public_key_hex>::<multiaddr>` format used by `p2p.seeds.peer_seeds`
+pub fn validate_seed_peer(seed_peer: &str) -> Result<(), String> {
+    let (public_key, address) = seed_peer
+        .split_once("::")
+        .ok_or_else(|| "Seed peer must be in the format <public_key>::<address>".to_string())?;
+    RistrettoPublicKey::from_hex(public_key).map_err(|_| "Invalid public key".to_string())?;
+    if !address.starts_with('/') || address.len() < 2 {
+        return Err("Invalid peer address".to_string());
+    }
+    Ok(())
+}
+
+/// Seed peers listed under `[<network>.p2p.seeds]` in one of the node's TOML config presets
+pub fn preset_peer_seeds(preset: &str, network_key: &str) -> Vec<String> {
+    let header = format!("[{}.p2p.seeds]", network_key);
+    let mut seeds = vec![];
+    let mut in_section = false;
+    let mut in_peer_seeds = false;
+    for line in preset.lines() {
+        // Seed peers never contain `#`, so everything after it is a comment
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if !in_peer_seeds && line.starts_with('[') {
+            in_section = line == header;
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+        if line.starts_with("peer_seeds") {
+            in_peer_seeds = true;
+        }
+        if in_peer_seeds {
+            seeds.extend(
+                line.split('"')
+                    .skip(1)
+                    .step_by(2)
+                    .filter(|seed| validate_seed_peer(seed).is_ok())
+                    .map(str::to_string),
+            );
+            if line.ends_with(']') {
+                in_peer_seeds = false;
+            }
+        }
+    }
+    seeds
+}
+
+pub fn validate_peer_public_key(public_key: &str) -> Result<(), String> {
+    RistrettoPublicKey::from_hex(public_key)
+        .map(|_| ())
+        .map_err(|_| "Invalid public key".to_string())
+}
+
+/// Renders the binary multiaddr returned by the node into its textual form, falling back to hex for
+/// protocols we do not decode.
+pub fn decode_multiaddr(bytes: &[u8]) -> String {
+    let mut result = String::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        let Some((code, code_length)) = decode_varint(&bytes[index..]) else {
+            return bytes.to_hex();
+        };
+        index += code_length;
+        let decoded = match code {
+            MULTIADDR_IP4 if bytes.len() >= index + 4 => {
+                let ip = Ipv4Addr::new(
+                    bytes[index],
+                    bytes[index + 1],
+                    bytes[index + 2],
+                    bytes[index + 3],
+                );
+                index += 4;
+                Some(format!("/ip4/{}", ip))
+            }
+            MULTIADDR_IP6 if bytes.len() >= index + 16 => {
+                let mut octets = [0u8; 16];
+                octets.copy_from_slice(&bytes[index..index + 16]);
+                index += 16;
+                Some(format!("/ip6/{}", Ipv6Addr::from(octets)))
+            }
+            MULTIADDR_TCP | MULTIADDR_UDP if bytes.len() >= index + 2 => {
+                let port = u16::from_be_bytes([bytes[index], bytes[index + 1]]);
+                index += 2;
+                let protocol = if code == MULTIADDR_TCP { "tcp" } else { "udp" };
+                Some(format!("/{}/{}", protocol, port))
+            }
+            _ => None,
+        };
+
+        match decoded {
+            Some(part) => {
+                let _unused = write!(result, "{}", part);
+            }
+            None => return bytes.to_hex(),
+        }
+    }
+    result
+}
+
+/// Decodes an unsigned LEB128 varint, returning the value and the number of bytes it took
+fn decode_varint(bytes: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (index, byte) in bytes.iter().enumerate().take(9) {
+        value |= u64::from(byte & 0x7f) << (7 * index);
+        if byte & 0x80 == 0 {
+            return Some((value, index + 1));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_multiaddr_ip4_tcp() {
+        // /ip4/10.0.0.1/tcp/18269
+        let bytes = [0x04, 10, 0, 0, 1, 0x06, 0x47, 0x5d];
+        assert_eq!(decode_multiaddr(&bytes), "/ip4/10.0.0.1/tcp/18269");
+    }
+
+    #[test]
+    fn test_decode_multiaddr_ip6_udp() {
+        // /ip6/::1/udp/80, udp (273) takes two varint bytes
+        let mut bytes = vec![0x29];
+        bytes.extend_from_slice(&Ipv6Addr::LOCALHOST.octets());
+        bytes.extend_from_slice(&[0x91, 0x02, 0x00, 0x50]);
+        assert_eq!(decode_multiaddr(&bytes), "/ip6/::1/udp/80");
+    }
+
+    #[test]
+    fn test_decode_multiaddr_unknown_protocol_falls_back_to_hex() {
+        let bytes = [0xbd, 0x03, 0x01, 0x02];
+        assert_eq!(decode_multiaddr(&bytes), bytes.to_hex());
+        let truncated = [0x91];
+        assert_eq!(decode_multiaddr(&truncated), truncated.to_hex());
+    }
+
+    #[test]
+    fn test_validate_seed_peer() {
+        let seed = "0c3fe3c23866ed3827e1cd72aae0c9d364d860d597993104e90d9a9401e52f05::/ip4/10.0.0.1/tcp/18189";
+        assert!(validate_seed_peer(seed).is_ok());
+        assert!(validate_seed_peer("/ip4/10.0.0.1/tcp/18189").is_err());
+        assert!(validate_seed_peer("zz::/ip4/10.0.0.1/tcp/18189").is_err());
+    }
+
+    #[test]
+    fn test_preset_peer_seeds() {
+        let seed = "0c3fe3c23866ed3827e1cd72aae0c9d364d860d597993104e90d9a9401e52f05::/ip4/10.0.0.1/tcp/18189";
+        let preset = format!(
+            "[nextnet.p2p.seeds]\npeer_seeds = [\"{seed}\"]\n\n\
+             [mainnet.p2p.seeds]\n\
+             dns_seeds = [\"seeds.tari.com\"]\n\
+             # Custom specified peer seed nodes\n\
+             peer_seeds = [\n    # 1\n    \"{seed}\",\n    # \"{seed}\",\n]\n\n\
+             [base_node]\npeer_seeds = [\"{seed}\"]\n"
+        );
+        assert_eq!(
+            preset_peer_seeds(&preset, "mainnet"),
+            vec![seed.to_string()]
+        );
+        assert!(preset_peer_seeds(&preset, "esmeralda").is_empty());
+    }
+
+    #[test]
+    fn test_banned_peer_expiry() {
+        let expired = BannedPeer {
+            public_key: "00".to_string(),
+            address: None,
+            addresses: vec![],
+            reason: "test".to_string(),
+            banned_at: SystemTime::now() - Duration::from_secs(20),
+            banned_until: Some(SystemTime::now() - Duration::from_secs(10)),
+        };
+        assert!(!expired.is_active());
+
+        let permanent = BannedPeer::new("00".to_string(), None, "test".to_string(), None);
+        assert!(permanent.is_active());
+    }
+
+    #[test]
+    fn test_sync_peers_past_fork_point() {
+        let peer = |public_key: &str, address: &str, claimed_tip_height| SyncPeer {
+            public_key: public_key.to_string(),
+            addresses: vec![address.to_string()],
+            claimed_tip_height,
+        };
+        let mut records = vec![];
+        record_sync_peers(
+            &mut records,
+            vec![
+                peer("aa", "/ip4/10.0.0.1/tcp/1", 900),
+                peer("bb", "/ip4/10.0.0.2/tcp/1", 1_200),
+            ],
+        );
+        record_sync_peers(&mut records, vec![peer("aa", "/ip4/10.0.0.3/tcp/1", 1_100)]);
+        assert_eq!(records.len(), 2);
+        assert_eq!(
+            records[0].addresses,
+            vec!["/ip4/10.0.0.1/tcp/1", "/ip4/10.0.0.3/tcp/1"]
+        );
+
+        let past = peers_past_fork_point(&records, 1_000);
+        assert_eq!(past.len(), 2);
+        let past = peers_past_fork_point(&records, 1_150);
+        assert_eq!(past.len(), 1);
+        assert_eq!(past[0].public_key, "bb");
+    }
+
+    #[test]
+    fn test_banned_peer_excluded_dial_addresses() {
+        let mut banned = BannedPeer::new(
+            "00".to_string(),
+            Some("/ip4/10.0.0.1/tcp/18189".to_string()),
+            "test".to_string(),
+            None,
+        );
+        banned.add_addresses(vec![
+            "/ip4/10.0.0.1/tcp/18189".to_string(),
+            "/ip6/::1/tcp/18189".to_string(),
+            "/onion3/abcdef:18141".to_string(),
+        ]);
+        assert_eq!(
+            banned.excluded_dial_addresses(),
+            vec![
+                "/ip4/10.0.0.1/tcp/18189".to_string(),
+                "/ip6/::1/tcp/18189".to_string()
+            ]
+        );
+
+        let key_only = BannedPeer::new("00".to_string(), None, "test".to_string(), None);
+        assert!(key_only.excluded_dial_addresses().is_empty());
+    }
+}
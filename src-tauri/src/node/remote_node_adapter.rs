@@ -31,6 +31,8 @@ use crate::{
     node::{
         node_adapter::{NodeAdapter, NodeAdapterService, NodeStatusMonitor},
         node_manager::NodeType,
+        node_storage::NodeStorageMode,
+        peer_management::BannedPeer,
     },
     process_adapter::{ProcessAdapter, ProcessInstanceTrait},
     BaseNodeStatus,
@@ -38,7 +40,7 @@ use crate::{
 use anyhow::Error;
 use std::{
     path::PathBuf,
-    sync::{atomic::AtomicU64, Arc},
+    sync::{atomic::AtomicU64, Arc, RwLock},
 };
 
 const LOG_TARGET: &str = "tari::universe::remote_node_adapter";
@@ -125,6 +127,18 @@ impl NodeAdapter for RemoteNodeAdapter {
         log::info!(target: LOG_TARGET, "RemoteNodeAdapter doesn't use tor_control_port");
     }
 
+    fn set_custom_seed_peers(&mut self, _custom_seed_peers: Vec<String>) {
+        log::info!(target: LOG_TARGET, "RemoteNodeAdapter doesn't use custom seed peers");
+    }
+
+    fn set_banned_peers(&mut self, _banned_peers: Vec<BannedPeer>) {
+        log::info!(target: LOG_TARGET, "RemoteNodeAdapter doesn't use banned peers");
+    }
+
+    fn set_storage_mode(&mut self, _storage_mode: NodeStorageMode) {
+        log::info!(target: LOG_TARGET, "RemoteNodeAdapter doesn't use storage mode");
+    }
+
     async fn get_connection_details(&self) -> Result<(RistrettoPublicKey, String), anyhow::Error> {
         let node_service = self.get_service();
         if let Some(node_service) = node_service {
@@ -188,6 +202,8 @@ impl ProcessAdapter for RemoteNodeAdapter {
                 self.status_broadcast.clone(),
                 Arc::new(AtomicU64::new(0)),
                 None, // Used only by Local Node
+                Arc::new(RwLock::new(vec![])),
+                Arc::new(RwLock::new(vec![])),
             ),
         ))
     }
@@ -0,0 +1,273 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// Rates are averaged over this window so a single slow batch does not swing the ETA
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+/// Minimum span of samples before a rate is reported at all
+const MIN_RATE_SPAN: Duration = Duration::from_secs(5);
+/// Height not moving for this long while syncing means the current peers are not serving us
+pub const SYNC_STALL_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// Block sync downloads and validates full blocks, which is roughly an order of magnitude slower than header
+/// sync. Used for the block part of the ETA until a block rate has been measured.
+const ASSUMED_BLOCK_TO_HEADER_RATE_RATIO: f64 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncPhase {
+    Header,
+    Block,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SyncEstimate {
+    pub headers_per_second: Option<f64>,
+    pub blocks_per_second: Option<f64>,
+    pub eta_secs: Option<u64>,
+    pub is_stalled: bool,
+}
+
+impl SyncEstimate {
+    /// Adds the estimate to the progress params sent to the setup progress tracker and background sync events
+    pub fn write_progress_params(&self, progress_params: &mut HashMap<String, String>) {
+        if let Some(rate) = self.headers_per_second {
+            progress_params.insert("headers_per_second".to_string(), format!("{:.2}", rate));
+        }
+        if let Some(rate) = self.blocks_per_second {
+            progress_params.insert("blocks_per_second".to_string(), format!("{:.2}", rate));
+        }
+        if let Some(eta) = self.eta_secs {
+            progress_params.insert("eta_seconds".to_string(), eta.to_string());
+        }
+        progress_params.insert("is_stalled".to_string(), self.is_stalled.to_string());
+    }
+}
+
+#[derive(Debug, Default)]
+struct RateWindow {
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl RateWindow {
+    fn push(&mut self, now: Instant, height: u64) {
+        // Heights can go backwards after a reorg or restart, start over rather than report a negative rate
+        if self.samples.back().is_some_and(|(_, last)| *last > height) {
+            self.samples.clear();
+        }
+        self.samples.push_back((now, height));
+        while self.samples.len() > 2
+            && self
+                .samples
+                .front()
+                .is_some_and(|(at, _)| now.duration_since(*at) > RATE_WINDOW)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    fn rate(&self) -> Option<f64> {
+        let (first_at, first_height) = self.samples.front()?;
+        let (last_at, last_height) = self.samples.back()?;
+        let span = last_at.duration_since(*first_at);
+        if span < MIN_RATE_SPAN {
+            return None;
+        }
+        Some((last_height - first_height) as f64 / span.as_secs_f64())
+    }
+}
+
+/// Tracks header and block sync heights reported by the node to derive throughput, an ETA and stalls.
+#[derive(Debug, Default)]
+pub struct SyncRateTracker {
+    headers: RateWindow,
+    blocks: RateWindow,
+    last_progress: Option<(SyncPhase, u64, Instant)>,
+}
+
+impl SyncRateTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the node's progress. `local_block_height` is the local chain tip, which keeps advancing from where
+    /// it left off even while the node is still downloading headers.
+    pub fn record(
+        &mut self,
+        phase: SyncPhase,
+        local_height: u64,
+        local_block_height: u64,
+        tip_height: u64,
+        now: Instant,
+    ) -> SyncEstimate {
+        match phase {
+            SyncPhase::Header => self.headers.push(now, local_height),
+            SyncPhase::Block => self.blocks.push(now, local_height),
+        }
+
+        let stalled_since = match self.last_progress {
+            Some((last_phase, last_height, since))
+                if last_phase == phase && last_height == local_height =>
+            {
+                since
+            }
+            _ => {
+                self.last_progress = Some((phase, local_height, now));
+                now
+            }
+        };
+        let is_stalled =
+            local_height < tip_height && now.duration_since(stalled_since) >= SYNC_STALL_TIMEOUT;
+
+        let headers_per_second = self.headers.rate();
+        let blocks_per_second = self.blocks.rate();
+        let eta_secs = match phase {
+            SyncPhase::Header => headers_per_second.and_then(|header_rate| {
+                let block_rate =
+                    blocks_per_second.unwrap_or(header_rate * ASSUMED_BLOCK_TO_HEADER_RATE_RATIO);
+                let header_eta = eta(tip_height.saturating_sub(local_height), header_rate)?;
+                let block_eta = eta(tip_height.saturating_sub(local_block_height), block_rate)?;
+                Some(header_eta + block_eta)
+            }),
+            SyncPhase::Block => blocks_per_second
+                .and_then(|rate| eta(tip_height.saturating_sub(local_height), rate)),
+        };
+
+        SyncEstimate {
+            headers_per_second,
+            blocks_per_second,
+            eta_secs,
+            is_stalled,
+        }
+    }
+}
+
+fn eta(remaining: u64, rate: f64) -> Option<u64> {
+    if remaining == 0 {
+        return Some(0);
+    }
+    if rate <= 0.0 {
+        return None;
+    }
+    Some((remaining as f64 / rate).ceil() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_eta_includes_slower_block_phase() {
+        let mut tracker = SyncRateTracker::new();
+        let start = Instant::now();
+        tracker.record(SyncPhase::Header, 0, 0, 10_000, start);
+        let estimate = tracker.record(
+            SyncPhase::Header,
+            1_000,
+            0,
+            10_000,
+            start + Duration::from_secs(10),
+        );
+        assert_eq!(estimate.headers_per_second, Some(100.0));
+        assert_eq!(estimate.blocks_per_second, None);
+        // 9000 headers at 100/s plus 10000 blocks at the assumed 10/s
+        assert_eq!(estimate.eta_secs, Some(90 + 1_000));
+        assert!(!estimate.is_stalled);
+    }
+
+    #[test]
+    fn block_eta_uses_measured_block_rate() {
+        let mut tracker = SyncRateTracker::new();
+        let start = Instant::now();
+        tracker.record(SyncPhase::Block, 1_000, 1_000, 2_000, start);
+        let estimate = tracker.record(
+            SyncPhase::Block,
+            1_200,
+            1_200,
+            2_000,
+            start + Duration::from_secs(20),
+        );
+        assert_eq!(estimate.blocks_per_second, Some(10.0));
+        assert_eq!(estimate.eta_secs, Some(80));
+    }
+
+    #[test]
+    fn rate_only_reflects_recent_window() {
+        let mut tracker = SyncRateTracker::new();
+        let start = Instant::now();
+        tracker.record(SyncPhase::Block, 0, 0, 100_000, start);
+        tracker.record(
+            SyncPhase::Block,
+            10_000,
+            0,
+            100_000,
+            start + Duration::from_secs(10),
+        );
+        tracker.record(
+            SyncPhase::Block,
+            10_100,
+            0,
+            100_000,
+            start + Duration::from_secs(100),
+        );
+        let estimate = tracker.record(
+            SyncPhase::Block,
+            10_200,
+            0,
+            100_000,
+            start + Duration::from_secs(110),
+        );
+        assert_eq!(estimate.blocks_per_second, Some(10.0));
+    }
+
+    #[test]
+    fn detects_stall_only_when_height_does_not_move() {
+        let mut tracker = SyncRateTracker::new();
+        let start = Instant::now();
+        tracker.record(SyncPhase::Block, 500, 500, 1_000, start);
+        let estimate = tracker.record(
+            SyncPhase::Block,
+            500,
+            500,
+            1_000,
+            start + SYNC_STALL_TIMEOUT - Duration::from_secs(1),
+        );
+        assert!(!estimate.is_stalled);
+        let estimate = tracker.record(
+            SyncPhase::Block,
+            500,
+            500,
+            1_000,
+            start + SYNC_STALL_TIMEOUT,
+        );
+        assert!(estimate.is_stalled);
+
+        let estimate = tracker.record(
+            SyncPhase::Block,
+            501,
+            501,
+            1_000,
+            start + SYNC_STALL_TIMEOUT + Duration::from_secs(1),
+        );
+        assert!(!estimate.is_stalled);
+    }
+}
@@ -0,0 +1,281 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::transaction_index::{
+    TX_DIRECTION_OUTBOUND, TX_STATUS_BROADCAST, TX_STATUS_MINED_CONFIRMED,
+    TX_STATUS_MINED_UNCONFIRMED, TX_STATUS_REJECTED,
+};
+use crate::wallet_adapter::TransactionInfo;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Transactions still waiting to be mined this long after submission are reported as stuck
+pub const STUCK_AFTER_SECS: u64 = 30 * 60;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OutgoingTransactionState {
+    Pending,
+    Broadcast,
+    MinedUnconfirmed,
+    Confirmed,
+    Rejected,
+    Cancelled,
+}
+
+impl OutgoingTransactionState {
+    pub fn from_transaction(transaction: &TransactionInfo) -> Self {
+        if transaction.is_cancelled {
+            return Self::Cancelled;
+        }
+        match transaction.status {
+            TX_STATUS_BROADCAST => Self::Broadcast,
+            TX_STATUS_MINED_UNCONFIRMED => Self::MinedUnconfirmed,
+            TX_STATUS_MINED_CONFIRMED => Self::Confirmed,
+            TX_STATUS_REJECTED => Self::Rejected,
+            _ => Self::Pending,
+        }
+    }
+
+    pub fn is_final(self) -> bool {
+        matches!(self, Self::Confirmed | Self::Rejected | Self::Cancelled)
+    }
+
+    /// Only transactions that have not reached a block yet can be cancelled or rebroadcast
+    pub fn is_unmined(self) -> bool {
+        matches!(self, Self::Pending | Self::Broadcast)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct OutgoingTransaction {
+    #[serde(flatten)]
+    pub transaction: TransactionInfo,
+    pub state: OutgoingTransactionState,
+    pub is_stuck: bool,
+}
+
+impl OutgoingTransaction {
+    fn new(transaction: TransactionInfo, now_secs: u64) -> Self {
+        let state = OutgoingTransactionState::from_transaction(&transaction);
+        let is_stuck = state.is_unmined()
+            && now_secs.saturating_sub(transaction.timestamp) >= STUCK_AFTER_SECS;
+        Self {
+            transaction,
+            state,
+            is_stuck,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OutgoingTransactionUpdate {
+    pub transaction: OutgoingTransaction,
+    pub previous_state: Option<OutgoingTransactionState>,
+}
+
+/// Outgoing transactions of the spend wallet keyed by tx id, tracked until they settle.
+#[derive(Debug, Default)]
+pub struct OutgoingTransactions {
+    transactions: HashMap<u64, OutgoingTransaction>,
+}
+
+impl OutgoingTransactions {
+    /// Merges fresh wallet data and returns an update for every transaction that is new, changed state or became
+    /// stuck. Inbound transactions are ignored.
+    pub fn apply(
+        &mut self,
+        transactions: Vec<TransactionInfo>,
+        now_secs: u64,
+    ) -> Vec<OutgoingTransactionUpdate> {
+        let mut updates = Vec::new();
+        for transaction in transactions {
+            if transaction.direction != TX_DIRECTION_OUTBOUND {
+                continue;
+            }
+            let tracked = OutgoingTransaction::new(transaction, now_secs);
+            let previous = self
+                .transactions
+                .insert(tracked.transaction.tx_id, tracked.clone());
+            let previous_state = previous.as_ref().map(|tx| tx.state);
+            let changed = previous.map_or(true, |previous| {
+                previous.state != tracked.state || previous.is_stuck != tracked.is_stuck
+            });
+            if changed {
+                updates.push(OutgoingTransactionUpdate {
+                    transaction: tracked,
+                    previous_state,
+                });
+            }
+        }
+        updates
+    }
+
+    pub fn get(&self, tx_id: u64) -> Option<&OutgoingTransaction> {
+        self.transactions.get(&tx_id)
+    }
+
+    /// Ids of transactions that still need polling
+    pub fn unsettled_ids(&self) -> Vec<u64> {
+        self.transactions
+            .values()
+            .filter(|tx| !tx.state.is_final())
+            .map(|tx| tx.transaction.tx_id)
+            .collect()
+    }
+
+    /// All tracked transactions, newest first
+    pub fn list(&self) -> Vec<OutgoingTransaction> {
+        let mut transactions: Vec<OutgoingTransaction> =
+            self.transactions.values().cloned().collect();
+        transactions.sort_by(|a, b| {
+            b.transaction
+                .timestamp
+                .cmp(&a.transaction.timestamp)
+                .then(b.transaction.tx_id.cmp(&a.transaction.tx_id))
+        });
+        transactions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction_index::TX_DIRECTION_INBOUND;
+    use tari_core::transactions::tari_amount::MicroMinotari;
+
+    fn tx(tx_id: u64, status: i32, direction: i32, timestamp: u64) -> TransactionInfo {
+        TransactionInfo {
+            tx_id,
+            source_address: String::new(),
+            dest_address: String::new(),
+            status,
+            amount: MicroMinotari(1_000),
+            is_cancelled: false,
+            direction,
+            excess_sig: vec![],
+            fee: 10,
+            timestamp,
+            payment_id: String::new(),
+            mined_in_block_height: 0,
+        }
+    }
+
+    #[test]
+    fn maps_wallet_status_to_state() {
+        let mut cancelled = tx(1, TX_STATUS_REJECTED, TX_DIRECTION_OUTBOUND, 0);
+        cancelled.is_cancelled = true;
+        assert_eq!(
+            OutgoingTransactionState::from_transaction(&cancelled),
+            OutgoingTransactionState::Cancelled
+        );
+        let cases = [
+            (0, OutgoingTransactionState::Pending),
+            (TX_STATUS_BROADCAST, OutgoingTransactionState::Broadcast),
+            (
+                TX_STATUS_MINED_UNCONFIRMED,
+                OutgoingTransactionState::MinedUnconfirmed,
+            ),
+            (
+                TX_STATUS_MINED_CONFIRMED,
+                OutgoingTransactionState::Confirmed,
+            ),
+            (TX_STATUS_REJECTED, OutgoingTransactionState::Rejected),
+        ];
+        for (status, expected) in cases {
+            assert_eq!(
+                OutgoingTransactionState::from_transaction(&tx(
+                    1,
+                    status,
+                    TX_DIRECTION_OUTBOUND,
+                    0
+                )),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn reports_only_changes() {
+        let mut outgoing = OutgoingTransactions::default();
+        let updates = outgoing.apply(
+            vec![
+                tx(1, 0, TX_DIRECTION_OUTBOUND, 100),
+                tx(2, 0, TX_DIRECTION_INBOUND, 100),
+            ],
+            100,
+        );
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].previous_state, None);
+
+        assert!(outgoing
+            .apply(vec![tx(1, 0, TX_DIRECTION_OUTBOUND, 100)], 200)
+            .is_empty());
+
+        let updates = outgoing.apply(
+            vec![tx(1, TX_STATUS_BROADCAST, TX_DIRECTION_OUTBOUND, 100)],
+            200,
+        );
+        assert_eq!(
+            updates[0].previous_state,
+            Some(OutgoingTransactionState::Pending)
+        );
+        assert_eq!(
+            updates[0].transaction.state,
+            OutgoingTransactionState::Broadcast
+        );
+    }
+
+    #[test]
+    fn flags_stuck_transactions_until_mined() {
+        let mut outgoing = OutgoingTransactions::default();
+        outgoing.apply(
+            vec![tx(1, TX_STATUS_BROADCAST, TX_DIRECTION_OUTBOUND, 0)],
+            10,
+        );
+
+        let updates = outgoing.apply(
+            vec![tx(1, TX_STATUS_BROADCAST, TX_DIRECTION_OUTBOUND, 0)],
+            STUCK_AFTER_SECS,
+        );
+        assert!(updates[0].transaction.is_stuck);
+
+        let updates = outgoing.apply(
+            vec![tx(1, TX_STATUS_MINED_UNCONFIRMED, TX_DIRECTION_OUTBOUND, 0)],
+            STUCK_AFTER_SECS * 2,
+        );
+        assert!(!updates[0].transaction.is_stuck);
+    }
+
+    #[test]
+    fn settled_transactions_are_not_polled() {
+        let mut outgoing = OutgoingTransactions::default();
+        outgoing.apply(
+            vec![
+                tx(1, TX_STATUS_MINED_CONFIRMED, TX_DIRECTION_OUTBOUND, 0),
+                tx(2, TX_STATUS_BROADCAST, TX_DIRECTION_OUTBOUND, 0),
+            ],
+            0,
+        );
+        assert_eq!(outgoing.unsettled_ids(), vec![2]);
+    }
+}
@@ -0,0 +1,505 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{collections::HashSet, path::PathBuf, str::FromStr, sync::LazyLock};
+
+use anyhow::{anyhow, Error};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use tari_common_types::tari_address::TariAddressFeatures;
+use tari_core::transactions::tari_amount::{MicroMinotari, Minotari};
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+
+use crate::{
+    events_manager::EventsManager,
+    internal_wallet::InternalWallet,
+    transaction_index::{TransactionDirection, TransactionIndex, TransactionQuery, MAX_PAGE_SIZE},
+    utils::{
+        address_utils::{verify_send, verify_tari_address},
+        store_utils::{current_network_key, load_json_store, save_json_store},
+        time_utils::unix_now,
+    },
+    UniverseAppState,
+};
+
+const LOG_TARGET: &str = "tari::universe::payment_request";
+const PAYMENT_REQUESTS_FILE: &str = "payment_requests.json";
+pub const URI_SCHEME: &str = "tari";
+/// Payment ids travel in the encrypted output data, which has limited room
+pub const MAX_PAYMENT_ID_LENGTH: usize = 256;
+
+static INSTANCE: LazyLock<PaymentRequests> = LazyLock::new(PaymentRequests::new);
+
+/// Parses a user supplied XTM amount such as `1.5` into micro minotari
+pub fn parse_minotari_amount(amount: &str) -> Result<MicroMinotari, String> {
+    let amount = Minotari::from_str(amount.trim()).map_err(|e| e.to_string())?;
+    Ok(MicroMinotari::from(amount))
+}
+
+/// Formats micro minotari as a plain decimal XTM amount without trailing zeros
+pub fn format_minotari_amount(amount: MicroMinotari) -> String {
+    let micro = amount.as_u64();
+    let whole = micro / 1_000_000;
+    let fraction = micro % 1_000_000;
+    if fraction == 0 {
+        return whole.to_string();
+    }
+    format!("{}.{:06}", whole, fraction)
+        .trim_end_matches('0')
+        .to_string()
+}
+
+pub fn validate_payment_id(payment_id: &str) -> Result<(), String> {
+    if payment_id.is_empty() {
+        return Err("Payment ID cannot be empty".to_string());
+    }
+    if payment_id.len() > MAX_PAYMENT_ID_LENGTH {
+        return Err(format!(
+            "Payment ID cannot be longer than {} bytes",
+            MAX_PAYMENT_ID_LENGTH
+        ));
+    }
+    if payment_id.chars().any(char::is_control) {
+        return Err("Payment ID cannot contain control characters".to_string());
+    }
+    Ok(())
+}
+
+/// Contents of a `tari:<address>?amount=<xtm>&payment_id=<id>&network=<network>` payment URI
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PaymentUri {
+    pub address: String,
+    pub amount: Option<MicroMinotari>,
+    pub payment_id: Option<String>,
+    pub network: String,
+}
+
+impl PaymentUri {
+    pub fn new(
+        address: &str,
+        amount: Option<MicroMinotari>,
+        payment_id: Option<String>,
+    ) -> Result<Self, String> {
+        verify_send(address.to_string(), TariAddressFeatures::ONE_SIDED)?;
+        let address = verify_tari_address(address)?;
+        if amount.is_some_and(|amount| amount == MicroMinotari(0)) {
+            return Err("Requested amount must be greater than zero".to_string());
+        }
+        let payment_id = payment_id.filter(|id| !id.is_empty());
+        if let Some(payment_id) = &payment_id {
+            validate_payment_id(payment_id)?;
+        }
+
+        Ok(Self {
+            address: address.to_base58(),
+            amount,
+            payment_id,
+            network: address.network().to_string().to_lowercase(),
+        })
+    }
+
+    pub fn to_uri(&self) -> String {
+        let mut params = vec![];
+        if let Some(amount) = self.amount {
+            params.push(format!("amount={}", format_minotari_amount(amount)));
+        }
+        if let Some(payment_id) = &self.payment_id {
+            params.push(format!("payment_id={}", urlencoding::encode(payment_id)));
+        }
+        params.push(format!("network={}", urlencoding::encode(&self.network)));
+        format!("{}:{}?{}", URI_SCHEME, self.address, params.join("&"))
+    }
+
+    /// Parses and validates a payment URI. `memo` and `note` are accepted as aliases of `payment_id`.
+    pub fn parse(uri: &str) -> Result<Self, String> {
+        let uri = uri.trim();
+        let rest = uri
+            .split_once(':')
+            .filter(|(scheme, _)| scheme.eq_ignore_ascii_case(URI_SCHEME))
+            .map(|(_, rest)| rest.trim_start_matches("//"))
+            .ok_or_else(|| format!("Not a {}: payment URI", URI_SCHEME))?;
+        let (address, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let address = urlencoding::decode(address.trim_end_matches('/'))
+            .map_err(|_| "Invalid address encoding".to_string())?;
+
+        let mut amount = None;
+        let mut payment_id = None;
+        let mut network = None;
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = urlencoding::decode(&value.replace('+', " "))
+                .map_err(|_| format!("Invalid encoding of '{}'", key))?
+                .into_owned();
+            match key.to_ascii_lowercase().as_str() {
+                "amount" => amount = Some(parse_minotari_amount(&value)?),
+                "payment_id" | "memo" | "note" => payment_id = Some(value),
+                "network" => network = Some(value.to_lowercase()),
+                _ => {}
+            }
+        }
+
+        let parsed = Self::new(&address, amount, payment_id)?;
+        if let Some(network) = network {
+            if network != parsed.network {
+                return Err(format!(
+                    "Payment request is for {} but the address is on {}",
+                    network, parsed.network
+                ));
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ParsedPaymentUri {
+    pub request: PaymentUri,
+    /// Why the requested amount cannot be sent right now, e.g. insufficient balance
+    pub amount_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentRequestStatus {
+    Open,
+    PartiallyPaid,
+    Paid,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PaymentRequest {
+    #[serde(flatten)]
+    pub request: PaymentUri,
+    pub uri: String,
+    pub label: Option<String>,
+    pub created_at: u64,
+    pub status: PaymentRequestStatus,
+    pub received: MicroMinotari,
+    pub matched_tx_ids: Vec<u64>,
+}
+
+impl PaymentRequest {
+    fn is_awaiting_payment(&self) -> bool {
+        matches!(
+            self.status,
+            PaymentRequestStatus::Open | PaymentRequestStatus::PartiallyPaid
+        )
+    }
+
+    /// Applies the inbound transactions carrying this request's payment id. Returns true when anything changed.
+    fn apply_payments(&mut self, payments: &[(u64, MicroMinotari)]) -> bool {
+        let received = payments
+            .iter()
+            .fold(MicroMinotari(0), |total, (_, amount)| total + *amount);
+        let mut matched_tx_ids: Vec<u64> = payments.iter().map(|(tx_id, _)| *tx_id).collect();
+        matched_tx_ids.sort_unstable();
+
+        let status = match self.request.amount {
+            _ if received == MicroMinotari(0) => PaymentRequestStatus::Open,
+            Some(amount) if received < amount => PaymentRequestStatus::PartiallyPaid,
+            _ => PaymentRequestStatus::Paid,
+        };
+        let changed = status != self.status
+            || received != self.received
+            || matched_tx_ids != self.matched_tx_ids;
+        self.status = status;
+        self.received = received;
+        self.matched_tx_ids = matched_tx_ids;
+        changed
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PaymentRequestsStore {
+    requests: Vec<PaymentRequest>,
+}
+
+struct PaymentRequestsState {
+    store: PaymentRequestsStore,
+    path: Option<PathBuf>,
+}
+
+/// Payment requests handed out for the wallet's own address, matched to incoming transactions by payment id.
+pub struct PaymentRequests {
+    state: Mutex<PaymentRequestsState>,
+}
+
+impl PaymentRequests {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(PaymentRequestsState {
+                store: PaymentRequestsStore::default(),
+                path: None,
+            }),
+        }
+    }
+
+    pub fn current() -> &'static PaymentRequests {
+        &INSTANCE
+    }
+
+    pub async fn load(&self, app_handle: &AppHandle) -> Result<(), Error> {
+        let path = app_handle
+            .path()
+            .app_config_dir()?
+            .join(PAYMENT_REQUESTS_FILE);
+        let store = load_json_store(&path).await?;
+        let mut state = self.state.lock().await;
+        state.store = store;
+        state.path = Some(path);
+        Ok(())
+    }
+
+    async fn save(&self, state: &PaymentRequestsState) -> Result<(), Error> {
+        let Some(path) = state.path.clone() else {
+            return Err(anyhow!("Payment requests have not been loaded"));
+        };
+        save_json_store(&path, &state.store).await
+    }
+
+    /// Requests for the current network, newest first
+    pub async fn list(&self) -> Vec<PaymentRequest> {
+        let network = current_network_key();
+        let state = self.state.lock().await;
+        let mut requests: Vec<PaymentRequest> = state
+            .store
+            .requests
+            .iter()
+            .filter(|r| r.request.network == network)
+            .cloned()
+            .collect();
+        requests.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        requests
+    }
+
+    /// Creates a request for the wallet's own address. A payment id is generated when none is given, and it must
+    /// not clash with another request still awaiting payment.
+    pub async fn create(
+        &self,
+        app_handle: &AppHandle,
+        amount: Option<String>,
+        payment_id: Option<String>,
+        label: Option<String>,
+    ) -> Result<PaymentRequest, Error> {
+        let config_path = app_handle.path().app_config_dir()?;
+        let address = InternalWallet::load_or_create(config_path)
+            .await?
+            .get_tari_address()
+            .to_base58();
+        let amount = amount
+            .filter(|a| !a.trim().is_empty())
+            .map(|a| parse_minotari_amount(&a))
+            .transpose()
+            .map_err(|e| anyhow!(e))?;
+
+        let mut state = self.state.lock().await;
+        let taken: HashSet<String> = state
+            .store
+            .requests
+            .iter()
+            .filter(|r| r.is_awaiting_payment())
+            .filter_map(|r| r.request.payment_id.clone())
+            .collect();
+        let payment_id = match payment_id.map(|id| id.trim().to_string()) {
+            Some(id) if !id.is_empty() => {
+                if taken.contains(&id) {
+                    return Err(anyhow!(
+                        "An open payment request already uses payment ID {}",
+                        id
+                    ));
+                }
+                id
+            }
+            _ => loop {
+                let id = format!("{:016x}", rand::random::<u64>());
+                if !taken.contains(&id) {
+                    break id;
+                }
+            },
+        };
+
+        let request =
+            PaymentUri::new(&address, amount, Some(payment_id)).map_err(|e| anyhow!(e))?;
+        let payment_request = PaymentRequest {
+            uri: request.to_uri(),
+            request,
+            label: label
+                .map(|l| l.trim().to_string())
+                .filter(|l| !l.is_empty()),
+            created_at: unix_now(),
+            status: PaymentRequestStatus::Open,
+            received: MicroMinotari(0),
+            matched_tx_ids: vec![],
+        };
+        state.store.requests.push(payment_request.clone());
+        self.save(&state).await?;
+        info!(target: LOG_TARGET, "Created payment request {}", payment_request.uri);
+        Ok(payment_request)
+    }
+
+    pub async fn cancel(&self, payment_id: &str) -> Result<PaymentRequest, Error> {
+        let mut state = self.state.lock().await;
+        let request = state
+            .store
+            .requests
+            .iter_mut()
+            .find(|r| {
+                r.is_awaiting_payment() && r.request.payment_id.as_deref() == Some(payment_id)
+            })
+            .ok_or_else(|| anyhow!("No open payment request with payment ID {}", payment_id))?;
+        request.status = PaymentRequestStatus::Cancelled;
+        let request = request.clone();
+        self.save(&state).await?;
+        Ok(request)
+    }
+
+    /// Matches indexed inbound transactions received after a request was created to the requests still awaiting
+    /// payment, and emits updates for the ones that changed. Called after every transaction index sync.
+    pub async fn reconcile(&self, app_handle: &AppHandle) {
+        let network = current_network_key();
+        // Only the active wallet is indexed, requests made for other wallets wait until they are active again
+        let address = app_handle
+            .state::<UniverseAppState>()
+            .tari_address
+            .read()
+            .await
+            .to_base58();
+        let mut state = self.state.lock().await;
+        let mut updated = vec![];
+        for request in state.store.requests.iter_mut().filter(|r| {
+            r.is_awaiting_payment() && r.request.network == network && r.request.address == address
+        }) {
+            let Some(payment_id) = request.request.payment_id.clone() else {
+                continue;
+            };
+            let query = TransactionQuery {
+                limit: Some(MAX_PAGE_SIZE),
+                direction: Some(TransactionDirection::Inbound),
+                payment_id: Some(payment_id),
+                ..Default::default()
+            };
+            let payments: Vec<(u64, MicroMinotari)> =
+                match TransactionIndex::current().query(&query).await {
+                    Ok(page) => page
+                        .transactions
+                        .iter()
+                        .filter(|tx| !tx.is_cancelled && tx.timestamp >= request.created_at)
+                        .map(|tx| (tx.tx_id, tx.amount))
+                        .collect(),
+                    Err(e) => {
+                        warn!(target: LOG_TARGET, "Could not match payment request: {:?}", e);
+                        continue;
+                    }
+                };
+            if request.apply_payments(&payments) {
+                updated.push(request.clone());
+            }
+        }
+        if updated.is_empty() {
+            return;
+        }
+        if let Err(e) = self.save(&state).await {
+            warn!(target: LOG_TARGET, "Failed to save payment requests: {:?}", e);
+        }
+        drop(state);
+
+        for request in updated {
+            info!(target: LOG_TARGET, "Payment request {:?} is now {:?}", request.request.payment_id, request.status);
+            EventsManager::handle_payment_request_updated(app_handle, request).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ESME_ONE_SIDED_ADDRESS: &str = "f25eNHz2YnBVKHaqNuacGyDFB321RwwCnTr4vb2SjQCgDZVXyNNthc7zftQKRDu6evLjvSUD8W5akpPMdhS4HQ9kF3g";
+    const ESME_INTERACTIVE_ADDRESS: &str = "f45eNHz2YnBVKHaqNuacGyDFB321RwwCnTr4vb2SjQCgDZVXyNNthc7zftQKRDu6evLjvSUD8W5akpPMdhS4HQ9kF31";
+
+    #[test]
+    fn uri_round_trips() {
+        let request = PaymentUri::new(
+            ESME_ONE_SIDED_ADDRESS,
+            Some(MicroMinotari(1_500_000)),
+            Some("invoice #42 & co".to_string()),
+        )
+        .unwrap();
+        let uri = request.to_uri();
+        assert!(uri.starts_with("tari:f25e"));
+        assert!(uri.contains("amount=1.5&"));
+        assert_eq!(PaymentUri::parse(&uri).unwrap(), request);
+    }
+
+    #[test]
+    fn parse_accepts_aliases_and_slashes() {
+        let uri = format!("TARI://{}?memo=coffee&amount=2", ESME_ONE_SIDED_ADDRESS);
+        let parsed = PaymentUri::parse(&uri).unwrap();
+        assert_eq!(parsed.payment_id.as_deref(), Some("coffee"));
+        assert_eq!(parsed.amount, Some(MicroMinotari(2_000_000)));
+    }
+
+    #[test]
+    fn parse_rejects_invalid_requests() {
+        assert!(PaymentUri::parse("bitcoin:abc").is_err());
+        assert!(PaymentUri::parse("tari:not-an-address").is_err());
+        assert!(PaymentUri::parse(&format!("tari:{}", ESME_INTERACTIVE_ADDRESS)).is_err());
+        assert!(PaymentUri::parse(&format!("tari:{}?amount=abc", ESME_ONE_SIDED_ADDRESS)).is_err());
+        assert!(PaymentUri::parse(&format!("tari:{}?amount=0", ESME_ONE_SIDED_ADDRESS)).is_err());
+        assert!(
+            PaymentUri::parse(&format!("tari:{}?network=mainnet", ESME_ONE_SIDED_ADDRESS)).is_err()
+        );
+    }
+
+    #[test]
+    fn formats_amounts_without_trailing_zeros() {
+        assert_eq!(format_minotari_amount(MicroMinotari(5_000_000)), "5");
+        assert_eq!(format_minotari_amount(MicroMinotari(1_250_000)), "1.25");
+        assert_eq!(format_minotari_amount(MicroMinotari(1)), "0.000001");
+    }
+
+    #[test]
+    fn payments_move_request_through_statuses() {
+        let mut request = PaymentRequest {
+            request: PaymentUri::new(
+                ESME_ONE_SIDED_ADDRESS,
+                Some(MicroMinotari(1_000)),
+                Some("id".to_string()),
+            )
+            .unwrap(),
+            uri: String::new(),
+            label: None,
+            created_at: 0,
+            status: PaymentRequestStatus::Open,
+            received: MicroMinotari(0),
+            matched_tx_ids: vec![],
+        };
+        assert!(!request.apply_payments(&[]));
+        assert!(request.apply_payments(&[(1, MicroMinotari(400))]));
+        assert_eq!(request.status, PaymentRequestStatus::PartiallyPaid);
+        assert!(request.apply_payments(&[(2, MicroMinotari(600)), (1, MicroMinotari(400))]));
+        assert_eq!(request.status, PaymentRequestStatus::Paid);
+        assert_eq!(request.matched_tx_ids, vec![1, 2]);
+    }
+}
@@ -279,6 +279,52 @@ impl ProgressStep for ProgressSetupUnknownPlan {
     }
 }
 
+#[derive(Clone, PartialEq)]
+pub enum ProgressOrphanChainRecoveryPlan {
+    CompareWithReference,
+    FindForkPoint,
+    BanForkPeers,
+    RewindChain,
+    Done,
+}
+
+impl ProgressStep for ProgressOrphanChainRecoveryPlan {
+    type ChannelEvent = ProgressPlanEventPayload;
+
+    fn get_event_type(&self) -> ProgressEvents {
+        ProgressEvents::OrphanChainRecovery
+    }
+
+    fn get_progress_weight(&self) -> u8 {
+        match self {
+            ProgressOrphanChainRecoveryPlan::CompareWithReference => 1,
+            ProgressOrphanChainRecoveryPlan::FindForkPoint => 3,
+            ProgressOrphanChainRecoveryPlan::BanForkPeers => 1,
+            ProgressOrphanChainRecoveryPlan::RewindChain => 3,
+            ProgressOrphanChainRecoveryPlan::Done => 1,
+        }
+    }
+
+    fn get_title(&self) -> String {
+        match self {
+            ProgressOrphanChainRecoveryPlan::CompareWithReference => {
+                "compare-with-reference".to_string()
+            }
+            ProgressOrphanChainRecoveryPlan::FindForkPoint => "find-fork-point".to_string(),
+            ProgressOrphanChainRecoveryPlan::BanForkPeers => "ban-fork-peers".to_string(),
+            ProgressOrphanChainRecoveryPlan::RewindChain => "rewind-chain".to_string(),
+            ProgressOrphanChainRecoveryPlan::Done => "done".to_string(),
+        }
+    }
+
+    fn resolve_to_event(&self) -> Self::ChannelEvent {
+        ProgressPlanEventPayload {
+            event_type: self.get_event_type(),
+            title: self.get_title(),
+        }
+    }
+}
+
 #[allow(dead_code)]
 #[derive(Clone, PartialEq)]
 pub enum ProgressPlans {
@@ -287,6 +333,7 @@ pub enum ProgressPlans {
     Hardware(ProgressSetupHardwarePlan),
     Wallet(ProgressSetupWalletPlan),
     Unknown(ProgressSetupUnknownPlan),
+    OrphanChainRecovery(ProgressOrphanChainRecoveryPlan),
 }
 #[allow(dead_code)]
 impl ProgressPlans {
@@ -297,6 +344,7 @@ impl ProgressPlans {
             ProgressPlans::Hardware(plan) => plan.get_event_type(),
             ProgressPlans::Wallet(plan) => plan.get_event_type(),
             ProgressPlans::Unknown(plan) => plan.get_event_type(),
+            ProgressPlans::OrphanChainRecovery(plan) => plan.get_event_type(),
         }
     }
 }
@@ -311,6 +359,7 @@ impl ProgressStep for ProgressPlans {
             ProgressPlans::Hardware(plan) => plan.get_event_type(),
             ProgressPlans::Wallet(plan) => plan.get_event_type(),
             ProgressPlans::Unknown(plan) => plan.get_event_type(),
+            ProgressPlans::OrphanChainRecovery(plan) => plan.get_event_type(),
         }
     }
 
@@ -321,6 +370,7 @@ impl ProgressStep for ProgressPlans {
             ProgressPlans::Hardware(plan) => plan.get_title(),
             ProgressPlans::Wallet(plan) => plan.get_title(),
             ProgressPlans::Unknown(plan) => plan.get_title(),
+            ProgressPlans::OrphanChainRecovery(plan) => plan.get_title(),
         }
     }
 
@@ -331,6 +381,7 @@ impl ProgressStep for ProgressPlans {
             ProgressPlans::Hardware(plan) => plan.resolve_to_event(),
             ProgressPlans::Wallet(plan) => plan.resolve_to_event(),
             ProgressPlans::Unknown(plan) => plan.resolve_to_event(),
+            ProgressPlans::OrphanChainRecovery(plan) => plan.resolve_to_event(),
         }
     }
 
@@ -341,6 +392,7 @@ impl ProgressStep for ProgressPlans {
             ProgressPlans::Hardware(plan) => plan.get_progress_weight(),
             ProgressPlans::Wallet(plan) => plan.get_progress_weight(),
             ProgressPlans::Unknown(plan) => plan.get_progress_weight(),
+            ProgressPlans::OrphanChainRecovery(plan) => plan.get_progress_weight(),
         }
     }
 }
@@ -353,6 +405,7 @@ impl ProgressPlans {
             ProgressPlans::Hardware(_) => "setup-hardware".to_string(),
             ProgressPlans::Wallet(_) => "setup-wallet".to_string(),
             ProgressPlans::Unknown(_) => "setup-unknown".to_string(),
+            ProgressPlans::OrphanChainRecovery(_) => "orphan-chain-recovery".to_string(),
         }
     }
 
@@ -363,6 +416,7 @@ impl ProgressPlans {
             ProgressPlans::Hardware(_) => 0.1,
             ProgressPlans::Wallet(_) => 0.1,
             ProgressPlans::Unknown(_) => 0.1,
+            ProgressPlans::OrphanChainRecovery(_) => 1.0,
         }
     }
 
@@ -373,6 +427,7 @@ impl ProgressPlans {
             ProgressPlans::Hardware(_) => 60.0,
             ProgressPlans::Wallet(_) => 80.0,
             ProgressPlans::Unknown(_) => 90.0,
+            ProgressPlans::OrphanChainRecovery(_) => 0.0,
         }
     }
 }
@@ -24,9 +24,16 @@ use std::{collections::HashMap, time::Duration};
 
 use crate::{
     binaries::{Binaries, BinaryResolver},
-    configs::{config_core::ConfigCore, trait_config::ConfigImpl},
+    configs::{
+        config_core::{ConfigCore, ConfigCoreContent},
+        trait_config::ConfigImpl,
+    },
+    data_locations::DataComponent,
     events_manager::EventsManager,
-    node::node_manager::{NodeManagerError, STOP_ON_ERROR_CODES},
+    node::{
+        node_manager::{NodeManagerError, STOP_ON_ERROR_CODES},
+        orphan_chain_recovery::{recover_from_orphan_chain, RecoveryTrigger},
+    },
     progress_tracker_old::ProgressTracker,
     progress_trackers::{
         progress_plans::{ProgressPlans, ProgressSetupNodePlan},
@@ -95,6 +102,10 @@ impl SetupPhaseImpl for NodeSetupPhase {
         &self.app_handle
     }
 
+    fn get_data_component(&self) -> Option<DataComponent> {
+        Some(DataComponent::Node)
+    }
+
     fn create_progress_stepper(app_handle: AppHandle) -> ProgressStepper {
         ProgressStepperBuilder::new()
             .add_step(ProgressPlans::Node(ProgressSetupNodePlan::BinariesTor))
@@ -219,7 +230,7 @@ impl SetupPhaseImpl for NodeSetupPhase {
             .resolve_step(ProgressPlans::Node(ProgressSetupNodePlan::StartingNode))
             .await;
 
-        info!(target: LOG_TARGET, "Starting node manager, grpc address: {}", self.app_configuration.base_node_grpc_address);
+        info!(target: LOG_TARGET, "Starting node manager");
 
         for _i in 0..2 {
             match state
@@ -243,8 +254,15 @@ impl SetupPhaseImpl for NodeSetupPhase {
                     if let NodeManagerError::ExitCode(code) = e {
                         if STOP_ON_ERROR_CODES.contains(&code) {
                             warn!(target: LOG_TARGET, "Database for node is corrupt or needs a restart, deleting and trying again.");
-                            state.node_manager.clean_data_folder(&data_dir).await?;
-                            state.wallet_manager.clean_data_folder(&data_dir).await?;
+                            let default_data_dir = self.app_handle.path().app_local_data_dir()?;
+                            state
+                                .node_manager
+                                .clean_data_folder(&default_data_dir)
+                                .await?;
+                            state
+                                .wallet_manager
+                                .clean_data_folder(&default_data_dir)
+                                .await?;
                         }
                         continue;
                     }
@@ -357,6 +375,25 @@ impl SetupPhaseImpl for NodeSetupPhase {
                             Ok(is_stuck) => {
                                 EventsManager::handle_stuck_on_orphan_chain(&app_handle_clone, is_stuck)
                             .await;
+                                if is_stuck {
+                                    let app_handle = app_handle_clone.clone();
+                                    // Recovery restarts the node phase, which closes this task tracker
+                                    TasksTrackers::current().common.get_task_tracker().await.spawn(async move {
+                                        match recover_from_orphan_chain(&app_handle, RecoveryTrigger::Automatic).await {
+                                            Ok(Some(report)) => {
+                                                info!(target: LOG_TARGET, "Orphan chain recovery finished: {:?}", report);
+                                            }
+                                            Ok(None) => {
+                                                EventsManager::handle_stuck_on_orphan_chain(&app_handle, false).await;
+                                            }
+                                            Err(e) => {
+                                                warn!(target: LOG_TARGET, "Orphan chain recovery skipped: {}", e);
+                                            }
+                                        }
+                                    });
+                                } else if *ConfigCore::content().await.orphan_chain_recovery_attempts() > 0 {
+                                    let _unused = ConfigCore::update_field(ConfigCoreContent::set_orphan_chain_recovery_attempts, 0).await;
+                                }
                             }
                             Err(ref e) => {
                                 error!(target: LOG_TARGET, "{}", e);
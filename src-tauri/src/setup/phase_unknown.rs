@@ -23,6 +23,7 @@
 use crate::{
     binaries::{Binaries, BinaryResolver},
     configs::{config_core::ConfigCore, trait_config::ConfigImpl},
+    data_locations::DataComponent,
     events_manager::EventsManager,
     p2pool_manager::P2poolConfig,
     progress_tracker_old::ProgressTracker,
@@ -96,6 +97,10 @@ impl SetupPhaseImpl for UnknownSetupPhase {
         &self.app_handle
     }
 
+    fn get_data_component(&self) -> Option<DataComponent> {
+        Some(DataComponent::P2pool)
+    }
+
     fn create_progress_stepper(app_handle: AppHandle) -> ProgressStepper {
         ProgressStepperBuilder::new()
             .add_step(ProgressPlans::Unknown(
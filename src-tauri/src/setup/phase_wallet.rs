@@ -20,12 +20,15 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 use crate::{
+    auto_sweep::AutoSweep,
     binaries::{Binaries, BinaryResolver},
     configs::{
         config_core::ConfigCore,
         config_ui::{ConfigUI, ConfigUIContent},
+        config_wallet::ConfigWallet,
         trait_config::ConfigImpl,
     },
+    data_locations::DataComponent,
     events_emitter::EventsEmitter,
     events_manager::EventsManager,
     progress_tracker_old::ProgressTracker,
@@ -36,6 +39,7 @@ use crate::{
     },
     setup::{setup_manager::SetupPhase, utils::conditional_sleeper},
     tasks_tracker::TasksTrackers,
+    wallet_profiles::{active_wallet_id, wallet_data_dir},
     UniverseAppState,
 };
 use anyhow::Error;
@@ -97,6 +101,10 @@ impl SetupPhaseImpl for WalletSetupPhase {
         &self.app_handle
     }
 
+    fn get_data_component(&self) -> Option<DataComponent> {
+        Some(DataComponent::Wallet)
+    }
+
     fn create_progress_stepper(app_handle: AppHandle) -> ProgressStepper {
         ProgressStepperBuilder::new()
             .add_step(ProgressPlans::Wallet(
@@ -171,6 +179,7 @@ impl SetupPhaseImpl for WalletSetupPhase {
     async fn setup_inner(&self) -> Result<(), Error> {
         let mut progress_stepper = self.progress_stepper.lock().await;
         let (data_dir, config_dir, log_dir) = self.get_app_dirs()?;
+        let data_dir = wallet_data_dir(&data_dir, &active_wallet_id().await);
         let state = self.app_handle.state::<UniverseAppState>();
 
         // TODO Remove once not needed
@@ -213,20 +222,23 @@ impl SetupPhaseImpl for WalletSetupPhase {
             ))
             .await;
 
-        let mut spend_wallet_manager = state.spend_wallet_manager.write().await;
+        // A watch-only wallet has no seed to spend with
+        let spend_wallet_manager = state.spend_wallet_manager.read().await.clone();
+        if *ConfigWallet::content().await.is_watch_only() {
+            info!(target: LOG_TARGET, "Watch-only wallet, the spend wallet stays disabled");
+            spend_wallet_manager.disable().await;
+            return Ok(());
+        }
+
+        // The spend wallet itself only starts once a send or coin operation needs it
         spend_wallet_manager
-            .init(
-                TasksTrackers::current()
-                    .wallet_phase
-                    .get_signal()
-                    .await
-                    .clone(),
-                data_dir,
-                config_dir,
-                log_dir,
-            )
-            .await?;
-        drop(spend_wallet_manager);
+            .prepare(data_dir, config_dir, log_dir)
+            .await;
+        let app_handle = self.get_app_handle().clone();
+        spend_wallet_manager
+            .spawn_status_tracker(app_handle.clone())
+            .await;
+        AutoSweep::spawn(app_handle).await;
 
         Ok(())
     }
@@ -426,7 +426,7 @@ impl SetupManager {
             });
     }
 
-    async fn shutdown_phases(&self, app_handle: AppHandle, phases: Vec<SetupPhase>) {
+    pub async fn shutdown_phases(&self, app_handle: AppHandle, phases: Vec<SetupPhase>) {
         // We are cancelling the wait_for_unlock_conditions listener to avoid it from triggering
         // As we are shutting down the phases one by one which could lead to unwanted unlocks
         self.cancellation_token.lock().await.cancel();
@@ -472,7 +472,7 @@ impl SetupManager {
         self.wait_for_unlock_conditions(app_handle.clone()).await;
     }
 
-    async fn resume_phases(&self, app_handle: AppHandle, phases: Vec<SetupPhase>) {
+    pub async fn resume_phases(&self, app_handle: AppHandle, phases: Vec<SetupPhase>) {
         if !phases.is_empty() {
             EventsManager::handle_restarting_phases(&app_handle, phases.clone()).await;
         }
@@ -498,6 +498,18 @@ impl SetupManager {
         }
     }
 
+    /// Whether the phase is shut down, either never started or stopped through `shutdown_phases`
+    pub fn is_phase_stopped(&self, phase: &SetupPhase) -> bool {
+        let status = match phase {
+            SetupPhase::Core => self.core_phase_status.borrow(),
+            SetupPhase::Hardware => self.hardware_phase_status.borrow(),
+            SetupPhase::Node => self.node_phase_status.borrow(),
+            SetupPhase::Wallet => self.wallet_phase_status.borrow(),
+            SetupPhase::Unknown => self.unknown_phase_status.borrow(),
+        };
+        matches!(*status, PhaseStatus::None)
+    }
+
     async fn unlock_app(&self, app_handle: AppHandle) {
         if *self.is_app_unlocked.lock().await {
             debug!(target: LOG_TARGET, "App is already unlocked");
@@ -26,6 +26,7 @@ use anyhow::Error;
 use tauri::{AppHandle, Manager};
 use tokio::sync::watch::{Receiver, Sender};
 
+use crate::data_locations::{DataComponent, DataLocations};
 use crate::progress_trackers::ProgressStepper;
 
 use super::setup_manager::PhaseStatus;
@@ -50,8 +51,12 @@ pub trait SetupPhaseImpl {
     async fn setup_inner(&self) -> Result<(), Error>;
     async fn finalize_setup(&self) -> Result<(), Error>;
     fn get_app_handle(&self) -> &AppHandle;
+    /// Component whose data location, if relocated, replaces the default data dir for this phase.
+    fn get_data_component(&self) -> Option<DataComponent> {
+        None
+    }
     fn get_app_dirs(&self) -> Result<(PathBuf, PathBuf, PathBuf), Error> {
-        let data_dir = self
+        let default_data_dir = self
             .get_app_handle()
             .path()
             .app_local_data_dir()
@@ -66,6 +71,10 @@ pub trait SetupPhaseImpl {
             .path()
             .app_log_dir()
             .expect("Could not get log dir");
+        let data_dir = match self.get_data_component() {
+            Some(component) => DataLocations::current().resolve(component, &default_data_dir),
+            None => default_data_dir,
+        };
 
         Ok((data_dir, config_dir, log_dir))
     }
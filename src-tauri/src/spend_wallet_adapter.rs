@@ -20,163 +20,338 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use crate::coin_management::WalletOutput;
+use crate::internal_wallet::generate_password;
 use crate::port_allocator::PortAllocator;
-use crate::process_adapter::{
-    ProcessAdapter, ProcessInstance, ProcessInstanceTrait, ProcessStartupSpec, StatusMonitor,
-};
-use crate::tasks_tracker::TasksTrackers;
+use crate::process_adapter::{ProcessAdapter, ProcessInstance, ProcessStartupSpec};
 use crate::utils::file_utils::convert_to_string;
 use crate::utils::logging_utils::setup_logging;
-use crate::{internal_wallet::InternalWallet, process_adapter::HealthStatus};
-use anyhow::Error;
+use crate::wallet_adapter::{
+    connect_wallet_grpc, fetch_completed_transactions, TransactionInfo, WalletGrpcAuth,
+    WalletGrpcClient, WalletState, WalletStatusMonitor,
+};
+use anyhow::{anyhow, Error};
 use log::info;
+use minotari_node_grpc_client::grpc::payment_recipient::PaymentType;
+use minotari_node_grpc_client::grpc::{
+    CancelTransactionRequest, CoinJoinRequest, CoinSplitRequest, Empty, GetTransactionInfoRequest,
+    GetUnspentOutputsRequest, PaymentRecipient, RevalidateRequest, TransferRequest, UserPaymentId,
+};
 use std::collections::HashMap;
-use std::path::PathBuf;
-use std::str::FromStr;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
 use tari_common::configuration::Network;
-use tari_core::transactions::tari_amount::{MicroMinotari, Minotari};
+use tari_common_types::tari_address::TariAddress;
+use tari_core::transactions::tari_amount::MicroMinotari;
 use tari_crypto::ristretto::RistrettoPublicKey;
-use tari_shutdown::{Shutdown, ShutdownSignal};
+use tari_shutdown::Shutdown;
 use tari_utilities::hex::Hex;
-use tonic::async_trait;
-// TODO: Ensure we actually need this
-// #[cfg(target_os = "windows")]
-// use crate::utils::windows_setup_utils::add_firewall_rule;
+use tari_utilities::SafePassword;
+use tokio::fs;
+use tokio::sync::watch;
+
+#[cfg(target_os = "windows")]
+use crate::utils::windows_setup_utils::add_firewall_rule;
 
 const LOG_TARGET: &str = "tari::universe::spend_wallet_adapter";
+// Records which address the spend wallet database was recovered for, so it is only recovered once per seed
+const RECOVERY_MARKER_FILE: &str = "recovered_address";
+pub const DEFAULT_FEE_PER_GRAM: u64 = 5;
+const GRPC_USERNAME: &str = "universe";
 
-#[derive(Clone)]
+/// Full (spending) wallet kept separate from the view-only mining wallet. It is recovered from the seed words once
+/// and started on demand, so that sends go straight through its authenticated gRPC interface.
 pub struct SpendWalletAdapter {
     pub(crate) base_node_public_key: Option<RistrettoPublicKey>,
     pub(crate) base_node_address: Option<String>,
     pub(crate) tcp_listener_port: u16,
-    app_shutdown: Option<ShutdownSignal>,
-    data_dir: Option<PathBuf>,
-    config_dir: Option<PathBuf>,
-    log_dir: Option<PathBuf>,
-    wallet_binary: Option<PathBuf>,
+    pub(crate) grpc_port: u16,
+    pub(crate) state_broadcast: watch::Sender<Option<WalletState>>,
+    /// Encrypts the wallet database, kept in the credential store
+    pub(crate) database_password: Option<SafePassword>,
+    /// Generated again for every process start, so it only lives in the wallet's environment and in memory
+    grpc_password: Option<String>,
+    grpc_auth: WalletGrpcAuth,
 }
 
 impl SpendWalletAdapter {
-    pub fn new() -> Self {
+    pub fn new(state_broadcast: watch::Sender<Option<WalletState>>) -> Self {
         let tcp_listener_port = PortAllocator::new().assign_port_with_fallback();
+        let grpc_port = PortAllocator::new().assign_port_with_fallback();
         Self {
             base_node_address: None,
             base_node_public_key: None,
             tcp_listener_port,
-            app_shutdown: None,
-            data_dir: None,
-            config_dir: None,
-            log_dir: None,
-            wallet_binary: None,
+            grpc_port,
+            state_broadcast,
+            database_password: None,
+            grpc_password: None,
+            grpc_auth: WalletGrpcAuth::default(),
         }
     }
-}
 
-impl SpendWalletAdapter {
-    pub async fn init(
-        &mut self,
-        app_shutdown: ShutdownSignal,
-        data_dir: PathBuf,
-        config_dir: PathBuf,
-        log_dir: PathBuf,
-        wallet_binary: PathBuf,
-    ) -> Result<(), Error> {
-        info!(target: LOG_TARGET, "Initializing spend wallet adapter");
-
-        self.app_shutdown = Some(app_shutdown);
-        self.data_dir = Some(data_dir);
-        self.config_dir = Some(config_dir);
-        self.log_dir = Some(log_dir);
-        self.wallet_binary = Some(wallet_binary);
-
-        let _unused = self.erase_related_data().await;
-        std::fs::create_dir_all(self.get_working_dir())?;
+    /// Picks a fresh gRPC password for the next process start.
+    pub fn rotate_grpc_password(&mut self) -> Result<(), Error> {
+        let grpc_password = generate_password(32);
+        self.grpc_auth = WalletGrpcAuth::basic(GRPC_USERNAME, &grpc_password)?;
+        self.grpc_password = Some(grpc_password);
+        Ok(())
+    }
+
+    async fn client(&self) -> Result<WalletGrpcClient, Error> {
+        connect_wallet_grpc(self.wallet_grpc_address(), self.grpc_auth.clone()).await
+    }
+
+    pub fn working_dir(data_dir: &Path) -> PathBuf {
+        data_dir.join("spend_wallet")
+    }
+
+    fn network_dir(data_dir: &Path) -> PathBuf {
+        Self::working_dir(data_dir).join(
+            Network::get_current_or_user_setting_or_default()
+                .to_string()
+                .to_lowercase(),
+        )
+    }
+
+    fn log_config_file(log_dir: &Path) -> PathBuf {
+        log_dir
+            .join("spend_wallet")
+            .join("configs")
+            .join("log4rs_config_spend_wallet.yml")
+    }
+
+    pub fn setup_logging(log_dir: &Path) -> Result<(), Error> {
         setup_logging(
-            &self.get_log_config_file(),
-            &self.get_log_dir(),
+            &Self::log_config_file(log_dir),
+            log_dir,
             include_str!("../log4rs/spend_wallet_sample.yml"),
         )?;
+        Ok(())
+    }
+
+    pub async fn is_recovered_for(data_dir: &Path, address: &TariAddress) -> bool {
+        match fs::read_to_string(Self::network_dir(data_dir).join(RECOVERY_MARKER_FILE)).await {
+            Ok(recovered_address) => recovered_address.trim() == address.to_base58(),
+            Err(_) => false,
+        }
+    }
+
+    pub async fn mark_recovered_for(data_dir: &Path, address: &TariAddress) -> Result<(), Error> {
+        let network_dir = Self::network_dir(data_dir);
+        fs::create_dir_all(&network_dir).await?;
+        fs::write(network_dir.join(RECOVERY_MARKER_FILE), address.to_base58()).await?;
+        Ok(())
+    }
 
+    pub async fn erase_network_data(data_dir: &Path) -> Result<(), Error> {
+        let network_dir = Self::network_dir(data_dir);
+        if fs::try_exists(&network_dir).await? {
+            fs::remove_dir_all(network_dir).await?;
+        }
         Ok(())
     }
 
+    /// Builds a one-off `--recovery --auto-exit` run that restores the wallet database from the seed words.
+    /// This is the only time the seed words are handed to the wallet process.
+    pub fn recovery_instance(
+        &self,
+        data_dir: PathBuf,
+        log_dir: PathBuf,
+        binary_version_path: PathBuf,
+        seed_words: String,
+    ) -> Result<ProcessInstance, Error> {
+        let mut args = self.get_shared_args(&data_dir, &log_dir)?;
+        args.push("--auto-exit".to_string());
+        args.push("--recovery".to_string());
+
+        let mut envs = self.get_envs()?;
+        envs.insert("MINOTARI_WALLET_SEED_WORDS".to_string(), seed_words);
+
+        Ok(ProcessInstance {
+            shutdown: Shutdown::new(),
+            handle: None,
+            startup_spec: ProcessStartupSpec {
+                file_path: binary_version_path,
+                envs: Some(envs),
+                args,
+                pid_file_name: "spend_wallet_recovery.pid".to_string(),
+                data_dir,
+                name: "spend_wallet_recovery".to_string(),
+            },
+        })
+    }
+
+    /// Submits a one-sided payment to a stealth address and returns the wallet's transaction id without waiting
+    /// for it to be broadcast or mined.
     pub async fn send_one_sided_to_stealth_address(
-        &mut self,
-        _amount: String,
-        destination: String,
+        &self,
+        amount: MicroMinotari,
+        destination: &TariAddress,
         payment_id: Option<String>,
-    ) -> Result<(), Error> {
-        let seed_words = self.get_seed_words(self.get_config_dir()).await?;
-        let t_amount = Minotari::from_str(_amount.as_str())?;
-        let converted_amount = MicroMinotari::from(t_amount);
-        let amount = converted_amount.to_string();
-        let commands = vec![
-            ExecutionCommand::new("recovery")
-                .with_extra_args(vec!["--recovery".to_string()])
-                .with_extra_envs(HashMap::from([(
-                    "MINOTARI_WALLET_SEED_WORDS".to_string(),
-                    seed_words.clone(),
-                )])),
-            ExecutionCommand::new("sync").with_extra_args(vec!["sync".to_string()]),
-            ExecutionCommand::new("send-one-sided").with_extra_args({
-                let mut args = vec!["send-one-sided-to-stealth-address".to_string()];
-                if let Some(id) = payment_id {
-                    args.extend(vec!["--payment-id".to_string(), id]);
-                }
-                args.extend(vec![amount, destination]);
-                args
+        fee_per_gram: u64,
+    ) -> Result<u64, Error> {
+        let mut client = self.client().await?;
+        let recipient = PaymentRecipient {
+            address: destination.to_base58(),
+            amount: amount.as_u64(),
+            fee_per_gram,
+            payment_type: PaymentType::OneSidedToStealthAddress as i32,
+            user_payment_id: payment_id.map(|id| UserPaymentId {
+                utf8_string: id,
+                ..Default::default()
             }),
-        ];
-
-        for command in commands {
-            let (mut instance, _monitor) = self.spawn(
-                self.get_data_dir(),
-                self.get_config_dir(),
-                self.get_log_dir(),
-                self.get_wallet_binary(),
-                // TODO: Check if this is correct
-                false,
-            )?;
-
-            instance.startup_spec.args.extend(command.extra_args);
-            instance
-                .startup_spec
-                .envs
-                .get_or_insert_with(HashMap::new)
-                .extend(command.extra_envs);
-
-            instance
-                .start(
-                    TasksTrackers::current()
-                        .wallet_phase
-                        .get_task_tracker()
-                        .await,
-                )
-                .await?;
-            let exit_code = instance.wait().await?;
-
-            if exit_code != 0 {
-                let _unused = self.erase_related_data().await;
-                return Err(anyhow::anyhow!(
-                    "Command '{}' failed with exit code: {}",
-                    command.name,
-                    exit_code
-                ));
-            }
+            ..Default::default()
+        };
+        let response = client
+            .transfer(TransferRequest {
+                recipients: vec![recipient],
+            })
+            .await?
+            .into_inner();
+        let result = response
+            .results
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("Spend wallet returned no transfer result"))?;
+        if !result.is_success {
+            return Err(anyhow!("Transfer failed: {}", result.failure_message));
         }
+        info!(target: LOG_TARGET, "Submitted one-sided transaction {} to {}", result.transaction_id, destination);
+        Ok(result.transaction_id)
+    }
+
+    pub async fn get_transactions(&self, tx_ids: Vec<u64>) -> Result<Vec<TransactionInfo>, Error> {
+        let mut client = self.client().await?;
+        let response = client
+            .get_transaction_info(GetTransactionInfoRequest {
+                transaction_ids: tx_ids,
+            })
+            .await?
+            .into_inner();
+        Ok(response
+            .transactions
+            .into_iter()
+            .map(TransactionInfo::from_grpc)
+            .collect())
+    }
+
+    pub async fn get_completed_transactions(&self) -> Result<Vec<TransactionInfo>, Error> {
+        Ok(
+            fetch_completed_transactions(self.wallet_grpc_address(), self.grpc_auth.clone())
+                .await?,
+        )
+    }
 
-        self.erase_related_data().await?;
+    pub async fn cancel_transaction(&self, tx_id: u64) -> Result<(), Error> {
+        let mut client = self.client().await?;
+        let response = client
+            .cancel_transaction(CancelTransactionRequest { tx_id })
+            .await?
+            .into_inner();
+        if !response.is_success {
+            return Err(anyhow!(
+                "Could not cancel transaction {}: {}",
+                tx_id,
+                response.failure_message
+            ));
+        }
         Ok(())
     }
 
-    async fn erase_related_data(&self) -> Result<(), Error> {
-        std::fs::remove_dir_all(self.get_working_dir())?;
+    /// Marks every transaction for revalidation against the base node. Transactions that turn out not to be in the
+    /// mempool or chain are picked up by the wallet's broadcast protocol again.
+    pub async fn revalidate_transactions(&self) -> Result<(), Error> {
+        let mut client = self.client().await?;
+        client
+            .revalidate_all_transactions(RevalidateRequest {})
+            .await?;
         Ok(())
     }
 
-    fn get_shared_args(&self) -> Result<Vec<String>, Error> {
+    pub async fn get_unspent_amounts(&self) -> Result<Vec<MicroMinotari>, Error> {
+        let mut client = self.client().await?;
+        let response = client.get_unspent_amounts(Empty {}).await?.into_inner();
+        Ok(response.amount.into_iter().map(MicroMinotari).collect())
+    }
+
+    pub async fn get_unspent_outputs(&self) -> Result<Vec<WalletOutput>, Error> {
+        let mut client = self.client().await?;
+        let response = client
+            .get_unspent_outputs(GetUnspentOutputsRequest {})
+            .await?
+            .into_inner();
+        Ok(response
+            .outputs
+            .into_iter()
+            .map(|output| WalletOutput {
+                commitment: output.commitment.to_hex(),
+                value: MicroMinotari(output.value),
+                maturity: output.maturity,
+                mined_height: (output.mined_height > 0).then_some(output.mined_height),
+            })
+            .collect())
+    }
+
+    /// Spends exactly the outputs with the given hex commitments into a single output back to the wallet
+    pub async fn coin_join(&self, commitments: &[String], fee_per_gram: u64) -> Result<u64, Error> {
+        let commitments = commitments
+            .iter()
+            .map(|commitment| {
+                Vec::<u8>::from_hex(commitment)
+                    .map_err(|_| anyhow!("Invalid output commitment {}", commitment))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let input_count = commitments.len();
+        let mut client = self.client().await?;
+        let response = client
+            .coin_join(CoinJoinRequest {
+                commitments,
+                fee_per_gram,
+            })
+            .await?
+            .into_inner();
+        info!(target: LOG_TARGET, "Submitted coin join transaction {} ({} inputs)", response.tx_id, input_count);
+        Ok(response.tx_id)
+    }
+
+    /// Spends enough of the wallet's outputs to create `split_count` new outputs of `amount_per_split` each, paying
+    /// back to itself
+    pub async fn coin_split(
+        &self,
+        amount_per_split: MicroMinotari,
+        split_count: u64,
+        fee_per_gram: u64,
+    ) -> Result<u64, Error> {
+        let mut client = self.client().await?;
+        let response = client
+            .coin_split(CoinSplitRequest {
+                amount_per_split: amount_per_split.as_u64(),
+                split_count,
+                fee_per_gram,
+                ..Default::default()
+            })
+            .await?
+            .into_inner();
+        info!(target: LOG_TARGET, "Submitted coin split transaction {} ({} x {})", response.tx_id, split_count, amount_per_split);
+        Ok(response.tx_id)
+    }
+
+    pub fn wallet_grpc_address(&self) -> String {
+        format!("http://127.0.0.1:{}", self.grpc_port)
+    }
+
+    fn get_envs(&self) -> Result<HashMap<String, String>, Error> {
+        let database_password = self
+            .database_password
+            .as_ref()
+            .ok_or_else(|| anyhow!("Spend wallet password not set"))?;
+        Ok(HashMap::from([(
+            "MINOTARI_WALLET_PASSWORD".to_string(),
+            String::from_utf8(database_password.reveal().clone())?,
+        )]))
+    }
+
+    fn get_shared_args(&self, data_dir: &Path, log_dir: &Path) -> Result<Vec<String>, Error> {
         let network = Network::get_current_or_user_setting_or_default();
         let dns_seeds = match network {
             Network::MainNet => "ip4.seeds.tari.com,ip6.seeds.tari.com".to_string(),
@@ -190,12 +365,11 @@ impl SpendWalletAdapter {
 
         let shared_args = vec![
             "-b".to_string(),
-            convert_to_string(self.get_working_dir())?,
+            convert_to_string(Self::working_dir(data_dir))?,
             "--non-interactive-mode".to_string(),
-            "--auto-exit".to_string(),
             format!(
                 "--log-config={}",
-                convert_to_string(self.get_log_config_file())?
+                convert_to_string(Self::log_config_file(log_dir))?
             ),
             "-p".to_string(),
             format!(
@@ -203,10 +377,10 @@ impl SpendWalletAdapter {
                 self.base_node_public_key
                     .as_ref()
                     .map(|k| k.to_hex())
-                    .expect("Base node public key not set"),
+                    .ok_or_else(|| anyhow!("Base node public key not set"))?,
                 self.base_node_address
                     .as_ref()
-                    .expect("Base node address not set")
+                    .ok_or_else(|| anyhow!("Base node address not set"))?
             ),
             "-p".to_string(),
             "wallet.p2p.transport.type=tcp".to_string(),
@@ -226,85 +400,69 @@ impl SpendWalletAdapter {
 
         Ok(shared_args)
     }
-
-    async fn get_seed_words(&self, config_path: PathBuf) -> Result<String, Error> {
-        let internal_wallet = InternalWallet::load_or_create(config_path).await?;
-        let seed_words = internal_wallet.decrypt_seed_words().await?;
-        Ok(seed_words.join(" ").reveal().to_string())
-    }
-
-    fn get_config_dir(&self) -> PathBuf {
-        self.config_dir.clone().expect("Config dir not defined")
-    }
-
-    fn get_wallet_binary(&self) -> PathBuf {
-        self.wallet_binary
-            .clone()
-            .expect("Wallet binary not defined")
-    }
-
-    fn get_data_dir(&self) -> PathBuf {
-        self.data_dir.clone().expect("Data dir not defined")
-    }
-
-    fn get_log_dir(&self) -> PathBuf {
-        self.log_dir.clone().expect("Log dir not defined")
-    }
-
-    fn get_working_dir(&self) -> PathBuf {
-        self.get_data_dir().join("spend_wallet")
-    }
-
-    fn get_log_config_file(&self) -> PathBuf {
-        self.get_log_dir()
-            .join("spend_wallet")
-            .join("configs")
-            .join("log4rs_config_spend_wallet.yml")
-    }
-}
-
-#[derive(Clone)]
-pub struct DummyStatusMonitor;
-
-#[async_trait]
-impl StatusMonitor for DummyStatusMonitor {
-    // Question: What actually should be here?
-    async fn check_health(&self, _uptime: Duration, _timeout_duration: Duration) -> HealthStatus {
-        HealthStatus::Healthy
-    }
 }
 
 impl ProcessAdapter for SpendWalletAdapter {
-    type StatusMonitor = DummyStatusMonitor;
+    type StatusMonitor = WalletStatusMonitor;
     type ProcessInstance = ProcessInstance;
 
     fn spawn_inner(
         &self,
-        base_folder: PathBuf,
-        _config_folder: PathBuf,
-        _log_folder: PathBuf,
+        data_dir: PathBuf,
+        _config_dir: PathBuf,
+        log_dir: PathBuf,
         binary_version_path: PathBuf,
         _is_first_start: bool,
-    ) -> Result<(Self::ProcessInstance, Self::StatusMonitor), anyhow::Error> {
-        let shared_args = self.get_shared_args()?;
-        let envs = HashMap::from([(
-            "MINOTARI_WALLET_PASSWORD".to_string(),
-            "asjhfahjajhdfvarehnavrahuyg28397823yauifh24@@$@84y8".to_string(),
-        )]);
+    ) -> Result<(Self::ProcessInstance, Self::StatusMonitor), Error> {
+        info!(target: LOG_TARGET, "Starting spend wallet");
+        std::fs::create_dir_all(Self::working_dir(&data_dir))?;
+
+        let grpc_password = self
+            .grpc_password
+            .clone()
+            .ok_or_else(|| anyhow!("Spend wallet gRPC password not set"))?;
+        // Passed through the environment rather than `-p` so the password stays out of the process list
+        let mut envs = self.get_envs()?;
+        envs.insert(
+            "TARI_WALLET__GRPC_AUTHENTICATION__USERNAME".to_string(),
+            GRPC_USERNAME.to_string(),
+        );
+        envs.insert(
+            "TARI_WALLET__GRPC_AUTHENTICATION__PASSWORD".to_string(),
+            grpc_password,
+        );
+
+        let mut args = self.get_shared_args(&data_dir, &log_dir)?;
+        args.extend([
+            "--grpc-enabled".to_string(),
+            "--grpc-address".to_string(),
+            format!("/ip4/127.0.0.1/tcp/{}", self.grpc_port),
+        ]);
+
+        #[cfg(target_os = "windows")]
+        add_firewall_rule(
+            "minotari_console_wallet.exe".to_string(),
+            binary_version_path.clone(),
+        )?;
+
         let instance = ProcessInstance {
             shutdown: Shutdown::new(),
             handle: None,
             startup_spec: ProcessStartupSpec {
                 file_path: binary_version_path,
                 envs: Some(envs),
-                args: shared_args,
+                args,
                 pid_file_name: self.pid_file_name().to_string(),
-                data_dir: base_folder,
+                data_dir,
                 name: self.name().to_string(),
             },
         };
 
-        Ok((instance, DummyStatusMonitor))
+        Ok((
+            instance,
+            WalletStatusMonitor::new(self.grpc_port, self.state_broadcast.clone())
+                .with_grpc_auth(self.grpc_auth.clone()),
+        ))
     }
 
     fn name(&self) -> &str {
@@ -315,31 +473,3 @@ impl ProcessAdapter for SpendWalletAdapter {
         "spend_wallet.pid"
     }
 }
-
-#[derive(Debug)]
-struct ExecutionCommand {
-    name: String,
-    extra_args: Vec<String>,
-    extra_envs: HashMap<String, String>,
-}
-
-impl ExecutionCommand {
-    fn new(name: impl Into<String>) -> Self {
-        Self {
-            name: name.into(),
-            extra_args: Vec::new(),
-            extra_envs: HashMap::new(),
-        }
-    }
-
-    fn with_extra_args(mut self, extra_args: Vec<String>) -> Self {
-        self.extra_args.extend(extra_args);
-        self
-    }
-
-    #[allow(dead_code)]
-    fn with_extra_envs(mut self, extra_envs: HashMap<String, String>) -> Self {
-        self.extra_envs.extend(extra_envs);
-        self
-    }
-}
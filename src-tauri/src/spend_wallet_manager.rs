@@ -20,72 +20,714 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use crate::binaries::Binaries;
-use crate::binaries::BinaryResolver;
+use crate::binaries::{Binaries, BinaryResolver};
+use crate::coin_management::{CoinOperation, CoinOperationPreview, UtxoOverview};
+use crate::credential_manager::{Credential, CredentialError, CredentialManager};
+use crate::events_manager::EventsManager;
+use crate::fee_estimation::{FeeEstimates, FeeSelection, SendSummary};
+use crate::internal_wallet::{generate_password, InternalWallet};
 use crate::node::node_manager::NodeManager;
-use crate::spend_wallet_adapter::SpendWalletAdapter;
-use anyhow::Error;
-use log::info;
+use crate::outgoing_transactions::{
+    OutgoingTransaction, OutgoingTransactionUpdate, OutgoingTransactions,
+};
+use crate::process_adapter::ProcessInstanceTrait;
+use crate::process_stats_collector::ProcessStatsCollectorBuilder;
+use crate::process_watcher::ProcessWatcher;
+use crate::spend_wallet_adapter::{SpendWalletAdapter, DEFAULT_FEE_PER_GRAM};
+use crate::tasks_tracker::TasksTrackers;
+use crate::utils::address_utils::{verify_send, verify_tari_address};
+use crate::utils::time_utils::unix_now;
+use crate::wallet_adapter::{TransactionInfo, WalletState};
+use anyhow::{anyhow, Error};
+use futures_util::future::FusedFuture;
+use log::{info, warn};
 use std::path::PathBuf;
-use tari_shutdown::ShutdownSignal;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tari_common_types::tari_address::{TariAddress, TariAddressFeatures};
+use tari_core::transactions::tari_amount::{MicroMinotari, Minotari};
+use tari_utilities::SafePassword;
+use tauri::AppHandle;
+use tokio::sync::{watch, Mutex, RwLock};
 
 const LOG_TARGET: &str = "tari::universe::spend_wallet_manager";
+const SYNC_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+const STATUS_POLL_INTERVAL: Duration = Duration::from_secs(10);
+// Revalidation touches every transaction in the wallet, so stuck transactions trigger it at most this often
+const AUTO_REBROADCAST_INTERVAL: Duration = Duration::from_secs(30 * 60);
+// The spend wallet holds the spend keys, so it only runs while something needs it
+const IDLE_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10 * 60);
 
 pub struct SpendWalletManager {
-    adapter: SpendWalletAdapter,
+    watcher: Arc<RwLock<ProcessWatcher<SpendWalletAdapter>>>,
     node_manager: NodeManager,
+    outgoing_transactions: Arc<RwLock<OutgoingTransactions>>,
+    last_rebroadcast: Arc<Mutex<Option<Instant>>>,
+    // Kept so the spend wallet can be started on demand, `None` while the active wallet cannot spend
+    start_paths: Arc<RwLock<Option<SpendWalletStartPaths>>>,
+    last_used: Arc<Mutex<Instant>>,
+}
+
+#[derive(Clone)]
+struct SpendWalletStartPaths {
+    base_path: PathBuf,
+    config_path: PathBuf,
+    log_path: PathBuf,
 }
 
 impl Clone for SpendWalletManager {
     fn clone(&self) -> Self {
         Self {
-            adapter: self.adapter.clone(),
+            watcher: self.watcher.clone(),
             node_manager: self.node_manager.clone(),
+            outgoing_transactions: self.outgoing_transactions.clone(),
+            last_rebroadcast: self.last_rebroadcast.clone(),
+            start_paths: self.start_paths.clone(),
+            last_used: self.last_used.clone(),
         }
     }
 }
 
 impl SpendWalletManager {
-    pub fn new(node_manager: NodeManager) -> Self {
-        let adapter = SpendWalletAdapter::new();
+    pub fn new(
+        node_manager: NodeManager,
+        stats_collector: &mut ProcessStatsCollectorBuilder,
+    ) -> Self {
+        let (state_broadcast, _) = watch::channel::<Option<WalletState>>(None);
+        let adapter = SpendWalletAdapter::new(state_broadcast);
+        let process_watcher = ProcessWatcher::new(adapter, stats_collector.take_spend_wallet());
 
         Self {
-            adapter,
+            watcher: Arc::new(RwLock::new(process_watcher)),
             node_manager,
+            outgoing_transactions: Arc::new(RwLock::new(OutgoingTransactions::default())),
+            last_rebroadcast: Arc::new(Mutex::new(None)),
+            start_paths: Arc::new(RwLock::new(None)),
+            last_used: Arc::new(Mutex::new(Instant::now())),
         }
     }
 
-    pub async fn init(
-        &mut self,
-        app_shutdown: ShutdownSignal,
-        base_path: PathBuf,
-        config_path: PathBuf,
-        log_path: PathBuf,
-    ) -> Result<(), Error> {
-        let binary_path = BinaryResolver::current()
+    /// Remembers where the spend wallet of the active wallet lives without starting it. The process is only started
+    /// once a send or coin operation needs it.
+    pub async fn prepare(&self, base_path: PathBuf, config_path: PathBuf, log_path: PathBuf) {
+        *self.start_paths.write().await = Some(SpendWalletStartPaths {
+            base_path,
+            config_path,
+            log_path,
+        });
+    }
+
+    /// Used for watch-only wallets, which have no seed to spend with
+    pub async fn disable(&self) {
+        *self.start_paths.write().await = None;
+    }
+
+    /// Starts the spend wallet unless it is already running, and keeps it from being stopped as idle for a while.
+    async fn ensure_running(&self) -> Result<(), Error> {
+        *self.last_used.lock().await = Instant::now();
+        if self.watcher.read().await.is_running() {
+            return Ok(());
+        }
+        let start_paths = self
+            .start_paths
             .read()
             .await
-            .resolve_path_to_binary_files(Binaries::Wallet)
+            .clone()
+            .ok_or_else(|| anyhow!("The active wallet cannot spend"))?;
+        self.ensure_started(start_paths).await
+    }
+
+    /// Starts the spend wallet, recovering its database from the seed words first if it has never been created for
+    /// the current wallet address or with the current database password.
+    async fn ensure_started(&self, start_paths: SpendWalletStartPaths) -> Result<(), Error> {
+        let SpendWalletStartPaths {
+            base_path,
+            config_path,
+            log_path,
+        } = start_paths;
+        let shutdown_signal = TasksTrackers::current().wallet_phase.get_signal().await;
+        let task_tracker = TasksTrackers::current()
+            .wallet_phase
+            .get_task_tracker()
+            .await;
+
+        self.node_manager.wait_ready().await?;
+
+        let mut process_watcher = self.watcher.write().await;
+        if process_watcher.is_running()
+            || shutdown_signal.is_terminated()
+            || shutdown_signal.is_triggered()
+        {
+            return Ok(());
+        }
+
+        let (public_key, public_address) = self.node_manager.get_connection_details().await?;
+        info!(target: LOG_TARGET, "Starting spend wallet with node {:?}:{:?}", public_key, public_address);
+        process_watcher.adapter.base_node_public_key = Some(public_key);
+        process_watcher.adapter.base_node_address = Some(public_address);
+        SpendWalletAdapter::setup_logging(&log_path)?;
+
+        let (database_password, is_new_password) =
+            load_or_create_database_password(config_path.clone()).await?;
+        process_watcher.adapter.database_password = Some(database_password);
+        process_watcher.adapter.rotate_grpc_password()?;
+
+        let internal_wallet = InternalWallet::load_or_create(config_path.clone()).await?;
+        let tari_address = internal_wallet.get_tari_address();
+        // A database created with another password cannot be opened, so it is recovered again
+        if is_new_password || !SpendWalletAdapter::is_recovered_for(&base_path, &tari_address).await
+        {
+            info!(target: LOG_TARGET, "Spend wallet has not been recovered for this seed yet, recovering");
+            SpendWalletAdapter::erase_network_data(&base_path).await?;
+
+            let binary_path = BinaryResolver::current()
+                .read()
+                .await
+                .resolve_path_to_binary_files(Binaries::Wallet)
+                .await?;
+            let seed_words = internal_wallet
+                .decrypt_seed_words()
+                .await?
+                .join(" ")
+                .reveal()
+                .to_string();
+            let mut recovery = process_watcher.adapter.recovery_instance(
+                base_path.clone(),
+                log_path.clone(),
+                binary_path,
+                seed_words,
+            )?;
+            recovery.start(task_tracker.clone()).await?;
+            let exit_code = recovery.wait().await?;
+            if exit_code != 0 {
+                let _unused = SpendWalletAdapter::erase_network_data(&base_path).await;
+                return Err(anyhow!(
+                    "Spend wallet recovery failed with exit code: {}",
+                    exit_code
+                ));
+            }
+            SpendWalletAdapter::mark_recovered_for(&base_path, &tari_address).await?;
+        }
+
+        process_watcher
+            .start(
+                base_path,
+                config_path,
+                log_path,
+                Binaries::Wallet,
+                shutdown_signal,
+                task_tracker,
+            )
             .await?;
-        self.adapter
-            .init(app_shutdown, base_path, config_path, log_path, binary_path)
+        process_watcher.wait_ready().await?;
+        let transactions = process_watcher.adapter.get_completed_transactions().await;
+        drop(process_watcher);
+
+        match transactions {
+            Ok(transactions) => {
+                self.outgoing_transactions
+                    .write()
+                    .await
+                    .apply(transactions, unix_now());
+            }
+            Err(e) => {
+                warn!(target: LOG_TARGET, "Could not load outgoing transactions: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Stops the spend wallet once it has not been used for a while and none of its transactions still need
+    /// watching.
+    async fn stop_if_idle(&self) -> Result<(), Error> {
+        let has_unsettled = !self
+            .outgoing_transactions
+            .read()
             .await
+            .unsettled_ids()
+            .is_empty();
+        if !is_idle(self.last_used.lock().await.elapsed(), has_unsettled) {
+            return Ok(());
+        }
+        let mut process_watcher = self.watcher.write().await;
+        if process_watcher.is_running() {
+            info!(target: LOG_TARGET, "Stopping idle spend wallet");
+            process_watcher.stop().await?;
+        }
+        Ok(())
     }
 
+    /// Validates and submits a one-sided payment once the spend wallet has caught up with `target_height`,
+    /// returning the transaction id straight away. Progress is reported through [`Self::spawn_status_tracker`].
     pub async fn send_one_sided_to_stealth_address(
-        &mut self,
+        &self,
+        app: &AppHandle,
         amount: String,
         destination: String,
         payment_id: Option<String>,
-    ) -> Result<(), Error> {
-        self.node_manager.wait_ready().await?;
-        let (public_key, public_address) = self.node_manager.get_connection_details().await?;
-        self.adapter.base_node_public_key = Some(public_key.clone());
-        self.adapter.base_node_address = Some(public_address.clone());
-        info!(target: LOG_TARGET, "[send_one_sided_to_stealth_address] with node {:?}:{:?}", public_key, public_address);
+        fee: Option<FeeSelection>,
+        target_height: u64,
+    ) -> Result<u64, Error> {
+        let (amount, destination) = Self::parse_send(&amount, &destination)?;
+        let fee_per_gram = self.resolve_fee_per_gram(fee).await?;
+
+        self.ensure_running().await?;
+        self.wait_for_scan_to_height(target_height).await?;
+
+        let process_watcher = self.watcher.read().await;
+        let tx_id = process_watcher
+            .adapter
+            .send_one_sided_to_stealth_address(amount, &destination, payment_id, fee_per_gram)
+            .await?;
+        let submitted = process_watcher.adapter.get_transactions(vec![tx_id]).await;
+        drop(process_watcher);
+
+        match submitted {
+            Ok(transactions) => self.apply_and_emit(app, transactions).await,
+            Err(e) => {
+                warn!(target: LOG_TARGET, "Could not read back submitted transaction {}: {}", tx_id, e);
+            }
+        }
+
+        Ok(tx_id)
+    }
+
+    /// Dry run of [`Self::send_one_sided_to_stealth_address`]: validates the send and estimates its fee and change
+    /// from the wallet's current outputs without submitting anything.
+    pub async fn preview_send(
+        &self,
+        amount: String,
+        destination: String,
+        fee: Option<FeeSelection>,
+    ) -> Result<SendSummary, Error> {
+        let (amount, _destination) = Self::parse_send(&amount, &destination)?;
+        let fee_per_gram = self.resolve_fee_per_gram(fee).await?;
+        let unspent = self.get_unspent_amounts().await?;
+        SendSummary::new(&unspent, amount, fee_per_gram)
+    }
+
+    pub async fn get_fee_estimates(&self) -> Result<FeeEstimates, Error> {
+        let mempool = self.node_manager.get_mempool_stats().await?;
+        Ok(FeeEstimates::from_mempool(mempool))
+    }
+
+    fn parse_send(amount: &str, destination: &str) -> Result<(MicroMinotari, TariAddress), Error> {
+        let amount = MicroMinotari::from(Minotari::from_str(amount)?);
+        verify_send(destination.to_string(), TariAddressFeatures::ONE_SIDED)
+            .map_err(|e| anyhow!(e))?;
+        let destination = verify_tari_address(destination).map_err(|e| anyhow!(e))?;
+        Ok((amount, destination))
+    }
+
+    /// Sends without an explicit fee keep the default fee-per-gram. Presets fall back to it too when the node
+    /// cannot report its mempool.
+    async fn resolve_fee_per_gram(&self, fee: Option<FeeSelection>) -> Result<u64, Error> {
+        let Some(fee) = fee else {
+            return Ok(DEFAULT_FEE_PER_GRAM);
+        };
+        let fee = fee.validate()?;
+        let estimates = match fee {
+            FeeSelection::Custom { .. } => None,
+            FeeSelection::Priority { .. } => {
+                FeeEstimates::or_fallback(self.get_fee_estimates().await)
+            }
+        };
+        Ok(fee.fee_per_gram(estimates.as_ref()))
+    }
+
+    async fn wait_for_scan_to_height(&self, target_height: u64) -> Result<(), Error> {
+        let process_watcher = self.watcher.read().await;
+        if !process_watcher.is_running() {
+            return Err(anyhow!("Spend wallet is not running"));
+        }
+        let mut state_receiver = process_watcher.adapter.state_broadcast.subscribe();
+        drop(process_watcher);
+
+        let wait_for_height = async {
+            loop {
+                let scanned_height = state_receiver
+                    .borrow_and_update()
+                    .as_ref()
+                    .map(|state| state.scanned_height);
+                if scanned_height.is_some_and(|height| height >= target_height) {
+                    return Ok(());
+                }
+                if state_receiver.changed().await.is_err() {
+                    return Err(anyhow!("Spend wallet stopped while syncing"));
+                }
+            }
+        };
+        tokio::time::timeout(SYNC_TIMEOUT, wait_for_height)
+            .await
+            .map_err(|_| anyhow!("Timed out waiting for the spend wallet to sync"))?
+    }
+
+    pub async fn get_outgoing_transactions(&self) -> Vec<OutgoingTransaction> {
+        self.outgoing_transactions.read().await.list()
+    }
+
+    /// Cancels an outgoing transaction that has not been mined yet.
+    pub async fn cancel_transaction(&self, app: &AppHandle, tx_id: u64) -> Result<(), Error> {
+        self.ensure_unmined(tx_id).await?;
+        self.ensure_running().await?;
+        let process_watcher = self.watcher.read().await;
+        process_watcher.adapter.cancel_transaction(tx_id).await?;
+        let transactions = process_watcher
+            .adapter
+            .get_transactions(vec![tx_id])
+            .await?;
+        drop(process_watcher);
+
+        info!(target: LOG_TARGET, "Cancelled outgoing transaction {}", tx_id);
+        self.apply_and_emit(app, transactions).await;
+        Ok(())
+    }
+
+    /// Asks the spend wallet to revalidate all of its transactions, which broadcasts every unmined one again. The
+    /// wallet has no way to rebroadcast a single transaction.
+    pub async fn revalidate_transactions(&self) -> Result<(), Error> {
+        if self
+            .outgoing_transactions
+            .read()
+            .await
+            .list()
+            .iter()
+            .all(|tx| !tx.state.is_unmined())
+        {
+            return Err(anyhow!("No outgoing transaction is waiting to be mined"));
+        }
+        self.ensure_running().await?;
+        self.watcher
+            .read()
+            .await
+            .adapter
+            .revalidate_transactions()
+            .await?;
+        *self.last_rebroadcast.lock().await = Some(Instant::now());
+        info!(target: LOG_TARGET, "Revalidating outgoing transactions");
+        Ok(())
+    }
+
+    async fn get_unspent_amounts(&self) -> Result<Vec<MicroMinotari>, Error> {
+        self.ensure_running().await?;
+        self.watcher
+            .read()
+            .await
+            .adapter
+            .get_unspent_amounts()
+            .await
+    }
+
+    /// Lists the spend wallet's unspent outputs with their status at `tip_height`, along with how many of them are
+    /// dust. Without an explicit `dust_threshold`, outputs are dust when spending them costs over 1% of their value.
+    pub async fn get_utxo_overview(
+        &self,
+        tip_height: u64,
+        dust_threshold: Option<MicroMinotari>,
+    ) -> Result<UtxoOverview, Error> {
+        self.ensure_running().await?;
+        let outputs = self
+            .watcher
+            .read()
+            .await
+            .adapter
+            .get_unspent_outputs()
+            .await?;
+        Ok(UtxoOverview::new(
+            outputs,
+            tip_height,
+            MicroMinotari(DEFAULT_FEE_PER_GRAM),
+            dust_threshold,
+        ))
+    }
+
+    pub async fn preview_coin_operation(
+        &self,
+        operation: CoinOperation,
+        tip_height: u64,
+        fee_per_gram: Option<u64>,
+    ) -> Result<CoinOperationPreview, Error> {
+        let overview = self.get_utxo_overview(tip_height, None).await?;
+        CoinOperationPreview::new(
+            &overview,
+            operation,
+            MicroMinotari(fee_per_gram.unwrap_or(DEFAULT_FEE_PER_GRAM)),
+        )
+    }
+
+    /// Joins the previewed outputs by commitment, or splits through a coin split back to the wallet itself. The
+    /// preview is recomputed from the current outputs so the submission always matches what the wallet holds, and
+    /// the transaction is then tracked like any outgoing one.
+    pub async fn execute_coin_operation(
+        &self,
+        app: &AppHandle,
+        operation: CoinOperation,
+        tip_height: u64,
+        fee_per_gram: Option<u64>,
+    ) -> Result<u64, Error> {
+        let preview = self
+            .preview_coin_operation(operation, tip_height, fee_per_gram)
+            .await?;
+        let process_watcher = self.watcher.read().await;
+        let tx_id = match preview.operation {
+            CoinOperation::Join => {
+                process_watcher
+                    .adapter
+                    .coin_join(&preview.commitments, preview.fee_per_gram.as_u64())
+                    .await?
+            }
+            CoinOperation::Split { .. } => {
+                process_watcher
+                    .adapter
+                    .coin_split(
+                        preview.amount_per_output,
+                        preview.output_count,
+                        preview.fee_per_gram.as_u64(),
+                    )
+                    .await?
+            }
+        };
+        let submitted = process_watcher.adapter.get_transactions(vec![tx_id]).await;
+        drop(process_watcher);
+
+        match submitted {
+            Ok(transactions) => self.apply_and_emit(app, transactions).await,
+            Err(e) => {
+                warn!(target: LOG_TARGET, "Could not read back coin operation transaction {}: {}", tx_id, e);
+            }
+        }
+        Ok(tx_id)
+    }
+
+    async fn ensure_unmined(&self, tx_id: u64) -> Result<(), Error> {
+        let outgoing_transactions = self.outgoing_transactions.read().await;
+        let transaction = outgoing_transactions
+            .get(tx_id)
+            .ok_or_else(|| anyhow!("Unknown outgoing transaction {}", tx_id))?;
+        if !transaction.state.is_unmined() {
+            return Err(anyhow!(
+                "Transaction {} is already {:?}",
+                tx_id,
+                transaction.state
+            ));
+        }
+        Ok(())
+    }
+
+    /// Polls the outgoing transactions that have not settled yet while the spend wallet runs, emitting an event for
+    /// every state change and rebroadcasting transactions that get stuck. Stops the spend wallet once it is idle.
+    pub async fn spawn_status_tracker(&self, app: AppHandle) {
+        let manager = self.clone();
+        let mut shutdown_signal = TasksTrackers::current().wallet_phase.get_signal().await;
+        TasksTrackers::current()
+            .wallet_phase
+            .get_task_tracker()
+            .await
+            .spawn(async move {
+                // Start from scratch, the wallet phase may have been restarted for another wallet. The list is loaded
+                // again when the spend wallet starts.
+                *manager.outgoing_transactions.write().await = OutgoingTransactions::default();
+
+                let mut interval = tokio::time::interval(STATUS_POLL_INTERVAL);
+                loop {
+                    tokio::select! {
+                        _ = interval.tick() => {
+                            if !manager.watcher.read().await.is_running() {
+                                continue;
+                            }
+                            if let Err(e) = manager.refresh_outgoing_transactions(&app).await {
+                                warn!(target: LOG_TARGET, "Could not refresh outgoing transactions: {}", e);
+                            }
+                            if let Err(e) = manager.stop_if_idle().await {
+                                warn!(target: LOG_TARGET, "Could not stop idle spend wallet: {}", e);
+                            }
+                        }
+                        _ = shutdown_signal.wait() => {
+                            info!(target: LOG_TARGET, "Stopping outgoing transaction status tracker");
+                            break;
+                        }
+                    }
+                }
+            });
+    }
+
+    async fn refresh_outgoing_transactions(&self, app: &AppHandle) -> Result<(), Error> {
+        let unsettled = self.outgoing_transactions.read().await.unsettled_ids();
+        if unsettled.is_empty() {
+            return Ok(());
+        }
+
+        let transactions = self
+            .watcher
+            .read()
+            .await
+            .adapter
+            .get_transactions(unsettled)
+            .await?;
+        self.apply_and_emit(app, transactions).await;
+
+        let stuck = self
+            .outgoing_transactions
+            .read()
+            .await
+            .list()
+            .into_iter()
+            .find(|tx| tx.is_stuck);
+        if let Some(stuck) = stuck {
+            let recently_rebroadcast = self
+                .last_rebroadcast
+                .lock()
+                .await
+                .is_some_and(|at| at.elapsed() < AUTO_REBROADCAST_INTERVAL);
+            if !recently_rebroadcast {
+                warn!(target: LOG_TARGET, "Outgoing transaction {} is stuck, revalidating", stuck.transaction.tx_id);
+                self.revalidate_transactions().await?;
+            }
+        }
+        Ok(())
+    }
 
-        self.adapter
-            .send_one_sided_to_stealth_address(amount, destination, payment_id)
+    async fn apply_and_emit(&self, app: &AppHandle, transactions: Vec<TransactionInfo>) {
+        let updates = self
+            .outgoing_transactions
+            .write()
             .await
+            .apply(transactions, unix_now());
+        for OutgoingTransactionUpdate {
+            transaction,
+            previous_state,
+        } in updates
+        {
+            info!(target: LOG_TARGET, "Outgoing transaction {} is now {:?} (was {:?}, stuck: {})", transaction.transaction.tx_id, transaction.state, previous_state, transaction.is_stuck);
+            EventsManager::handle_outgoing_transaction_updated(app, transaction, previous_state)
+                .await;
+        }
+    }
+}
+
+fn is_idle(since_last_use: Duration, has_unsettled: bool) -> bool {
+    !has_unsettled && since_last_use >= IDLE_SHUTDOWN_TIMEOUT
+}
+
+/// Reads the spend wallet database password from the credential store, creating it on first use. The flag is set for
+/// a new password, which cannot open a database created before it.
+async fn load_or_create_database_password(
+    config_path: PathBuf,
+) -> Result<(SafePassword, bool), Error> {
+    let cm = CredentialManager::default_with_dir(config_path);
+    let mut credential = match cm.get_credentials().await {
+        Ok(credential) => credential,
+        Err(CredentialError::NoEntry(_)) => Credential::default(),
+        Err(e) => return Err(e.into()),
+    };
+    if let Some(password) = credential.spend_wallet_password.take() {
+        return Ok((password, false));
+    }
+    let password = generate_password(32);
+    credential.spend_wallet_password = Some(SafePassword::from(password.clone()));
+    cm.set_credentials(&credential).await?;
+    Ok((SafePassword::from(password), true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process_adapter::ProcessAdapter;
+    use tari_crypto::ristretto::RistrettoPublicKey;
+
+    const DESTINATION: &str = "f25eNHz2YnBVKHaqNuacGyDFB321RwwCnTr4vb2SjQCgDZVXyNNthc7zftQKRDu6evLjvSUD8W5akpPMdhS4HQ9kF3g";
+
+    fn adapter() -> SpendWalletAdapter {
+        let (state_broadcast, _) = watch::channel::<Option<WalletState>>(None);
+        let mut adapter = SpendWalletAdapter::new(state_broadcast);
+        adapter.base_node_public_key = Some(RistrettoPublicKey::default());
+        adapter.base_node_address = Some("/ip4/127.0.0.1/tcp/18189".to_string());
+        adapter
+    }
+
+    #[test]
+    fn parses_amount_and_one_sided_destination() {
+        let (amount, destination) = SpendWalletManager::parse_send("1.5", DESTINATION).unwrap();
+        assert_eq!(amount, MicroMinotari(1_500_000));
+        assert_eq!(destination.to_base58(), DESTINATION);
+
+        assert!(SpendWalletManager::parse_send("lots", DESTINATION).is_err());
+        assert!(SpendWalletManager::parse_send("1", "invalid_address").is_err());
+    }
+
+    #[test]
+    fn stops_only_when_idle_and_settled() {
+        assert!(!is_idle(Duration::from_secs(60), false));
+        assert!(!is_idle(IDLE_SHUTDOWN_TIMEOUT, true));
+        assert!(is_idle(IDLE_SHUTDOWN_TIMEOUT, false));
+    }
+
+    #[test]
+    fn refuses_to_start_without_passwords() {
+        let data_dir = std::env::temp_dir().join("spend_wallet_manager_no_passwords");
+        let mut adapter = adapter();
+        let spawn = |adapter: &SpendWalletAdapter| {
+            adapter.spawn_inner(
+                data_dir.clone(),
+                data_dir.clone(),
+                data_dir.clone(),
+                PathBuf::from("minotari_console_wallet"),
+                false,
+            )
+        };
+        assert!(spawn(&adapter).is_err());
+
+        adapter.database_password = Some(SafePassword::from("database"));
+        assert!(spawn(&adapter).is_err());
+
+        adapter.rotate_grpc_password().unwrap();
+        assert!(spawn(&adapter).is_ok());
+        let _unused = std::fs::remove_dir_all(&data_dir);
+    }
+
+    #[test]
+    fn passes_secrets_through_the_environment_only() {
+        let data_dir = std::env::temp_dir().join("spend_wallet_manager_secrets");
+        let mut adapter = adapter();
+        adapter.database_password = Some(SafePassword::from("database"));
+        adapter.rotate_grpc_password().unwrap();
+        let (instance, _monitor) = adapter
+            .spawn_inner(
+                data_dir.clone(),
+                data_dir.clone(),
+                data_dir.clone(),
+                PathBuf::from("minotari_console_wallet"),
+                false,
+            )
+            .unwrap();
+        let _unused = std::fs::remove_dir_all(&data_dir);
+
+        let envs = instance.startup_spec.envs.unwrap();
+        assert_eq!(envs["MINOTARI_WALLET_PASSWORD"], "database");
+        let grpc_password = &envs["TARI_WALLET__GRPC_AUTHENTICATION__PASSWORD"];
+        assert_eq!(grpc_password.len(), 32);
+        assert!(envs.contains_key("TARI_WALLET__GRPC_AUTHENTICATION__USERNAME"));
+        assert!(instance
+            .startup_spec
+            .args
+            .iter()
+            .all(|arg| !arg.contains("database") && !arg.contains(grpc_password.as_str())));
+
+        adapter.rotate_grpc_password().unwrap();
+        let (instance, _monitor) = adapter
+            .spawn_inner(
+                data_dir.clone(),
+                data_dir.clone(),
+                data_dir.clone(),
+                PathBuf::from("minotari_console_wallet"),
+                false,
+            )
+            .unwrap();
+        let _unused = std::fs::remove_dir_all(&data_dir);
+        assert_ne!(
+            &instance.startup_spec.envs.unwrap()["TARI_WALLET__GRPC_AUTHENTICATION__PASSWORD"],
+            grpc_password
+        );
     }
 }
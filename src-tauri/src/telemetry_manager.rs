@@ -617,6 +617,11 @@ async fn get_telemetry_data_inner(
         stats_collector.get_wallet_stats(),
         "wallet",
     );
+    add_process_stats(
+        &mut extra_data,
+        stats_collector.get_spend_wallet_stats(),
+        "spend_wallet",
+    );
 
     let (download_speed, upload_speed, latency) = *NetworkStatus::current()
         .get_network_speeds_receiver()
@@ -0,0 +1,353 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{collections::HashMap, path::PathBuf};
+
+use anyhow::{anyhow, Error};
+use chrono::{DateTime, FixedOffset, Local, Utc};
+use log::info;
+use serde::{Deserialize, Serialize};
+use tari_core::transactions::tari_amount::MicroMinotari;
+use tokio::io::{AsyncWrite, AsyncWriteExt, BufWriter};
+
+use crate::transaction_index::{
+    is_coinbase_status, TransactionDirection, TransactionIndex, TransactionKind, TransactionQuery,
+    MAX_PAGE_SIZE, TX_DIRECTION_INBOUND, TX_DIRECTION_OUTBOUND,
+};
+use crate::wallet_adapter::TransactionInfo;
+
+const LOG_TARGET: &str = "tari::universe::transaction_export";
+const CSV_HEADER: &str = "timestamp,tx_id,type,direction,status,amount_xtm,amount_micro_xtm,fee_xtm,fee_micro_xtm,block_height,payment_id,counterparty_address,counterparty_label";
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExportTimezone {
+    Utc,
+    Local,
+    /// Fixed offset from UTC in minutes, e.g. 120 for UTC+02:00
+    Offset {
+        minutes: i32,
+    },
+}
+
+impl ExportTimezone {
+    fn format(&self, timestamp: u64) -> Result<String, Error> {
+        let utc = DateTime::<Utc>::from_timestamp(i64::try_from(timestamp)?, 0)
+            .ok_or_else(|| anyhow!("Timestamp {} out of range", timestamp))?;
+        Ok(match self {
+            ExportTimezone::Utc => utc.to_rfc3339(),
+            ExportTimezone::Local => utc.with_timezone(&Local).to_rfc3339(),
+            ExportTimezone::Offset { minutes } => {
+                let offset = FixedOffset::east_opt(minutes * 60)
+                    .ok_or_else(|| anyhow!("Invalid timezone offset {} minutes", minutes))?;
+                utc.with_timezone(&offset).to_rfc3339()
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransactionExportRequest {
+    pub path: PathBuf,
+    pub format: ExportFormat,
+    pub timezone: ExportTimezone,
+    pub from_timestamp: Option<u64>,
+    pub to_timestamp: Option<u64>,
+    pub kind: Option<TransactionKind>,
+    pub direction: Option<TransactionDirection>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TransactionExportSummary {
+    pub path: PathBuf,
+    pub exported: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ExportRow {
+    timestamp: String,
+    tx_id: u64,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    direction: &'static str,
+    status: i32,
+    amount_xtm: String,
+    amount_micro_xtm: u64,
+    fee_xtm: String,
+    fee_micro_xtm: u64,
+    block_height: Option<u64>,
+    payment_id: String,
+    counterparty_address: String,
+    counterparty_label: Option<String>,
+}
+
+impl ExportRow {
+    fn new(
+        tx: &TransactionInfo,
+        timezone: &ExportTimezone,
+        labels: &HashMap<String, String>,
+    ) -> Result<Self, Error> {
+        let (direction, counterparty_address) = match tx.direction {
+            TX_DIRECTION_INBOUND => ("inbound", tx.source_address.clone()),
+            TX_DIRECTION_OUTBOUND => ("outbound", tx.dest_address.clone()),
+            _ => ("unknown", String::new()),
+        };
+        let is_coinbase = is_coinbase_status(tx.status);
+        Ok(Self {
+            timestamp: timezone.format(tx.timestamp)?,
+            tx_id: tx.tx_id,
+            kind: if is_coinbase { "coinbase" } else { "transfer" },
+            direction,
+            status: tx.status,
+            amount_xtm: format_xtm(tx.amount),
+            amount_micro_xtm: tx.amount.as_u64(),
+            fee_xtm: format_xtm(MicroMinotari(tx.fee)),
+            fee_micro_xtm: tx.fee,
+            block_height: (tx.mined_in_block_height > 0).then_some(tx.mined_in_block_height),
+            payment_id: tx.payment_id.clone(),
+            counterparty_label: labels.get(&counterparty_address).cloned(),
+            // Coinbase outputs have no sender worth showing
+            counterparty_address: if is_coinbase {
+                String::new()
+            } else {
+                counterparty_address
+            },
+        })
+    }
+
+    fn to_csv(&self) -> String {
+        [
+            self.timestamp.clone(),
+            self.tx_id.to_string(),
+            self.kind.to_string(),
+            self.direction.to_string(),
+            self.status.to_string(),
+            self.amount_xtm.clone(),
+            self.amount_micro_xtm.to_string(),
+            self.fee_xtm.clone(),
+            self.fee_micro_xtm.to_string(),
+            self.block_height.map(|h| h.to_string()).unwrap_or_default(),
+            csv_text(&self.payment_id),
+            csv_text(&self.counterparty_address),
+            csv_text(self.counterparty_label.as_deref().unwrap_or_default()),
+        ]
+        .join(",")
+    }
+}
+
+fn format_xtm(amount: MicroMinotari) -> String {
+    let micro = amount.as_u64();
+    format!("{}.{:06}", micro / 1_000_000, micro % 1_000_000)
+}
+
+/// Quotes free text for CSV and defuses values a spreadsheet would otherwise evaluate as a formula
+fn csv_text(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) || value.starts_with('\'') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+/// Writes rows one page at a time so the export never holds the whole history in memory
+struct ExportWriter<W: AsyncWrite + Unpin> {
+    writer: W,
+    format: ExportFormat,
+    rows_written: usize,
+}
+
+impl<W: AsyncWrite + Unpin> ExportWriter<W> {
+    async fn start(mut writer: W, format: ExportFormat) -> Result<Self, Error> {
+        match format {
+            ExportFormat::Csv => {
+                writer
+                    .write_all(format!("{}\n", CSV_HEADER).as_bytes())
+                    .await?
+            }
+            ExportFormat::Json => writer.write_all(b"[\n").await?,
+        }
+        Ok(Self {
+            writer,
+            format,
+            rows_written: 0,
+        })
+    }
+
+    async fn write_row(&mut self, row: &ExportRow) -> Result<(), Error> {
+        let line = match self.format {
+            ExportFormat::Csv => format!("{}\n", row.to_csv()),
+            ExportFormat::Json => {
+                let separator = if self.rows_written == 0 { "" } else { ",\n" };
+                format!("{}{}", separator, serde_json::to_string(row)?)
+            }
+        };
+        self.writer.write_all(line.as_bytes()).await?;
+        self.rows_written += 1;
+        Ok(())
+    }
+
+    async fn finish(mut self) -> Result<(W, usize), Error> {
+        if self.format == ExportFormat::Json {
+            self.writer.write_all(b"\n]\n").await?;
+        }
+        self.writer.flush().await?;
+        Ok((self.writer, self.rows_written))
+    }
+}
+
+/// Exports the indexed wallet history matching the request. The file is written next to the target and only
+/// moved into place once complete.
+pub async fn export_transactions(
+    request: TransactionExportRequest,
+    labels: HashMap<String, String>,
+) -> Result<TransactionExportSummary, Error> {
+    if !request.path.is_absolute() {
+        return Err(anyhow!("Export path must be absolute"));
+    }
+    if let (Some(from), Some(to)) = (request.from_timestamp, request.to_timestamp) {
+        if from > to {
+            return Err(anyhow!("Export date range start is after its end"));
+        }
+    }
+
+    let tmp_path = request.path.with_extension("partial");
+    let file = tokio::fs::File::create(&tmp_path).await?;
+    let result = async {
+        let mut writer = ExportWriter::start(BufWriter::new(file), request.format).await?;
+        let mut query = TransactionQuery {
+            limit: Some(MAX_PAGE_SIZE),
+            from_timestamp: request.from_timestamp,
+            to_timestamp: request.to_timestamp,
+            kind: request.kind,
+            direction: request.direction,
+            ..Default::default()
+        };
+        loop {
+            let page = TransactionIndex::current().query(&query).await?;
+            for tx in &page.transactions {
+                writer
+                    .write_row(&ExportRow::new(tx, &request.timezone, &labels)?)
+                    .await?;
+            }
+            match page.next_cursor {
+                Some(cursor) => query.cursor = Some(cursor),
+                None => break,
+            }
+        }
+        writer.finish().await
+    }
+    .await;
+
+    match result {
+        Ok((_, exported)) => {
+            tokio::fs::rename(&tmp_path, &request.path).await?;
+            info!(target: LOG_TARGET, "Exported {} transactions to {:?}", exported, request.path);
+            Ok(TransactionExportSummary {
+                path: request.path,
+                exported,
+            })
+        }
+        Err(e) => {
+            let _unused = tokio::fs::remove_file(&tmp_path).await;
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tx() -> TransactionInfo {
+        TransactionInfo {
+            tx_id: 7,
+            source_address: "sender".to_string(),
+            dest_address: "me".to_string(),
+            status: 6,
+            amount: MicroMinotari(12_345_678),
+            is_cancelled: false,
+            direction: TX_DIRECTION_INBOUND,
+            excess_sig: vec![],
+            fee: 25,
+            timestamp: 1_700_000_000,
+            payment_id: "=SUM(A1), \"rent\"".to_string(),
+            mined_in_block_height: 100,
+        }
+    }
+
+    #[test]
+    fn formats_amounts_and_timezones() {
+        assert_eq!(format_xtm(MicroMinotari(12_345_678)), "12.345678");
+        assert_eq!(format_xtm(MicroMinotari(5)), "0.000005");
+        assert_eq!(
+            ExportTimezone::Utc.format(1_700_000_000).unwrap(),
+            "2023-11-14T22:13:20+00:00"
+        );
+        assert_eq!(
+            ExportTimezone::Offset { minutes: 120 }
+                .format(1_700_000_000)
+                .unwrap(),
+            "2023-11-15T00:13:20+02:00"
+        );
+        assert!(ExportTimezone::Offset { minutes: 24 * 60 }
+            .format(1_700_000_000)
+            .is_err());
+    }
+
+    #[test]
+    fn csv_row_escapes_text_and_resolves_labels() {
+        let labels = HashMap::from([("sender".to_string(), "Pool, Inc".to_string())]);
+        let row = ExportRow::new(&tx(), &ExportTimezone::Utc, &labels).unwrap();
+        assert_eq!(
+            row.to_csv(),
+            "2023-11-14T22:13:20+00:00,7,transfer,inbound,6,12.345678,12345678,0.000025,25,100,\"'=SUM(A1), \"\"rent\"\"\",sender,\"Pool, Inc\""
+        );
+    }
+
+    #[tokio::test]
+    async fn writes_valid_json_array() {
+        let row = ExportRow::new(&tx(), &ExportTimezone::Utc, &HashMap::new()).unwrap();
+        let mut writer = ExportWriter::start(Vec::new(), ExportFormat::Json)
+            .await
+            .unwrap();
+        writer.write_row(&row).await.unwrap();
+        writer.write_row(&row).await.unwrap();
+        let (output, rows) = writer.finish().await.unwrap();
+        assert_eq!(rows, 2);
+        let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(parsed.as_array().unwrap().len(), 2);
+        assert_eq!(parsed[0]["amount_micro_xtm"], 12_345_678);
+        assert_eq!(parsed[0]["type"], "transfer");
+    }
+}
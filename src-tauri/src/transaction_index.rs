@@ -0,0 +1,789 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::LazyLock,
+    time::Duration,
+};
+
+use anyhow::{anyhow, Error};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use tari_common::configuration::Network;
+use tari_core::transactions::tari_amount::MicroMinotari;
+use tauri::{AppHandle, Manager};
+use tokio::{fs::OpenOptions, io::AsyncWriteExt, sync::Mutex, time::interval};
+
+use crate::{
+    data_locations::{get_component_root, DataComponent},
+    events_manager::EventsManager,
+    payment_request::PaymentRequests,
+    tasks_tracker::TasksTrackers,
+    wallet_adapter::TransactionInfo,
+    wallet_manager::{WalletManager, WalletManagerError},
+    wallet_profiles::{active_wallet_id, wallet_data_dir},
+    UniverseAppState,
+};
+
+const LOG_TARGET: &str = "tari::universe::transaction_index";
+const SYNC_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+const DEFAULT_PAGE_SIZE: usize = 20;
+pub const MAX_PAGE_SIZE: usize = 500;
+/// The journal is folded back into the snapshot once it holds more entries than this or than the index itself
+const JOURNAL_COMPACT_MIN_ENTRIES: usize = 256;
+
+// Values of `TransactionInfo::direction` and `TransactionInfo::status` as sent over the wallet gRPC
+pub const TX_DIRECTION_INBOUND: i32 = 1;
+pub const TX_DIRECTION_OUTBOUND: i32 = 2;
+pub const TX_STATUS_BROADCAST: i32 = 1;
+pub const TX_STATUS_MINED_UNCONFIRMED: i32 = 2;
+pub const TX_STATUS_MINED_CONFIRMED: i32 = 6;
+pub const TX_STATUS_REJECTED: i32 = 7;
+pub const TX_STATUS_ONE_SIDED_CONFIRMED: i32 = 9;
+pub const TX_STATUS_COINBASE: i32 = 5;
+pub const TX_STATUS_COINBASE_UNCONFIRMED: i32 = 12;
+pub const TX_STATUS_COINBASE_CONFIRMED: i32 = 13;
+pub const TX_STATUS_COINBASE_NOT_IN_BLOCKCHAIN: i32 = 14;
+
+static INSTANCE: LazyLock<TransactionIndex> = LazyLock::new(TransactionIndex::new);
+
+pub fn is_coinbase_status(status: i32) -> bool {
+    matches!(
+        status,
+        TX_STATUS_COINBASE
+            | TX_STATUS_COINBASE_UNCONFIRMED
+            | TX_STATUS_COINBASE_CONFIRMED
+            | TX_STATUS_COINBASE_NOT_IN_BLOCKCHAIN
+    )
+}
+
+/// Transactions in these states no longer change, so syncs skip them once indexed
+fn is_final(tx: &TransactionInfo) -> bool {
+    tx.is_cancelled
+        || matches!(
+            tx.status,
+            TX_STATUS_MINED_CONFIRMED
+                | TX_STATUS_REJECTED
+                | TX_STATUS_ONE_SIDED_CONFIRMED
+                | TX_STATUS_COINBASE_CONFIRMED
+        )
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionDirection {
+    Inbound,
+    Outbound,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionKind {
+    Coinbase,
+    Transfer,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TransactionQuery {
+    /// Opaque cursor from the previous page, `None` starts from the newest transaction
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+    pub direction: Option<TransactionDirection>,
+    /// Raw wallet statuses. When empty, rejected and orphaned coinbase transactions are hidden.
+    pub statuses: Vec<i32>,
+    pub from_timestamp: Option<u64>,
+    pub to_timestamp: Option<u64>,
+    pub min_amount: Option<MicroMinotari>,
+    pub max_amount: Option<MicroMinotari>,
+    pub payment_id: Option<String>,
+    pub kind: Option<TransactionKind>,
+    /// Case insensitive match against source and destination addresses and the payment id memo
+    pub search: Option<String>,
+}
+
+impl TransactionQuery {
+    fn matches(&self, tx: &TransactionInfo, search: Option<&str>) -> bool {
+        if self.statuses.is_empty() {
+            if tx.status == TX_STATUS_REJECTED || tx.status == TX_STATUS_COINBASE_NOT_IN_BLOCKCHAIN
+            {
+                return false;
+            }
+        } else if !self.statuses.contains(&tx.status) {
+            return false;
+        }
+        let direction_matches = match self.direction {
+            Some(TransactionDirection::Inbound) => tx.direction == TX_DIRECTION_INBOUND,
+            Some(TransactionDirection::Outbound) => tx.direction == TX_DIRECTION_OUTBOUND,
+            None => true,
+        };
+        let kind_matches = match self.kind {
+            Some(TransactionKind::Coinbase) => is_coinbase_status(tx.status),
+            Some(TransactionKind::Transfer) => !is_coinbase_status(tx.status),
+            None => true,
+        };
+        direction_matches
+            && kind_matches
+            && self
+                .from_timestamp
+                .map_or(true, |from| tx.timestamp >= from)
+            && self.to_timestamp.map_or(true, |to| tx.timestamp <= to)
+            && self.min_amount.map_or(true, |min| tx.amount >= min)
+            && self.max_amount.map_or(true, |max| tx.amount <= max)
+            && self
+                .payment_id
+                .as_ref()
+                .map_or(true, |payment_id| &tx.payment_id == payment_id)
+            && search.map_or(true, |search| {
+                tx.source_address.to_lowercase().contains(search)
+                    || tx.dest_address.to_lowercase().contains(search)
+                    || tx.payment_id.to_lowercase().contains(search)
+            })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TransactionPage {
+    pub transactions: Vec<TransactionInfo>,
+    pub next_cursor: Option<String>,
+    pub total_matching: usize,
+    /// Address book labels of the counterparties on this page, keyed by address
+    pub counterparty_labels: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TransactionStatusChange {
+    pub transaction: TransactionInfo,
+    pub previous_status: i32,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TransactionIndexChanges {
+    pub added: Vec<TransactionInfo>,
+    pub status_changes: Vec<TransactionStatusChange>,
+}
+
+impl TransactionIndexChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.status_changes.is_empty()
+    }
+}
+
+/// Newest first, with the tx id breaking ties between transactions from the same second
+fn sort_key(tx: &TransactionInfo) -> (u64, u64) {
+    (tx.timestamp, tx.tx_id)
+}
+
+fn encode_cursor(tx: &TransactionInfo) -> String {
+    format!("{}:{}", tx.timestamp, tx.tx_id)
+}
+
+fn decode_cursor(cursor: &str) -> Result<(u64, u64), Error> {
+    let (timestamp, tx_id) = cursor
+        .split_once(':')
+        .ok_or_else(|| anyhow!("Invalid transaction cursor"))?;
+    Ok((timestamp.parse()?, tx_id.parse()?))
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TransactionIndexStore {
+    transactions: HashMap<u64, TransactionInfo>,
+    /// Transactions written by `apply` that have not been persisted yet
+    #[serde(skip)]
+    dirty: Vec<u64>,
+}
+
+impl TransactionIndexStore {
+    /// Inserts new transactions and replaces ones whose details changed. Transactions that disappeared from the
+    /// wallet are kept so the history survives a wallet rescan.
+    fn apply(&mut self, fetched: Vec<TransactionInfo>) -> TransactionIndexChanges {
+        let mut changes = TransactionIndexChanges::default();
+        for tx in fetched {
+            match self.transactions.get(&tx.tx_id) {
+                None => changes.added.push(tx.clone()),
+                Some(existing) if existing != &tx => {
+                    if existing.status != tx.status
+                        || existing.is_cancelled != tx.is_cancelled
+                        || existing.mined_in_block_height != tx.mined_in_block_height
+                    {
+                        changes.status_changes.push(TransactionStatusChange {
+                            transaction: tx.clone(),
+                            previous_status: existing.status,
+                        });
+                    }
+                }
+                Some(_) => continue,
+            }
+            self.dirty.push(tx.tx_id);
+            self.transactions.insert(tx.tx_id, tx);
+        }
+        changes
+            .added
+            .sort_by_key(|tx| std::cmp::Reverse(sort_key(tx)));
+        changes
+    }
+
+    fn take_dirty(&mut self) -> Vec<TransactionInfo> {
+        let dirty = std::mem::take(&mut self.dirty);
+        dirty
+            .into_iter()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .filter_map(|tx_id| self.transactions.get(&tx_id).cloned())
+            .collect()
+    }
+
+    fn final_ids(&self) -> HashSet<u64> {
+        self.transactions
+            .values()
+            .filter(|tx| is_final(tx))
+            .map(|tx| tx.tx_id)
+            .collect()
+    }
+
+    fn pending_ids(&self) -> Vec<u64> {
+        self.transactions
+            .values()
+            .filter(|tx| !is_final(tx))
+            .map(|tx| tx.tx_id)
+            .collect()
+    }
+
+    fn query(&self, query: &TransactionQuery) -> Result<TransactionPage, Error> {
+        let cursor = query.cursor.as_deref().map(decode_cursor).transpose()?;
+        let search = query
+            .search
+            .as_ref()
+            .map(|search| search.trim().to_lowercase())
+            .filter(|search| !search.is_empty());
+        let mut matching: Vec<&TransactionInfo> = self
+            .transactions
+            .values()
+            .filter(|tx| query.matches(tx, search.as_deref()))
+            .collect();
+        matching.sort_by_key(|tx| std::cmp::Reverse(sort_key(tx)));
+
+        let total_matching = matching.len();
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE);
+        let start = cursor.map_or(0, |cursor| {
+            matching.partition_point(|tx| sort_key(tx) >= cursor)
+        });
+        let transactions: Vec<TransactionInfo> = matching
+            .iter()
+            .skip(start)
+            .take(limit)
+            .map(|tx| (*tx).clone())
+            .collect();
+        let next_cursor = if start + transactions.len() < total_matching {
+            transactions.last().map(encode_cursor)
+        } else {
+            None
+        };
+        Ok(TransactionPage {
+            transactions,
+            next_cursor,
+            total_matching,
+            counterparty_labels: HashMap::new(),
+        })
+    }
+}
+
+struct TransactionIndexState {
+    store: TransactionIndexStore,
+    path: Option<PathBuf>,
+    /// Entries appended to the journal since the snapshot was last written
+    journal_entries: usize,
+    /// Cursor handed out by the last `history_page` call, used by the continuation based history command
+    history_cursor: Option<String>,
+}
+
+/// Local index of wallet transactions, kept in sync with the wallet gRPC and persisted next to the wallet data.
+pub struct TransactionIndex {
+    state: Mutex<TransactionIndexState>,
+    sync_lock: Mutex<()>,
+}
+
+impl TransactionIndex {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(TransactionIndexState {
+                store: TransactionIndexStore::default(),
+                path: None,
+                journal_entries: 0,
+                history_cursor: None,
+            }),
+            sync_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn current() -> &'static TransactionIndex {
+        &INSTANCE
+    }
+
+    fn get_store_path(wallet_root: &Path) -> PathBuf {
+        wallet_root.join("transaction_index").join(format!(
+            "{}.json",
+            Network::get_current_or_user_setting_or_default()
+                .to_string()
+                .to_lowercase()
+        ))
+    }
+
+    /// Changes since the last snapshot are appended here instead of rewriting the whole index
+    fn get_journal_path(store_path: &Path) -> PathBuf {
+        store_path.with_extension("journal")
+    }
+
+    pub async fn load(&self, app_handle: &AppHandle) -> Result<(), Error> {
+        let path = Self::get_store_path(&wallet_data_dir(
+            &get_component_root(app_handle, DataComponent::Wallet)?,
+            &active_wallet_id().await,
+        ));
+        let mut store: TransactionIndexStore = match tokio::fs::read_to_string(&path).await {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!(target: LOG_TARGET, "Discarding unreadable transaction index {:?}: {:?}", path, e);
+                TransactionIndexStore::default()
+            }),
+            Err(_) => TransactionIndexStore::default(),
+        };
+        let mut journal_entries = 0;
+        if let Ok(content) = tokio::fs::read_to_string(Self::get_journal_path(&path)).await {
+            for line in content.lines().filter(|line| !line.trim().is_empty()) {
+                match serde_json::from_str::<TransactionInfo>(line) {
+                    Ok(tx) => {
+                        store.transactions.insert(tx.tx_id, tx);
+                        journal_entries += 1;
+                    }
+                    // A crash during an append leaves a torn last line, the next sync fetches that transaction again
+                    Err(e) => {
+                        warn!(target: LOG_TARGET, "Skipping unreadable transaction index journal entry: {:?}", e)
+                    }
+                }
+            }
+        }
+        let mut state = self.state.lock().await;
+        state.store = store;
+        state.path = Some(path);
+        state.journal_entries = journal_entries;
+        state.history_cursor = None;
+        Ok(())
+    }
+
+    /// Drops the index together with its file, used when the wallet data is wiped
+    pub async fn clear(&self) -> Result<(), Error> {
+        let mut state = self.state.lock().await;
+        state.store = TransactionIndexStore::default();
+        state.journal_entries = 0;
+        state.history_cursor = None;
+        if let Some(path) = &state.path {
+            for path in [path.clone(), Self::get_journal_path(path)] {
+                if path.exists() {
+                    tokio::fs::remove_file(path).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends the transactions changed since the last call to the journal, or folds everything into a new snapshot
+    /// once the journal has grown past the index itself.
+    async fn persist(&self) -> Result<(), Error> {
+        let mut state = self.state.lock().await;
+        let changed = state.store.take_dirty();
+        let Some(path) = state.path.clone() else {
+            return Ok(());
+        };
+        if changed.is_empty() {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let journal_path = Self::get_journal_path(&path);
+        let journal_entries = state.journal_entries + changed.len();
+        if journal_entries > JOURNAL_COMPACT_MIN_ENTRIES.max(state.store.transactions.len()) {
+            let content = serde_json::to_string(&state.store)?;
+            let tmp_path = path.with_extension("json.tmp");
+            tokio::fs::write(&tmp_path, content).await?;
+            tokio::fs::rename(&tmp_path, &path).await?;
+            if journal_path.exists() {
+                tokio::fs::remove_file(&journal_path).await?;
+            }
+            state.journal_entries = 0;
+            return Ok(());
+        }
+
+        let mut lines = String::new();
+        for tx in &changed {
+            lines.push_str(&serde_json::to_string(tx)?);
+            lines.push('\n');
+        }
+        let mut journal = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&journal_path)
+            .await?;
+        journal.write_all(lines.as_bytes()).await?;
+        journal.flush().await?;
+        state.journal_entries = journal_entries;
+        Ok(())
+    }
+
+    /// Pulls the wallet's completed transactions that are not already indexed in a final state, updates the index
+    /// and emits the changes.
+    pub async fn sync(
+        &self,
+        app_handle: &AppHandle,
+        wallet_manager: &WalletManager,
+    ) -> Result<TransactionIndexChanges, WalletManagerError> {
+        let _sync_guard = self.sync_lock.lock().await;
+        let final_ids = self.state.lock().await.store.final_ids();
+        let fetched = wallet_manager
+            .get_completed_transactions_except(&final_ids)
+            .await?;
+        Ok(self.apply_fetched(app_handle, fetched).await)
+    }
+
+    /// Re-reads only the indexed transactions that are still waiting for confirmation. Enough after a new block that
+    /// left the balance untouched, since new transactions always move the balance.
+    pub async fn refresh_pending(
+        &self,
+        app_handle: &AppHandle,
+        wallet_manager: &WalletManager,
+    ) -> Result<TransactionIndexChanges, WalletManagerError> {
+        let _sync_guard = self.sync_lock.lock().await;
+        let pending_ids = self.state.lock().await.store.pending_ids();
+        if pending_ids.is_empty() {
+            return Ok(TransactionIndexChanges::default());
+        }
+        let fetched = wallet_manager.get_transactions(pending_ids).await?;
+        Ok(self.apply_fetched(app_handle, fetched).await)
+    }
+
+    async fn apply_fetched(
+        &self,
+        app_handle: &AppHandle,
+        fetched: Vec<TransactionInfo>,
+    ) -> TransactionIndexChanges {
+        let (changes, was_empty) = {
+            let mut state = self.state.lock().await;
+            let was_empty = state.store.transactions.is_empty();
+            (state.store.apply(fetched), was_empty)
+        };
+        if let Err(e) = self.persist().await {
+            error!(target: LOG_TARGET, "Failed to save transaction index: {:?}", e);
+        }
+        if changes.is_empty() {
+            return changes;
+        }
+
+        info!(target: LOG_TARGET, "Transaction index updated: {} added, {} status changes", changes.added.len(), changes.status_changes.len());
+        // The first fill of an empty index would otherwise replay the whole wallet history as new transactions
+        let added = if was_empty {
+            vec![]
+        } else {
+            changes.added.clone()
+        };
+        EventsManager::handle_transactions_updated(
+            app_handle,
+            added,
+            changes.status_changes.clone(),
+        )
+        .await;
+        PaymentRequests::current().reconcile(app_handle).await;
+        changes
+    }
+
+    pub async fn query(&self, query: &TransactionQuery) -> Result<TransactionPage, Error> {
+        self.state.lock().await.store.query(query)
+    }
+
+    /// Continuation based paging kept for the original history command. Only the cursor is remembered, the
+    /// transactions themselves come from the index.
+    pub async fn history_page(
+        &self,
+        continuation: bool,
+        limit: Option<u32>,
+    ) -> Result<Vec<TransactionInfo>, Error> {
+        let mut state = self.state.lock().await;
+        let cursor = if continuation {
+            match state.history_cursor.clone() {
+                Some(cursor) => Some(cursor),
+                None => return Ok(vec![]),
+            }
+        } else {
+            None
+        };
+        let page = state.store.query(&TransactionQuery {
+            cursor,
+            limit: limit.map(|limit| limit as usize),
+            ..Default::default()
+        })?;
+        state.history_cursor = page.next_cursor;
+        Ok(page.transactions)
+    }
+
+    pub async fn spawn_sync_task(app_handle: AppHandle) {
+        info!(target: LOG_TARGET, "Spawning transaction index sync");
+        if let Err(e) = TransactionIndex::current().load(&app_handle).await {
+            error!(target: LOG_TARGET, "Transaction index will not be persisted: {:?}", e);
+        }
+
+        TasksTrackers::current()
+            .common
+            .get_task_tracker()
+            .await
+            .spawn(async move {
+                let state = app_handle.state::<UniverseAppState>();
+                let wallet_state_watch_rx = (*state.wallet_state_watch_rx).clone();
+                let wallet_manager = state.wallet_manager.clone();
+                let mut shutdown_signal = TasksTrackers::current().common.get_signal().await;
+                let mut interval = interval(SYNC_CHECK_INTERVAL);
+                let mut last_synced_state = None;
+                loop {
+                    tokio::select! {
+                        _ = interval.tick() => {
+                            let Some(wallet_state) = wallet_state_watch_rx
+                                .borrow()
+                                .as_ref()
+                                .map(|state| (state.scanned_height, state.balance.clone()))
+                            else {
+                                continue;
+                            };
+                            let index = TransactionIndex::current();
+                            let result = match &last_synced_state {
+                                Some(last_state) if *last_state == wallet_state => continue,
+                                // A new block alone can only move confirmations of what is already indexed
+                                Some((_, last_balance)) if *last_balance == wallet_state.1 => {
+                                    index.refresh_pending(&app_handle, &wallet_manager).await
+                                }
+                                _ => index.sync(&app_handle, &wallet_manager).await,
+                            };
+                            match result {
+                                Ok(_) => last_synced_state = Some(wallet_state),
+                                Err(WalletManagerError::WalletNotStarted) => {}
+                                Err(e) => warn!(target: LOG_TARGET, "Failed to sync transaction index: {}", e),
+                            }
+                        }
+                        _ = shutdown_signal.wait() => {
+                            break;
+                        }
+                    }
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tx(tx_id: u64, timestamp: u64, status: i32, direction: i32, amount: u64) -> TransactionInfo {
+        TransactionInfo {
+            tx_id,
+            source_address: format!("source{}", tx_id),
+            dest_address: "dest".to_string(),
+            status,
+            amount: MicroMinotari(amount),
+            is_cancelled: false,
+            direction,
+            excess_sig: vec![],
+            fee: 0,
+            timestamp,
+            payment_id: if tx_id % 2 == 0 {
+                "Invoice 42".to_string()
+            } else {
+                String::new()
+            },
+            mined_in_block_height: 0,
+        }
+    }
+
+    fn store() -> TransactionIndexStore {
+        let mut store = TransactionIndexStore::default();
+        store.apply(vec![
+            tx(1, 100, 6, TX_DIRECTION_INBOUND, 1_000),
+            tx(2, 200, 1, TX_DIRECTION_OUTBOUND, 2_000),
+            tx(
+                3,
+                200,
+                TX_STATUS_COINBASE_CONFIRMED,
+                TX_DIRECTION_INBOUND,
+                3_000,
+            ),
+            tx(4, 300, TX_STATUS_REJECTED, TX_DIRECTION_OUTBOUND, 4_000),
+            tx(
+                5,
+                400,
+                TX_STATUS_COINBASE_UNCONFIRMED,
+                TX_DIRECTION_INBOUND,
+                5_000,
+            ),
+        ]);
+        store
+    }
+
+    #[test]
+    fn pages_newest_first_with_stable_cursor() {
+        let mut store = store();
+        let first = store
+            .query(&TransactionQuery {
+                limit: Some(2),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(first.total_matching, 4);
+        assert_eq!(
+            first
+                .transactions
+                .iter()
+                .map(|tx| tx.tx_id)
+                .collect::<Vec<_>>(),
+            vec![5, 3]
+        );
+
+        // A transaction arriving between page requests must not shift the next page
+        store.apply(vec![tx(6, 500, 1, TX_DIRECTION_INBOUND, 6_000)]);
+        let second = store
+            .query(&TransactionQuery {
+                cursor: first.next_cursor,
+                limit: Some(2),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(
+            second
+                .transactions
+                .iter()
+                .map(|tx| tx.tx_id)
+                .collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+        assert!(second.next_cursor.is_none());
+    }
+
+    #[test]
+    fn filters_by_kind_direction_amount_and_search() {
+        let store = store();
+        let coinbase = store
+            .query(&TransactionQuery {
+                kind: Some(TransactionKind::Coinbase),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(coinbase.total_matching, 2);
+
+        let outbound = store
+            .query(&TransactionQuery {
+                direction: Some(TransactionDirection::Outbound),
+                statuses: vec![1, TX_STATUS_REJECTED],
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(outbound.total_matching, 2);
+
+        let ranged = store
+            .query(&TransactionQuery {
+                min_amount: Some(MicroMinotari(1_500)),
+                max_amount: Some(MicroMinotari(3_000)),
+                from_timestamp: Some(150),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(ranged.total_matching, 2);
+
+        let memo = store
+            .query(&TransactionQuery {
+                search: Some("invoice".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(
+            memo.transactions
+                .iter()
+                .map(|tx| tx.tx_id)
+                .collect::<Vec<_>>(),
+            vec![2]
+        );
+    }
+
+    #[test]
+    fn reports_status_changes() {
+        let mut store = store();
+        let mut confirmed = tx(
+            5,
+            400,
+            TX_STATUS_COINBASE_CONFIRMED,
+            TX_DIRECTION_INBOUND,
+            5_000,
+        );
+        confirmed.mined_in_block_height = 10;
+        let changes = store.apply(vec![confirmed, tx(1, 100, 6, TX_DIRECTION_INBOUND, 1_000)]);
+        assert!(changes.added.is_empty());
+        assert_eq!(changes.status_changes.len(), 1);
+        assert_eq!(
+            changes.status_changes[0].previous_status,
+            TX_STATUS_COINBASE_UNCONFIRMED
+        );
+    }
+
+    #[test]
+    fn splits_final_and_pending_transactions() {
+        let store = store();
+        let mut final_ids: Vec<u64> = store.final_ids().into_iter().collect();
+        final_ids.sort();
+        assert_eq!(final_ids, vec![1, 3, 4]);
+        let mut pending_ids = store.pending_ids();
+        pending_ids.sort();
+        assert_eq!(pending_ids, vec![2, 5]);
+    }
+
+    #[test]
+    fn only_changed_transactions_are_dirty() {
+        let mut store = store();
+        assert_eq!(store.take_dirty().len(), 5);
+        assert!(store.take_dirty().is_empty());
+
+        let mut cancelled = tx(2, 200, 1, TX_DIRECTION_OUTBOUND, 2_000);
+        cancelled.is_cancelled = true;
+        store.apply(vec![tx(1, 100, 6, TX_DIRECTION_INBOUND, 1_000), cancelled]);
+        let dirty = store.take_dirty();
+        assert_eq!(dirty.iter().map(|tx| tx.tx_id).collect::<Vec<_>>(), vec![2]);
+        assert!(store.final_ids().contains(&2));
+    }
+
+    #[test]
+    fn rejects_malformed_cursor() {
+        assert!(store()
+            .query(&TransactionQuery {
+                cursor: Some("nope".to_string()),
+                ..Default::default()
+            })
+            .is_err());
+    }
+}
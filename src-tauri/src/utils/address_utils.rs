@@ -20,9 +20,29 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use monero::util::address::AddressType;
+use serde::Serialize;
 use std::str::FromStr;
 use tari_common::configuration::Network;
 use tari_common_types::tari_address::{TariAddress, TariAddressFeatures};
+use tari_utilities::hex::to_hex;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MoneroAddressKind {
+    Standard,
+    Integrated,
+    Subaddress,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct MoneroAddressInfo {
+    pub kind: MoneroAddressKind,
+    /// Payment id embedded in an integrated address, hex encoded
+    pub payment_id: Option<String>,
+    /// monerod refuses to build block templates paying to a subaddress
+    pub can_receive_mining_rewards: bool,
+}
 
 pub fn verify_tari_address(address: &str) -> Result<TariAddress, String> {
     let tari_address =
@@ -45,9 +65,47 @@ pub fn verify_send(address: String, sending_method: TariAddressFeatures) -> Resu
     Ok(())
 }
 
+/// Checks the network byte and checksum of a Monero address. Merge mining always pays out on Monero mainnet,
+/// whatever Tari network is in use.
+pub fn verify_monero_address(address: &str) -> Result<MoneroAddressInfo, String> {
+    let address = monero::Address::from_str(address.trim())
+        .map_err(|e| format!("Invalid Monero address: {}", e))?;
+    if address.network != monero::Network::Mainnet {
+        return Err(format!(
+            "Monero {:?} addresses cannot receive mining rewards",
+            address.network
+        ));
+    }
+    let (kind, payment_id) = match address.addr_type {
+        AddressType::Standard => (MoneroAddressKind::Standard, None),
+        AddressType::Integrated(payment_id) => (
+            MoneroAddressKind::Integrated,
+            Some(to_hex(payment_id.as_bytes())),
+        ),
+        AddressType::SubAddress => (MoneroAddressKind::Subaddress, None),
+    };
+    Ok(MoneroAddressInfo {
+        kind,
+        payment_id,
+        can_receive_mining_rewards: kind != MoneroAddressKind::Subaddress,
+    })
+}
+
+pub fn verify_monero_payout_address(address: &str) -> Result<MoneroAddressInfo, String> {
+    let info = verify_monero_address(address)?;
+    if !info.can_receive_mining_rewards {
+        return Err(
+            "Monero does not support mining to a subaddress, use the primary address instead"
+                .to_string(),
+        );
+    }
+    Ok(info)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::consts::DEFAULT_MONERO_ADDRESS;
 
     const ESME_ONE_SIDED_ADDRESS: &str = "f25eNHz2YnBVKHaqNuacGyDFB321RwwCnTr4vb2SjQCgDZVXyNNthc7zftQKRDu6evLjvSUD8W5akpPMdhS4HQ9kF3g";
     const ESME_INTERACTIVE_ADDRESS: &str = "f45eNHz2YnBVKHaqNuacGyDFB321RwwCnTr4vb2SjQCgDZVXyNNthc7zftQKRDu6evLjvSUD8W5akpPMdhS4HQ9kF31";
@@ -128,4 +186,42 @@ mod tests {
             _ => panic!("Expected an error but got success"),
         }
     }
+
+    fn monero_keys() -> (monero::PublicKey, monero::PublicKey) {
+        let address = monero::Address::from_str(DEFAULT_MONERO_ADDRESS).unwrap();
+        (address.public_spend, address.public_view)
+    }
+
+    #[test]
+    fn test_verify_monero_address_kinds() {
+        let (spend, view) = monero_keys();
+        let standard = verify_monero_address(DEFAULT_MONERO_ADDRESS).unwrap();
+        assert_eq!(standard.kind, MoneroAddressKind::Standard);
+        assert!(verify_monero_payout_address(DEFAULT_MONERO_ADDRESS).is_ok());
+
+        let payment_id = monero::util::address::PaymentId::from_slice(&[1u8; 8]);
+        let integrated =
+            monero::Address::integrated(monero::Network::Mainnet, spend, view, payment_id);
+        let info = verify_monero_payout_address(&integrated.to_string()).unwrap();
+        assert_eq!(info.kind, MoneroAddressKind::Integrated);
+        assert_eq!(info.payment_id.as_deref(), Some("0101010101010101"));
+
+        let subaddress = monero::Address::subaddress(monero::Network::Mainnet, spend, view);
+        let info = verify_monero_address(&subaddress.to_string()).unwrap();
+        assert_eq!(info.kind, MoneroAddressKind::Subaddress);
+        assert!(verify_monero_payout_address(&subaddress.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_verify_monero_address_rejects_other_networks_and_bad_checksum() {
+        let (spend, view) = monero_keys();
+        let stagenet = monero::Address::standard(monero::Network::Stagenet, spend, view);
+        assert!(verify_monero_address(&stagenet.to_string()).is_err());
+
+        let mut corrupted = DEFAULT_MONERO_ADDRESS.to_string();
+        let last = corrupted.pop().unwrap();
+        corrupted.push(if last == 'A' { 'B' } else { 'A' });
+        assert!(verify_monero_address(&corrupted).is_err());
+        assert!(verify_monero_address("not a monero address").is_err());
+    }
 }
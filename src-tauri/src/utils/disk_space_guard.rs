@@ -0,0 +1,383 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    path::PathBuf,
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Error};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tokio::{sync::Mutex, time::interval};
+
+use crate::{
+    configs::{config_core::ConfigCore, trait_config::ConfigImpl},
+    data_locations::{get_component_root, DataComponent},
+    events_manager::EventsManager,
+    setup::setup_manager::{SetupManager, SetupPhase},
+    tasks_tracker::TasksTrackers,
+    utils::file_utils::{get_available_disk_space, get_dir_size},
+};
+
+const LOG_TARGET: &str = "tari::universe::disk_space_guard";
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+// Growth rate is measured over the last hour of samples
+const GROWTH_WINDOW: Duration = Duration::from_secs(60 * 60);
+const GIB: u64 = 1024 * 1024 * 1024;
+// Phases stopped when the disk is critically full, in shutdown order
+const GUARDED_PHASES: [SetupPhase; 2] = [SetupPhase::Wallet, SetupPhase::Node];
+
+static INSTANCE: LazyLock<DiskSpaceGuard> = LazyLock::new(DiskSpaceGuard::new);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DiskSpaceThresholds {
+    /// Free space below which the user is warned.
+    pub warning_free_bytes: u64,
+    /// Free space below which the node and wallet are stopped.
+    pub critical_free_bytes: u64,
+    /// Warn when the critical threshold is expected to be reached within this many seconds.
+    pub warning_eta_secs: u64,
+}
+
+impl Default for DiskSpaceThresholds {
+    fn default() -> Self {
+        Self {
+            warning_free_bytes: 10 * GIB,
+            critical_free_bytes: 2 * GIB,
+            warning_eta_secs: 24 * 60 * 60,
+        }
+    }
+}
+
+impl DiskSpaceThresholds {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.critical_free_bytes == 0 {
+            return Err(anyhow!("Critical threshold must be greater than zero"));
+        }
+        if self.warning_free_bytes <= self.critical_free_bytes {
+            return Err(anyhow!(
+                "Warning threshold must be greater than the critical threshold"
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DiskSpaceLevel {
+    Ok,
+    Warning,
+    Critical,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiskSpaceStatus {
+    pub component: DataComponent,
+    pub root: PathBuf,
+    pub free_bytes: u64,
+    pub used_bytes: u64,
+    pub growth_bytes_per_hour: Option<u64>,
+    pub eta_to_critical_secs: Option<u64>,
+    pub level: DiskSpaceLevel,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct DiskSample {
+    taken_at: Instant,
+    free_bytes: u64,
+    used_bytes: u64,
+}
+
+#[derive(Default)]
+struct DiskSpaceGuardState {
+    samples: HashMap<DataComponent, VecDeque<DiskSample>>,
+    statuses: Vec<DiskSpaceStatus>,
+    // Phases shut down by the guard. Only the ones that are still stopped get resumed
+    stopped_phases: Vec<SetupPhase>,
+}
+
+pub struct DiskSpaceGuard {
+    state: Mutex<DiskSpaceGuardState>,
+}
+
+impl DiskSpaceGuard {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(DiskSpaceGuardState::default()),
+        }
+    }
+
+    pub fn current() -> &'static DiskSpaceGuard {
+        &INSTANCE
+    }
+
+    pub async fn get_statuses(&self) -> Vec<DiskSpaceStatus> {
+        self.state.lock().await.statuses.clone()
+    }
+
+    pub async fn spawn_monitor(app_handle: AppHandle) {
+        info!(target: LOG_TARGET, "Spawning disk space guard");
+        TasksTrackers::current()
+            .common
+            .get_task_tracker()
+            .await
+            .spawn(async move {
+                let mut shutdown_signal = TasksTrackers::current().common.get_signal().await;
+                let mut interval = interval(CHECK_INTERVAL);
+                loop {
+                    tokio::select! {
+                        _ = interval.tick() => {
+                            if let Err(e) = DiskSpaceGuard::current().check(&app_handle).await {
+                                error!(target: LOG_TARGET, "Disk space check failed: {:?}", e);
+                            }
+                        }
+                        _ = shutdown_signal.wait() => {
+                            break;
+                        }
+                    }
+                }
+            });
+    }
+
+    async fn check(&self, app_handle: &AppHandle) -> Result<(), Error> {
+        let thresholds = ConfigCore::content().await.disk_space_thresholds().clone();
+        let mut roots = vec![];
+        for component in [
+            DataComponent::Node,
+            DataComponent::Wallet,
+            DataComponent::P2pool,
+        ] {
+            roots.push((component, get_component_root(app_handle, component)?));
+        }
+
+        let measured = tokio::task::spawn_blocking(move || {
+            roots
+                .into_iter()
+                .filter_map(|(component, root)| {
+                    let free_bytes = get_available_disk_space(&root)?;
+                    let used_bytes = component
+                        .folders()
+                        .iter()
+                        .map(|folder| get_dir_size(&root.join(folder)))
+                        .sum();
+                    Some((component, root, free_bytes, used_bytes))
+                })
+                .collect::<Vec<_>>()
+        })
+        .await?;
+
+        let now = Instant::now();
+        let mut state = self.state.lock().await;
+        let mut statuses = vec![];
+        for (component, root, free_bytes, used_bytes) in measured {
+            let samples = state.samples.entry(component).or_default();
+            samples.push_back(DiskSample {
+                taken_at: now,
+                free_bytes,
+                used_bytes,
+            });
+            while samples
+                .front()
+                .is_some_and(|sample| now.duration_since(sample.taken_at) > GROWTH_WINDOW)
+            {
+                samples.pop_front();
+            }
+            let (level, growth_bytes_per_hour, eta_to_critical_secs) =
+                evaluate_samples(samples, &thresholds);
+            statuses.push(DiskSpaceStatus {
+                component,
+                root,
+                free_bytes,
+                used_bytes,
+                growth_bytes_per_hour,
+                eta_to_critical_secs,
+                level,
+            });
+        }
+
+        let guarded_level = statuses
+            .iter()
+            .filter(|status| {
+                matches!(
+                    status.component,
+                    DataComponent::Node | DataComponent::Wallet
+                )
+            })
+            .map(|status| status.level)
+            .max()
+            .unwrap_or(DiskSpaceLevel::Ok);
+        for status in statuses.iter().filter(|s| s.level != DiskSpaceLevel::Ok) {
+            warn!(target: LOG_TARGET, "Low disk space for {:?} in {:?}: {} bytes free, ETA to critical: {:?}s", status.component, status.root, status.free_bytes, status.eta_to_critical_secs);
+        }
+
+        // Work from the actual phase state, the phases may have been restarted or stopped by something else
+        let setup_manager = SetupManager::get_instance();
+        let mut stop_phases = vec![];
+        let mut resume_phases = vec![];
+        if guarded_level == DiskSpaceLevel::Critical {
+            stop_phases = GUARDED_PHASES
+                .into_iter()
+                .filter(|phase| !setup_manager.is_phase_stopped(phase))
+                .collect();
+            for phase in &stop_phases {
+                if !state.stopped_phases.contains(phase) {
+                    state.stopped_phases.push(phase.clone());
+                }
+            }
+        } else if guarded_level == DiskSpaceLevel::Ok {
+            // Resume in reverse order of the shutdown so the node is up before the wallet
+            resume_phases = GUARDED_PHASES
+                .into_iter()
+                .rev()
+                .filter(|phase| {
+                    state.stopped_phases.contains(phase) && setup_manager.is_phase_stopped(phase)
+                })
+                .collect();
+            state.stopped_phases.clear();
+        }
+        let guarded_phases = state.stopped_phases.clone();
+        state.statuses = statuses.clone();
+        drop(state);
+
+        if !stop_phases.is_empty() {
+            warn!(target: LOG_TARGET, "Disk space critical, stopping {:?} to protect their databases", stop_phases);
+            setup_manager
+                .shutdown_phases(app_handle.clone(), stop_phases)
+                .await;
+        }
+        if !resume_phases.is_empty() {
+            info!(target: LOG_TARGET, "Disk space recovered, restarting {:?}", resume_phases);
+            setup_manager
+                .resume_phases(app_handle.clone(), resume_phases)
+                .await;
+        }
+
+        let phases_stopped = guarded_phases
+            .iter()
+            .any(|phase| setup_manager.is_phase_stopped(phase));
+        EventsManager::handle_disk_space_status_update(app_handle, statuses, phases_stopped).await;
+        Ok(())
+    }
+}
+
+/// Returns the level, the data growth per hour and the seconds left until the critical threshold.
+///
+/// The ETA uses whichever is faster: the volume's free space shrinking (other apps writing to the
+/// same disk) or the component's own data growing.
+fn evaluate_samples(
+    samples: &VecDeque<DiskSample>,
+    thresholds: &DiskSpaceThresholds,
+) -> (DiskSpaceLevel, Option<u64>, Option<u64>) {
+    let Some(latest) = samples.back() else {
+        return (DiskSpaceLevel::Ok, None, None);
+    };
+    let oldest = samples.front().unwrap_or(latest);
+    let elapsed_secs = latest
+        .taken_at
+        .duration_since(oldest.taken_at)
+        .as_secs_f64();
+
+    let (growth_bytes_per_hour, eta_to_critical_secs) = if elapsed_secs > 0.0 {
+        let data_growth = latest.used_bytes.saturating_sub(oldest.used_bytes) as f64 / elapsed_secs;
+        let free_decline =
+            oldest.free_bytes.saturating_sub(latest.free_bytes) as f64 / elapsed_secs;
+        let consumption = data_growth.max(free_decline);
+        let eta = (consumption > 0.0).then(|| {
+            (latest
+                .free_bytes
+                .saturating_sub(thresholds.critical_free_bytes) as f64
+                / consumption) as u64
+        });
+        (Some((data_growth * 3600.0) as u64), eta)
+    } else {
+        (None, None)
+    };
+
+    let level = if latest.free_bytes <= thresholds.critical_free_bytes {
+        DiskSpaceLevel::Critical
+    } else if latest.free_bytes <= thresholds.warning_free_bytes
+        || eta_to_critical_secs.is_some_and(|eta| eta <= thresholds.warning_eta_secs)
+    {
+        DiskSpaceLevel::Warning
+    } else {
+        DiskSpaceLevel::Ok
+    };
+
+    (level, growth_bytes_per_hour, eta_to_critical_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(points: &[(u64, u64, u64)]) -> VecDeque<DiskSample> {
+        let start = Instant::now();
+        points
+            .iter()
+            .map(|(secs, free_bytes, used_bytes)| DiskSample {
+                taken_at: start + Duration::from_secs(*secs),
+                free_bytes: *free_bytes,
+                used_bytes: *used_bytes,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn critical_when_below_threshold() {
+        let thresholds = DiskSpaceThresholds::default();
+        let (level, _, _) = evaluate_samples(&samples(&[(0, GIB, 0)]), &thresholds);
+        assert_eq!(level, DiskSpaceLevel::Critical);
+    }
+
+    #[test]
+    fn warns_ahead_using_growth_rate() {
+        let thresholds = DiskSpaceThresholds::default();
+        // 50 GiB free, shrinking by 1 GiB per hour: ~48h to critical
+        let slow = samples(&[(0, 51 * GIB, 0), (3600, 50 * GIB, GIB)]);
+        let (level, growth, eta) = evaluate_samples(&slow, &thresholds);
+        assert_eq!(level, DiskSpaceLevel::Ok);
+        assert_eq!(growth, Some(GIB));
+        assert_eq!(eta, Some(48 * 3600));
+
+        // Same free space, shrinking by 4 GiB per hour: 12h to critical
+        let fast = samples(&[(0, 54 * GIB, 0), (3600, 50 * GIB, 0)]);
+        let (level, _, eta) = evaluate_samples(&fast, &thresholds);
+        assert_eq!(level, DiskSpaceLevel::Warning);
+        assert_eq!(eta, Some(12 * 3600));
+    }
+
+    #[test]
+    fn rejects_inverted_thresholds() {
+        let thresholds = DiskSpaceThresholds {
+            warning_free_bytes: GIB,
+            critical_free_bytes: 2 * GIB,
+            ..Default::default()
+        };
+        assert!(thresholds.validate().is_err());
+        assert!(DiskSpaceThresholds::default().validate().is_ok());
+    }
+}
@@ -20,9 +20,11 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::fs;
 use std::path::{Component, Path, PathBuf};
 
 use anyhow::anyhow;
+use sysinfo::{Disk, Disks};
 
 /// Returns a relative path from one path to another.
 pub fn make_relative_path(root: &Path, current: &Path) -> PathBuf {
@@ -62,3 +64,39 @@ pub fn convert_to_string(path: PathBuf) -> Result<String, anyhow::Error> {
         .map(|s| s.to_string())
         .ok_or_else(|| anyhow!("Could not convert path to string"))
 }
+
+/// Returns the total size in bytes of all files under the given path. Unreadable entries are skipped.
+pub fn get_dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => get_dir_size(&entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+fn find_disk<'a>(disks: &'a Disks, path: &Path) -> Option<&'a Disk> {
+    let path = dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    disks
+        .list()
+        .iter()
+        .filter(|disk| path.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().components().count())
+}
+
+/// Returns the available space in bytes on the disk that holds the given path.
+pub fn get_available_disk_space(path: &Path) -> Option<u64> {
+    let disks = Disks::new_with_refreshed_list();
+    find_disk(&disks, path).map(|disk| disk.available_space())
+}
+
+/// Returns the mount point of the disk that holds the given path.
+pub fn get_disk_mount_point(path: &Path) -> Option<PathBuf> {
+    let disks = Disks::new_with_refreshed_list();
+    find_disk(&disks, path).map(|disk| disk.mount_point().to_path_buf())
+}
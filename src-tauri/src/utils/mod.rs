@@ -22,6 +22,7 @@
 
 pub mod address_utils;
 pub mod app_flow_utils;
+pub mod disk_space_guard;
 pub mod file_utils;
 pub mod formatting_utils;
 pub mod locks_utils;
@@ -30,6 +31,8 @@ pub mod macos_utils;
 pub mod math_utils;
 pub mod network_status;
 pub mod platform_utils;
+pub mod store_utils;
+pub mod time_utils;
 pub mod wallet_utils;
 
 pub mod system_status;
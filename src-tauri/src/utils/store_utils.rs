@@ -0,0 +1,123 @@
+// Copyright 2025. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{io::ErrorKind, path::Path};
+
+use anyhow::{anyhow, Error};
+use log::warn;
+use serde::{de::DeserializeOwned, Serialize};
+use tari_common::configuration::Network;
+
+use super::time_utils::unix_now;
+
+const LOG_TARGET: &str = "tari::universe::store_utils";
+
+/// Key that stores file their per-network records under
+pub fn current_network_key() -> String {
+    Network::get_current_or_user_setting_or_default()
+        .to_string()
+        .to_lowercase()
+}
+
+/// Reads a JSON store, falling back to the default when the file does not exist yet. A file that cannot be parsed is
+/// moved aside first, so the next save cannot overwrite what the user had.
+pub async fn load_json_store<T: DeserializeOwned + Default>(path: &Path) -> Result<T, Error> {
+    let content = match tokio::fs::read_to_string(path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(T::default()),
+        Err(e) => return Err(e.into()),
+    };
+    match serde_json::from_str(&content) {
+        Ok(store) => Ok(store),
+        Err(e) => {
+            let file_name = path
+                .file_name()
+                .ok_or_else(|| anyhow!("Invalid store path {:?}", path))?
+                .to_string_lossy();
+            let aside = path.with_file_name(format!("{}.corrupt-{}", file_name, unix_now()));
+            tokio::fs::rename(path, &aside).await?;
+            warn!(target: LOG_TARGET, "Moved unreadable store {:?} to {:?}: {:?}", path, aside, e);
+            Ok(T::default())
+        }
+    }
+}
+
+/// Writes a JSON store through a temporary file, so a crash never leaves a half written store behind
+pub async fn save_json_store<T: Serialize>(path: &Path, store: &T) -> Result<(), Error> {
+    let content = serde_json::to_string_pretty(store)?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let tmp_path = path.with_extension("json.tmp");
+    tokio::fs::write(&tmp_path, content).await?;
+    tokio::fs::rename(&tmp_path, path).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn test_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("store_utils_{}", name));
+        let _unused = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[tokio::test]
+    async fn round_trips_and_defaults_when_missing() {
+        let dir = test_dir("round_trip");
+        let path = dir.join("store.json");
+        let empty: HashMap<String, u64> = load_json_store(&path).await.unwrap();
+        assert!(empty.is_empty());
+
+        let store = HashMap::from([("a".to_string(), 1u64)]);
+        save_json_store(&path, &store).await.unwrap();
+        let loaded: HashMap<String, u64> = load_json_store(&path).await.unwrap();
+        assert_eq!(loaded, store);
+        let _unused = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn moves_unreadable_store_aside() {
+        let dir = test_dir("corrupt");
+        let path = dir.join("store.json");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&path, "{ not json").unwrap();
+
+        let loaded: HashMap<String, u64> = load_json_store(&path).await.unwrap();
+        assert!(loaded.is_empty());
+        assert!(!path.exists());
+        let aside: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(aside.len(), 1);
+        assert!(aside[0].starts_with("store.json.corrupt-"));
+        assert_eq!(
+            std::fs::read_to_string(dir.join(&aside[0])).unwrap(),
+            "{ not json"
+        );
+        let _unused = std::fs::remove_dir_all(&dir);
+    }
+}
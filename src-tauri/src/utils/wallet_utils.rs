@@ -24,7 +24,7 @@ use std::env::temp_dir;
 
 use crate::{
     consts::DEFAULT_MONERO_ADDRESS,
-    credential_manager::{Credential, CredentialManager},
+    credential_manager::{Credential, CredentialError, CredentialManager},
     APPLICATION_FOLDER_ID,
 };
 use anyhow::Error;
@@ -45,21 +45,23 @@ pub async fn create_monereo_address() -> Result<String, Error> {
 
     let cm = CredentialManager::default_with_dir(config_dir);
 
-    if let Ok(cred) = cm.get_credentials().await {
-        if let Some(seed) = cred.monero_seed {
-            info!(target: LOG_TARGET, "Found monero seed in credential manager");
-            let seed = MoneroSeed::new(seed);
-            return Ok(seed
-                .to_address::<Mainnet>()
-                .unwrap_or(DEFAULT_MONERO_ADDRESS.to_string()));
-        }
+    // Only a missing entry may be created here, an unreadable backend must not get its credentials overwritten
+    let existing = match cm.get_credentials().await {
+        Ok(cred) => Some(cred),
+        Err(CredentialError::NoEntry(_)) => None,
+        Err(e) => return Err(e.into()),
+    };
+    if let Some(seed) = existing.as_ref().and_then(|cred| cred.monero_seed) {
+        info!(target: LOG_TARGET, "Found monero seed in credential manager");
+        let seed = MoneroSeed::new(seed);
+        return Ok(seed
+            .to_address::<Mainnet>()
+            .unwrap_or(DEFAULT_MONERO_ADDRESS.to_string()));
     }
 
     let monero_seed = MoneroSeed::generate()?;
-    let cred = Credential {
-        tari_seed_passphrase: None,
-        monero_seed: Some(*monero_seed.inner()),
-    };
+    let mut cred = existing.unwrap_or_default();
+    cred.monero_seed = Some(*monero_seed.inner());
 
     info!(target: LOG_TARGET, "Setting monero seed in credential manager");
     cm.set_credentials(&cred).await?;
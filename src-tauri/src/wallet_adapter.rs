@@ -29,13 +29,16 @@ use crate::utils::file_utils::convert_to_string;
 use crate::utils::logging_utils::setup_logging;
 use anyhow::Error;
 use async_trait::async_trait;
+use base64::prelude::*;
 use log::{info, warn};
 use minotari_node_grpc_client::grpc::wallet_client::WalletClient;
 use minotari_node_grpc_client::grpc::{
     GetBalanceResponse, GetCompletedTransactionsRequest, GetCompletedTransactionsResponse,
-    GetStateRequest, NetworkStatusResponse,
+    GetStateRequest, GetTransactionInfoRequest, NetworkStatusResponse,
+    TransactionInfo as GrpcTransactionInfo,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::time::Duration;
 use tari_common::configuration::Network;
@@ -46,7 +49,11 @@ use tari_crypto::ristretto::RistrettoPublicKey;
 use tari_shutdown::Shutdown;
 use tari_utilities::hex::Hex;
 use tokio::sync::{watch, Mutex};
-use tonic::Streaming;
+use tonic::metadata::{Ascii, MetadataValue};
+use tonic::service::interceptor::InterceptedService;
+use tonic::service::Interceptor;
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Request, Status, Streaming};
 
 #[cfg(target_os = "windows")]
 use crate::utils::windows_setup_utils::add_firewall_rule;
@@ -64,7 +71,6 @@ pub struct WalletAdapter {
     pub(crate) grpc_port: u16,
     pub(crate) state_broadcast: watch::Sender<Option<WalletState>>,
     pub(crate) wallet_birthday: Option<u16>,
-    completed_transactions_stream: Mutex<Option<Streaming<GetCompletedTransactionsResponse>>>,
     coinbase_transactions_stream: Mutex<Option<Streaming<GetCompletedTransactionsResponse>>>,
 }
 
@@ -83,7 +89,6 @@ impl WalletAdapter {
             grpc_port,
             state_broadcast,
             wallet_birthday: None,
-            completed_transactions_stream: Mutex::new(None),
             coinbase_transactions_stream: Mutex::new(None),
         }
     }
@@ -96,74 +101,47 @@ impl WalletAdapter {
         self.connect_with_local_node = connect_with_local_node;
     }
 
-    pub async fn get_transactions_history(
+    /// Streams every completed transaction the wallet knows about, regardless of status.
+    pub async fn get_completed_transactions(
         &self,
-        continuation: bool,
-        limit: Option<u32>,
     ) -> Result<Vec<TransactionInfo>, WalletStatusMonitorError> {
-        // TODO: Implement starting point instead of continuation
-        let mut stream = if continuation
-            && self.completed_transactions_stream.lock().await.is_some()
-        {
-            self.completed_transactions_stream
-                .lock()
-                .await
-                .take()
-                .expect("completed_transactions_stream not found")
-        } else {
-            let mut client = WalletClient::connect(self.wallet_grpc_address())
-                .await
-                .map_err(|_e| WalletStatusMonitorError::WalletNotStarted)?;
-            let res = client
-                .get_completed_transactions(GetCompletedTransactionsRequest { payment_id: None })
-                .await
-                .map_err(|e| WalletStatusMonitorError::UnknownError(e.into()))?;
-            res.into_inner()
-        };
+        fetch_completed_transactions(self.wallet_grpc_address(), WalletGrpcAuth::default()).await
+    }
 
-        let mut transactions: Vec<TransactionInfo> = Vec::new();
+    /// Same as `get_completed_transactions`, but transactions listed in `skip` are dropped from the stream without
+    /// being converted.
+    pub async fn get_completed_transactions_except(
+        &self,
+        skip: &HashSet<u64>,
+    ) -> Result<Vec<TransactionInfo>, WalletStatusMonitorError> {
+        fetch_completed_transactions_except(
+            self.wallet_grpc_address(),
+            WalletGrpcAuth::default(),
+            skip,
+        )
+        .await
+    }
 
-        while let Some(message) = stream
-            .message()
+    /// Looks up the given transactions only, in whatever state the wallet has them.
+    pub async fn get_transactions(
+        &self,
+        tx_ids: Vec<u64>,
+    ) -> Result<Vec<TransactionInfo>, WalletStatusMonitorError> {
+        let mut client = WalletClient::connect(self.wallet_grpc_address())
             .await
-            .map_err(|e| WalletStatusMonitorError::UnknownError(e.into()))?
-        {
-            let tx = message.transaction.ok_or_else(|| {
-                WalletStatusMonitorError::UnknownError(anyhow::anyhow!("Transaction not found"))
-            })?;
-            if tx.status == 14 || tx.status == 7 {
-                // Remove TRANSACTION_STATUS_COINBASE_NOT_IN_BLOCK_CHAIN and REJECTED
-                continue;
-            }
-            let source_address = TariAddress::from_bytes(&tx.source_address)?;
-            let dest_address = TariAddress::from_bytes(&tx.dest_address)?;
-
-            transactions.push(TransactionInfo {
-                tx_id: tx.tx_id,
-                source_address: source_address.to_base58(),
-                dest_address: dest_address.to_base58(),
-                status: tx.status,
-                amount: MicroMinotari(tx.amount),
-                is_cancelled: tx.is_cancelled,
-                direction: tx.direction,
-                excess_sig: tx.excess_sig,
-                fee: tx.fee,
-                timestamp: tx.timestamp,
-                payment_id: PaymentId::from_bytes(&tx.payment_id).user_data_as_string(),
-                mined_in_block_height: tx.mined_in_block_height,
-            });
-            if let Some(limit) = limit {
-                if transactions.len() >= limit as usize {
-                    break;
-                }
-            }
-        }
-
-        self.completed_transactions_stream
-            .lock()
+            .map_err(|_e| WalletStatusMonitorError::WalletNotStarted)?;
+        let response = client
+            .get_transaction_info(GetTransactionInfoRequest {
+                transaction_ids: tx_ids,
+            })
             .await
-            .replace(stream);
-        Ok(transactions)
+            .map_err(|e| WalletStatusMonitorError::UnknownError(e.into()))?
+            .into_inner();
+        Ok(response
+            .transactions
+            .into_iter()
+            .map(TransactionInfo::from_grpc)
+            .collect())
     }
 
     pub async fn get_coinbase_transactions(
@@ -470,10 +448,7 @@ impl ProcessAdapter for WalletAdapter {
                     name: self.name().to_string(),
                 },
             },
-            WalletStatusMonitor {
-                grpc_port: self.grpc_port,
-                state_broadcast: self.state_broadcast.clone(),
-            },
+            WalletStatusMonitor::new(self.grpc_port, self.state_broadcast.clone()),
         ))
     }
 
@@ -486,8 +461,46 @@ impl ProcessAdapter for WalletAdapter {
     }
 }
 
+/// Credentials for a wallet started with gRPC authentication, sent as a basic `authorization` header with every
+/// call. The default sends nothing, for wallets that only listen without authentication.
+#[derive(Clone, Default)]
+pub struct WalletGrpcAuth {
+    header: Option<MetadataValue<Ascii>>,
+}
+
+impl WalletGrpcAuth {
+    pub fn basic(username: &str, password: &str) -> Result<Self, Error> {
+        let token = BASE64_STANDARD.encode(format!("{}:{}", username, password));
+        Ok(Self {
+            header: Some(format!("Basic {}", token).parse()?),
+        })
+    }
+}
+
+impl Interceptor for WalletGrpcAuth {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if let Some(header) = &self.header {
+            request
+                .metadata_mut()
+                .insert("authorization", header.clone());
+        }
+        Ok(request)
+    }
+}
+
+pub type WalletGrpcClient = WalletClient<InterceptedService<Channel, WalletGrpcAuth>>;
+
+pub async fn connect_wallet_grpc(
+    grpc_address: String,
+    grpc_auth: WalletGrpcAuth,
+) -> Result<WalletGrpcClient, Error> {
+    let channel = Endpoint::from_shared(grpc_address)?.connect().await?;
+    Ok(WalletClient::with_interceptor(channel, grpc_auth))
+}
+
 pub struct WalletStatusMonitor {
     grpc_port: u16,
+    grpc_auth: WalletGrpcAuth,
     state_broadcast: watch::Sender<Option<WalletState>>,
 }
 
@@ -495,6 +508,7 @@ impl Clone for WalletStatusMonitor {
     fn clone(&self) -> Self {
         Self {
             grpc_port: self.grpc_port,
+            grpc_auth: self.grpc_auth.clone(),
             state_broadcast: self.state_broadcast.clone(),
         }
     }
@@ -526,12 +540,25 @@ impl StatusMonitor for WalletStatusMonitor {
 }
 
 impl WalletStatusMonitor {
+    pub fn new(grpc_port: u16, state_broadcast: watch::Sender<Option<WalletState>>) -> Self {
+        Self {
+            grpc_port,
+            grpc_auth: WalletGrpcAuth::default(),
+            state_broadcast,
+        }
+    }
+
+    pub fn with_grpc_auth(mut self, grpc_auth: WalletGrpcAuth) -> Self {
+        self.grpc_auth = grpc_auth;
+        self
+    }
+
     fn wallet_grpc_address(&self) -> String {
         format!("http://127.0.0.1:{}", self.grpc_port)
     }
 
     pub async fn get_status(&self) -> Result<WalletState, WalletStatusMonitorError> {
-        let mut client = WalletClient::connect(self.wallet_grpc_address())
+        let mut client = connect_wallet_grpc(self.wallet_grpc_address(), self.grpc_auth.clone())
             .await
             .map_err(|_e| WalletStatusMonitorError::WalletNotStarted)?;
         let res = client
@@ -628,7 +655,7 @@ pub enum ConnectivityStatus {
     Offline,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, PartialEq)]
 pub struct WalletBalance {
     pub available_balance: MicroMinotari,
     pub timelocked_balance: MicroMinotari,
@@ -647,7 +674,54 @@ impl WalletBalance {
     }
 }
 
-#[derive(Debug, Serialize, Clone)]
+/// Streams every completed transaction known to the wallet listening on `grpc_address`, regardless of status.
+pub async fn fetch_completed_transactions(
+    grpc_address: String,
+    grpc_auth: WalletGrpcAuth,
+) -> Result<Vec<TransactionInfo>, WalletStatusMonitorError> {
+    fetch_completed_transactions_except(grpc_address, grpc_auth, &HashSet::new()).await
+}
+
+/// Streams the completed transactions known to the wallet listening on `grpc_address`, except the ones in `skip`.
+pub async fn fetch_completed_transactions_except(
+    grpc_address: String,
+    grpc_auth: WalletGrpcAuth,
+    skip: &HashSet<u64>,
+) -> Result<Vec<TransactionInfo>, WalletStatusMonitorError> {
+    let mut client = connect_wallet_grpc(grpc_address, grpc_auth)
+        .await
+        .map_err(|_e| WalletStatusMonitorError::WalletNotStarted)?;
+    let mut stream = client
+        .get_completed_transactions(GetCompletedTransactionsRequest { payment_id: None })
+        .await
+        .map_err(|e| WalletStatusMonitorError::UnknownError(e.into()))?
+        .into_inner();
+
+    let mut transactions: Vec<TransactionInfo> = Vec::new();
+    while let Some(message) = stream
+        .message()
+        .await
+        .map_err(|e| WalletStatusMonitorError::UnknownError(e.into()))?
+    {
+        let tx = message.transaction.ok_or_else(|| {
+            WalletStatusMonitorError::UnknownError(anyhow::anyhow!("Transaction not found"))
+        })?;
+        if skip.contains(&tx.tx_id) {
+            continue;
+        }
+        transactions.push(TransactionInfo::from_grpc(tx));
+    }
+    Ok(transactions)
+}
+
+/// Coinbase outputs carry no sender, so fall back to hex for anything that is not a valid address
+fn address_to_base58(bytes: &[u8]) -> String {
+    TariAddress::from_bytes(bytes)
+        .map(|address| address.to_base58())
+        .unwrap_or_else(|_| bytes.to_hex())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct TransactionInfo {
     pub tx_id: u64,
     pub source_address: String,
@@ -662,3 +736,22 @@ pub struct TransactionInfo {
     pub payment_id: String,
     pub mined_in_block_height: u64,
 }
+
+impl TransactionInfo {
+    pub fn from_grpc(tx: GrpcTransactionInfo) -> Self {
+        Self {
+            tx_id: tx.tx_id,
+            source_address: address_to_base58(&tx.source_address),
+            dest_address: address_to_base58(&tx.dest_address),
+            status: tx.status,
+            amount: MicroMinotari(tx.amount),
+            is_cancelled: tx.is_cancelled,
+            direction: tx.direction,
+            excess_sig: tx.excess_sig,
+            fee: tx.fee,
+            timestamp: tx.timestamp,
+            payment_id: PaymentId::from_bytes(&tx.payment_id).user_data_as_string(),
+            mined_in_block_height: tx.mined_in_block_height,
+        }
+    }
+}
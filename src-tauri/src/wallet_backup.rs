@@ -0,0 +1,308 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+use std::path::Path;
+
+use anyhow::{anyhow, Error};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use tari_common_types::tari_address::TariAddress;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    address_book::{AddressBook, Contact},
+    configs::{
+        config_wallet::{ConfigWallet, ConfigWalletContent},
+        trait_config::ConfigImpl,
+    },
+    crypto::{self, validate_passphrase, KdfParams},
+    internal_wallet::InternalWallet,
+    monero_payout,
+    utils::{store_utils::current_network_key, time_utils::unix_now},
+};
+
+const LOG_TARGET: &str = "tari::universe::wallet_backup";
+const BACKUP_VERSION: u32 = 1;
+
+/// What a backup restores. Never logged, it holds the seed words in the clear.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WalletBackup {
+    pub seed_words: Vec<String>,
+    pub birthday: u16,
+    pub network: String,
+    pub tari_address: String,
+    pub monero_address: String,
+    #[serde(default)]
+    pub monero_address_is_generated: bool,
+    /// Seed of the generated Monero address, so rewards paid to it stay recoverable after a restore
+    #[serde(default)]
+    pub monero_seed: Option<[u8; 32]>,
+    #[serde(default)]
+    pub contacts: Vec<Contact>,
+}
+
+/// On-disk form of a backup. Only the version and network are readable without the passphrase, and both are
+/// authenticated together with the ciphertext.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletBackupFile {
+    pub version: u32,
+    pub network: String,
+    pub created_at: u64,
+    pub kdf: KdfParams,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+impl WalletBackupFile {
+    fn associated_data(version: u32, network: &str) -> Vec<u8> {
+        format!("tari-universe-wallet-backup:{}:{}", version, network).into_bytes()
+    }
+
+    pub fn seal(
+        backup: &WalletBackup,
+        passphrase: &str,
+        kdf: KdfParams,
+        created_at: u64,
+    ) -> Result<Self, Error> {
+        validate_passphrase(passphrase)?;
+        let key = kdf.derive_key(passphrase.as_bytes())?;
+        let plaintext = serde_json::to_vec(backup)?;
+        let (nonce, ciphertext) = crypto::seal(
+            &key,
+            &plaintext,
+            &Self::associated_data(BACKUP_VERSION, &backup.network),
+        )
+        .map_err(|_| anyhow!("Could not encrypt backup"))?;
+
+        Ok(Self {
+            version: BACKUP_VERSION,
+            network: backup.network.clone(),
+            created_at,
+            kdf,
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    /// Decrypts the backup, refusing backups of another network before spending time on the key derivation
+    pub fn open(&self, passphrase: &str, expected_network: &str) -> Result<WalletBackup, Error> {
+        if self.version > BACKUP_VERSION {
+            return Err(anyhow!("Backup version {} is not supported", self.version));
+        }
+        if self.network != expected_network {
+            return Err(anyhow!(
+                "This backup belongs to {} but the app is running on {}",
+                self.network,
+                expected_network
+            ));
+        }
+
+        let key = self.kdf.derive_key(passphrase.as_bytes())?;
+        let nonce = hex::decode(&self.nonce).map_err(|_| anyhow!("Backup file is corrupted"))?;
+        let ciphertext =
+            hex::decode(&self.ciphertext).map_err(|_| anyhow!("Backup file is corrupted"))?;
+        let plaintext = crypto::open(
+            &key,
+            &nonce,
+            &ciphertext,
+            &Self::associated_data(self.version, &self.network),
+        )
+        .map_err(|_| anyhow!("Wrong passphrase or corrupted backup"))?;
+
+        let backup: WalletBackup = serde_json::from_slice(&plaintext)
+            .map_err(|e| anyhow!("Backup contents are unreadable: {}", e))?;
+        if backup.network != self.network {
+            return Err(anyhow!("Backup file is corrupted"));
+        }
+        Ok(backup)
+    }
+}
+
+/// Writes an encrypted backup of the active wallet to `path`
+pub async fn export_wallet_backup(
+    app_handle: &AppHandle,
+    path: &Path,
+    passphrase: &str,
+) -> Result<(), Error> {
+    validate_passphrase(passphrase)?;
+    let wallet = InternalWallet::load_or_create(app_handle.path().app_config_dir()?).await?;
+    let seed_words = wallet.decrypt_seed_words().await?;
+    let seed_words = (0..seed_words.len())
+        .map(|i| seed_words.get_word(i).cloned())
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|e| anyhow!("Could not read seed words: {:?}", e))?;
+
+    let config_wallet = ConfigWallet::content().await;
+    let monero_address = config_wallet.monero_address().clone();
+    let monero_address_is_generated = *config_wallet.monero_address_is_generated();
+    let monero_seed = if monero_address_is_generated {
+        monero_payout::current_seed(app_handle.path().app_config_dir()?)
+            .await?
+            .filter(|seed| {
+                monero_payout::seed_address(*seed).is_ok_and(|address| address == monero_address)
+            })
+    } else {
+        None
+    };
+    if monero_address_is_generated && monero_seed.is_none() {
+        warn!(target: LOG_TARGET, "Seed of the generated Monero address is not stored, it is left out of the backup");
+    }
+
+    let backup = WalletBackup {
+        seed_words,
+        birthday: wallet.get_birthday().await?,
+        network: current_network_key(),
+        tari_address: wallet.get_tari_address().to_base58(),
+        monero_address,
+        monero_address_is_generated,
+        monero_seed,
+        contacts: AddressBook::current().list(false).await,
+    };
+    let passphrase = passphrase.to_string();
+    // Argon2 with the default memory cost takes a noticeable moment, keep it off the async workers
+    let file = tokio::task::spawn_blocking(move || {
+        WalletBackupFile::seal(&backup, &passphrase, KdfParams::default(), unix_now())
+    })
+    .await??;
+
+    tokio::fs::write(path, serde_json::to_string_pretty(&file)?).await?;
+    info!(target: LOG_TARGET, "Exported wallet backup to {:?}", path);
+    Ok(())
+}
+
+/// Replaces the active wallet with the one in the backup and restores its Monero address, Monero seed and contacts.
+/// The caller still has to wipe the local wallet data and restart, as after a seed import.
+pub async fn restore_wallet_backup(
+    app_handle: &AppHandle,
+    path: &Path,
+    passphrase: &str,
+) -> Result<TariAddress, Error> {
+    let content = tokio::fs::read_to_string(path).await?;
+    let file: WalletBackupFile =
+        serde_json::from_str(&content).map_err(|_| anyhow!("Not a wallet backup file"))?;
+    let passphrase = passphrase.to_string();
+    let backup =
+        tokio::task::spawn_blocking(move || file.open(&passphrase, &current_network_key()))
+            .await??;
+
+    // Check the seed against the backed up address before the current wallet gets replaced
+    let restored_address = InternalWallet::address_from_seed_words(&backup.seed_words).await?;
+    if restored_address.to_base58() != backup.tari_address {
+        return Err(anyhow!(
+            "Backup seed words do not match the backed up address {}",
+            backup.tari_address
+        ));
+    }
+
+    let wallet =
+        InternalWallet::create_from_seed(app_handle.path().app_config_dir()?, backup.seed_words)
+            .await?;
+    let tari_address = wallet.get_tari_address();
+    if wallet.get_birthday().await.ok() != Some(backup.birthday) {
+        warn!(target: LOG_TARGET, "Restored wallet birthday differs from the backed up {}", backup.birthday);
+    }
+
+    // A generated address is only restored as generated together with its seed, otherwise it could not be recovered
+    let monero_seed = backup.monero_seed.filter(|seed| {
+        monero_payout::seed_address(*seed).is_ok_and(|address| address == backup.monero_address)
+    });
+    match monero_seed {
+        Some(seed) if backup.monero_address_is_generated => {
+            monero_payout::store_seed(app_handle.path().app_config_dir()?, seed).await?;
+            ConfigWallet::update_field(
+                ConfigWalletContent::set_generated_monero_address,
+                backup.monero_address,
+            )
+            .await?;
+        }
+        _ if !backup.monero_address.is_empty() => {
+            if backup.monero_address_is_generated {
+                warn!(target: LOG_TARGET, "Backup has no seed for its generated Monero address, restoring it as a user address");
+            }
+            ConfigWallet::update_field(
+                ConfigWalletContent::set_user_monero_address,
+                backup.monero_address,
+            )
+            .await?;
+        }
+        _ => {}
+    }
+    if let Err(e) = AddressBook::current()
+        .import_contacts(backup.contacts)
+        .await
+    {
+        warn!(target: LOG_TARGET, "Could not restore contacts from backup: {:?}", e);
+    }
+
+    info!(target: LOG_TARGET, "Restored wallet {} from backup", tari_address.to_base58());
+    Ok(tari_address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSPHRASE: &str = "correct horse battery";
+
+    fn backup() -> WalletBackup {
+        WalletBackup {
+            seed_words: vec!["word".to_string(); 24],
+            birthday: 42,
+            network: "esmeralda".to_string(),
+            tari_address: "address".to_string(),
+            monero_address: "monero".to_string(),
+            monero_address_is_generated: true,
+            monero_seed: Some([7u8; 32]),
+            contacts: vec![],
+        }
+    }
+
+    fn light_kdf() -> KdfParams {
+        KdfParams::with_random_salt(64, 1, 1)
+    }
+
+    #[test]
+    fn backup_round_trips() {
+        let file = WalletBackupFile::seal(&backup(), PASSPHRASE, light_kdf(), 1).unwrap();
+        let restored = file.open(PASSPHRASE, "esmeralda").unwrap();
+        assert_eq!(restored.seed_words, backup().seed_words);
+        assert_eq!(restored.birthday, 42);
+        assert_eq!(restored.monero_address, "monero");
+        assert!(restored.monero_address_is_generated);
+        assert_eq!(restored.monero_seed, Some([7u8; 32]));
+    }
+
+    #[test]
+    fn open_rejects_wrong_passphrase_and_network() {
+        let file = WalletBackupFile::seal(&backup(), PASSPHRASE, light_kdf(), 1).unwrap();
+        assert!(file.open("incorrect passphrase", "esmeralda").is_err());
+        assert!(file.open(PASSPHRASE, "nextnet").is_err());
+
+        let mut relabelled = file.clone();
+        relabelled.network = "nextnet".to_string();
+        assert!(relabelled.open(PASSPHRASE, "nextnet").is_err());
+    }
+
+    #[test]
+    fn seal_rejects_short_passphrase() {
+        assert!(WalletBackupFile::seal(&backup(), "short", light_kdf(), 1).is_err());
+    }
+}
@@ -20,17 +20,21 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use crate::data_locations::{DataComponent, DataLocations};
 use crate::events_emitter::EventsEmitter;
 use crate::internal_wallet::InternalWallet;
 use crate::node::node_manager::{NodeManager, NodeManagerError};
 use crate::process_stats_collector::ProcessStatsCollectorBuilder;
 use crate::process_watcher::ProcessWatcher;
 use crate::tasks_tracker::TasksTrackers;
+use crate::transaction_index::TransactionIndex;
 use crate::wallet_adapter::TransactionInfo;
 use crate::wallet_adapter::WalletStatusMonitorError;
 use crate::wallet_adapter::{WalletAdapter, WalletState};
+use crate::wallet_profiles::{active_wallet_id, wallet_data_dir};
 use crate::BaseNodeStatus;
 use futures_util::future::FusedFuture;
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
@@ -156,25 +160,44 @@ impl WalletManager {
     }
 
     pub async fn clean_data_folder(&self, base_path: &Path) -> Result<(), anyhow::Error> {
+        let base_path = wallet_data_dir(
+            &DataLocations::current().resolve(DataComponent::Wallet, base_path),
+            &active_wallet_id().await,
+        );
         fs::remove_dir_all(
             base_path
                 .join("wallet")
                 .join(Network::get_current().to_string().to_lowercase()),
         )
         .await?;
+        TransactionIndex::current().clear().await?;
         log::info!(target: LOG_TARGET, "Cleaning wallet data folder");
         Ok(())
     }
 
-    pub async fn get_transactions_history(
+    pub async fn get_completed_transactions_except(
         &self,
-        continuation: bool,
-        limit: Option<u32>,
+        skip: &HashSet<u64>,
+    ) -> Result<Vec<TransactionInfo>, WalletManagerError> {
+        let process_watcher = self.watcher.read().await;
+        process_watcher
+            .adapter
+            .get_completed_transactions_except(skip)
+            .await
+            .map_err(|e| match e {
+                WalletStatusMonitorError::WalletNotStarted => WalletManagerError::WalletNotStarted,
+                _ => WalletManagerError::UnknownError(e.into()),
+            })
+    }
+
+    pub async fn get_transactions(
+        &self,
+        tx_ids: Vec<u64>,
     ) -> Result<Vec<TransactionInfo>, WalletManagerError> {
         let process_watcher = self.watcher.read().await;
         process_watcher
             .adapter
-            .get_transactions_history(continuation, limit)
+            .get_transactions(tx_ids)
             .await
             .map_err(|e| match e {
                 WalletStatusMonitorError::WalletNotStarted => WalletManagerError::WalletNotStarted,
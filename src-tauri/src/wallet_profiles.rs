@@ -0,0 +1,256 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Error};
+use log::{info, warn};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tari_common::configuration::Network;
+use tauri::{AppHandle, Manager};
+
+use crate::{
+    configs::{
+        config_wallet::{ConfigWallet, ConfigWalletContent},
+        trait_config::ConfigImpl,
+    },
+    credential_manager::CredentialManager,
+    data_locations::{get_component_root, DataComponent},
+    internal_wallet::InternalWallet,
+    utils::time_utils::unix_now,
+};
+
+const LOG_TARGET: &str = "tari::universe::wallet_profiles";
+
+/// Id of the wallet every install starts with. It keeps the original file locations and keyring entry, so
+/// existing installs need no migration.
+pub const DEFAULT_WALLET_ID: &str = "default";
+const DEFAULT_WALLET_NAME: &str = "Main";
+pub const WALLETS_DIR: &str = "wallets";
+pub const MAX_WALLET_NAME_LENGTH: usize = 32;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WalletProfile {
+    pub id: String,
+    pub name: String,
+    pub created_at: u64,
+}
+
+impl WalletProfile {
+    pub fn default_profile() -> Self {
+        Self {
+            id: DEFAULT_WALLET_ID.to_string(),
+            name: DEFAULT_WALLET_NAME.to_string(),
+            created_at: 0,
+        }
+    }
+
+    /// Names are compared case-insensitively so the wallet switcher never shows two entries that look the same.
+    pub fn new(name: &str, existing: &[WalletProfile], created_at: u64) -> Result<Self, Error> {
+        let name = validate_wallet_name(name)?;
+        if existing
+            .iter()
+            .any(|profile| profile.name.to_lowercase() == name.to_lowercase())
+        {
+            return Err(anyhow!("A wallet named {} already exists", name));
+        }
+
+        let mut rng = rand::thread_rng();
+        let id = loop {
+            let id = format!("wallet_{:08x}", rng.gen::<u32>());
+            if !existing.iter().any(|profile| profile.id == id) {
+                break id;
+            }
+        };
+
+        Ok(Self {
+            id,
+            name,
+            created_at,
+        })
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.id == DEFAULT_WALLET_ID
+    }
+
+    /// Removal deletes the seed for good, so the user has to type the wallet name back exactly.
+    pub fn confirms_removal(&self, confirmation: &str) -> bool {
+        confirmation.trim() == self.name
+    }
+}
+
+pub fn validate_wallet_name(name: &str) -> Result<String, Error> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(anyhow!("Wallet name cannot be empty"));
+    }
+    if name.chars().count() > MAX_WALLET_NAME_LENGTH {
+        return Err(anyhow!(
+            "Wallet name cannot be longer than {} characters",
+            MAX_WALLET_NAME_LENGTH
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// Directory holding `wallet_config.json` of a wallet. The default wallet lives directly in the network folder.
+pub fn wallet_config_dir(app_config_dir: &Path, wallet_id: &str) -> PathBuf {
+    let network_dir = app_config_dir.join(
+        Network::get_current_or_user_setting_or_default()
+            .to_string()
+            .to_lowercase(),
+    );
+    if wallet_id == DEFAULT_WALLET_ID {
+        network_dir
+    } else {
+        network_dir.join(WALLETS_DIR).join(wallet_id)
+    }
+}
+
+/// Root for the wallet processes and transaction index of a wallet, inside the wallet data component root.
+pub fn wallet_data_dir(component_root: &Path, wallet_id: &str) -> PathBuf {
+    if wallet_id == DEFAULT_WALLET_ID {
+        component_root.to_path_buf()
+    } else {
+        component_root.join(WALLETS_DIR).join(wallet_id)
+    }
+}
+
+pub async fn active_wallet_id() -> String {
+    ConfigWallet::content().await.active_wallet_id().clone()
+}
+
+pub async fn list_wallets() -> Vec<WalletProfile> {
+    ConfigWallet::content().await.wallet_profiles().clone()
+}
+
+/// Adds a named wallet next to the existing ones, from seed words or from a fresh seed. It does not become active.
+pub async fn create_wallet(
+    app_handle: &AppHandle,
+    name: &str,
+    seed_words: Option<Vec<String>>,
+) -> Result<WalletProfile, Error> {
+    let mut profiles = list_wallets().await;
+    let profile = WalletProfile::new(name, &profiles, unix_now())?;
+    InternalWallet::create_for(app_handle.path().app_config_dir()?, &profile.id, seed_words)
+        .await?;
+
+    profiles.push(profile.clone());
+    ConfigWallet::update_field(ConfigWalletContent::set_wallet_profiles, profiles).await?;
+    info!(target: LOG_TARGET, "Created wallet {} ({})", profile.name, profile.id);
+    Ok(profile)
+}
+
+/// Deletes a named wallet together with its credentials and local data. The default and the active wallet stay.
+/// `confirmation` has to repeat the wallet name.
+pub async fn remove_wallet(
+    app_handle: &AppHandle,
+    wallet_id: &str,
+    confirmation: &str,
+) -> Result<(), Error> {
+    if wallet_id == DEFAULT_WALLET_ID {
+        return Err(anyhow!("The main wallet cannot be removed"));
+    }
+    if wallet_id == active_wallet_id().await {
+        return Err(anyhow!("Switch to another wallet before removing this one"));
+    }
+    let mut profiles = list_wallets().await;
+    let profile = profiles
+        .iter()
+        .find(|profile| profile.id == wallet_id)
+        .ok_or_else(|| anyhow!("Unknown wallet {}", wallet_id))?;
+    if !profile.confirms_removal(confirmation) {
+        return Err(anyhow!(
+            "Type the wallet name {} to confirm the removal",
+            profile.name
+        ));
+    }
+    profiles.retain(|profile| profile.id != wallet_id);
+
+    let config_path = app_handle.path().app_config_dir()?;
+    CredentialManager::for_wallet(config_path.clone(), wallet_id)
+        .delete_credentials()
+        .await?;
+    for dir in [
+        wallet_config_dir(&config_path, wallet_id),
+        wallet_data_dir(
+            &get_component_root(app_handle, DataComponent::Wallet)?,
+            wallet_id,
+        ),
+    ] {
+        if dir.exists() {
+            if let Err(e) = tokio::fs::remove_dir_all(&dir).await {
+                warn!(target: LOG_TARGET, "Could not remove wallet directory {:?}: {:?}", dir, e);
+            }
+        }
+    }
+
+    ConfigWallet::update_field(ConfigWalletContent::set_wallet_profiles, profiles).await?;
+    info!(target: LOG_TARGET, "Removed wallet {}", wallet_id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_profile_validates_name() {
+        let existing = vec![WalletProfile::default_profile()];
+        assert!(WalletProfile::new("  ", &existing, 0).is_err());
+        assert!(WalletProfile::new(&"x".repeat(MAX_WALLET_NAME_LENGTH + 1), &existing, 0).is_err());
+        assert!(WalletProfile::new("main", &existing, 0).is_err());
+
+        let profile = WalletProfile::new(" Team payout ", &existing, 5).unwrap();
+        assert_eq!(profile.name, "Team payout");
+        assert_eq!(profile.created_at, 5);
+        assert!(profile.id.starts_with("wallet_"));
+        assert!(!profile.is_default());
+    }
+
+    #[test]
+    fn removal_needs_the_exact_wallet_name() {
+        let profile = WalletProfile::new("Team payout", &[], 0).unwrap();
+        assert!(profile.confirms_removal("Team payout"));
+        assert!(profile.confirms_removal(" Team payout "));
+        assert!(!profile.confirms_removal("team payout"));
+        assert!(!profile.confirms_removal(""));
+    }
+
+    #[test]
+    fn default_wallet_keeps_original_locations() {
+        let root = PathBuf::from("/data");
+        assert_eq!(wallet_data_dir(&root, DEFAULT_WALLET_ID), root);
+        assert_eq!(
+            wallet_data_dir(&root, "wallet_0000abcd"),
+            root.join(WALLETS_DIR).join("wallet_0000abcd")
+        );
+
+        let config_dir = wallet_config_dir(&root, DEFAULT_WALLET_ID);
+        assert_eq!(config_dir.parent(), Some(root.as_path()));
+        assert_eq!(
+            wallet_config_dir(&root, "wallet_0000abcd"),
+            config_dir.join(WALLETS_DIR).join("wallet_0000abcd")
+        );
+    }
+}
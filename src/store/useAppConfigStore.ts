@@ -25,6 +25,9 @@ const configCoreInitialState: ConfigCore = {
 const configWalletInitialState: ConfigWallet = {
     created_at: '',
     keyring_accessed: false,
+    is_watch_only: false,
+    active_wallet_id: 'default',
+    wallet_profiles: [],
     monero_address: '',
     monero_address_is_generated: false,
 };
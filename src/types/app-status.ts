@@ -1,3 +1,5 @@
+import { SecretBackendConfig, SecretsCommandBackend } from '@app/types/configs.ts';
+
 export interface TorConfig {
     control_port: number;
     use_bridges: boolean;
@@ -238,3 +240,151 @@ export interface NetworkStatus {
     latency: number;
     is_too_low: boolean;
 }
+
+export interface AppLockStatus {
+    enabled: boolean;
+    locked: boolean;
+    auto_lock_secs: number;
+    failed_attempts: number;
+    locked_until?: number;
+}
+
+export type SweepTrigger = { type: 'threshold'; amount: number } | { type: 'schedule'; interval_secs: number };
+
+export interface AutoSweepSettings {
+    enabled: boolean;
+    destination: string;
+    trigger: SweepTrigger;
+    reserve: number;
+    payment_id?: string;
+}
+
+export interface SweepRecord {
+    swept_at: number;
+    amount: number;
+    destination: string;
+    tx_id?: number;
+    error?: string;
+}
+
+export interface AutoSweepStatus {
+    settings?: AutoSweepSettings;
+    destination_confirmed: boolean;
+    last_sweep_at?: number;
+    history: SweepRecord[];
+}
+
+export type OutputStatus = 'spendable' | 'immature' | 'unconfirmed';
+
+export interface UnspentOutput {
+    commitment: string;
+    value: number;
+    maturity: number;
+    height?: number;
+    status: OutputStatus;
+    is_dust: boolean;
+}
+
+export interface UtxoStats {
+    count: number;
+    total: number;
+    spendable_count: number;
+    spendable_total: number;
+    smallest?: number;
+    largest?: number;
+    dust_threshold: number;
+    dust_count: number;
+    dust_total: number;
+    input_spend_cost: number;
+}
+
+export interface UtxoOverview {
+    outputs: UnspentOutput[];
+    stats: UtxoStats;
+}
+
+export type CoinOperation = { type: 'join' } | { type: 'split'; amount_per_split: number; split_count: number };
+
+export interface CoinOperationPreview {
+    operation: CoinOperation;
+    inputs: number;
+    input_total: number;
+    amount_per_output: number;
+    output_count: number;
+    fee: number;
+    fee_per_gram: number;
+    change: number;
+    commitments: string[];
+}
+
+export type FeePriority = 'slow' | 'normal' | 'fast';
+
+export type FeeSelection = { type: 'priority'; priority: FeePriority } | { type: 'custom'; fee_per_gram: number };
+
+export interface BlockFeeStats {
+    min_fee_per_gram: number;
+    avg_fee_per_gram: number;
+    max_fee_per_gram: number;
+}
+
+export interface MempoolStats {
+    unconfirmed_txs: number;
+    unconfirmed_weight: number;
+    block_fees: BlockFeeStats[];
+}
+
+export interface FeeEstimates {
+    slow: number;
+    normal: number;
+    fast: number;
+    mempool: MempoolStats;
+    blocks_to_clear: number;
+}
+
+export interface SendSummary {
+    amount: number;
+    fee: number;
+    fee_per_gram: number;
+    total: number;
+    change: number;
+    inputs: number;
+    available: number;
+}
+
+export type MoneroAddressKind = 'standard' | 'integrated' | 'subaddress';
+
+export interface MoneroAddressInfo {
+    kind: MoneroAddressKind;
+    payment_id?: string;
+    can_receive_mining_rewards: boolean;
+}
+
+export interface MoneroAddressHistoryEntry {
+    address: string;
+    is_generated: boolean;
+    set_at: number;
+    recoverable: boolean;
+}
+
+export interface MoneroAddressStatus {
+    address: string;
+    is_generated: boolean;
+    info?: MoneroAddressInfo;
+    recoverable: boolean;
+    warning?: string;
+    history: MoneroAddressHistoryEntry[];
+}
+
+export interface MoneroRegeneration {
+    address: string;
+    seed_words: string[];
+}
+
+export type SecretBackendKind = 'keyring' | 'fallback_file' | 'encrypted_file' | 'command' | 'directory';
+
+export interface SecretBackendStatus {
+    config: SecretBackendConfig | SecretsCommandBackend;
+    active: SecretBackendKind;
+    locked: boolean;
+    writable: boolean;
+}
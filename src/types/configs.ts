@@ -27,7 +27,28 @@ export interface ConfigWallet {
     created_at: string;
     monero_address: string;
     monero_address_is_generated: boolean;
+    monero_address_history: MoneroAddressRecord[];
     keyring_accessed: boolean;
+    is_watch_only: boolean;
+    active_wallet_id: string;
+    wallet_profiles: WalletProfile[];
+    secret_backend: SecretBackendConfig;
+}
+export type SecretBackendConfig =
+    | { type: 'keyring' }
+    | { type: 'encrypted_file' }
+    | { type: 'directory'; directory: string };
+// Only set from the TARI_UNIVERSE_SECRETS_COMMAND environment variable, reported back in the backend status
+export type SecretsCommandBackend = { type: 'command'; program: string };
+export interface MoneroAddressRecord {
+    address: string;
+    is_generated: boolean;
+    set_at: number;
+}
+export interface WalletProfile {
+    id: string;
+    name: string;
+    created_at: number;
 }
 export interface ConfigUI {
     created_at: string;
@@ -7,17 +7,31 @@ import {
     MaxConsumptionLevels,
     GpuThreads,
     P2poolConnections,
+    AppLockStatus,
+    AutoSweepSettings,
+    AutoSweepStatus,
+    CoinOperation,
+    CoinOperationPreview,
+    UtxoOverview,
+    FeeEstimates,
+    FeeSelection,
+    SendSummary,
+    MoneroAddressInfo,
+    MoneroAddressStatus,
+    MoneroRegeneration,
+    SecretBackendStatus,
 } from './app-status';
 import { Language } from '@app/i18initializer';
 import { PaperWalletDetails } from '@app/types/app-status.ts';
 import { displayMode, modeType } from '@app/store/types.ts';
 import { SignData } from '@app/types/ws.ts';
+import { SecretBackendConfig, WalletProfile } from '@app/types/configs.ts';
 
 declare module '@tauri-apps/api/core' {
     function invoke(
         param: 'send_one_sided_to_stealth_address',
-        payload: { amount: string; destination: string; paymentId?: string }
-    ): Promise<void>;
+        payload: { amount: string; destination: string; paymentId?: string; fee?: FeeSelection }
+    ): Promise<number>;
     function invoke(
         param: 'set_should_always_use_system_language',
         payload: { shouldAlwaysUseSystemLanguage: boolean }
@@ -70,6 +84,58 @@ declare module '@tauri-apps/api/core' {
         payload: { continuation: boolean; limit?: number }
     ): Promise<TransactionInfo[]>;
     function invoke(param: 'import_seed_words', payload: { seedWords: string[] }): Promise<void>;
+    function invoke(
+        param: 'import_view_key',
+        payload: { viewPrivateKey: string; spendPublicKey: string; birthday?: number }
+    ): Promise<void>;
+    function invoke(param: 'get_wallet_profiles'): Promise<WalletProfile[]>;
+    function invoke(
+        param: 'create_wallet_profile',
+        payload: { name: string; seedWords?: string[] }
+    ): Promise<WalletProfile>;
+    function invoke(
+        param: 'remove_wallet_profile',
+        payload: { walletId: string; confirmation: string }
+    ): Promise<void>;
+    function invoke(param: 'switch_wallet', payload: { walletId: string }): Promise<void>;
+    function invoke(param: 'export_wallet_backup', payload: { path: string; passphrase: string }): Promise<void>;
+    function invoke(param: 'import_wallet_backup', payload: { path: string; passphrase: string }): Promise<void>;
+    function invoke(param: 'get_app_lock_status'): Promise<AppLockStatus>;
+    function invoke(
+        param: 'set_app_lock',
+        payload: { newSecret: string; currentSecret?: string }
+    ): Promise<AppLockStatus>;
+    function invoke(param: 'remove_app_lock', payload: { currentSecret: string }): Promise<AppLockStatus>;
+    function invoke(param: 'unlock_app', payload: { secret: string }): Promise<AppLockStatus>;
+    function invoke(param: 'lock_app'): Promise<AppLockStatus>;
+    function invoke(param: 'set_app_auto_lock', payload: { autoLockSecs: number }): Promise<AppLockStatus>;
+    function invoke(param: 'get_auto_sweep'): Promise<AutoSweepStatus>;
+    function invoke(param: 'set_auto_sweep', payload: { settings: AutoSweepSettings }): Promise<AutoSweepStatus>;
+    function invoke(param: 'confirm_auto_sweep_destination', payload: { destination: string }): Promise<AutoSweepStatus>;
+    function invoke(param: 'get_utxo_overview', payload?: { dustThreshold?: number }): Promise<UtxoOverview>;
+    function invoke(
+        param: 'preview_coin_operation',
+        payload: { operation: CoinOperation; feePerGram?: number }
+    ): Promise<CoinOperationPreview>;
+    function invoke(
+        param: 'execute_coin_operation',
+        payload: { operation: CoinOperation; feePerGram?: number }
+    ): Promise<number>;
+    function invoke(param: 'get_fee_estimates'): Promise<FeeEstimates>;
+    function invoke(
+        param: 'preview_send',
+        payload: { amount: string; destination: string; fee?: FeeSelection }
+    ): Promise<SendSummary>;
+    function invoke(param: 'validate_monero_address', payload: { address: string }): Promise<MoneroAddressInfo>;
+    function invoke(param: 'get_monero_address_status'): Promise<MoneroAddressStatus>;
+    function invoke(param: 'begin_monero_address_regeneration'): Promise<MoneroRegeneration>;
+    function invoke(param: 'confirm_monero_address_regeneration', payload: { seedWords: string[] }): Promise<string>;
+    function invoke(param: 'get_secret_backend_status'): Promise<SecretBackendStatus>;
+    function invoke(
+        param: 'set_secret_backend',
+        payload: { backend: SecretBackendConfig; passphrase?: string }
+    ): Promise<SecretBackendStatus>;
+    function invoke(param: 'unlock_secret_backend', payload: { passphrase: string }): Promise<SecretBackendStatus>;
     function invoke(param: 'get_tor_config'): Promise<TorConfig>;
     function invoke(param: 'set_tor_config', payload: { config: TorConfig }): Promise<TorConfig>;
     function invoke(param: 'fetch_tor_bridges'): Promise<string[]>;